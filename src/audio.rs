@@ -0,0 +1,157 @@
+use anyhow::Result;
+use grafton_ndi::{AudioFrame, Receiver, ReceiverBandwidth, ReceiverOptions};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::ndi_input::{find_source, InputManager, SourceState};
+
+/// Without audio for this long a source counts as lost and is searched for again.
+pub const LOSS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A block of planar 32-bit float audio, independent of any NDI frame lifetime.
+#[derive(Debug, Clone)]
+pub struct AudioBlock {
+    pub sample_rate: i32,
+    pub channels: i32,
+    pub samples: i32,
+    /// Planar: all samples of channel 0, then channel 1, ...
+    pub data: Vec<f32>,
+}
+
+impl AudioBlock {
+    fn from_frame(frame: &AudioFrame) -> Self {
+        Self {
+            sample_rate: frame.sample_rate,
+            channels: frame.num_channels,
+            samples: frame.num_samples,
+            data: frame.data().to_vec(),
+        }
+    }
+
+    /// Build an NDI frame for sending.
+    pub fn to_frame(&self) -> Result<AudioFrame> {
+        Ok(AudioFrame::builder()
+            .sample_rate(self.sample_rate)
+            .channels(self.channels)
+            .samples(self.samples)
+            .data(self.data.clone())
+            .build()?)
+    }
+}
+
+/// Receives audio only from an NDI source and forwards it to a channel's output,
+/// so a channel's sound can come from a different source than its video. The
+/// source may carry no video at all, like a mixing console's NDI feed: its
+/// state follows the audio, and it is searched for again once that stops.
+pub struct NdiAudioInput {
+    pub state: Arc<Mutex<SourceState>>,
+    pub frames_received: Arc<Mutex<u64>>,
+    _thread: std::thread::JoinHandle<()>,
+}
+
+impl NdiAudioInput {
+    pub fn start(
+        inputs: &InputManager,
+        source_name: &str,
+        groups: Option<&str>,
+        output: std::sync::mpsc::Sender<AudioBlock>,
+        cancel: CancellationToken,
+    ) -> Result<Self> {
+        let state = Arc::new(Mutex::new(SourceState::Searching));
+        let frames_received: Arc<Mutex<u64>> = Arc::new(Mutex::new(0));
+
+        let input = AudioSource {
+            name: source_name.to_string(),
+            groups: groups.map(str::to_string),
+            state: state.clone(),
+            frames_received: frames_received.clone(),
+        };
+        let inputs = inputs.clone();
+
+        let thread = std::thread::Builder::new()
+            .name(format!("ndi-audio-{}", source_name))
+            .spawn(move || {
+                crate::realtime::apply_current_thread(crate::realtime::ThreadRole::Ndi);
+                let name = input.name.clone();
+                if let Err(e) = input.receive_loop(&inputs, output, cancel) {
+                    tracing::error!("NDI audio input '{}' error: {}", name, e);
+                }
+            })
+            .expect("Failed to spawn NDI audio thread");
+
+        Ok(Self {
+            state,
+            frames_received,
+            _thread: thread,
+        })
+    }
+}
+
+/// The receive thread's side of an `NdiAudioInput`.
+struct AudioSource {
+    name: String,
+    groups: Option<String>,
+    state: Arc<Mutex<SourceState>>,
+    frames_received: Arc<Mutex<u64>>,
+}
+
+impl AudioSource {
+    fn receive_loop(
+        &self,
+        inputs: &InputManager,
+        output: std::sync::mpsc::Sender<AudioBlock>,
+        cancel: CancellationToken,
+    ) -> Result<()> {
+        while !cancel.is_cancelled() {
+            tracing::info!("NDI audio: searching for source '{}'...", self.name);
+            let source = find_source(inputs.finder(), &self.name, self.groups.as_deref(), &cancel)?;
+
+            // Audio-only bandwidth: the SDK doesn't pull the source's video at all
+            let recv_opts = ReceiverOptions::builder(source)
+                .bandwidth(ReceiverBandwidth::AudioOnly)
+                .build();
+            let receiver = Receiver::new(inputs.ndi(), &recv_opts)?;
+            let mut last_audio = Instant::now();
+
+            while !cancel.is_cancelled() {
+                match receiver.capture_audio_timeout(Duration::from_millis(100)) {
+                    Ok(Some(frame)) => {
+                        if output.send(AudioBlock::from_frame(&frame)).is_err() {
+                            // Output gone — channel is shutting down
+                            return Ok(());
+                        }
+                        last_audio = Instant::now();
+                        *self.frames_received.lock().unwrap() += 1;
+                        self.set_state(SourceState::Connected);
+                    }
+                    Ok(None) if last_audio.elapsed() >= LOSS_TIMEOUT => {
+                        self.set_state(SourceState::Lost);
+                        break;
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        tracing::warn!("NDI audio receive error: {}", e);
+                        self.set_state(SourceState::Lost);
+                        std::thread::sleep(Duration::from_secs(1));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn set_state(&self, new: SourceState) {
+        let mut state = self.state.lock().unwrap();
+        match (*state, new) {
+            (SourceState::Lost, SourceState::Connected) => {
+                tracing::info!("NDI audio: source '{}' is back", self.name)
+            }
+            (SourceState::Connected, SourceState::Lost) => {
+                tracing::warn!("NDI audio: lost source '{}'", self.name)
+            }
+            _ => {}
+        }
+        *state = new;
+    }
+}
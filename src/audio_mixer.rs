@@ -0,0 +1,319 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::audio::{AudioBlock, NdiAudioInput};
+use crate::config::{AudioMixConfig, ChannelConfig};
+use crate::ndi_input::InputManager;
+
+/// Gains outside this range (dB) are refused.
+pub const GAIN_RANGE: std::ops::RangeInclusive<f32> = -96.0..=24.0;
+
+/// Blocks a source buffers before it is mixed in, to ride out network jitter.
+const PRIME_BLOCKS: usize = 2;
+/// Audio a source may queue before the oldest is dropped, bounding its latency
+/// when its clock runs fast against the mixer's.
+const MAX_QUEUED: Duration = Duration::from_millis(200);
+
+/// Gain and mute settings of a channel's mixer, changed at runtime through
+/// the control API.
+#[derive(Debug, Clone, Serialize)]
+pub struct MixLevels {
+    pub master_db: f32,
+    /// The NDI sources, then the overlays with `audio`
+    pub sources: Vec<SourceLevel>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct SourceLevel {
+    pub gain_db: f32,
+    pub mute: bool,
+}
+
+impl MixLevels {
+    pub fn of(channel: &ChannelConfig) -> Self {
+        let mix = channel.audio_mix().unwrap_or_default();
+        let overlays = channel.all_browser_overlays();
+        Self {
+            master_db: mix.master_db,
+            sources: mix
+                .sources
+                .iter()
+                .map(|s| SourceLevel {
+                    gain_db: s.gain_db,
+                    mute: s.mute,
+                })
+                .chain(overlays.iter().filter(|o| o.audio).map(|o| SourceLevel {
+                    gain_db: o.audio_gain_db,
+                    mute: false,
+                }))
+                .collect(),
+        }
+    }
+
+    /// Linear gain of source `index` including the master level; 0 when muted.
+    fn gain(&self, index: usize) -> f32 {
+        match self.sources.get(index) {
+            Some(source) if !source.mute => db_to_gain(source.gain_db + self.master_db),
+            _ => 0.0,
+        }
+    }
+}
+
+fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/// Mixes the audio of several NDI sources and overlay pages into a channel's
+/// output. Each NDI source is received audio-only; every input is converted
+/// to the mix format and summed with its gain. The mix goes out one video
+/// frame's worth at a time on its own clock.
+pub struct AudioMixer {
+    pub levels: Arc<Mutex<MixLevels>>,
+    /// One per configured NDI source, in config order
+    pub inputs: Vec<NdiAudioInput>,
+    _thread: std::thread::JoinHandle<()>,
+}
+
+impl AudioMixer {
+    /// Mix `channel.audio_mix()`; `overlays` are the audio of its overlays
+    /// with `audio`, in config order.
+    pub fn start(
+        inputs: &InputManager,
+        channel: &ChannelConfig,
+        config: &AudioMixConfig,
+        overlays: Vec<Receiver<AudioBlock>>,
+        output: Sender<AudioBlock>,
+        cancel: CancellationToken,
+    ) -> Result<Self> {
+        let frame_rate = channel.frame_rate;
+        let mut audio_inputs = Vec::new();
+        let mut queues = Vec::new();
+        for source in &config.sources {
+            let (tx, rx) = std::sync::mpsc::channel::<AudioBlock>();
+            audio_inputs.push(NdiAudioInput::start(
+                inputs,
+                &source.source,
+                source.groups.as_deref(),
+                tx,
+                cancel.clone(),
+            )?);
+            queues.push(SourceQueue::new(rx, config.channels as usize));
+        }
+        for rx in overlays {
+            queues.push(SourceQueue::new(rx, config.channels as usize));
+        }
+        let levels = Arc::new(Mutex::new(MixLevels::of(channel)));
+
+        let format = MixFormat {
+            sample_rate: config.sample_rate as i32,
+            channels: config.channels as usize,
+            block: (config.sample_rate / frame_rate).max(1) as usize,
+        };
+        let levels_ref = levels.clone();
+        let thread = std::thread::Builder::new()
+            .name(format!("audio-mix-{}", channel.name))
+            .spawn(move || {
+                crate::realtime::apply_current_thread(crate::realtime::ThreadRole::Ndi);
+                mix_loop(queues, levels_ref, format, output, cancel);
+            })
+            .expect("Failed to spawn audio mixer thread");
+
+        Ok(Self {
+            levels,
+            inputs: audio_inputs,
+            _thread: thread,
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+struct MixFormat {
+    sample_rate: i32,
+    channels: usize,
+    /// Samples per channel in each mixed block
+    block: usize,
+}
+
+fn mix_loop(
+    mut queues: Vec<SourceQueue>,
+    levels: Arc<Mutex<MixLevels>>,
+    format: MixFormat,
+    output: Sender<AudioBlock>,
+    cancel: CancellationToken,
+) {
+    let period = Duration::from_secs_f64(format.block as f64 / format.sample_rate as f64);
+    let mut gains: Vec<f32> = {
+        let levels = levels.lock().unwrap();
+        (0..queues.len()).map(|i| levels.gain(i)).collect()
+    };
+    let mut next = Instant::now() + period;
+
+    while !cancel.is_cancelled() {
+        let now = Instant::now();
+        if next > now {
+            std::thread::sleep(next - now);
+        } else if now - next > Duration::from_secs(1) {
+            // Stalled (suspended machine?): carry on from now instead of catching up in a burst
+            next = now;
+        }
+        next += period;
+
+        let levels = levels.lock().unwrap().clone();
+        let mut mixed = vec![0.0f32; format.channels * format.block];
+        for (i, queue) in queues.iter_mut().enumerate() {
+            queue.receive(format);
+            // Gain changes ramp over the block instead of clicking
+            let gain = levels.gain(i);
+            queue.mix_into(&mut mixed, format.block, gains[i], gain);
+            gains[i] = gain;
+        }
+
+        let block = AudioBlock {
+            sample_rate: format.sample_rate,
+            channels: format.channels as i32,
+            samples: format.block as i32,
+            data: mixed,
+        };
+        if output.send(block).is_err() {
+            // Output gone — channel is shutting down
+            break;
+        }
+    }
+}
+
+/// A source's audio converted to the mix format, waiting to be mixed.
+struct SourceQueue {
+    rx: Receiver<AudioBlock>,
+    /// One queue per mix channel, at the mix sample rate
+    channels: Vec<VecDeque<f32>>,
+    resampler: Resampler,
+    /// Enough is buffered to mix; cleared when the queue runs dry
+    primed: bool,
+}
+
+impl SourceQueue {
+    fn new(rx: Receiver<AudioBlock>, channels: usize) -> Self {
+        Self {
+            rx,
+            channels: vec![VecDeque::new(); channels],
+            resampler: Resampler::new(channels),
+            primed: false,
+        }
+    }
+
+    /// Take in what the source has sent since the last block.
+    fn receive(&mut self, format: MixFormat) {
+        while let Ok(block) = self.rx.try_recv() {
+            let samples = block.samples.max(0) as usize;
+            let source_channels = block.channels.max(0) as usize;
+            if samples == 0 || block.data.len() < samples * source_channels {
+                continue;
+            }
+            // A mono source feeds every mix channel; extra source channels are dropped
+            let planes: Vec<Option<&[f32]>> = (0..format.channels)
+                .map(|c| {
+                    let from = if source_channels == 1 { 0 } else { c };
+                    (from < source_channels)
+                        .then(|| &block.data[from * samples..(from + 1) * samples])
+                })
+                .collect();
+            let rates = (block.sample_rate, format.sample_rate);
+            self.resampler
+                .run(rates, samples, &planes, &mut self.channels);
+        }
+
+        let queued = self.channels[0].len();
+        let max = ((MAX_QUEUED.as_secs_f64() * format.sample_rate as f64) as usize)
+            .max((PRIME_BLOCKS + 2) * format.block);
+        if queued > max {
+            let excess = queued - PRIME_BLOCKS * format.block;
+            for channel in &mut self.channels {
+                channel.drain(..excess);
+            }
+        }
+        if self.channels[0].len() >= PRIME_BLOCKS * format.block {
+            self.primed = true;
+        }
+    }
+
+    /// Add one block of this source to `mixed`, ramping its gain from `from` to `to`.
+    fn mix_into(&mut self, mixed: &mut [f32], block: usize, from: f32, to: f32) {
+        if !self.primed {
+            return;
+        }
+        if self.channels[0].len() < block {
+            // Ran dry: play what's left, then buffer up again
+            self.primed = false;
+        }
+        for (channel, out) in self.channels.iter_mut().zip(mixed.chunks_mut(block)) {
+            let take = block.min(channel.len());
+            for (i, (out, sample)) in out.iter_mut().zip(channel.drain(..take)).enumerate() {
+                *out += sample * (from + (to - from) * i as f32 / block as f32);
+            }
+        }
+    }
+}
+
+/// Linear interpolation from a source's sample rate to the mix rate, carried
+/// across blocks so there are no seams between them.
+struct Resampler {
+    /// Source rate the position is in
+    rate: i32,
+    /// Position of the next output sample, in source samples after `last`
+    position: f64,
+    /// Each channel's last source sample of the previous block
+    last: Vec<f32>,
+}
+
+impl Resampler {
+    fn new(channels: usize) -> Self {
+        Self {
+            rate: 0,
+            position: 0.0,
+            last: vec![0.0; channels],
+        }
+    }
+
+    /// Convert `samples` samples per channel at `rates.0` to `rates.1`. Channels
+    /// without a plane are silent.
+    fn run(
+        &mut self,
+        (rate, to): (i32, i32),
+        samples: usize,
+        planes: &[Option<&[f32]>],
+        out: &mut [VecDeque<f32>],
+    ) {
+        if rate <= 0 {
+            return;
+        }
+        if rate != self.rate {
+            *self = Self::new(self.last.len());
+            self.rate = rate;
+        }
+        let step = rate as f64 / to as f64;
+        let mut end = self.position;
+        for ((plane, out), last) in planes.iter().zip(out).zip(&mut self.last) {
+            let mut position = self.position;
+            let sample = |i: usize| match (i, plane) {
+                (0, _) => *last,
+                (i, Some(plane)) => plane[i - 1],
+                (_, None) => 0.0,
+            };
+            while position < samples as f64 {
+                let i = position as usize;
+                let frac = (position - i as f64) as f32;
+                let (a, b) = (sample(i), sample(i + 1));
+                out.push_back(a + (b - a) * frac);
+                position += step;
+            }
+            *last = plane.and_then(|p| p.last().copied()).unwrap_or(0.0);
+            end = position - samples as f64;
+        }
+        self.position = end;
+    }
+}
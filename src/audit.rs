@@ -0,0 +1,65 @@
+use serde::Serialize;
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Append-only JSON Lines record of control mutations: who changed what, when.
+pub struct AuditLog {
+    file: Mutex<std::fs::File>,
+}
+
+/// One control request, accepted or not.
+#[derive(Serialize)]
+pub struct AuditEntry<'a> {
+    /// Where the request came in: "http", "websocket", "stdin" or "socket"
+    pub via: &'a str,
+    /// Name of the API token used, or "anonymous"
+    pub client: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
+    /// "POST /slate" for HTTP, the command's action for the control stream
+    pub request: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<serde_json::Value>,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Line<'a> {
+    /// Unix time in milliseconds
+    timestamp: u128,
+    #[serde(flatten)]
+    entry: &'a AuditEntry<'a>,
+}
+
+impl AuditLog {
+    pub fn open(path: &str) -> anyhow::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| anyhow::anyhow!("audit log {}: {}", path, e))?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn record(&self, entry: &AuditEntry<'_>) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        let Ok(mut line) = serde_json::to_string(&Line { timestamp, entry }) else {
+            return;
+        };
+        line.push('\n');
+        // One write per line so entries never interleave
+        if let Err(e) = self.file.lock().unwrap().write_all(line.as_bytes()) {
+            tracing::warn!("Audit log write failed: {}", e);
+        }
+    }
+}
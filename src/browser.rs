@@ -1,31 +1,63 @@
 use anyhow::Result;
 use base64::Engine;
+use chromiumoxide::auth::Credentials;
 use chromiumoxide::browser::{Browser, BrowserConfig};
 use chromiumoxide::cdp::browser_protocol::dom::Rgba;
 use chromiumoxide::cdp::browser_protocol::emulation::{
     SetDefaultBackgroundColorOverrideParams, SetDeviceMetricsOverrideParams,
 };
+use chromiumoxide::cdp::browser_protocol::input::{
+    DispatchKeyEventParams, DispatchKeyEventType, DispatchMouseEventParams, DispatchMouseEventType,
+    InsertTextParams,
+};
+use chromiumoxide::cdp::browser_protocol::network::{Headers, SetExtraHttpHeadersParams};
 use chromiumoxide::cdp::browser_protocol::page::{
     CaptureScreenshotFormat, CaptureScreenshotParams, EventScreencastFrame,
-    ScreencastFrameAckParams, StartScreencastFormat, StartScreencastParams,
-    StopScreencastParams,
+    ScreencastFrameAckParams, StartScreencastFormat, StartScreencastParams, StopScreencastParams,
+};
+use chromiumoxide::cdp::js_protocol::runtime::{
+    AddBindingParams, EventBindingCalled, ExecutionContextId,
 };
+use chromiumoxide::listeners::EventStream;
 use chromiumoxide::page::ScreenshotParams;
+use chromiumoxide::Page;
 use futures::StreamExt;
-use image::RgbaImage;
+use image::{ImageFormat, RgbaImage};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::mpsc::{Receiver, Sender};
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::{watch, Notify, OnceCell};
 use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
 
+use crate::audio::{AudioBlock, LOSS_TIMEOUT};
+use crate::config::{BrowserOverlayConfig, CaptureMode, OverlayLoadFailure, ReloadMode};
+use crate::cpu_budget::{self, CpuBudgetStatus};
+use crate::data_binding;
+use crate::errors::{record, ErrorCode, ErrorSlot};
+use crate::ndi_input::SourceState;
+
+/// How often `css_file` is checked for changes.
+const CSS_FILE_POLL: Duration = Duration::from_secs(1);
+
+/// Window over which screencast anomalies are weighed for the capture health.
+const HEALTH_WINDOW: Duration = Duration::from_secs(10);
+
 /// Shared browser instance for all channels.
 pub struct SharedBrowser {
-    browser: Browser,
+    pages: Arc<PagePool>,
     _handler: JoinHandle<()>,
 }
 
 impl SharedBrowser {
-    pub async fn launch() -> Result<Self> {
+    /// Launch Chromium and open `prewarm` blank tabs for the overlays about to start.
+    pub async fn launch(prewarm: usize, spare: usize) -> Result<Self> {
         let config = BrowserConfig::builder()
             .disable_default_args()
             .new_headless_mode()
@@ -77,54 +109,745 @@ impl SharedBrowser {
 
         tracing::info!("Headless browser launched");
 
-        Ok(Self {
+        let pages = Arc::new(PagePool {
             browser,
+            spare,
+            state: Mutex::default(),
+        });
+        pages.prewarm(prewarm).await;
+
+        Ok(Self {
+            pages,
             _handler: handle,
         })
     }
 
-    pub fn browser(&self) -> &Browser {
-        &self.browser
+    pub fn pages(&self) -> &Arc<PagePool> {
+        &self.pages
+    }
+
+    /// Chromium's product string, e.g. "HeadlessChrome/131.0.6778.85".
+    pub async fn version(&self) -> Option<String> {
+        self.pages.browser.version().await.ok().map(|v| v.product)
+    }
+}
+
+/// Blank tabs opened ahead of time, so overlays don't each wait for Chromium
+/// to create a tab. After the startup batch is used up, `spare` tabs are kept
+/// ready for seamless reloads and channels started later.
+pub struct PagePool {
+    browser: Browser,
+    spare: usize,
+    state: Mutex<PoolState>,
+}
+
+#[derive(Default)]
+struct PoolState {
+    pages: Vec<Page>,
+    /// Tabs being opened to top the pool back up
+    opening: usize,
+}
+
+impl PagePool {
+    /// Open `count` tabs concurrently and add them to the pool.
+    async fn prewarm(&self, count: usize) {
+        if count == 0 {
+            return;
+        }
+        let started = std::time::Instant::now();
+        let opened =
+            futures::future::join_all((0..count).map(|_| self.browser.new_page("about:blank")))
+                .await;
+        let mut state = self.state.lock().unwrap();
+        for page in opened {
+            match page {
+                Ok(page) => state.pages.push(page),
+                Err(e) => tracing::warn!("Failed to pre-open browser tab: {}", e),
+            }
+        }
+        tracing::info!(
+            "{} browser tabs pre-opened in {} ms",
+            state.pages.len(),
+            started.elapsed().as_millis()
+        );
+    }
+
+    /// A blank tab: a pooled one if available, otherwise a new one.
+    pub async fn take(self: &Arc<Self>) -> Result<Page> {
+        let (pooled, top_up) = {
+            let mut state = self.state.lock().unwrap();
+            let page = state.pages.pop();
+            let top_up = state.pages.len() + state.opening < self.spare;
+            if top_up {
+                state.opening += 1;
+            }
+            (page, top_up)
+        };
+        if top_up {
+            let pool = self.clone();
+            tokio::spawn(async move {
+                let page = pool.browser.new_page("about:blank").await;
+                let mut state = pool.state.lock().unwrap();
+                state.opening -= 1;
+                match page {
+                    Ok(page) => state.pages.push(page),
+                    Err(e) => tracing::debug!("Failed to open spare browser tab: {}", e),
+                }
+            });
+        }
+        match pooled {
+            Some(page) => Ok(page),
+            None => Ok(self.browser.new_page("about:blank").await?),
+        }
     }
 }
 
-/// Per-channel browser overlay that captures transparent screenshots.
+/// A channel's buffer for an overlay's latest frame and its frame number.
+type FrameSlot = Arc<Mutex<Option<(RgbaImage, i64)>>>;
+
+/// Numbers captured frames across all overlays, so a restarted capture never
+/// reuses a frame number another channel's GPU upload may still be keyed by.
+static NEXT_FRAME: AtomicI64 = AtomicI64::new(0);
+
+/// Per-channel browser overlay that captures transparent screenshots. Apart
+/// from `latest_frame`, everything is shared with other channels showing the
+/// same overlay.
 pub struct BrowserOverlay {
-    pub latest_frame: Arc<Mutex<Option<RgbaImage>>>,
+    pub latest_frame: FrameSlot,
     pub loaded: Arc<Mutex<bool>>,
-    _task: JoinHandle<()>,
+    /// Last navigation error while the page isn't loaded (`on_load_failure = "continue"`)
+    pub error: Arc<Mutex<Option<String>>>,
+    /// The tab currently feeding frames (None until loaded), for remote interaction
+    pub page: Arc<Mutex<Option<Page>>>,
+    pub capture: Arc<Mutex<CaptureStats>>,
+    /// CPU use and throttling (None without `cpu_budget`)
+    pub cpu_budget: Option<Arc<Mutex<CpuBudgetStatus>>>,
+    pub last_error: ErrorSlot,
+    /// The channels fed by this overlay's capture
+    pub share: OverlayShare,
+}
+
+/// Fans an overlay's captured frames out to every channel showing it.
+#[derive(Clone, Default)]
+pub struct OverlayShare {
+    slots: Arc<Mutex<Vec<FrameSlot>>>,
+    /// Hash of the page and capture settings, naming the frames for shared uploads
+    source: u64,
+    /// Asks the capture for a fresh screenshot, for a channel that just joined
+    refresh: Arc<Notify>,
+    /// Inline css from a config reload, replacing the configured `css`
+    css: Arc<Mutex<Option<String>>>,
+    /// Channels mixing the page's audio (overlays with `audio`)
+    audio: Arc<Mutex<Vec<Sender<AudioBlock>>>>,
+    audio_state: Arc<Mutex<SourceState>>,
+    audio_blocks: Arc<Mutex<u64>>,
+}
+
+impl OverlayShare {
+    /// Channels currently showing the overlay.
+    pub fn channels(&self) -> usize {
+        self.slots.lock().unwrap().len()
+    }
+
+    /// Identifies the overlay's frames across channels, along with each frame's number.
+    pub fn source_key(&self) -> u64 {
+        self.source
+    }
+
+    /// Replace the overlay's inline css in its current tab and in the tabs it
+    /// reloads into.
+    pub async fn set_css(&self, page: Option<Page>, css: &str) {
+        *self.css.lock().unwrap() = Some(css.to_string());
+        if let Some(page) = page {
+            set_style(&page, INLINE_CSS_ID, css).await;
+        }
+    }
+
+    /// Apply css set by a config reload to a freshly opened tab.
+    async fn reapply_css(&self, page: &Page) {
+        let css = self.css.lock().unwrap().clone();
+        if let Some(css) = css {
+            set_style(page, INLINE_CSS_ID, &css).await;
+        }
+    }
+
+    /// Receive the page's audio until the receiver is dropped (overlays with `audio`).
+    pub fn subscribe_audio(&self) -> Receiver<AudioBlock> {
+        let (tx, rx) = std::sync::mpsc::channel();
+        self.audio.lock().unwrap().push(tx);
+        rx
+    }
+
+    /// State of the page's audio and the blocks received, for status reporting.
+    pub fn audio_status(&self) -> (Arc<Mutex<SourceState>>, Arc<Mutex<u64>>) {
+        (self.audio_state.clone(), self.audio_blocks.clone())
+    }
+
+    fn publish_audio(&self, block: AudioBlock, url: &str) {
+        {
+            let mut state = self.audio_state.lock().unwrap();
+            if *state == SourceState::Lost {
+                tracing::info!("Browser overlay {}: audio is back", url);
+            }
+            *state = SourceState::Connected;
+        }
+        *self.audio_blocks.lock().unwrap() += 1;
+        self.audio
+            .lock()
+            .unwrap()
+            .retain(|tx| tx.send(block.clone()).is_ok());
+    }
+
+    /// The page has sent no audio for `LOSS_TIMEOUT`.
+    fn audio_stopped(&self, url: &str) {
+        let mut state = self.audio_state.lock().unwrap();
+        if *state == SourceState::Connected {
+            tracing::warn!("Browser overlay {}: audio stopped", url);
+            *state = SourceState::Lost;
+        }
+    }
+
+    fn publish(&self, frame: RgbaImage) {
+        let number = NEXT_FRAME.fetch_add(1, Ordering::Relaxed);
+        let slots = self.slots.lock().unwrap();
+        if let Some((last, others)) = slots.split_last() {
+            for slot in others {
+                *slot.lock().unwrap() = Some((frame.clone(), number));
+            }
+            *last.lock().unwrap() = Some((frame, number));
+        }
+    }
+
+    fn add_slot(&self) -> FrameSlot {
+        let slot = FrameSlot::default();
+        self.slots.lock().unwrap().push(slot.clone());
+        slot
+    }
+
+    /// Remove a channel's slot, returning how many channels are left.
+    fn remove_slot(&self, slot: &FrameSlot) -> usize {
+        let mut slots = self.slots.lock().unwrap();
+        slots.retain(|s| !Arc::ptr_eq(s, slot));
+        slots.len()
+    }
+}
+
+/// An overlay's tab and capture task, shared by the channels showing it.
+struct OverlayCapture {
+    share: OverlayShare,
+    loaded: Arc<Mutex<bool>>,
+    error: Arc<Mutex<Option<String>>>,
+    page: Arc<Mutex<Option<Page>>>,
+    capture: Arc<Mutex<CaptureStats>>,
+    cpu_budget: Option<Arc<Mutex<CpuBudgetStatus>>>,
+    last_error: ErrorSlot,
+    /// Stops the capture once no channel shows the overlay
+    cancel: CancellationToken,
+}
+
+/// Starts browser overlays. Channels showing the same page at the same size
+/// share one tab and capture; each still gets every frame in its own buffer.
+#[derive(Clone)]
+pub struct OverlayManager {
+    pages: Arc<PagePool>,
+    captures: Arc<Mutex<HashMap<String, Arc<OnceCell<Arc<OverlayCapture>>>>>>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureHealth {
+    /// No transparency anomalies in the last window
+    #[default]
+    Good,
+    /// Occasional anomalies — the layer may flicker now and then
+    Degraded,
+    /// Frequent anomalies — consider `capture_mode = "screenshot"`
+    Poor,
+}
+
+/// Capture diagnostics of one overlay, shared with status reporting.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CaptureStats {
+    pub health: CaptureHealth,
+    /// Screencast frames received from Chromium
+    pub screencast_frames: u64,
+    /// Screencast frames dropped by the quality gate
+    pub discarded_frames: u64,
+    /// Screencast frames with broken partial alpha, or whose transparency
+    /// disagrees with the last screenshot (the cause of flicker)
+    pub alpha_anomalies: u64,
+    /// Direct screenshots taken
+    pub screenshots: u64,
+}
+
+impl CaptureStats {
+    /// Zero the counters, keeping the current health.
+    pub fn reset_counters(&mut self) {
+        *self = Self {
+            health: self.health,
+            ..Self::default()
+        };
+    }
+}
+
+/// Classifies captured frames and keeps `CaptureStats` up to date.
+struct CaptureMonitor {
+    stats: Arc<Mutex<CaptureStats>>,
+    /// The overlay's last error, for failures capture recovers from
+    last_error: ErrorSlot,
+    url: String,
+    /// Fraction of transparent pixels in the last direct screenshot
+    screenshot_transparency: Option<f32>,
+    window_start: std::time::Instant,
+    window_frames: u32,
+    window_anomalies: u32,
+}
+
+/// Summary of a frame's alpha channel.
+struct AlphaProfile {
+    /// Any opaque pixel that isn't pure white (real content, e.g. video)
+    opaque_content: bool,
+    max_alpha: u8,
+    transparent_fraction: f32,
+}
+
+impl AlphaProfile {
+    fn of(img: &RgbaImage) -> Self {
+        let mut opaque_content = false;
+        let mut max_alpha = 0;
+        let mut transparent = 0usize;
+        for p in img.pixels() {
+            let [r, g, b, a] = p.0;
+            max_alpha = max_alpha.max(a);
+            if a < 16 {
+                transparent += 1;
+            } else if a > 128 && !(r == 255 && g == 255 && b == 255) {
+                opaque_content = true;
+            }
+        }
+        let total = (img.width() as usize * img.height() as usize).max(1);
+        Self {
+            opaque_content,
+            max_alpha,
+            transparent_fraction: transparent as f32 / total as f32,
+        }
+    }
+}
+
+impl CaptureMonitor {
+    fn new(stats: Arc<Mutex<CaptureStats>>, last_error: ErrorSlot, url: &str) -> Self {
+        Self {
+            stats,
+            last_error,
+            url: url.to_string(),
+            screenshot_transparency: None,
+            window_start: std::time::Instant::now(),
+            window_frames: 0,
+            window_anomalies: 0,
+        }
+    }
+
+    fn screenshot(&mut self, img: &RgbaImage) {
+        self.screenshot_transparency = Some(AlphaProfile::of(img).transparent_fraction);
+        self.stats.lock().unwrap().screenshots += 1;
+        // Static pages send few screencast frames; let the health recover anyway
+        if self.window_start.elapsed() >= HEALTH_WINDOW {
+            self.update_health();
+        }
+    }
+
+    /// Record a screencast frame; returns whether it should be shown.
+    fn screencast(&mut self, img: &RgbaImage) -> bool {
+        let profile = AlphaProfile::of(img);
+        // Broken alpha: the whole frame is faintly visible (alpha ~5-15) instead of transparent
+        let broken_alpha = profile.max_alpha > 0 && profile.max_alpha <= 128;
+        // The screenshot shows a mostly transparent page but this frame is mostly opaque:
+        // showing it flips the layer between see-through and solid
+        let mismatch = profile.opaque_content
+            && self
+                .screenshot_transparency
+                .is_some_and(|t| t > 0.5 && profile.transparent_fraction < 0.05);
+        let anomaly = broken_alpha || mismatch;
+
+        {
+            let mut stats = self.stats.lock().unwrap();
+            stats.screencast_frames += 1;
+            if !profile.opaque_content {
+                stats.discarded_frames += 1;
+            }
+            if anomaly {
+                stats.alpha_anomalies += 1;
+            }
+        }
+
+        self.window_frames += 1;
+        if anomaly {
+            self.window_anomalies += 1;
+        }
+        if self.window_start.elapsed() >= HEALTH_WINDOW {
+            self.update_health();
+        }
+
+        profile.opaque_content
+    }
+
+    fn update_health(&mut self) {
+        let ratio = self.window_anomalies as f32 / self.window_frames.max(1) as f32;
+        let health = if self.window_anomalies == 0 {
+            CaptureHealth::Good
+        } else if ratio < 0.25 {
+            CaptureHealth::Degraded
+        } else {
+            CaptureHealth::Poor
+        };
+        let mut stats = self.stats.lock().unwrap();
+        if health == CaptureHealth::Poor && stats.health != CaptureHealth::Poor {
+            tracing::warn!(
+                "Browser overlay {}: {} of {} screencast frames had alpha anomalies; \
+                 capture_mode = \"screenshot\" avoids the flicker",
+                self.url,
+                self.window_anomalies,
+                self.window_frames
+            );
+        }
+        stats.health = health;
+        drop(stats);
+
+        self.window_start = std::time::Instant::now();
+        self.window_frames = 0;
+        self.window_anomalies = 0;
+    }
+}
+
+/// Synthetic input sent to an overlay page through the API.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Interaction {
+    /// Left click at a point, or at the centre of the first element matching `selector`
+    Click {
+        #[serde(default)]
+        x: Option<f64>,
+        #[serde(default)]
+        y: Option<f64>,
+        #[serde(default)]
+        selector: Option<String>,
+    },
+    /// Insert text into the focused element
+    Type { text: String },
+    /// Press and release a named key, e.g. "Enter", "Escape", "ArrowDown"
+    Key { key: String },
+    /// Mouse wheel at a point (viewport centre by default)
+    Scroll {
+        #[serde(default)]
+        x: Option<f64>,
+        #[serde(default)]
+        y: Option<f64>,
+        #[serde(default)]
+        delta_x: f64,
+        #[serde(default)]
+        delta_y: f64,
+    },
 }
 
-impl BrowserOverlay {
+/// Overlays with equal keys can share a capture: everything that affects the
+/// page or how it's captured counts, but not how a channel layers the result.
+fn share_key(cfg: &BrowserOverlayConfig) -> String {
+    let mut page_cfg = cfg.clone();
+    page_cfg.z_index = 0;
+    page_cfg.opacity = 1.0;
+    page_cfg.crop = None;
+    page_cfg.placement = None;
+    page_cfg.rotation = 0.0;
+    page_cfg.skew_x = 0.0;
+    page_cfg.skew_y = 0.0;
+    page_cfg.filters = Vec::new();
+    page_cfg.luma_key = None;
+    page_cfg.audio_gain_db = 0.0;
+    format!("{:?}", page_cfg)
+}
+
+impl OverlayManager {
+    pub fn new(pages: &Arc<PagePool>) -> Self {
+        Self {
+            pages: pages.clone(),
+            captures: Arc::default(),
+        }
+    }
+
+    /// Show an overlay on a channel until `cancel` is cancelled, loading its
+    /// page unless another channel already shows it.
     pub async fn start(
-        browser: &Browser,
-        url: &str,
-        width: u32,
-        height: u32,
-        css: &str,
-        reload_interval: u64,
+        &self,
+        cfg: &BrowserOverlayConfig,
         cancel: CancellationToken,
-    ) -> Result<Self> {
-        let latest_frame: Arc<Mutex<Option<RgbaImage>>> = Arc::new(Mutex::new(None));
+    ) -> Result<BrowserOverlay> {
+        let key = share_key(cfg);
+        loop {
+            let cell = self
+                .captures
+                .lock()
+                .unwrap()
+                .entry(key.clone())
+                .or_default()
+                .clone();
+            let capture = cell
+                .get_or_try_init(|| async {
+                    OverlayCapture::start(&self.pages, cfg).await.map(Arc::new)
+                })
+                .await?
+                .clone();
+
+            // Join under the map lock, so the capture can't be retired in between
+            let captures = self.captures.lock().unwrap();
+            if capture.cancel.is_cancelled() {
+                continue;
+            }
+            let slot = capture.share.add_slot();
+            drop(captures);
+            if capture.share.channels() > 1 {
+                tracing::info!(
+                    "Browser overlay {}: sharing the capture with another channel",
+                    cfg.url
+                );
+                capture.share.refresh.notify_one();
+            }
+
+            let manager = self.clone();
+            let (key, joined, leaving) = (key.clone(), capture.clone(), slot.clone());
+            tokio::spawn(async move {
+                cancel.cancelled().await;
+                manager.leave(&key, &joined, &leaving);
+            });
+
+            return Ok(BrowserOverlay {
+                latest_frame: slot,
+                loaded: capture.loaded.clone(),
+                error: capture.error.clone(),
+                page: capture.page.clone(),
+                capture: capture.capture.clone(),
+                cpu_budget: capture.cpu_budget.clone(),
+                last_error: capture.last_error.clone(),
+                share: capture.share.clone(),
+            });
+        }
+    }
+
+    /// Take a channel off a capture, stopping the capture with its last channel.
+    fn leave(&self, key: &str, capture: &Arc<OverlayCapture>, slot: &FrameSlot) {
+        let mut captures = self.captures.lock().unwrap();
+        if capture.share.remove_slot(slot) > 0 {
+            return;
+        }
+        capture.cancel.cancel();
+        if captures
+            .get(key)
+            .and_then(|cell| cell.get())
+            .is_some_and(|c| Arc::ptr_eq(c, capture))
+        {
+            captures.remove(key);
+        }
+    }
+}
+
+impl OverlayCapture {
+    async fn start(pages: &Arc<PagePool>, cfg: &BrowserOverlayConfig) -> Result<Self> {
+        let cancel = CancellationToken::new();
+        let mut hasher = DefaultHasher::new();
+        share_key(cfg).hash(&mut hasher);
+        let share = OverlayShare {
+            source: hasher.finish(),
+            ..Default::default()
+        };
         let loaded: Arc<Mutex<bool>> = Arc::new(Mutex::new(false));
+        let error: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
+        let current_page: Arc<Mutex<Option<Page>>> = Arc::new(Mutex::new(None));
+        let capture: Arc<Mutex<CaptureStats>> = Arc::new(Mutex::new(CaptureStats::default()));
+        let last_error: ErrorSlot = ErrorSlot::default();
 
-        let frame_ref = latest_frame.clone();
+        let share_ref = share.clone();
         let loaded_ref = loaded.clone();
+        let error_ref = error.clone();
+        let page_ref = current_page.clone();
+        let last_error_ref = last_error.clone();
+        let monitor = CaptureMonitor::new(capture.clone(), last_error.clone(), &cfg.url);
+
+        let page = match open_with_retry(pages, cfg, &cancel).await {
+            Ok(page) => Some(page),
+            Err(e) if cfg.on_load_failure == OverlayLoadFailure::Continue => {
+                tracing::error!(
+                    "Browser overlay {} failed to load, continuing without it: {}",
+                    cfg.url,
+                    e
+                );
+                *error.lock().unwrap() = Some(e.to_string());
+                record(&last_error, ErrorCode::BrowserLoadFailed, &e);
+                None
+            }
+            Err(e) => {
+                return Err(anyhow::anyhow!(
+                    "Browser overlay {} failed to load: {}",
+                    cfg.url,
+                    e
+                ))
+            }
+        };
+        if page.is_some() {
+            *loaded_ref.lock().unwrap() = true;
+            tracing::info!("Browser overlay loaded: {}", cfg.url);
+        }
+
+        let cpu_budget = cfg.cpu_budget.as_ref().map(|budget| {
+            let status = Arc::new(Mutex::new(CpuBudgetStatus {
+                max_percent: budget.max_percent,
+                ..Default::default()
+            }));
+            cpu_budget::spawn(
+                budget,
+                &cfg.url,
+                current_page.clone(),
+                status.clone(),
+                cancel.clone(),
+            );
+            status
+        });
+
+        let pages = pages.clone();
+        let cfg = cfg.clone();
+
+        let cancel_ref = cancel.clone();
+        tokio::spawn(async move {
+            // Failed at startup: keep retrying in the background until it loads
+            let page = match page {
+                Some(page) => page,
+                None => match retry_until_loaded(&pages, &cfg, &error_ref, &cancel_ref).await {
+                    Some(page) => {
+                        *error_ref.lock().unwrap() = None;
+                        *loaded_ref.lock().unwrap() = true;
+                        tracing::info!("Browser overlay loaded after retrying: {}", cfg.url);
+                        page
+                    }
+                    None => return,
+                },
+            };
+            *page_ref.lock().unwrap() = Some(page.clone());
+            if let Err(e) = capture_loop(
+                &pages, page, &cfg, share_ref, &page_ref, monitor, cancel_ref,
+            )
+            .await
+            {
+                tracing::error!("Browser overlay error: {}", e);
+                record(&last_error_ref, ErrorCode::BrowserCaptureFailed, &e);
+            }
+            *page_ref.lock().unwrap() = None;
+        });
+
+        Ok(Self {
+            share,
+            loaded,
+            error,
+            page: current_page,
+            capture,
+            cpu_budget,
+            last_error,
+            cancel,
+        })
+    }
+}
+
+/// Open the overlay page, retrying up to `navigation_retries` more times.
+async fn open_with_retry(
+    pages: &Arc<PagePool>,
+    cfg: &BrowserOverlayConfig,
+    cancel: &CancellationToken,
+) -> Result<Page> {
+    let mut attempt = 0;
+    loop {
+        match open_page(pages, cfg).await {
+            Ok(page) => return Ok(page),
+            Err(e) if attempt < cfg.navigation_retries && !cancel.is_cancelled() => {
+                attempt += 1;
+                tracing::warn!(
+                    "Browser overlay {}: navigation failed ({}), retrying in {}s ({}/{})",
+                    cfg.url,
+                    e,
+                    cfg.retry_delay,
+                    attempt,
+                    cfg.navigation_retries
+                );
+                tokio::select! {
+                    _ = cancel.cancelled() => return Err(e),
+                    _ = tokio::time::sleep(Duration::from_secs(cfg.retry_delay)) => {}
+                }
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Retry an overlay that failed at startup every `retry_delay` seconds.
+/// Returns None if cancelled first.
+async fn retry_until_loaded(
+    pages: &Arc<PagePool>,
+    cfg: &BrowserOverlayConfig,
+    error: &Mutex<Option<String>>,
+    cancel: &CancellationToken,
+) -> Option<Page> {
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => return None,
+            _ = tokio::time::sleep(Duration::from_secs(cfg.retry_delay)) => {}
+        }
+        match open_page(pages, cfg).await {
+            Ok(page) => return Some(page),
+            Err(e) => {
+                tracing::debug!("Browser overlay {}: retry failed: {}", cfg.url, e);
+                *error.lock().unwrap() = Some(e.to_string());
+            }
+        }
+    }
+}
 
-        // Create blank page first, set up autoplay and viewport, then navigate
-        let page = browser.new_page("about:blank").await?;
+/// Create a page with the overlay viewport, autoplay shim and CSS, and navigate it to the URL.
+async fn open_page(pages: &Arc<PagePool>, cfg: &BrowserOverlayConfig) -> Result<Page> {
+    // Read js_file before opening a tab so a missing file leaves nothing behind
+    let script = match cfg.js_file {
+        Some(ref path) => Some(
+            std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("js_file {}: {}", path, e))?,
+        ),
+        None => None,
+    };
+
+    // Start from a blank page, set up autoplay and viewport, then navigate
+    let page = pages.take().await?;
+
+    // Set viewport size via CDP
+    let metrics =
+        SetDeviceMetricsOverrideParams::new(cfg.width as i64, cfg.height as i64, 1.0, false);
+    page.execute(metrics).await?;
 
-        // Set viewport size via CDP
-        let metrics = SetDeviceMetricsOverrideParams::new(width as i64, height as i64, 1.0, false);
-        page.execute(metrics).await?;
+    // Authenticated dashboards: answer basic auth challenges, or send a bearer token
+    if let Some(ref auth) = cfg.basic_auth {
+        page.authenticate(Credentials {
+            username: auth.user.clone(),
+            password: auth.password.clone(),
+        })
+        .await?;
+    }
+    if let Some(ref token) = cfg.bearer_token {
+        let headers = serde_json::json!({ "Authorization": format!("Bearer {}", token) });
+        page.execute(SetExtraHttpHeadersParams::new(Headers::new(headers)))
+            .await?;
+    }
 
-        // Register autoplay fix to run before any page JS on navigation
-        let _ = page
+    // Register autoplay fix to run before any page JS on navigation
+    let _ = page
             .evaluate_on_new_document(r#"
-                // Force all media to autoplay by intercepting play() rejections
+                // Force all media to autoplay by intercepting play() rejections. Media
+                // stays muted unless the overlay's audio is captured.
                 const origPlay = HTMLMediaElement.prototype.play;
                 HTMLMediaElement.prototype.play = function() {
-                    this.muted = true;
+                    if (!window.__ndimixerKeepSound) this.muted = true;
                     return origPlay.call(this).catch(() => {
                         this.muted = true;
                         return origPlay.call(this);
@@ -135,12 +858,12 @@ impl BrowserOverlay {
                     for (const m of mutations) {
                         for (const node of m.addedNodes) {
                             if (node.nodeName === 'VIDEO' || node.nodeName === 'AUDIO') {
-                                node.muted = true;
+                                if (!window.__ndimixerKeepSound) node.muted = true;
                                 node.play().catch(() => {});
                             }
                             if (node.querySelectorAll) {
                                 node.querySelectorAll('video, audio').forEach(el => {
-                                    el.muted = true;
+                                    if (!window.__ndimixerKeepSound) el.muted = true;
                                     el.play().catch(() => {});
                                 });
                             }
@@ -176,99 +899,307 @@ impl BrowserOverlay {
             "#)
             .await;
 
-        // Now navigate to the actual URL
-        page.goto(url).await?;
+    // Audio capture in every frame, reporting back through a binding
+    if cfg.audio {
+        page.execute(AddBindingParams::new(AUDIO_BINDING)).await?;
+        page.evaluate_on_new_document(AUDIO_CAPTURE_JS).await?;
+    }
 
-        // Simulate user clicks to establish "user activation" and hit any play buttons
-        let center_x = width as f64 / 2.0;
-        let center_y = height as f64 / 2.0;
-        let _ = page
+    // Per-overlay script, registered the same way so it runs before the page's own JS
+    if let Some(script) = script {
+        page.evaluate_on_new_document(script).await?;
+    }
+
+    // Now navigate to the actual URL, closing the tab again if it doesn't load
+    let navigation = async { page.goto(&cfg.url).await.map(|_| ()) };
+    let result = if cfg.navigation_timeout > 0 {
+        match tokio::time::timeout(Duration::from_secs(cfg.navigation_timeout), navigation).await {
+            Ok(result) => result.map_err(anyhow::Error::from),
+            Err(_) => Err(anyhow::anyhow!(
+                "navigation timed out after {}s",
+                cfg.navigation_timeout
+            )),
+        }
+    } else {
+        navigation.await.map_err(anyhow::Error::from)
+    };
+    if let Err(e) = result {
+        let _ = page.close().await;
+        return Err(e);
+    }
+
+    // Simulate user clicks to establish "user activation" and hit any play buttons
+    let center_x = cfg.width as f64 / 2.0;
+    let center_y = cfg.height as f64 / 2.0;
+    let _ = page
+        .click(chromiumoxide::layout::Point {
+            x: center_x,
+            y: center_y,
+        })
+        .await;
+
+    // Delayed click — Twitch embeds may take a moment to render their play button
+    let page_ref = page.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(3)).await;
+        let _ = page_ref
             .click(chromiumoxide::layout::Point {
                 x: center_x,
                 y: center_y,
             })
             .await;
+        tokio::time::sleep(Duration::from_secs(3)).await;
+        let _ = page_ref
+            .click(chromiumoxide::layout::Point {
+                x: center_x,
+                y: center_y,
+            })
+            .await;
+    });
 
-        // Delayed click — Twitch embeds may take a moment to render their play button
-        let page_ref = page.clone();
-        tokio::spawn(async move {
-            tokio::time::sleep(Duration::from_secs(3)).await;
-            let _ = page_ref
-                .click(chromiumoxide::layout::Point {
-                    x: center_x,
-                    y: center_y,
-                })
-                .await;
-            tokio::time::sleep(Duration::from_secs(3)).await;
-            let _ = page_ref
-                .click(chromiumoxide::layout::Point {
-                    x: center_x,
-                    y: center_y,
-                })
-                .await;
-        });
-
-        // Inject CSS if provided
-        if !css.is_empty() {
-            let js = format!(
-                r#"
-                const style = document.createElement('style');
-                style.textContent = `{}`;
-                document.head.appendChild(style);
-                "#,
-                css.replace('`', "\\`")
-            );
-            let _ = page.evaluate(js).await;
-        }
+    inject_css(&page, cfg).await;
 
-        *loaded_ref.lock().unwrap() = true;
-        tracing::info!("Browser overlay loaded: {}", url);
+    Ok(page)
+}
 
-        let url_owned = url.to_string();
+/// Binding the audio capture script sends the page's audio through.
+const AUDIO_BINDING: &str = "__ndimixerAudio";
 
-        let task = tokio::spawn(async move {
-            if let Err(e) =
-                capture_loop(page, &url_owned, width, height, reload_interval, frame_ref, cancel)
-                    .await
-            {
-                tracing::error!("Browser overlay error: {}", e);
+/// Runs in every frame of an overlay with `audio`: routes the frame's media
+/// elements through one Web Audio bus and sends it as 16-bit PCM,
+/// `"<sample rate>,<channels>,<base64 planar samples>"`, to `AUDIO_BINDING`.
+const AUDIO_CAPTURE_JS: &str = r#"
+    (() => {
+        window.__ndimixerKeepSound = true;
+        let ctx = null;
+        let bus = null;
+        const hooked = new WeakSet();
+        const send = (buffer) => {
+            if (typeof window.__ndimixerAudio !== 'function') return;
+            const n = buffer.length;
+            const channels = buffer.numberOfChannels;
+            const pcm = new Int16Array(n * channels);
+            for (let c = 0; c < channels; c++) {
+                const data = buffer.getChannelData(c);
+                for (let i = 0; i < n; i++) {
+                    pcm[c * n + i] = Math.max(-1, Math.min(1, data[i])) * 32767;
+                }
             }
-        });
+            const bytes = new Uint8Array(pcm.buffer);
+            let binary = '';
+            for (let i = 0; i < bytes.length; i += 0x8000) {
+                binary += String.fromCharCode.apply(null, bytes.subarray(i, i + 0x8000));
+            }
+            window.__ndimixerAudio(buffer.sampleRate + ',' + channels + ',' + btoa(binary));
+        };
+        const hook = (el) => {
+            if (hooked.has(el)) return;
+            hooked.add(el);
+            if (!ctx) {
+                ctx = new AudioContext();
+                bus = ctx.createGain();
+                const tap = ctx.createScriptProcessor(2048, 2, 2);
+                tap.onaudioprocess = (e) => send(e.inputBuffer);
+                bus.connect(tap);
+                tap.connect(ctx.destination);
+            }
+            try {
+                ctx.createMediaElementSource(el).connect(bus);
+            } catch (e) {}
+            if (ctx.state === 'suspended') ctx.resume();
+        };
+        const play = HTMLMediaElement.prototype.play;
+        HTMLMediaElement.prototype.play = function() {
+            hook(this);
+            return play.call(this);
+        };
+        document.addEventListener('play', (e) => {
+            if (e.target instanceof HTMLMediaElement) hook(e.target);
+        }, true);
+    })();
+"#;
 
+/// How long the frame whose audio is taken must be quiet before another
+/// frame's audio is taken instead.
+const AUDIO_STREAM_SWITCH: Duration = Duration::from_millis(500);
+
+/// An overlay page's audio, arriving through `AUDIO_BINDING`. Frames each send
+/// their own stream; one is taken at a time.
+struct PageAudio {
+    events: EventStream<EventBindingCalled>,
+    /// The frame being taken, and when it last sent audio
+    stream: Option<(ExecutionContextId, Instant)>,
+    idle_check: tokio::time::Interval,
+}
+
+impl PageAudio {
+    async fn listen(page: &Page) -> Result<Self> {
         Ok(Self {
-            latest_frame,
-            loaded,
-            _task: task,
+            events: page.event_listener::<EventBindingCalled>().await?,
+            stream: None,
+            idle_check: tokio::time::interval(Duration::from_secs(1)),
         })
     }
+
+    /// The next block, or None once a second while the page sends no audio.
+    async fn next(&mut self) -> Result<Option<AudioBlock>> {
+        loop {
+            tokio::select! {
+                event = self.events.next() => {
+                    let event = event.ok_or_else(|| anyhow::anyhow!("Audio event stream ended"))?;
+                    if let Some(block) = self.accept(&event) {
+                        return Ok(Some(block));
+                    }
+                }
+                _ = self.idle_check.tick() => {
+                    if self.idle() {
+                        return Ok(None);
+                    }
+                }
+            }
+        }
+    }
+
+    /// The block in `event`, unless it belongs to another frame than the one
+    /// currently taken.
+    fn accept(&mut self, event: &EventBindingCalled) -> Option<AudioBlock> {
+        if event.name != AUDIO_BINDING {
+            return None;
+        }
+        let now = Instant::now();
+        if let Some((id, at)) = self.stream {
+            if id != event.execution_context_id && now - at < AUDIO_STREAM_SWITCH {
+                return None;
+            }
+        }
+        let block = decode_audio(&event.payload)?;
+        self.stream = Some((event.execution_context_id, now));
+        Some(block)
+    }
+
+    /// No audio for `LOSS_TIMEOUT`.
+    fn idle(&self) -> bool {
+        self.stream
+            .is_none_or(|(_, at)| at.elapsed() >= LOSS_TIMEOUT)
+    }
 }
 
-async fn capture_loop(
-    page: chromiumoxide::Page,
-    _url: &str,
-    width: u32,
-    height: u32,
-    reload_interval: u64,
-    latest_frame: Arc<Mutex<Option<RgbaImage>>>,
-    cancel: CancellationToken,
-) -> Result<()> {
-    let b64 = base64::engine::general_purpose::STANDARD;
+/// Parse an `AUDIO_CAPTURE_JS` payload.
+fn decode_audio(payload: &str) -> Option<AudioBlock> {
+    let mut parts = payload.splitn(3, ',');
+    let sample_rate: i32 = parts.next()?.parse().ok()?;
+    let channels: i32 = parts.next()?.parse().ok()?;
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(parts.next()?)
+        .ok()?;
+    if channels <= 0 || bytes.is_empty() || bytes.len() % (2 * channels as usize) != 0 {
+        return None;
+    }
+    let data: Vec<f32> = bytes
+        .chunks_exact(2)
+        .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0)
+        .collect();
+    Some(AudioBlock {
+        sample_rate,
+        channels,
+        samples: data.len() as i32 / channels,
+        data,
+    })
+}
+
+/// Style element ids of the overlay's inline `css` and its `css_file`.
+const INLINE_CSS_ID: &str = "ndimixer-css";
+const CSS_FILE_ID: &str = "ndimixer-css-file";
+
+/// Inject the overlay's inline `css`, then its `css_file`.
+async fn inject_css(page: &Page, cfg: &BrowserOverlayConfig) {
+    if !cfg.css.is_empty() {
+        set_style(page, INLINE_CSS_ID, &cfg.css).await;
+    }
+    if let Some(ref path) = cfg.css_file {
+        match std::fs::read_to_string(path) {
+            Ok(css) => inject_css_file(page, &css).await,
+            Err(e) => tracing::warn!("Browser overlay css_file {}: {}", path, e),
+        }
+    }
+}
+
+/// Set the `css_file` stylesheet, replacing the one injected before.
+async fn inject_css_file(page: &Page, css: &str) {
+    set_style(page, CSS_FILE_ID, css).await;
+}
+
+/// Set the text of the page's style element `id`, creating it if needed.
+async fn set_style(page: &Page, id: &str, css: &str) {
+    let js = format!(
+        r#"
+            (() => {{
+                let style = document.getElementById({id});
+                if (!style) {{
+                    style = document.createElement('style');
+                    style.id = {id};
+                    document.head.appendChild(style);
+                }}
+                style.textContent = {css};
+            }})();
+            "#,
+        id = serde_json::to_string(id).unwrap_or_default(),
+        css = serde_json::to_string(css).unwrap_or_default()
+    );
+    let _ = page.evaluate(js).await;
+}
+
+/// Hand the page the latest value of the overlay's `data`, if it has one yet.
+async fn inject_data(
+    page: &Page,
+    cfg: &BrowserOverlayConfig,
+    data: &Option<watch::Receiver<Option<Value>>>,
+) {
+    let (Some(binding), Some(data)) = (&cfg.data, data) else {
+        return;
+    };
+    let value = data.borrow().clone();
+    if let Some(value) = value {
+        data_binding::inject(page, &binding.global, &value).await;
+    }
+}
 
-    // Initial screenshot with omit_background for correct transparency.
-    // Done BEFORE setting bg override (page.screenshot resets it as side effect).
+fn modified_time(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Decode a base64 PNG from CDP into RGBA.
+///
+/// CDP only delivers PNG, JPEG or WebP — there is no raw or shared-memory pixel
+/// format — so the encode in Chromium can't be skipped. This keeps our side as
+/// cheap as possible: no format sniffing, and no copy when the PNG is already
+/// 8-bit RGBA (always the case with the transparent background override).
+fn decode_frame(data: &str) -> Option<RgbaImage> {
+    let png = base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .ok()?;
+    image::load_from_memory_with_format(&png, ImageFormat::Png)
+        .ok()
+        .map(|img| img.into_rgba8())
+}
+
+/// Initial screenshot with omit_background for correct transparency.
+/// Must run BEFORE setting the bg override (page.screenshot resets it as side effect).
+async fn capture_initial_frame(page: &Page, frames: &OverlayShare) {
     let init_params = ScreenshotParams::builder()
         .format(CaptureScreenshotFormat::Png)
         .omit_background(true)
         .full_page(false)
         .build();
     if let Ok(png_data) = page.screenshot(init_params).await {
-        if let Ok(img) = image::load_from_memory(&png_data) {
-            *latest_frame.lock().unwrap() = Some(img.to_rgba8());
+        if let Ok(img) = image::load_from_memory_with_format(&png_data, ImageFormat::Png) {
+            frames.publish(img.into_rgba8());
         }
     }
+}
 
-    // Set transparent background — persists for screencast AND direct CaptureScreenshot.
-    // Unlike page.screenshot(), direct CaptureScreenshot does NOT reset this.
+/// Set transparent background — persists for screencast AND direct CaptureScreenshot.
+async fn set_transparent_background(page: &Page) {
     let _ = page
         .execute(SetDefaultBackgroundColorOverrideParams {
             color: Some(Rgba {
@@ -279,28 +1210,80 @@ async fn capture_loop(
             }),
         })
         .await;
+}
 
-    // Start screencast — frames used directly for dynamic content (video)
-    let mut stream = page.event_listener::<EventScreencastFrame>().await?;
+/// Subscribe to screencast frames and start the screencast at the overlay size.
+async fn start_screencast(
+    page: &Page,
+    cfg: &BrowserOverlayConfig,
+) -> Result<EventStream<EventScreencastFrame>> {
+    let stream = page.event_listener::<EventScreencastFrame>().await?;
+    // Frames the compositor would skip anyway aren't worth encoding and decoding
     page.execute(
         StartScreencastParams::builder()
             .format(StartScreencastFormat::Png)
-            .max_width(width as i64)
-            .max_height(height as i64)
-            .every_nth_frame(1)
+            .max_width(cfg.width as i64)
+            .max_height(cfg.height as i64)
+            .every_nth_frame(cfg.frame_divisor as i64)
             .build(),
     )
     .await?;
+    Ok(stream)
+}
 
-    tracing::info!("Screencast started ({}x{})", width, height);
+async fn capture_loop(
+    pages: &Arc<PagePool>,
+    mut page: Page,
+    cfg: &BrowserOverlayConfig,
+    frames: OverlayShare,
+    current_page: &Mutex<Option<Page>>,
+    mut monitor: CaptureMonitor,
+    cancel: CancellationToken,
+) -> Result<()> {
+    let (width, height) = (cfg.width, cfg.height);
+
+    frames.reapply_css(&page).await;
+    capture_initial_frame(&page, &frames).await;
+
+    // Unlike page.screenshot(), direct CaptureScreenshot does NOT reset the bg override.
+    set_transparent_background(&page).await;
+
+    // Start screencast — frames used directly for dynamic content (video)
+    let screencast = cfg.capture_mode == CaptureMode::Dual;
+    let mut stream = if screencast {
+        let stream = start_screencast(&page, cfg).await?;
+        tracing::info!("Screencast started ({}x{})", width, height);
+        Some(stream)
+    } else {
+        tracing::info!("Screenshot-only capture ({}x{})", width, height);
+        None
+    };
+
+    // The page's audio, for the channels mixing it
+    let mut audio = if cfg.audio {
+        Some(PageAudio::listen(&page).await?)
+    } else {
+        None
+    };
 
     // Periodic direct CaptureScreenshot for correct transparency on static overlays.
     // Uses CDP directly (not page.screenshot) so bg override is NOT reset.
-    let mut refresh_timer = tokio::time::interval(Duration::from_secs(2));
-    refresh_timer.tick().await;
+    let mut refresh_timer = if cfg.refresh_interval_ms > 0 {
+        Some(tokio::time::interval(Duration::from_millis(
+            cfg.refresh_interval_ms,
+        )))
+    } else {
+        None
+    };
+    if let Some(ref mut timer) = refresh_timer {
+        timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        timer.tick().await;
+    }
 
-    let mut reload_timer = if reload_interval > 0 {
-        Some(tokio::time::interval(Duration::from_secs(reload_interval)))
+    let mut reload_timer = if cfg.reload_interval > 0 {
+        Some(tokio::time::interval(Duration::from_secs(
+            cfg.reload_interval,
+        )))
     } else {
         None
     };
@@ -308,6 +1291,21 @@ async fn capture_loop(
         timer.tick().await;
     }
 
+    // Live reload of css_file
+    let mut css_timer = cfg
+        .css_file
+        .as_ref()
+        .map(|_| tokio::time::interval(CSS_FILE_POLL));
+    let mut css_modified = cfg.css_file.as_deref().and_then(modified_time);
+
+    // Data polled for the page; the poller stops with this loop
+    let data_cancel = cancel.child_token();
+    let _data_guard = data_cancel.clone().drop_guard();
+    let mut data = cfg
+        .data
+        .as_ref()
+        .map(|binding| data_binding::spawn(binding, data_cancel));
+
     loop {
         tokio::select! {
             _ = cancel.cancelled() => {
@@ -324,79 +1322,166 @@ async fn capture_loop(
                     std::future::pending::<tokio::time::Instant>().await
                 }
             } => {
-                tracing::debug!("Browser overlay reloading");
-                let _ = page.execute(StopScreencastParams {}).await;
-                let _ = page.reload().await;
-                tokio::time::sleep(Duration::from_millis(500)).await;
-
-                // Re-set transparent background
-                let _ = page.execute(SetDefaultBackgroundColorOverrideParams {
-                    color: Some(Rgba { r: 0, g: 0, b: 0, a: Some(0.0) }),
-                }).await;
-
-                stream = page.event_listener::<EventScreencastFrame>().await?;
-                page.execute(
-                    StartScreencastParams::builder()
-                        .format(StartScreencastFormat::Png)
-                        .max_width(width as i64)
-                        .max_height(height as i64)
-                        .every_nth_frame(1)
-                        .build(),
-                ).await?;
-
-                tracing::debug!("Screencast restarted after reload");
+                match cfg.reload_mode {
+                    ReloadMode::Reload => {
+                        tracing::debug!("Browser overlay reloading");
+                        let _ = page.execute(StopScreencastParams {}).await;
+                        let _ = page.reload().await;
+                        tokio::time::sleep(Duration::from_millis(500)).await;
+                        inject_css(&page, cfg).await;
+                        frames.reapply_css(&page).await;
+                        inject_data(&page, cfg, &data).await;
+
+                        set_transparent_background(&page).await;
+                        if screencast {
+                            stream = Some(start_screencast(&page, cfg).await?);
+                            tracing::debug!("Screencast restarted after reload");
+                        }
+                    }
+                    ReloadMode::Seamless => {
+                        // Load a fresh page in a second target while the current one keeps
+                        // feeding frames, then swap once the new page is ready.
+                        tracing::debug!("Browser overlay reloading (seamless)");
+                        match open_page(pages, cfg).await {
+                            Ok(new_page) => {
+                                frames.reapply_css(&new_page).await;
+                                inject_data(&new_page, cfg, &data).await;
+                                tokio::time::sleep(Duration::from_millis(500)).await;
+                                capture_initial_frame(&new_page, &frames).await;
+                                set_transparent_background(&new_page).await;
+                                let new_stream = if screencast {
+                                    Some(start_screencast(&new_page, cfg).await?)
+                                } else {
+                                    None
+                                };
+
+                                if cfg.audio {
+                                    audio = Some(PageAudio::listen(&new_page).await?);
+                                }
+                                *current_page.lock().unwrap() = Some(new_page.clone());
+                                let old_page = std::mem::replace(&mut page, new_page);
+                                stream = new_stream;
+                                let _ = old_page.execute(StopScreencastParams {}).await;
+                                let _ = old_page.close().await;
+
+                                tracing::debug!("Seamless reload swapped to new page");
+                            }
+                            Err(e) => {
+                                tracing::warn!(
+                                    "Seamless reload of {} failed, keeping current page: {}",
+                                    cfg.url,
+                                    e
+                                );
+                                record(&monitor.last_error, ErrorCode::BrowserLoadFailed, &e);
+                            }
+                        }
+                    }
+                }
             }
 
-            // Periodic direct screenshot for correct transparency on static overlays.
+            // css_file changed on disk: re-inject it
+            _ = async {
+                if let Some(ref mut timer) = css_timer {
+                    timer.tick().await
+                } else {
+                    std::future::pending::<tokio::time::Instant>().await
+                }
+            } => {
+                if let Some(ref path) = cfg.css_file {
+                    let modified = modified_time(path);
+                    if modified != css_modified {
+                        css_modified = modified;
+                        match std::fs::read_to_string(path) {
+                            Ok(css) => {
+                                inject_css_file(&page, &css).await;
+                                tracing::info!("Browser overlay {}: reloaded {}", cfg.url, path);
+                            }
+                            Err(e) => tracing::warn!("Browser overlay css_file {}: {}", path, e),
+                        }
+                    }
+                }
+            }
+
+            // New data for the page
+            changed = async {
+                match data {
+                    Some(ref mut data) => data.changed().await,
+                    None => std::future::pending().await,
+                }
+            } => {
+                if changed.is_ok() {
+                    inject_data(&page, cfg, &data).await;
+                } else {
+                    data = None;
+                }
+            }
+
+            // Periodic direct screenshot for correct transparency on static overlays,
+            // and one for each channel that starts sharing the overlay.
             // Uses CaptureScreenshot CDP command directly — does NOT reset bg override.
-            _ = refresh_timer.tick() => {
+            _ = async {
+                if let Some(ref mut timer) = refresh_timer {
+                    tokio::select! {
+                        _ = timer.tick() => {}
+                        _ = frames.refresh.notified() => {}
+                    }
+                } else {
+                    frames.refresh.notified().await
+                }
+            } => {
+                // optimize_for_speed: Chromium uses its fastest PNG compression level
                 let params = CaptureScreenshotParams::builder()
                     .format(CaptureScreenshotFormat::Png)
+                    .optimize_for_speed(true)
                     .build();
                 if let Ok(result) = page.execute(params).await {
-                    let data_str: String = result.data.clone().into();
-                    if let Ok(png_bytes) = b64.decode(&data_str) {
-                        if let Ok(img) = image::load_from_memory(&png_bytes) {
-                            *latest_frame.lock().unwrap() = Some(img.to_rgba8());
-                        }
+                    if let Some(rgba) = decode_frame(result.data.as_ref()) {
+                        monitor.screenshot(&rgba);
+                        frames.publish(rgba);
                     }
                 }
             }
 
+            // Page audio — fan it out to the channels mixing it
+            block = async {
+                match audio {
+                    Some(ref mut audio) => audio.next().await,
+                    None => std::future::pending().await,
+                }
+            } => {
+                match block? {
+                    Some(block) => frames.publish_audio(block, &cfg.url),
+                    None => frames.audio_stopped(&cfg.url),
+                }
+            }
+
             // Screencast frame — use directly only if it has real opaque content (video).
             // Discard frames with broken alpha or white-only backgrounds.
-            frame_event = stream.next() => {
+            frame_event = async {
+                match stream {
+                    Some(ref mut stream) => stream.next().await,
+                    None => std::future::pending().await,
+                }
+            } => {
                 match frame_event {
                     Some(event) => {
-                        let session_id = event.session_id;
-
-                        let data_str: String = event.data.clone().into();
-                        if let Ok(png_bytes) = b64.decode(&data_str) {
-                            if let Ok(img) = image::load_from_memory(&png_bytes) {
-                                let rgba = img.to_rgba8();
-
-                                // Quality gate: only use frame if it has opaque non-white
-                                // content (e.g. video). This filters out:
-                                // - Broken-alpha frames (screencast transparency bug, alpha 5-15)
-                                // - White-bg frames (after screenshot resets bg override)
-                                // - Empty transparent frames
-                                let has_opaque_content = rgba.pixels().any(|p| {
-                                    p.0[3] > 128
-                                        && !(p.0[0] == 255 && p.0[1] == 255 && p.0[2] == 255)
-                                });
+                        // Ack first so Chromium encodes the next frame while we decode this one
+                        let _ = page
+                            .execute(ScreencastFrameAckParams::new(event.session_id))
+                            .await;
 
-                                if has_opaque_content {
-                                    *latest_frame.lock().unwrap() = Some(rgba);
-                                }
+                        if let Some(rgba) = decode_frame(event.data.as_ref()) {
+                            // Quality gate: only use frame if it has opaque non-white
+                            // content (e.g. video). This filters out:
+                            // - Broken-alpha frames (screencast transparency bug, alpha 5-15)
+                            // - White-bg frames (after screenshot resets bg override)
+                            // - Empty transparent frames
+                            if monitor.screencast(&rgba) {
+                                frames.publish(rgba);
                             }
                         }
-
-                        let _ = page.execute(ScreencastFrameAckParams::new(session_id)).await;
-                    }
-                    None => {
-                        tracing::warn!("Screencast event stream ended");
-                        break;
                     }
+                    None => anyhow::bail!("Screencast event stream ended"),
                 }
             }
         }
@@ -404,3 +1489,78 @@ async fn capture_loop(
 
     Ok(())
 }
+
+/// Navigate an overlay's live tab to another URL. The configured URL comes back
+/// on the next reload.
+pub async fn navigate(page: &Page, url: &str) -> Result<()> {
+    tokio::time::timeout(Duration::from_secs(30), page.goto(url))
+        .await
+        .map_err(|_| anyhow::anyhow!("navigation to {} timed out", url))??;
+    Ok(())
+}
+
+/// Send a synthetic click, keystroke or scroll to an overlay page.
+/// `viewport` is the overlay's size, used for default scroll coordinates.
+pub async fn interact(page: &Page, viewport: (u32, u32), interaction: &Interaction) -> Result<()> {
+    match interaction {
+        Interaction::Click {
+            selector: Some(selector),
+            ..
+        } => {
+            page.find_element(selector.as_str())
+                .await
+                .map_err(|e| anyhow::anyhow!("selector '{}': {}", selector, e))?
+                .click()
+                .await?;
+        }
+        Interaction::Click {
+            x: Some(x),
+            y: Some(y),
+            ..
+        } => {
+            page.click(chromiumoxide::layout::Point { x: *x, y: *y })
+                .await?;
+        }
+        Interaction::Click { .. } => anyhow::bail!("click needs either x and y, or a selector"),
+        Interaction::Type { text } => {
+            page.execute(InsertTextParams::new(text.clone())).await?;
+        }
+        Interaction::Key { key } => {
+            let def = chromiumoxide::keys::get_key_definition(key)
+                .ok_or_else(|| anyhow::anyhow!("unknown key '{}'", key))?;
+            // Keys that produce text need a keyDown with text, others a rawKeyDown
+            let text = def.text.or((def.key.len() == 1).then_some(def.key));
+            let mut down = DispatchKeyEventParams::builder()
+                .key(def.key)
+                .code(def.code)
+                .windows_virtual_key_code(def.key_code)
+                .native_virtual_key_code(def.key_code);
+            let up = down.clone().r#type(DispatchKeyEventType::KeyUp);
+            down = match text {
+                Some(text) => down.r#type(DispatchKeyEventType::KeyDown).text(text),
+                None => down.r#type(DispatchKeyEventType::RawKeyDown),
+            };
+            page.execute(down.build().map_err(anyhow::Error::msg)?)
+                .await?;
+            page.execute(up.build().map_err(anyhow::Error::msg)?)
+                .await?;
+        }
+        Interaction::Scroll {
+            x,
+            y,
+            delta_x,
+            delta_y,
+        } => {
+            let params = DispatchMouseEventParams::builder()
+                .r#type(DispatchMouseEventType::MouseWheel)
+                .x(x.unwrap_or(viewport.0 as f64 / 2.0))
+                .y(y.unwrap_or(viewport.1 as f64 / 2.0))
+                .delta_x(*delta_x)
+                .delta_y(*delta_y)
+                .build()
+                .map_err(anyhow::Error::msg)?;
+            page.execute(params).await?;
+        }
+    }
+    Ok(())
+}
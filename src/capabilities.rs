@@ -0,0 +1,76 @@
+use serde::Serialize;
+
+use crate::config::{MAX_FILTER_PARAMS, MAX_FILTER_TEXTURES};
+
+/// What this build and host support, for `/capabilities`. Gathered once at
+/// startup; none of it changes while the mixer runs.
+#[derive(Debug, Clone, Serialize)]
+pub struct Capabilities {
+    pub version: String,
+    /// Cargo features compiled in
+    pub features: Vec<&'static str>,
+    /// Compositor in use: "gpu" or "cpu"
+    pub compositor: String,
+    /// Adapter of the GPU compositor (None on the CPU compositor)
+    pub gpu: Option<GpuInfo>,
+    /// NDI runtime version (None if it couldn't be read)
+    pub ndi_version: Option<String>,
+    /// Chromium product string (None when no channel uses browser overlays)
+    pub chromium_version: Option<String>,
+    pub filters: FilterSupport,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GpuInfo {
+    pub adapter: String,
+    /// Graphics API, e.g. "metal"
+    pub backend: String,
+    pub driver: String,
+}
+
+/// What `[[…filters]]` accept on this instance.
+#[derive(Debug, Clone, Serialize)]
+pub struct FilterSupport {
+    /// Filters run only on the GPU compositor
+    pub available: bool,
+    pub shader_language: &'static str,
+    pub max_params: usize,
+    pub max_textures: usize,
+    /// Image formats filter `textures` can be loaded from, by file extension
+    pub texture_formats: Vec<&'static str>,
+    /// `passes` for multi-pass filters
+    pub multipass: bool,
+}
+
+impl Capabilities {
+    pub fn new(
+        compositor: &str,
+        gpu: Option<GpuInfo>,
+        ndi_version: Option<String>,
+        chromium_version: Option<String>,
+    ) -> Self {
+        let mut features = Vec::new();
+        if cfg!(feature = "gpu") {
+            features.push("gpu");
+        }
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            features,
+            compositor: compositor.to_string(),
+            filters: FilterSupport {
+                available: gpu.is_some(),
+                shader_language: "wgsl",
+                max_params: MAX_FILTER_PARAMS,
+                max_textures: MAX_FILTER_TEXTURES,
+                texture_formats: image::ImageFormat::all()
+                    .filter(|f| f.reading_enabled())
+                    .filter_map(|f| f.extensions_str().first().copied())
+                    .collect(),
+                multipass: true,
+            },
+            gpu,
+            ndi_version,
+            chromium_version,
+        }
+    }
+}
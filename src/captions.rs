@@ -0,0 +1,274 @@
+use ab_glyph::FontArc;
+use anyhow::Result;
+use futures::StreamExt;
+use image::{ImageBuffer, Rgba, RgbaImage};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::config::{CaptionPosition, CaptionsConfig};
+use crate::text::{self, TextStyle};
+
+/// A single timed caption from an SRT/VTT file.
+#[derive(Debug, Clone)]
+struct Cue {
+    start: Duration,
+    end: Duration,
+    text: String,
+}
+
+/// Latest text received from a live caption feed.
+#[derive(Debug, Default)]
+struct LiveCaption {
+    text: String,
+    received_at: Option<Instant>,
+}
+
+/// Caption status info for reporting.
+pub struct CaptionsState {
+    pub source: String,
+    pub showing: Arc<Mutex<bool>>,
+}
+
+/// Burn-in captions layer: picks the caption for the current time and renders it
+/// into a transparent canvas-sized image, re-rendering only when the text changes.
+pub struct CaptionLayer {
+    cues: Vec<Cue>,
+    live: Option<Arc<Mutex<LiveCaption>>>,
+    live_hold: Duration,
+    offset_ms: i64,
+    start: Instant,
+    font: FontArc,
+    style: TextStyle,
+    position: CaptionPosition,
+    margin: u32,
+    width: u32,
+    height: u32,
+    current_text: Option<String>,
+    image: Option<RgbaImage>,
+    /// Bumped whenever the rendered caption changes
+    generation: u64,
+    showing: Arc<Mutex<bool>>,
+}
+
+impl CaptionLayer {
+    pub fn start(
+        cfg: &CaptionsConfig,
+        width: u32,
+        height: u32,
+        cancel: CancellationToken,
+    ) -> Result<Self> {
+        let font = text::load_font(cfg.font.as_deref().unwrap_or(text::DEFAULT_FONT))?;
+
+        let cues = match cfg.file {
+            Some(ref path) => {
+                let content = std::fs::read_to_string(path)
+                    .map_err(|e| anyhow::anyhow!("Failed to read captions {}: {}", path, e))?;
+                let cues = parse_cues(&content);
+                tracing::info!("Loaded {} caption cues from {}", cues.len(), path);
+                cues
+            }
+            None => Vec::new(),
+        };
+
+        let live = cfg.websocket.as_ref().map(|url| {
+            let live = Arc::new(Mutex::new(LiveCaption::default()));
+            let live_ref = live.clone();
+            let url = url.clone();
+            tokio::spawn(async move {
+                live_caption_loop(&url, live_ref, cancel).await;
+            });
+            live
+        });
+
+        Ok(Self {
+            cues,
+            live,
+            live_hold: Duration::from_millis(cfg.live_hold_ms),
+            offset_ms: cfg.offset_ms,
+            start: Instant::now(),
+            font,
+            style: TextStyle {
+                size: cfg.font_size,
+                color: text::parse_color(&cfg.color).unwrap_or([255, 255, 255, 255]),
+                background: text::parse_color(&cfg.background).unwrap_or([0, 0, 0, 0]),
+                padding: (cfg.font_size / 4.0) as u32,
+            },
+            position: cfg.position,
+            margin: cfg.margin,
+            width,
+            height,
+            current_text: None,
+            image: None,
+            generation: 0,
+            showing: Arc::new(Mutex::new(false)),
+        })
+    }
+
+    pub fn showing(&self) -> Arc<Mutex<bool>> {
+        self.showing.clone()
+    }
+
+    /// Returns the rendered caption for the current time and its generation,
+    /// or None when nothing is showing.
+    pub fn frame(&mut self) -> Option<(&RgbaImage, u64)> {
+        let text = self.current_caption();
+
+        if text != self.current_text {
+            *self.showing.lock().unwrap() = text.is_some();
+            self.image = text.as_deref().map(|t| self.render(t));
+            self.current_text = text;
+            self.generation += 1;
+        }
+
+        self.image.as_ref().map(|img| (img, self.generation))
+    }
+
+    fn current_caption(&self) -> Option<String> {
+        if let Some(ref live) = self.live {
+            let live = live.lock().unwrap();
+            return live
+                .received_at
+                .filter(|at| at.elapsed() < self.live_hold && !live.text.is_empty())
+                .map(|_| live.text.clone());
+        }
+
+        let elapsed = self.start.elapsed().as_millis() as i64 - self.offset_ms;
+        if elapsed < 0 {
+            return None;
+        }
+        let now = Duration::from_millis(elapsed as u64);
+        self.cues
+            .iter()
+            .find(|c| c.start <= now && now < c.end)
+            .map(|c| c.text.clone())
+    }
+
+    fn render(&self, caption: &str) -> RgbaImage {
+        let mut img: RgbaImage =
+            ImageBuffer::from_pixel(self.width, self.height, Rgba([0, 0, 0, 0]));
+        let max_width = self.width.saturating_sub(self.margin * 2) as f32;
+        let lines = text::wrap(&self.font, self.style.size, caption, max_width);
+        let (block_w, block_h) = text::block_size(&self.font, &self.style, &lines);
+
+        let x = (self.width as i32 - block_w as i32) / 2;
+        let y = match self.position {
+            CaptionPosition::Top => self.margin as i32,
+            CaptionPosition::Bottom => self.height as i32 - self.margin as i32 - block_h as i32,
+        };
+        text::draw_lines(&mut img, &self.font, &self.style, &lines, x, y);
+        img
+    }
+}
+
+/// Receive live captions from a WebSocket feed, reconnecting until cancelled.
+/// Each text message replaces the current caption; either plain text or
+/// JSON `{"text": "..."}` is accepted, and an empty text clears the caption.
+async fn live_caption_loop(url: &str, live: Arc<Mutex<LiveCaption>>, cancel: CancellationToken) {
+    loop {
+        if cancel.is_cancelled() {
+            break;
+        }
+
+        match tokio_tungstenite::connect_async(url).await {
+            Ok((mut ws, _)) => {
+                tracing::info!("Caption feed connected: {}", url);
+                loop {
+                    tokio::select! {
+                        _ = cancel.cancelled() => return,
+                        msg = ws.next() => match msg {
+                            Some(Ok(tokio_tungstenite::tungstenite::Message::Text(payload))) => {
+                                let payload = payload.to_string();
+                                let caption = serde_json::from_str::<serde_json::Value>(&payload)
+                                    .ok()
+                                    .and_then(|v| v.get("text")?.as_str().map(String::from))
+                                    .unwrap_or(payload);
+                                let mut live = live.lock().unwrap();
+                                live.text = caption.trim().to_string();
+                                live.received_at = Some(Instant::now());
+                            }
+                            Some(Ok(_)) => {}
+                            Some(Err(e)) => {
+                                tracing::warn!("Caption feed {} error: {}", url, e);
+                                break;
+                            }
+                            None => {
+                                tracing::warn!("Caption feed {} closed", url);
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                tracing::debug!("Caption feed {} connect failed: {}", url, e);
+            }
+        }
+
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = tokio::time::sleep(Duration::from_secs(2)) => {}
+        }
+    }
+}
+
+/// Parse SRT or WebVTT content into cues. Formatting tags are stripped.
+fn parse_cues(content: &str) -> Vec<Cue> {
+    let content = content.replace("\r\n", "\n");
+    let mut cues = Vec::new();
+
+    for block in content.split("\n\n") {
+        let mut lines = block.lines().skip_while(|l| !l.contains("-->"));
+        let Some(timing) = lines.next() else {
+            continue;
+        };
+        let mut parts = timing.split("-->");
+        let start = parts.next().and_then(|s| parse_timestamp(s.trim()));
+        // VTT cue settings (e.g. "align:start") follow the end timestamp
+        let end = parts
+            .next()
+            .and_then(|s| s.split_whitespace().next())
+            .and_then(parse_timestamp);
+
+        let text = lines.map(strip_tags).collect::<Vec<_>>().join("\n");
+        if let (Some(start), Some(end)) = (start, end) {
+            if !text.trim().is_empty() {
+                cues.push(Cue { start, end, text });
+            }
+        }
+    }
+
+    cues.sort_by_key(|c| c.start);
+    cues
+}
+
+/// Parse `HH:MM:SS,mmm`, `HH:MM:SS.mmm` or `MM:SS.mmm`.
+fn parse_timestamp(s: &str) -> Option<Duration> {
+    let s = s.replace(',', ".");
+    let (clock, millis) = s.split_once('.').unwrap_or((&s, "0"));
+    let fields: Vec<u64> = clock
+        .split(':')
+        .map(|f| f.parse().ok())
+        .collect::<Option<_>>()?;
+    let seconds = match fields.as_slice() {
+        [h, m, s] => h * 3600 + m * 60 + s,
+        [m, s] => m * 60 + s,
+        _ => return None,
+    };
+    let millis: u64 = format!("{:0<3}", millis).get(..3)?.parse().ok()?;
+    Some(Duration::from_millis(seconds * 1000 + millis))
+}
+
+fn strip_tags(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut in_tag = false;
+    for c in line.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out
+}
@@ -1,16 +1,49 @@
 use anyhow::Result;
-use chromiumoxide::Browser;
 use grafton_ndi::NDI;
 use image::{ImageBuffer, Rgba, RgbaImage};
-use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::{Duration, Instant};
 use tokio_util::sync::CancellationToken;
 
-use crate::browser::BrowserOverlay;
-use crate::compositor::{self, Layer, LayerSource};
-use crate::config::ChannelConfig;
-use crate::ndi_input::NdiInput;
-use crate::ndi_output::NdiOutput;
+use crate::audio::NdiAudioInput;
+use crate::audio_mixer::{AudioMixer, MixLevels};
+use crate::browser::{BrowserOverlay, CaptureStats, OverlayManager, OverlayShare};
+use crate::captions::{CaptionLayer, CaptionsState};
+use crate::chroma_key::ChromaKey;
+use crate::clock::{ClockLayer, ClockState};
+use crate::color::UyvyFrame;
+use crate::compositor::{self, Layer, LayerImage, SharedLayerKey, Transform, Warp};
+use crate::config::{
+    BrowserOverlayConfig, CaptureMode, ChannelConfig, ChromaKeyConfig, CompositorMode, Fit,
+    HookEvent, LossPolicy, LumaKeyConfig, OutputPixelFormat, OverloadPolicy, ReceiveFormat,
+    SceneConfig, SceneLayerConfig, Settings, TransitionConfig, WarmupMode,
+};
+use crate::cpu_budget::CpuBudgetStatus;
+use crate::errors::{record, ErrorCode, ErrorSlot};
+use crate::gpu_recovery::GpuHealth;
+use crate::hooks::Hooks;
+use crate::key_fill::KeyInput;
+#[cfg(feature = "gpu")]
+use crate::labels::SourceLabels;
+use crate::luma_key::LumaKey;
+use crate::ndi_input::{
+    CaptionTap, FrameProcessing, InputFormat, InputManager, InputShare, InputTally, NdiFrame,
+    NdiInput, SourceState,
+};
+use crate::ndi_output::{NdiOutput, OutputTally, RenameRequest};
+use crate::overload::{OverloadMonitor, OverloadStatus};
+use crate::rate_adapt::{blend_into, FrameRateConverter, Pick, RateAdapter};
+use crate::replay::{Replay, ReplayHandle};
+use crate::resources::{ChannelUsage, FrameRates};
+use crate::schedule::SharedSchedule;
+use crate::scopes::SharedScopes;
+use crate::source::{BrowserSource, ImageSource, LayerId, Source};
+use crate::stats_history::{MinuteRecorder, SharedStatsHistory, StatsHistory};
+use crate::text_overlay::TextOverlay;
+use crate::timer::{SharedTimer, TimerLayer};
+use crate::transition::{Lerp, Transition, Tween};
+use crate::virtual_camera::{VirtualCamera, VirtualCameraStatus};
 
 #[cfg(feature = "gpu")]
 pub type GpuCtxParam = Option<Arc<crate::gpu_context::GpuContext>>;
@@ -18,43 +51,901 @@ pub type GpuCtxParam = Option<Arc<crate::gpu_context::GpuContext>>;
 pub type GpuCtxParam = Option<Arc<()>>;
 
 /// Take the latest frame from a shared buffer (zero-copy swap instead of clone).
-fn take_frame(lock: &Mutex<Option<RgbaImage>>) -> Option<RgbaImage> {
+fn take_frame<T>(lock: &Mutex<Option<T>>) -> Option<T> {
     lock.lock().unwrap().take()
 }
 
+/// Put the captions that came with an input frame on the next output frame,
+/// so overlays don't strip them from the chain.
+fn forward_captions(
+    captions: &mut Option<String>,
+    tap: &Option<Arc<Mutex<CaptionTap>>>,
+    output: &mut NdiOutput,
+) {
+    let (Some(tap), Some(captions)) = (tap, captions.take()) else {
+        return;
+    };
+    output.attach_metadata(captions);
+    tap.lock().unwrap().forwarded += 1;
+}
+
+/// What went out last: the canvas, or the input frame when it was passed through.
+fn last_output<'a>(
+    canvas: &'a RgbaImage,
+    input: &'a Option<NdiFrame>,
+    input_on_air: bool,
+) -> &'a RgbaImage {
+    match input {
+        Some(frame) if input_on_air => &frame.image,
+        _ => canvas,
+    }
+}
+
+/// The image the output last converted, so `output_dedup` can repeat its buffer.
+#[derive(Clone, Copy, PartialEq)]
+enum OutputImage {
+    Slate,
+    Black,
+    /// The last composite, or the input frame passed through in its place
+    Composite,
+}
+
+/// Send `image` as `shown`, or with `output_dedup` re-send the output's last
+/// buffer instead when it already holds that image.
+fn send_output(
+    output: &mut NdiOutput,
+    image: &RgbaImage,
+    shown: OutputImage,
+    last_sent: &mut Option<OutputImage>,
+    dedup: bool,
+) {
+    if dedup && *last_sent == Some(shown) {
+        output.repeat_last();
+    } else {
+        let _ = output.send_frame(image);
+    }
+    *last_sent = Some(shown);
+}
+
+/// Before the passed-through input frame is replaced, move it into the canvas
+/// (no copy) so the canvas holds the last output again.
+fn reclaim_output(input_on_air: &mut bool, canvas: &mut RgbaImage, input: &mut Option<NdiFrame>) {
+    if std::mem::take(input_on_air) {
+        if let Some(frame) = input.take() {
+            *canvas = frame.image;
+        }
+    }
+}
+
+/// Nearest-neighbour scale `src` up to the size of `dst`, into `dst`'s buffer.
+fn upscale_into(src: &RgbaImage, dst: &mut RgbaImage) {
+    let (src_width, src_height) = (src.width() as usize, src.height() as usize);
+    let (dst_width, dst_height) = (dst.width() as usize, dst.height() as usize);
+    let src = src.as_raw();
+    for (y, row) in dst.chunks_exact_mut(dst_width * 4).enumerate() {
+        let start = y * src_height / dst_height * src_width * 4;
+        let src_row = &src[start..start + src_width * 4];
+        for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
+            let from = x * src_width / dst_width * 4;
+            pixel.copy_from_slice(&src_row[from..from + 4]);
+        }
+    }
+}
+
+/// How far a fade begun at `from` has got by `now`, from 0 to 1 (1 without a fade).
+fn fade_progress(from: Option<Instant>, length: Duration, now: Instant) -> f32 {
+    match from {
+        Some(from) if !length.is_zero() => {
+            (now.saturating_duration_since(from).as_secs_f32() / length.as_secs_f32()).min(1.0)
+        }
+        _ => 1.0,
+    }
+}
+
+/// The NDI layer's previous and latest frames blended for rate adaptation.
+enum BlendedFrame {
+    Rgba(RgbaImage),
+    Uyvy(UyvyFrame),
+}
+
+/// Blend the previous and latest NDI frames, reusing `scratch`'s buffer. None if
+/// they differ in kind or size (e.g. the source changed format).
+fn blend_frames(
+    previous: (&Option<NdiFrame>, &Option<UyvyFrame>),
+    latest: (&Option<NdiFrame>, &Option<UyvyFrame>),
+    weight: u16,
+    scratch: Option<BlendedFrame>,
+) -> Option<BlendedFrame> {
+    let mut buf = match scratch {
+        Some(BlendedFrame::Rgba(image)) => image.into_raw(),
+        Some(BlendedFrame::Uyvy(frame)) => frame.data,
+        None => Vec::new(),
+    };
+    match (previous, latest) {
+        ((Some(prev), _), (Some(last), _))
+            if prev.image.dimensions() == last.image.dimensions() =>
+        {
+            blend_into(&mut buf, prev.image.as_raw(), last.image.as_raw(), weight);
+            let (width, height) = last.image.dimensions();
+            ImageBuffer::from_raw(width, height, buf).map(BlendedFrame::Rgba)
+        }
+        ((_, Some(prev)), (_, Some(last)))
+            if (prev.width, prev.height) == (last.width, last.height) =>
+        {
+            blend_into(&mut buf, &prev.data, &last.data, weight);
+            Some(BlendedFrame::Uyvy(UyvyFrame {
+                width: last.width,
+                height: last.height,
+                data: buf,
+                roi: last.roi,
+                fit: last.fit,
+                range: last.range,
+                primaries: last.primaries,
+                timestamp: None,
+                received_at: last.received_at,
+                captions: None,
+            }))
+        }
+        _ => None,
+    }
+}
+
+/// A layer other than the NDI input, with its current mix.
+struct SourceLayer {
+    id: LayerId,
+    source: Box<dyn Source>,
+    opacity: f32,
+    z_index: i32,
+    transform: Transform,
+    /// Applied to each new frame when keying on the CPU
+    luma_key: Option<LumaKey>,
+}
+
+/// Everything a composite depends on besides filter time: each layer's identity,
+/// generation, opacity, z-order and placement, plus the overload mode and
+/// compare view.
+#[derive(PartialEq)]
+struct CompositeKey {
+    layers: Vec<(LayerId, u64, u32, i32, Transform)>,
+    policy: OverloadPolicy,
+    compare: CompareView,
+}
+
+impl CompositeKey {
+    fn new(layers: &[Layer<'_>], policy: OverloadPolicy, compare: CompareView) -> Self {
+        Self {
+            layers: layers
+                .iter()
+                .map(|l| {
+                    (
+                        l.source,
+                        l.generation,
+                        l.opacity.to_bits(),
+                        l.z_index,
+                        l.transform,
+                    )
+                })
+                .collect(),
+            policy,
+            compare,
+        }
+    }
+}
+
+/// Split-screen A/B view of a channel's filters, switched through the control API.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CompareView {
+    pub enabled: bool,
+    /// Divider position as a fraction of the width
+    pub split: f32,
+}
+
+/// What a blanked output shows instead of program, set through the control API.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlankMode {
+    #[default]
+    Black,
+    Slate,
+}
+
+impl BlankMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Black => "black",
+            Self::Slate => "slate",
+        }
+    }
+}
+
 /// Per-overlay status info for reporting.
 pub struct BrowserOverlayState {
     pub url: String,
     pub loaded: Arc<Mutex<bool>>,
+    pub error: Arc<Mutex<Option<String>>>,
     pub filters: Vec<String>,
+    /// Live tab for the interaction API
+    pub page: Arc<Mutex<Option<chromiumoxide::Page>>>,
+    pub viewport: (u32, u32),
+    pub capture_mode: CaptureMode,
+    pub capture: Arc<Mutex<CaptureStats>>,
+    pub cpu_budget: Option<Arc<Mutex<CpuBudgetStatus>>>,
+    pub last_error: ErrorSlot,
+    /// Channels fed by the same capture
+    pub share: OverlayShare,
+}
+
+/// A configured layer, as listed in the terminal UI.
+pub struct LayerInfo {
+    pub source: LayerId,
+    pub label: String,
+    pub z_index: i32,
+    pub group: Option<String>,
+}
+
+/// Layers faded or hidden together, on top of their own opacity.
+#[derive(Debug, Clone)]
+pub struct LayerGroup {
+    pub name: String,
+    pub opacity: Tween<f32>,
+    pub visible: bool,
+    pub members: Vec<LayerId>,
+}
+
+/// Operator mute/solo of a channel's layers (terminal UI).
+#[derive(Debug, Default)]
+pub struct LayerMix {
+    pub muted: Vec<LayerId>,
+    /// Only this layer is drawn while set
+    pub solo: Option<LayerId>,
+    /// Opacity set through the control API, replacing the configured one
+    pub opacity: Vec<(LayerId, Tween<f32>)>,
+    /// Canvas area set through the control API, replacing the configured or scene one
+    pub placement: Vec<(LayerId, Tween<compositor::Rect>)>,
+    /// From `layer_groups`, changed through the control API
+    pub groups: Vec<LayerGroup>,
+    /// Active scene, switched through the control API; layers it doesn't list are hidden
+    pub scene: Option<SceneConfig>,
+    /// Scene switch still easing in from the previous scene
+    transition: Option<SceneTransition>,
+}
+
+/// The scene and overrides a scene switch started from.
+#[derive(Debug)]
+struct SceneTransition {
+    transition: Transition,
+    scene: Option<SceneConfig>,
+    opacity: Vec<(LayerId, Tween<f32>)>,
+    placement: Vec<(LayerId, Tween<compositor::Rect>)>,
+}
+
+impl LayerMix {
+    pub fn is_visible(&self, source: LayerId) -> bool {
+        self.shown(source, Instant::now()) && scene_layer(self.scene.as_ref(), source).is_some()
+    }
+
+    /// Not muted, soloed away or in a hidden group, whatever the scene.
+    fn shown(&self, source: LayerId, now: Instant) -> bool {
+        let shown = match self.solo {
+            Some(solo) => solo == source,
+            None => !self.muted.contains(&source),
+        };
+        shown && self.group_opacity(source, now) > 0.0
+    }
+
+    /// Switch to `scene`, its opacities replacing any set through the control
+    /// API, and placements set through it dropped. With a `transition`, layers
+    /// ease over from the current scene.
+    pub fn set_scene(&mut self, scene: &SceneConfig, transition: Option<Transition>) {
+        let opacity = scene
+            .layers
+            .iter()
+            .filter_map(|l| Some((l.id, Tween::new(l.opacity?))))
+            .collect();
+        let scene_before = self.scene.replace(scene.clone());
+        let opacity_before = std::mem::replace(&mut self.opacity, opacity);
+        let placement_before = std::mem::take(&mut self.placement);
+        self.transition = transition.map(|transition| SceneTransition {
+            transition,
+            scene: scene_before,
+            opacity: opacity_before,
+            placement: placement_before,
+        });
+    }
+
+    /// The layer's opacity and canvas area on the frame at `now`: the
+    /// `configured` ones unless the control API or the active scene replace
+    /// them, eased from the previous scene while a transition runs, and scaled
+    /// by the running input `fade` and the layer's group. None while the layer
+    /// is hidden.
+    pub fn apply(
+        &mut self,
+        source: LayerId,
+        configured: (f32, compositor::Rect),
+        fade: f32,
+        now: Instant,
+    ) -> Option<(f32, compositor::Rect)> {
+        if !self.shown(source, now) {
+            return None;
+        }
+        let current = scene_mix(
+            self.scene.as_ref(),
+            (&mut self.opacity, &mut self.placement),
+            source,
+            configured,
+            now,
+        );
+        let (opacity, dst) = match self.transition {
+            Some(ref mut from) if !from.transition.finished(now) => {
+                let t = from.transition.progress(now);
+                let before = scene_mix(
+                    from.scene.as_ref(),
+                    (&mut from.opacity, &mut from.placement),
+                    source,
+                    configured,
+                    now,
+                );
+                match (before, current) {
+                    (Some((before, dst_before)), Some((after, dst_after))) => {
+                        (before.lerp(after, t), dst_before.lerp(dst_after, t))
+                    }
+                    (Some((before, dst)), None) => (before * (1.0 - t), dst),
+                    (None, Some((after, dst))) => (after * t, dst),
+                    (None, None) => return None,
+                }
+            }
+            _ => current?,
+        };
+        Some((opacity * fade * self.group_opacity(source, now), dst))
+    }
+
+    /// Opacity multiplier of the layer's group at `now`; 0 while the group is hidden.
+    pub fn group_opacity(&self, source: LayerId, now: Instant) -> f32 {
+        match self.groups.iter().find(|g| g.members.contains(&source)) {
+            Some(group) if group.visible => {
+                group.opacity.current(now).unwrap_or(group.opacity.target())
+            }
+            Some(_) => 0.0,
+            None => 1.0,
+        }
+    }
+
+    pub fn toggle_mute(&mut self, source: LayerId) {
+        if let Some(i) = self.muted.iter().position(|s| *s == source) {
+            self.muted.remove(i);
+        } else {
+            self.muted.push(source);
+        }
+    }
+
+    /// Replace the layer's opacity, easing to it over `transition`.
+    pub fn set_opacity(&mut self, source: LayerId, opacity: f32, transition: Option<Transition>) {
+        set_override(&mut self.opacity, source, opacity, transition);
+    }
+
+    /// Move the layer into `placement` over `transition`, or back where it's
+    /// configured (a cut) with None.
+    pub fn set_placement(
+        &mut self,
+        source: LayerId,
+        placement: Option<compositor::Rect>,
+        transition: Option<Transition>,
+    ) {
+        match placement {
+            Some(placement) => set_override(&mut self.placement, source, placement, transition),
+            None => self.placement.retain(|(s, _)| *s != source),
+        }
+    }
+
+    pub fn toggle_solo(&mut self, source: LayerId) {
+        self.solo = if self.solo == Some(source) {
+            None
+        } else {
+            Some(source)
+        };
+    }
+}
+
+/// How `scene` shows a layer: Some(None) without a scene, None if the scene
+/// doesn't list it.
+fn scene_layer(scene: Option<&SceneConfig>, source: LayerId) -> Option<Option<&SceneLayerConfig>> {
+    match scene {
+        Some(scene) => scene.layers.iter().find(|l| l.id == source).map(Some),
+        None => Some(None),
+    }
+}
+
+/// Control API opacity and placement overrides of a channel's layers.
+type Overrides<'a> = (
+    &'a mut [(LayerId, Tween<f32>)],
+    &'a mut [(LayerId, Tween<compositor::Rect>)],
+);
+
+/// The layer's opacity and canvas area under `scene` and `overrides`; None if
+/// the scene doesn't show it.
+fn scene_mix(
+    scene: Option<&SceneConfig>,
+    (opacity, placement): Overrides<'_>,
+    source: LayerId,
+    (configured_opacity, configured_dst): (f32, compositor::Rect),
+    now: Instant,
+) -> Option<(f32, compositor::Rect)> {
+    let layer = scene_layer(scene, source)?;
+    let dst = layer
+        .and_then(|l| l.placement)
+        .map_or(configured_dst, |p| p.rect());
+    Some((
+        overridden(opacity, source, configured_opacity, now),
+        overridden(placement, source, dst, now),
+    ))
+}
+
+/// The layer's value from `overrides` at `now`, `start` if it has none.
+fn overridden<T: Lerp>(
+    overrides: &mut [(LayerId, Tween<T>)],
+    source: LayerId,
+    start: T,
+    now: Instant,
+) -> T {
+    overrides
+        .iter_mut()
+        .find(|(s, _)| *s == source)
+        .map_or(start, |(_, tween)| tween.value(start, now))
+}
+
+fn set_override<T: Lerp>(
+    overrides: &mut Vec<(LayerId, Tween<T>)>,
+    source: LayerId,
+    value: T,
+    transition: Option<Transition>,
+) {
+    match overrides.iter_mut().find(|(s, _)| *s == source) {
+        Some((_, tween)) => tween.retarget(value, transition, Instant::now()),
+        None => overrides.push((source, Tween::towards(value, transition))),
+    }
+}
+
+/// Audio input status info for reporting.
+pub struct AudioInputState {
+    pub source: String,
+    /// Friendly name from `[sources]`
+    pub alias: Option<String>,
+    pub state: Arc<Mutex<SourceState>>,
+    pub frames_received: Arc<Mutex<u64>>,
+}
+
+/// Audio mixer status info for reporting, and its levels for the control API.
+pub struct AudioMixState {
+    pub sample_rate: u32,
+    pub channels: u32,
+    pub levels: Arc<Mutex<MixLevels>>,
+    /// One per mixed source, in config order
+    pub sources: Vec<AudioInputState>,
 }
 
 /// Runtime state for a single channel, used for status reporting.
 pub struct ChannelState {
     pub name: String,
-    pub output_name: String,
+    /// Changes when the output is renamed at runtime
+    pub output_name: Arc<Mutex<String>>,
+    /// Recreates the NDI sender under a new name (see `rename_output`)
+    output_renamer: std::sync::mpsc::Sender<RenameRequest>,
     pub width: u32,
     pub height: u32,
     pub frame_rate: u32,
-    pub ndi_connected: Arc<Mutex<bool>>,
+    /// Configured output picture aspect ratio (None = square pixels)
+    pub picture_aspect_ratio: Option<f32>,
+    pub output_pixel_format: OutputPixelFormat,
+    pub ndi_state: Arc<Mutex<SourceState>>,
     pub ndi_frames_received: Arc<Mutex<u64>>,
-    pub ndi_source: Option<String>,
+    pub ndi_last_error: ErrorSlot,
+    pub ndi_format: Arc<Mutex<Option<InputFormat>>>,
+    /// Tally forwarded to the NDI input's source
+    pub ndi_tally: Option<Arc<Mutex<InputTally>>>,
+    /// Changes when the input is switched to another source
+    pub ndi_source: Option<Arc<Mutex<InputSource>>>,
+    ndi_input: Option<NdiInput>,
+    /// Key source of an external key/fill pair, and its input
+    pub ndi_key: Option<(InputSource, NdiInput)>,
+    /// Receiver behind the NDI input, possibly shared with other channels
+    pub ndi_share: Option<InputShare>,
     pub ndi_filters: Vec<String>,
+    /// How the NDI input is fitted to the canvas
+    pub ndi_fit: Fit,
     pub browser_overlays: Vec<BrowserOverlayState>,
+    /// Configured layers, top of the stack first
+    pub layers: Vec<LayerInfo>,
+    /// `[[channel.scene]]` layer sets the control API switches between
+    pub scenes: Vec<SceneConfig>,
+    /// How switching scenes or the NDI source is rendered
+    pub transition: Option<TransitionConfig>,
+    pub layer_mix: Arc<Mutex<LayerMix>>,
+    /// Layer settings from config reloads, picked up by the render thread
+    live: Arc<Mutex<LiveSettings>>,
     pub channel_filters: Vec<String>,
+    pub compare: Arc<Mutex<CompareView>>,
+    /// Channel filters shown right of the compare divider (empty = unfiltered)
+    pub compare_filters: Vec<String>,
+    /// Latest output scopes (None when `[channel.scopes]` isn't configured)
+    pub scopes: Option<SharedScopes>,
+    pub replay: Option<ReplayHandle>,
+    pub virtual_camera: Option<Arc<Mutex<VirtualCameraStatus>>>,
     pub frames_output: Arc<Mutex<u64>>,
+    /// Frames repeated unchanged by `output_dedup` (None when it's off)
+    pub frames_repeated: Option<Arc<Mutex<u64>>>,
+    pub overload_policy: OverloadPolicy,
+    pub overload: Arc<Mutex<OverloadStatus>>,
+    /// Why the channel last composited without the GPU
+    pub gpu_fallback: ErrorSlot,
+    /// GPU fallback counts and recovery state (GPU compositor only)
+    pub gpu_health: Option<Arc<Mutex<GpuHealth>>>,
+    pub usage: Arc<Mutex<ChannelUsage>>,
+    /// Output and per-layer frame rates
+    pub frame_rates: Arc<Mutex<FrameRates>>,
+    /// Tally and receiver count reported by downstream receivers
+    pub output_tally: Arc<Mutex<OutputTally>>,
+    /// Per-minute aggregates for `/stats`
+    pub stats_history: SharedStatsHistory,
+    pub captions: Option<CaptionsState>,
+    pub clock: Option<ClockState>,
+    pub timer: Option<SharedTimer>,
+    /// Closed captions forwarded from the NDI input (None when passthrough is off)
+    pub cc_passthrough: Option<Arc<Mutex<CaptionTap>>>,
+    pub expose_captions: bool,
+    /// Start of the current counting period (startup or the last counter reset)
+    pub counters_since: Arc<Mutex<Instant>>,
+    /// Emergency slate replacing program output while true
+    pub slate_active: Arc<Mutex<bool>>,
+    /// Output held on black or the slate while layers keep running (None = program)
+    pub output_blank: Arc<Mutex<Option<BlankMode>>>,
+    /// Commands queued for frames of the output timeline
+    pub schedule: SharedSchedule,
+    /// Startup warmup still holding the output (see `warmup`)
+    pub warming_up: Arc<Mutex<bool>>,
+    pub hooks: Arc<Hooks>,
+    /// Zero point of the filter `time` uniform; reset to restart time-based effects
+    pub filter_clock: Arc<Mutex<Instant>>,
+    pub audio_input: Option<AudioInputState>,
+    pub audio_mix: Option<AudioMixState>,
+}
+
+/// The NDI source a channel's input takes.
+#[derive(Debug, Clone)]
+pub struct InputSource {
+    pub name: String,
+    /// Friendly name of the source from `[sources]`
+    pub alias: Option<String>,
+}
+
+impl InputSource {
+    /// The alias if there is one, otherwise the NDI name.
+    pub fn label(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+}
+
+impl ChannelState {
+    /// Recreate the channel's NDI sender under another name. Inputs, overlays
+    /// and the render loop keep running.
+    pub async fn rename_output(&self, output_name: &str) -> Result<()> {
+        let stopping = || anyhow::anyhow!("Channel '{}' is stopping", self.name);
+        let (reply, outcome) = tokio::sync::oneshot::channel();
+        self.output_renamer
+            .send((output_name.to_string(), reply))
+            .map_err(|_| stopping())?;
+        outcome.await.map_err(|_| stopping())??;
+        *self.output_name.lock().unwrap() = output_name.to_string();
+        self.hooks.set_output_name(output_name);
+        Ok(())
+    }
+
+    /// Switch the NDI input to another source without restarting the channel
+    /// (see `NdiInput::switch_source`).
+    pub fn switch_source(&self, source: InputSource) -> Result<()> {
+        let (Some(input), Some(current)) = (&self.ndi_input, &self.ndi_source) else {
+            anyhow::bail!("Channel '{}' has no NDI input", self.name);
+        };
+        input.switch_source(&source.name);
+        *current.lock().unwrap() = source;
+        Ok(())
+    }
+
+    /// Apply the live settings of a reloaded config (see
+    /// `ChannelConfig::copy_live_settings`). Overlays whose css differs from
+    /// `previous` get the new css injected into their tab.
+    pub async fn apply_live(&self, config: &ChannelConfig, previous: &ChannelConfig) {
+        {
+            let mut live = self.live.lock().unwrap();
+            *live = LiveSettings {
+                generation: live.generation + 1,
+                ..LiveSettings::of(config)
+            };
+        }
+        if let Some(ref mix) = self.audio_mix {
+            *mix.levels.lock().unwrap() = MixLevels::of(config);
+        }
+        // Only groups edited in the file, so the control API's changes to the others stay
+        {
+            let mut mix = self.layer_mix.lock().unwrap();
+            for cfg in &config.layer_groups {
+                let before = previous.layer_groups.iter().find(|g| g.name == cfg.name);
+                if before.is_some_and(|b| (b.opacity, b.visible) == (cfg.opacity, cfg.visible)) {
+                    continue;
+                }
+                if let Some(group) = mix.groups.iter_mut().find(|g| g.name == cfg.name) {
+                    (group.opacity, group.visible) = (Tween::new(cfg.opacity), cfg.visible);
+                }
+            }
+        }
+        let overlays = config
+            .all_browser_overlays()
+            .into_iter()
+            .zip(previous.all_browser_overlays())
+            .zip(&self.browser_overlays);
+        for ((cfg, before), overlay) in overlays {
+            if cfg.css != before.css {
+                let page = overlay.page.lock().unwrap().clone();
+                overlay.share.set_css(page, &cfg.css).await;
+            }
+        }
+    }
+}
+
+/// The groups `layers` are in, with their configured opacity and visibility
+/// (fully visible unless listed in `layer_groups`).
+fn layer_groups(config: &ChannelConfig, layers: &[LayerInfo]) -> Vec<LayerGroup> {
+    let mut groups: Vec<LayerGroup> = Vec::new();
+    for layer in layers {
+        let Some(ref name) = layer.group else {
+            continue;
+        };
+        match groups.iter_mut().find(|g| &g.name == name) {
+            Some(group) => group.members.push(layer.source),
+            None => {
+                let configured = config.layer_groups.iter().find(|g| &g.name == name);
+                groups.push(LayerGroup {
+                    name: name.clone(),
+                    opacity: Tween::new(configured.map_or(1.0, |g| g.opacity)),
+                    visible: configured.is_none_or(|g| g.visible),
+                    members: vec![layer.source],
+                });
+            }
+        }
+    }
+    groups
+}
+
+/// Layer settings a config reload changes while the channel runs.
+#[derive(Debug, Clone, Default)]
+struct LiveSettings {
+    /// Bumped on every reload, so the render thread notices
+    generation: u64,
+    /// Opacity and z_index of every layer
+    layers: Vec<(LayerId, f32, i32)>,
+    /// Chroma key settings, for keying on the CPU
+    chroma_key: Option<ChromaKeyConfig>,
+    /// Luma key settings of the layers that have one, for keying on the CPU
+    luma_keys: Vec<(LayerId, LumaKeyConfig)>,
+    #[cfg(feature = "gpu")]
+    filters: crate::gpu_recovery::FilterChains,
+}
+
+impl LiveSettings {
+    fn of(config: &ChannelConfig) -> Self {
+        Self {
+            generation: 0,
+            layers: config
+                .layers()
+                .iter()
+                .map(|l| (l.id, l.opacity, l.z_index))
+                .collect(),
+            chroma_key: config.ndi_input.as_ref().and_then(|c| c.chroma_key.clone()),
+            luma_keys: config
+                .ndi_input
+                .iter()
+                .filter_map(|c| Some((LayerId::Ndi, c.luma_key.clone()?)))
+                .chain(
+                    config
+                        .all_browser_overlays()
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(i, c)| Some((LayerId::Browser(i), c.luma_key.clone()?))),
+                )
+                .collect(),
+            #[cfg(feature = "gpu")]
+            filters: crate::gpu_recovery::FilterChains::of(config),
+        }
+    }
+}
+
+/// A runtime change to a configured channel, carried out by the supervisor.
+#[derive(Debug)]
+pub enum ChannelChange {
+    Enable,
+    Disable,
+    /// Restart the channel at another output resolution
+    Resolution {
+        width: u32,
+        height: u32,
+    },
+    /// Recreate the NDI sender under another name, keeping the pipeline running
+    OutputName(String),
+    /// Switch the NDI input to another source (NDI name or `[sources]` alias)
+    InputSource(String),
+    /// Apply a reloaded config's live settings, keeping the pipeline running
+    Reload(Box<ChannelConfig>),
+}
+
+/// Channel name, the change, and where to send the outcome.
+pub type ChannelRequest = (
+    String,
+    ChannelChange,
+    tokio::sync::oneshot::Sender<Result<()>>,
+);
+
+/// A configured channel that failed to start and isn't running.
+#[derive(Debug, Clone, Serialize)]
+pub struct FailedChannel {
+    pub name: String,
+    pub error: String,
+    /// Start attempts so far
+    pub attempts: u32,
+}
+
+/// The running channels. Channels are enabled, disabled and auto-provisioned at
+/// runtime, so readers take a snapshot rather than holding on to the list.
+#[derive(Clone, Default)]
+pub struct ChannelRegistry {
+    channels: Arc<Mutex<Vec<Arc<ChannelState>>>>,
+    /// Configured channels that are currently disabled
+    disabled: Arc<Mutex<Vec<String>>>,
+    /// Configured channels that failed to start (`on_channel_error` other than abort)
+    failed: Arc<Mutex<Vec<FailedChannel>>>,
+    /// Where channel changes go (see `Supervisor::run`)
+    requests: Arc<OnceLock<tokio::sync::mpsc::UnboundedSender<ChannelRequest>>>,
+}
+
+impl ChannelRegistry {
+    pub fn snapshot(&self) -> Vec<Arc<ChannelState>> {
+        self.channels.lock().unwrap().clone()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.channels
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|ch| ch.name == name)
+    }
+
+    pub fn insert(&self, state: Arc<ChannelState>) {
+        self.channels.lock().unwrap().push(state);
+    }
+
+    pub fn remove(&self, name: &str) -> Option<Arc<ChannelState>> {
+        let mut channels = self.channels.lock().unwrap();
+        let index = channels.iter().position(|ch| ch.name == name)?;
+        Some(channels.remove(index))
+    }
+
+    pub fn disabled(&self) -> Vec<String> {
+        self.disabled.lock().unwrap().clone()
+    }
+
+    pub fn set_disabled(&self, name: &str, disabled: bool) {
+        let mut list = self.disabled.lock().unwrap();
+        list.retain(|n| n != name);
+        if disabled {
+            list.push(name.to_string());
+        }
+    }
+
+    pub fn failed(&self) -> Vec<FailedChannel> {
+        self.failed.lock().unwrap().clone()
+    }
+
+    /// Record a failed start attempt of a configured channel.
+    pub fn record_failure(&self, name: &str, error: &anyhow::Error) {
+        let mut failed = self.failed.lock().unwrap();
+        match failed.iter_mut().find(|f| f.name == name) {
+            Some(entry) => {
+                entry.error = format!("{:#}", error);
+                entry.attempts += 1;
+            }
+            None => failed.push(FailedChannel {
+                name: name.to_string(),
+                error: format!("{:#}", error),
+                attempts: 1,
+            }),
+        }
+    }
+
+    /// Forget a channel's failure; returns whether it was failed.
+    pub fn clear_failure(&self, name: &str) -> bool {
+        let mut failed = self.failed.lock().unwrap();
+        let before = failed.len();
+        failed.retain(|f| f.name != name);
+        failed.len() != before
+    }
+
+    /// Receiver for channel change requests; call once.
+    pub fn requests(&self) -> tokio::sync::mpsc::UnboundedReceiver<ChannelRequest> {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        if self.requests.set(tx).is_err() {
+            panic!("requests called twice");
+        }
+        rx
+    }
+
+    /// Start or stop a configured channel's pipeline, keeping its configuration.
+    pub async fn set_enabled(&self, name: &str, enabled: bool) -> Result<()> {
+        let change = if enabled {
+            ChannelChange::Enable
+        } else {
+            ChannelChange::Disable
+        };
+        self.request(name, change).await
+    }
+
+    /// Change a configured channel's output resolution, restarting it if it runs.
+    pub async fn set_resolution(&self, name: &str, width: u32, height: u32) -> Result<()> {
+        self.request(name, ChannelChange::Resolution { width, height })
+            .await
+    }
+
+    /// Rename a configured channel's NDI output without restarting the channel.
+    pub async fn set_output_name(&self, name: &str, output_name: &str) -> Result<()> {
+        self.request(name, ChannelChange::OutputName(output_name.to_string()))
+            .await
+    }
+
+    /// Switch a configured channel's NDI input to another source without
+    /// restarting the channel.
+    pub async fn set_input_source(&self, name: &str, source: &str) -> Result<()> {
+        self.request(name, ChannelChange::InputSource(source.to_string()))
+            .await
+    }
+
+    /// Apply the live settings of a configured channel's reloaded config.
+    pub async fn reload(&self, config: ChannelConfig) -> Result<()> {
+        let name = config.name.clone();
+        self.request(&name, ChannelChange::Reload(Box::new(config)))
+            .await
+    }
+
+    async fn request(&self, name: &str, change: ChannelChange) -> Result<()> {
+        let tx = self
+            .requests
+            .get()
+            .ok_or_else(|| anyhow::anyhow!("Channels can't be changed at runtime"))?;
+        let (reply, outcome) = tokio::sync::oneshot::channel();
+        tx.send((name.to_string(), change, reply))
+            .map_err(|_| anyhow::anyhow!("Mixer is shutting down"))?;
+        outcome
+            .await
+            .map_err(|_| anyhow::anyhow!("Mixer is shutting down"))?
+    }
 }
 
 pub struct Channel {
     pub state: Arc<ChannelState>,
-    _thread: std::thread::JoinHandle<()>,
+    thread: std::thread::JoinHandle<()>,
 }
 
 impl Channel {
+    /// Wait for the render thread to exit after the channel was cancelled, so
+    /// its NDI sender is gone.
+    pub async fn finished(self) {
+        let thread = self.thread;
+        let _ = tokio::task::spawn_blocking(move || thread.join()).await;
+    }
+
     pub async fn start(
         config: &ChannelConfig,
+        settings: &Settings,
         ndi: &NDI,
-        browser: Option<&Browser>,
+        inputs: &InputManager,
+        overlays: Option<&OverlayManager>,
         gpu_ctx: GpuCtxParam,
         cancel: CancellationToken,
     ) -> Result<Self> {
@@ -62,46 +953,239 @@ impl Channel {
         let height = config.height;
         let frame_rate = config.frame_rate;
         let frame_interval = Duration::from_micros(1_000_000 / frame_rate as u64);
+        let stats_interval = Duration::from_millis(settings.stats_interval_ms);
 
-        // Start NDI input if configured (pre-resizes to output dims on its own thread)
-        let ndi_input = if let Some(ref ndi_cfg) = config.ndi_input {
-            Some(NdiInput::start(ndi, &ndi_cfg.source, width, height, cancel.clone())?)
-        } else {
-            None
+        // A channel can keep to the CPU or insist on the GPU; `auto` follows the mixer
+        let gpu_ctx = match (config.compositor, settings.compositor) {
+            (CompositorMode::Cpu, _) | (CompositorMode::Auto, CompositorMode::Cpu) => None,
+            (CompositorMode::Gpu, _) if gpu_ctx.is_none() => anyhow::bail!(
+                "Channel '{}': compositor = \"gpu\", but no GPU compositor is available",
+                config.name
+            ),
+            _ => gpu_ctx,
         };
 
-        // Start browser overlays
-        let overlay_configs = config.all_browser_overlays();
-        let mut browser_overlays = Vec::with_capacity(overlay_configs.len());
-        for browser_cfg in &overlay_configs {
-            let b = browser.ok_or_else(|| anyhow::anyhow!("Browser not available for overlay"))?;
-            browser_overlays.push(
-                BrowserOverlay::start(
-                    b,
-                    &browser_cfg.url,
-                    browser_cfg.width,
-                    browser_cfg.height,
-                    &browser_cfg.css,
-                    browser_cfg.reload_interval,
+        // Equal z_index is allowed (ties draw in a fixed order) but usually a mistake
+        let configured_layers = config.layers();
+        for (i, layer) in configured_layers.iter().enumerate() {
+            if let Some(other) = configured_layers[..i]
+                .iter()
+                .find(|other| other.z_index == layer.z_index)
+            {
+                tracing::warn!(
+                    "Channel '{}': layers {} and {} share z_index {}; {} draws on top",
+                    config.name,
+                    other.id,
+                    layer.id,
+                    layer.z_index,
+                    layer.id
+                );
+            }
+        }
+
+        let slate_image = crate::slate::render(config.slate_or(&settings.slate), width, height)?;
+        let slate_active: Arc<Mutex<bool>> = Arc::new(Mutex::new(false));
+        let slate_ref = slate_active.clone();
+        let black_image = RgbaImage::from_pixel(width, height, Rgba([0, 0, 0, 255]));
+        let output_blank: Arc<Mutex<Option<BlankMode>>> = Arc::new(Mutex::new(None));
+        let blank_ref = output_blank.clone();
+        let schedule: SharedSchedule = Default::default();
+        let warmup = config.warmup;
+        let warmup_timeout =
+            (config.warmup_timeout > 0).then(|| Duration::from_secs(config.warmup_timeout));
+        let warming_up: Arc<Mutex<bool>> = Arc::new(Mutex::new(warmup != WarmupMode::Off));
+        let warming_up_ref = warming_up.clone();
+        let hooks = Arc::new(Hooks::new(&config.name, &config.output_name, &config.hooks));
+        let hooks_ref = hooks.clone();
+        let filter_clock: Arc<Mutex<Instant>> = Arc::new(Mutex::new(Instant::now()));
+
+        // Subscribe to the NDI input if configured (pre-resized to output dims on
+        // the receiver's thread, which channels on the same source share)
+        let (ndi_input, ndi_key) = if let Some(ref ndi_cfg) = config.ndi_input {
+            // UYVY is only worth it when the GPU does the conversion
+            let uyvy = ndi_cfg.receive_format == ReceiveFormat::Uyvy
+                && gpu_ctx.is_some()
+                && ndi_cfg.key_source.is_none()
+                && config.conversion.is_none();
+            if ndi_cfg.receive_format == ReceiveFormat::Uyvy && ndi_cfg.key_source.is_some() {
+                tracing::warn!(
+                    "Channel '{}': the fill of a key/fill pair is keyed on the CPU — receiving RGBA",
+                    config.name
+                );
+            } else if ndi_cfg.receive_format == ReceiveFormat::Uyvy && config.conversion.is_some() {
+                tracing::warn!(
+                    "Channel '{}': standards conversion works on RGBA frames — receiving RGBA",
+                    config.name
+                );
+            } else if ndi_cfg.receive_format == ReceiveFormat::Uyvy && !uyvy {
+                tracing::warn!(
+                    "Channel '{}': receive_format = \"uyvy\" needs the GPU compositor — receiving RGBA",
+                    config.name
+                );
+            }
+            // A placed input is resized straight to its area, not the whole canvas
+            let (width, height) = ndi_cfg
+                .placement
+                .map_or((width, height), |p| (p.width, p.height));
+            let processing = FrameProcessing {
+                width,
+                height,
+                roi: ndi_cfg.roi,
+                fit: ndi_cfg.fit,
+                range: ndi_cfg.range,
+                primaries: ndi_cfg.primaries,
+                uyvy,
+                frame_rate,
+                queue: config.conversion.is_some(),
+            };
+            let source_timeout =
+                (ndi_cfg.source_timeout > 0).then(|| Duration::from_secs(ndi_cfg.source_timeout));
+            // The key gets the same crop and fit, so it lines up with the fill
+            let key = ndi_cfg.key_source.as_ref().map(|key_source| {
+                inputs.subscribe(
+                    key_source,
+                    ndi_cfg.groups.as_deref(),
+                    processing.clone(),
+                    source_timeout,
+                    false,
                     cancel.clone(),
                 )
-                .await?,
+            });
+            let fill = inputs.subscribe(
+                &ndi_cfg.source,
+                ndi_cfg.groups.as_deref(),
+                processing,
+                source_timeout,
+                ndi_cfg.caption_passthrough,
+                cancel.clone(),
             );
-        }
+            (Some(fill), key)
+        } else {
+            (None, None)
+        };
+
+        // Start browser overlays, loading their pages concurrently
+        let overlay_configs = config.all_browser_overlays();
+        let browser_overlays = if overlay_configs.is_empty() {
+            Vec::new()
+        } else {
+            let overlays =
+                overlays.ok_or_else(|| anyhow::anyhow!("Browser not available for overlay"))?;
+            futures::future::try_join_all(
+                overlay_configs
+                    .iter()
+                    .map(|browser_cfg| overlays.start(browser_cfg, cancel.clone())),
+            )
+            .await?
+        };
+
+        // Start captions layer if configured
+        let caption_layer = match config.captions {
+            Some(ref captions_cfg) => Some(CaptionLayer::start(
+                captions_cfg,
+                width,
+                height,
+                cancel.clone(),
+            )?),
+            None => None,
+        };
+
+        // Start clock layer if configured
+        let clock_layer = match config.clock {
+            Some(ref clock_cfg) => Some(ClockLayer::start(
+                clock_cfg,
+                width,
+                height,
+                frame_rate,
+                cancel.clone(),
+            )?),
+            None => None,
+        };
+
+        // Start timer layer if configured
+        let timer_layer = match config.timer {
+            Some(ref timer_cfg) => Some(TimerLayer::new(timer_cfg, width, height, frame_rate)?),
+            None => None,
+        };
 
         // Create NDI output
-        let ndi_output = NdiOutput::new(ndi, &config.output_name, width, height, frame_rate)?;
+        let picture_aspect_ratio = config.picture_aspect_ratio()?;
+        let ndi_output = match settings.snapshot_dir {
+            Some(ref dir) => NdiOutput::dry_run(&config.output_name, width, height, dir)?,
+            None => NdiOutput::new(
+                ndi,
+                &config.output_name,
+                width,
+                height,
+                frame_rate,
+                picture_aspect_ratio,
+                config.output_pixel_format,
+                settings
+                    .silent_audio
+                    .enabled
+                    .then_some(settings.silent_audio),
+            )?,
+        };
+        let output_dedup = config.output_dedup;
+        let frames_repeated = output_dedup.then(|| ndi_output.repeated());
+
+        // Start the channel's audio source, feeding the output directly
+        let audio_input = match config.audio_source {
+            Some(ref source) => Some(NdiAudioInput::start(
+                inputs,
+                source,
+                None,
+                ndi_output.audio_sender(),
+                cancel.clone(),
+            )?),
+            None => None,
+        };
+        // ...or mix it, with the audio of overlays that have it
+        let audio_overlays: Vec<(&BrowserOverlayConfig, &BrowserOverlay)> = overlay_configs
+            .iter()
+            .copied()
+            .zip(&browser_overlays)
+            .filter(|(cfg, _)| cfg.audio)
+            .collect();
+        let audio_mix_config = config.audio_mix();
+        let audio_mix = match audio_mix_config {
+            Some(ref audio) => Some(AudioMixer::start(
+                inputs,
+                config,
+                audio,
+                audio_overlays
+                    .iter()
+                    .map(|(_, overlay)| overlay.share.subscribe_audio())
+                    .collect(),
+                ndi_output.audio_sender(),
+                cancel.clone(),
+            )?),
+            None => None,
+        };
 
         // Build state for status reporting
-        let ndi_connected = ndi_input
+        let ndi_state = ndi_input
             .as_ref()
-            .map(|i| i.connected.clone())
-            .unwrap_or_else(|| Arc::new(Mutex::new(false)));
+            .map(|i| i.state.clone())
+            .unwrap_or_else(|| Arc::new(Mutex::new(SourceState::Searching)));
         let ndi_frames_received = ndi_input
             .as_ref()
             .map(|i| i.frames_received.clone())
             .unwrap_or_else(|| Arc::new(Mutex::new(0)));
         let frames_output: Arc<Mutex<u64>> = Arc::new(Mutex::new(0));
+        let overload_status: Arc<Mutex<OverloadStatus>> =
+            Arc::new(Mutex::new(OverloadStatus::default()));
+        let mut overload = OverloadMonitor::new(&config.overload, overload_status.clone());
+        let usage: Arc<Mutex<ChannelUsage>> = Arc::new(Mutex::new(ChannelUsage::default()));
+        let usage_ref = usage.clone();
+        let frame_rates: Arc<Mutex<FrameRates>> = Arc::default();
+        let frame_rates_ref = frame_rates.clone();
+        let stats_history: SharedStatsHistory =
+            Arc::new(Mutex::new(StatsHistory::new(settings.stats_history_hours)));
+        let mut minute_stats = MinuteRecorder::new(stats_history.clone(), frame_interval);
+        let gpu_fallback: ErrorSlot = ErrorSlot::default();
+        #[cfg(feature = "gpu")]
+        let gpu_health: Arc<Mutex<GpuHealth>> = Arc::new(Mutex::new(GpuHealth::default()));
 
         let browser_overlay_states: Vec<BrowserOverlayState> = overlay_configs
             .iter()
@@ -109,43 +1193,334 @@ impl Channel {
             .map(|(cfg, overlay)| BrowserOverlayState {
                 url: cfg.url.clone(),
                 loaded: overlay.loaded.clone(),
+                error: overlay.error.clone(),
                 filters: cfg.filters.iter().map(|f| f.shader.clone()).collect(),
+                page: overlay.page.clone(),
+                viewport: (cfg.width, cfg.height),
+                capture_mode: cfg.capture_mode,
+                capture: overlay.capture.clone(),
+                cpu_budget: overlay.cpu_budget.clone(),
+                last_error: overlay.last_error.clone(),
+                share: overlay.share.clone(),
             })
             .collect();
 
-        let state = ChannelState {
-            name: config.name.clone(),
-            output_name: config.output_name.clone(),
-            width,
-            height,
-            frame_rate,
-            ndi_connected: ndi_connected.clone(),
-            ndi_frames_received: ndi_frames_received.clone(),
-            ndi_source: config.ndi_input.as_ref().map(|c| c.source.clone()),
-            ndi_filters: config
-                .ndi_input
-                .as_ref()
-                .map(|c| c.filters.iter().map(|f| f.shader.clone()).collect())
-                .unwrap_or_default(),
-            browser_overlays: browser_overlay_states,
-            channel_filters: config.filters.iter().map(|f| f.shader.clone()).collect(),
-            frames_output: frames_output.clone(),
+        let mut layer_infos: Vec<LayerInfo> = configured_layers
+            .iter()
+            .map(|layer| LayerInfo {
+                source: layer.id,
+                label: match layer.id {
+                    LayerId::Ndi => config
+                        .ndi_input
+                        .as_ref()
+                        .map(|c| format!("NDI {}", c.alias.as_ref().unwrap_or(&c.source)))
+                        .unwrap_or_default(),
+                    LayerId::Browser(i) => overlay_configs[i].url.clone(),
+                    LayerId::Image(i) => config.image_overlays[i].path.clone(),
+                    LayerId::Text(i) => config.text_overlays[i].text.clone(),
+                    LayerId::Captions => "captions".to_string(),
+                    LayerId::Clock => "clock".to_string(),
+                    LayerId::Timer => "timer".to_string(),
+                },
+                z_index: layer.z_index,
+                group: layer.group.map(str::to_string),
+            })
+            .collect();
+        layer_infos.sort_by_key(|l| std::cmp::Reverse((l.z_index, l.source.tie_break())));
+        let layer_mix: Arc<Mutex<LayerMix>> = Arc::new(Mutex::new(LayerMix {
+            groups: layer_groups(config, &layer_infos),
+            ..Default::default()
+        }));
+        let live_settings = Arc::new(Mutex::new(LiveSettings::of(config)));
+        let replay = match config.replay {
+            Some(ref replay_cfg) => Some(Replay::start(
+                replay_cfg,
+                &config.name,
+                width,
+                height,
+                frame_rate,
+            )?),
+            None => None,
         };
+        let virtual_camera = match config.virtual_camera {
+            Some(ref camera_cfg) => Some(VirtualCamera::start(
+                camera_cfg,
+                &config.name,
+                width,
+                height,
+            )?),
+            None => None,
+        };
+        let scopes: Option<SharedScopes> = config.scopes.as_ref().map(|_| Default::default());
+        let scopes_config = config.scopes.clone();
 
-        // Layer z-index and opacity config
-        let ndi_z = config.ndi_input.as_ref().map(|c| c.z_index).unwrap_or(0);
-        let ndi_opacity = config.ndi_input.as_ref().map(|c| c.opacity).unwrap_or(1.0);
+        // Filters only run on the GPU, so there is nothing to compare without it
+        let compare = Arc::new(Mutex::new(CompareView {
+            enabled: gpu_ctx.is_some() && config.compare.as_ref().is_some_and(|c| c.enabled),
+            split: config.compare.as_ref().map_or(0.5, |c| c.split),
+        }));
 
-        // Collect browser overlay render info: (latest_frame_ref, opacity, z_index)
-        let browser_layers: Vec<(Arc<Mutex<Option<RgbaImage>>>, f32, i32)> = overlay_configs
+        let output_name = Arc::new(Mutex::new(config.output_name.clone()));
+        let output_tally = ndi_output.tally();
+        // Source names for the compare view labels, top of the stack first
+        #[cfg(feature = "gpu")]
+        let label_sources: Vec<(LayerId, String)> = layer_infos
             .iter()
-            .zip(browser_overlays.iter())
-            .map(|(cfg, overlay)| {
-                (overlay.latest_frame.clone(), cfg.opacity, cfg.z_index)
-            })
+            .map(|l| (l.source, l.label.clone()))
             .collect();
+        #[cfg(feature = "gpu")]
+        let (labels_enabled, labels_font) = config
+            .compare
+            .as_ref()
+            .map_or((true, None), |c| (c.labels, c.font.clone()));
+
+        let state =
+            ChannelState {
+                name: config.name.clone(),
+                output_name: output_name.clone(),
+                output_renamer: ndi_output.renamer(),
+                width,
+                height,
+                frame_rate,
+                picture_aspect_ratio,
+                output_pixel_format: config.output_pixel_format,
+                ndi_state: ndi_state.clone(),
+                ndi_frames_received: ndi_frames_received.clone(),
+                ndi_last_error: ndi_input
+                    .as_ref()
+                    .map(|i| i.last_error.clone())
+                    .unwrap_or_default(),
+                ndi_format: ndi_input
+                    .as_ref()
+                    .map(|i| i.format.clone())
+                    .unwrap_or_default(),
+                ndi_tally: ndi_input.as_ref().map(|i| i.tally.clone()),
+                ndi_source: config.ndi_input.as_ref().map(|c| {
+                    Arc::new(Mutex::new(InputSource {
+                        name: c.source.clone(),
+                        alias: c.alias.clone(),
+                    }))
+                }),
+                ndi_input: ndi_input.clone(),
+                ndi_key: config
+                    .ndi_input
+                    .as_ref()
+                    .and_then(|c| c.key_source.clone().map(|name| (name, c.key_alias.clone())))
+                    .zip(ndi_key.clone())
+                    .map(|((name, alias), input)| (InputSource { name, alias }, input)),
+                ndi_share: ndi_input.as_ref().map(|i| i.share.clone()),
+                ndi_filters: config
+                    .ndi_input
+                    .as_ref()
+                    .map(|c| c.filters.iter().map(|f| f.shader.clone()).collect())
+                    .unwrap_or_default(),
+                ndi_fit: config.ndi_input.as_ref().map(|c| c.fit).unwrap_or_default(),
+                browser_overlays: browser_overlay_states,
+                layers: layer_infos,
+                scenes: config.scene.clone(),
+                transition: config.transition,
+                layer_mix: layer_mix.clone(),
+                live: live_settings.clone(),
+                channel_filters: config.filters.iter().map(|f| f.shader.clone()).collect(),
+                compare: compare.clone(),
+                scopes: scopes.clone(),
+                replay: replay.as_ref().map(|r| r.handle()),
+                virtual_camera: virtual_camera.as_ref().map(|c| c.status.clone()),
+                compare_filters: config
+                    .compare
+                    .iter()
+                    .flat_map(|c| c.filters.iter().map(|f| f.shader.clone()))
+                    .collect(),
+                frames_output: frames_output.clone(),
+                frames_repeated,
+                overload_policy: config.overload.policy,
+                overload: overload_status,
+                gpu_fallback: gpu_fallback.clone(),
+                #[cfg(feature = "gpu")]
+                gpu_health: gpu_ctx.is_some().then(|| gpu_health.clone()),
+                #[cfg(not(feature = "gpu"))]
+                gpu_health: None,
+                usage,
+                frame_rates,
+                output_tally: output_tally.clone(),
+                stats_history,
+                captions: config.captions.as_ref().zip(caption_layer.as_ref()).map(
+                    |(cfg, layer)| CaptionsState {
+                        source: cfg
+                            .file
+                            .clone()
+                            .or_else(|| cfg.websocket.clone())
+                            .unwrap_or_default(),
+                        showing: layer.showing(),
+                    },
+                ),
+                clock: clock_layer.as_ref().map(|c| c.state()),
+                timer: timer_layer.as_ref().map(|t| t.timer()),
+                cc_passthrough: ndi_input.as_ref().and_then(|i| i.captions.clone()),
+                expose_captions: config.ndi_input.as_ref().is_some_and(|c| c.expose_captions),
+                counters_since: Arc::new(Mutex::new(Instant::now())),
+                slate_active,
+                output_blank,
+                schedule: schedule.clone(),
+                warming_up,
+                hooks,
+                filter_clock: filter_clock.clone(),
+                audio_input: config.audio_source.as_ref().zip(audio_input.as_ref()).map(
+                    |(source, input)| AudioInputState {
+                        source: source.clone(),
+                        alias: config.audio_source_alias.clone(),
+                        state: input.state.clone(),
+                        frames_received: input.frames_received.clone(),
+                    },
+                ),
+                audio_mix: audio_mix_config.as_ref().zip(audio_mix.as_ref()).map(
+                    |(audio, mixer)| AudioMixState {
+                        sample_rate: audio.sample_rate,
+                        channels: audio.channels,
+                        levels: mixer.levels.clone(),
+                        sources: audio
+                            .sources
+                            .iter()
+                            .zip(&mixer.inputs)
+                            .map(|(source, input)| AudioInputState {
+                                source: source.source.clone(),
+                                alias: source.alias.clone(),
+                                state: input.state.clone(),
+                                frames_received: input.frames_received.clone(),
+                            })
+                            .chain(audio_overlays.iter().map(|(cfg, overlay)| {
+                                let (state, frames_received) = overlay.share.audio_status();
+                                AudioInputState {
+                                    source: cfg.url.clone(),
+                                    alias: None,
+                                    state,
+                                    frames_received,
+                                }
+                            }))
+                            .collect(),
+                    },
+                ),
+            };
+
+        // Layer z-index and opacity config
+        let mut ndi_z = config.ndi_input.as_ref().map(|c| c.z_index).unwrap_or(0);
+        let mut ndi_opacity = config.ndi_input.as_ref().map(|c| c.opacity).unwrap_or(1.0);
+        let on_loss = config
+            .ndi_input
+            .as_ref()
+            .map(|c| c.on_loss)
+            .unwrap_or_default();
+        let (fade_in, fade_out) = config
+            .ndi_input
+            .as_ref()
+            .map(|c| {
+                (
+                    Duration::from_millis(c.fade_in_ms),
+                    Duration::from_millis(c.fade_out_ms),
+                )
+            })
+            .unwrap_or_default();
+        let has_ndi_input = ndi_input.is_some();
+        let mut rate_adapter = RateAdapter::new(
+            config
+                .ndi_input
+                .as_ref()
+                .map(|c| c.rate_adaptation)
+                .unwrap_or_default(),
+            frame_rate,
+        );
+        let mut converter = config
+            .conversion
+            .as_ref()
+            .map(|c| FrameRateConverter::new(c.method, c.delay_frames));
+        let ndi_transform = config
+            .ndi_input
+            .as_ref()
+            .map(|c| Transform::new(None, c.placement.map(|p| p.rect()), (width, height)))
+            .unwrap_or_default();
+
+        // Every layer but the NDI input, in config order, with its configured mix
+        let source_layer = |id: LayerId, source: Box<dyn Source>| {
+            let configured = configured_layers.iter().find(|l| l.id == id);
+            SourceLayer {
+                id,
+                source,
+                opacity: configured.map_or(1.0, |l| l.opacity),
+                z_index: configured.map_or(0, |l| l.z_index),
+                transform: Transform::default(),
+                luma_key: None,
+            }
+        };
+        let mut source_layers: Vec<SourceLayer> = Vec::new();
+        for (i, (cfg, overlay)) in overlay_configs.iter().zip(&browser_overlays).enumerate() {
+            let source = BrowserSource::new(
+                overlay.latest_frame.clone(),
+                overlay.loaded.clone(),
+                cfg.frame_divisor as u64,
+                overlay.share.source_key(),
+            );
+            source_layers.push(SourceLayer {
+                transform: Transform {
+                    warp: Warp::new(cfg.rotation, cfg.skew_x, cfg.skew_y),
+                    ..Transform::new(
+                        cfg.crop.map(|c| c.rect()),
+                        cfg.placement.map(|p| p.rect()),
+                        (width, height),
+                    )
+                },
+                ..source_layer(LayerId::Browser(i), Box::new(source))
+            });
+        }
+        // Image overlays never change, so the GPU compositor uploads each once
+        for (i, cfg) in config.image_overlays.iter().enumerate() {
+            let image = crate::image_overlay::load(cfg, width, height)?;
+            source_layers.push(source_layer(
+                LayerId::Image(i),
+                Box::new(ImageSource(image)),
+            ));
+        }
+        for (i, cfg) in config.text_overlays.iter().enumerate() {
+            let text = TextOverlay::new(
+                cfg,
+                &config.name,
+                output_name.clone(),
+                (width, height),
+                config.frame_rate,
+            )?;
+            source_layers.push(source_layer(LayerId::Text(i), Box::new(text)));
+        }
+        if let Some(captions) = caption_layer {
+            source_layers.push(source_layer(LayerId::Captions, Box::new(captions)));
+        }
+        if let Some(clock) = clock_layer {
+            source_layers.push(source_layer(LayerId::Clock, Box::new(clock)));
+        }
+        if let Some(timer) = timer_layer {
+            source_layers.push(source_layer(LayerId::Timer, Box::new(timer)));
+        }
 
         let ndi_latest = ndi_input.as_ref().map(|i| i.latest_frame.clone());
+        let ndi_latest_uyvy = ndi_input.as_ref().map(|i| i.latest_uyvy.clone());
+        let ndi_queue = ndi_input.as_ref().and_then(|i| i.queue.clone());
+        // Keyed frames differ from what other channels on the fill source get
+        let ndi_source_key = ndi_input
+            .as_ref()
+            .filter(|_| ndi_key.is_none())
+            .map(|i| i.source_key.clone());
+        // Output tally goes upstream to the fill and key sources alike
+        let input_tallies: Vec<Arc<Mutex<InputTally>>> = ndi_input
+            .iter()
+            .chain(ndi_key.iter())
+            .map(|i| i.tally.clone())
+            .collect();
+        let mut key_input = ndi_key.map(|input| {
+            let premultiplied = config
+                .ndi_input
+                .as_ref()
+                .is_some_and(|c| c.fill_premultiplied);
+            KeyInput::new(input, premultiplied)
+        });
+        let ndi_captions = ndi_input.as_ref().and_then(|i| i.captions.clone());
 
         let channel_name = config.name.clone();
 
@@ -157,30 +1532,61 @@ impl Channel {
             || overlay_configs.iter().any(|cfg| !cfg.filters.is_empty())
             || !config.filters.is_empty();
 
-        // Create per-channel GPU compositor if available
+        // Create per-channel GPU compositors if available, rebuilt on a fresh GPU
+        // context if they keep failing
         #[cfg(feature = "gpu")]
-        let mut gpu_compositor = {
+        let mut gpu_recovery = {
             let ndi_filter_configs: Vec<_> = config
                 .ndi_input
                 .as_ref()
-                .map(|c| c.filters.clone())
+                .map(|c| c.filter_chain())
                 .unwrap_or_default();
             let browser_filter_configs: Vec<Vec<_>> = overlay_configs
                 .iter()
-                .map(|cfg| cfg.filters.clone())
+                .map(|cfg| cfg.filter_chain())
                 .collect();
             let channel_filter_configs = config.filters.clone();
+            let compare_filter_configs = config
+                .compare
+                .as_ref()
+                .map(|c| c.filters.clone())
+                .filter(|f| !f.is_empty());
+            // Half-resolution compositor for the downscale overload policy
+            let downscale = config.overload.policy == OverloadPolicy::Downscale;
+            let filter_clock = filter_clock.clone();
 
-            gpu_ctx.map(|ctx| {
-                crate::gpu_compositor::GpuCompositor::new(
-                    ctx,
-                    width,
-                    height,
-                    &ndi_filter_configs,
-                    &browser_filter_configs,
-                    &channel_filter_configs,
-                )
-            })
+            let build: crate::gpu_recovery::BuildCompositors = Arc::new(move |ctx| {
+                let new = |width, height, channel_filters| {
+                    crate::gpu_compositor::GpuCompositor::new(
+                        ctx.clone(),
+                        width,
+                        height,
+                        &ndi_filter_configs,
+                        &browser_filter_configs,
+                        channel_filters,
+                        filter_clock.clone(),
+                    )
+                };
+                crate::gpu_recovery::Compositors {
+                    full: new(width, height, &channel_filter_configs),
+                    small: downscale.then(|| {
+                        new(
+                            (width / 2).max(1),
+                            (height / 2).max(1),
+                            &channel_filter_configs,
+                        )
+                    }),
+                    compare: compare_filter_configs
+                        .as_ref()
+                        .map(|filters| new(width, height, filters)),
+                }
+            });
+            crate::gpu_recovery::GpuRecovery::new(
+                gpu_ctx.as_ref(),
+                build,
+                gpu_health.clone(),
+                gpu_fallback.clone(),
+            )
         };
 
         // Suppress unused variable warning when gpu feature is off
@@ -190,23 +1596,88 @@ impl Channel {
         // Warn if filters configured but no GPU
         if has_any_filters {
             #[cfg(not(feature = "gpu"))]
-            tracing::warn!(
-                "Channel '{}': shader filters configured but GPU feature is disabled — filters will be skipped",
-                config.name
-            );
+            {
+                tracing::warn!(
+                    "Channel '{}': shader filters configured but GPU feature is disabled — filters will be skipped",
+                    config.name
+                );
+                record(
+                    &gpu_fallback,
+                    ErrorCode::GpuUnavailable,
+                    "GPU feature is disabled; shader filters are skipped",
+                );
+            }
             #[cfg(feature = "gpu")]
-            if gpu_compositor.is_none() {
+            if !gpu_recovery.is_available() {
                 tracing::warn!(
                     "Channel '{}': shader filters configured but GPU is unavailable — filters will be skipped",
                     config.name
                 );
+                record(
+                    &gpu_fallback,
+                    ErrorCode::GpuUnavailable,
+                    "No GPU adapter; shader filters are skipped",
+                );
+            }
+        }
+
+        // Unpaused filters may read `time`, so their output can change on every
+        // frame even when no layer did; those channels always re-composite.
+        #[cfg(feature = "gpu")]
+        let filters_animate = gpu_recovery.is_available()
+            && config
+                .ndi_input
+                .iter()
+                .flat_map(|c| c.filters.iter())
+                .chain(overlay_configs.iter().flat_map(|cfg| cfg.filters.iter()))
+                .chain(config.filters.iter())
+                .chain(config.compare.iter().flat_map(|c| c.filters.iter()))
+                .any(|f| !f.paused);
+        #[cfg(not(feature = "gpu"))]
+        let filters_animate = false;
+        // Filters on the input or the channel rule out passing input frames through
+        #[cfg(feature = "gpu")]
+        let input_filtered = gpu_recovery.is_available()
+            && (config.ndi_input.iter().any(|c| !c.filters.is_empty())
+                || !config.filters.is_empty());
+        #[cfg(not(feature = "gpu"))]
+        let input_filtered = false;
+        let chroma_key = config
+            .ndi_input
+            .as_ref()
+            .and_then(|c| c.chroma_key.as_ref());
+        let luma_key = config.ndi_input.as_ref().and_then(|c| c.luma_key.as_ref());
+        // Keys give the input per-pixel alpha, so it's always blended over
+        // black: never passed through or copied as it comes
+        let input_keyed = key_input.is_some() || chroma_key.is_some() || luma_key.is_some();
+        let input_filtered = input_filtered || input_keyed;
+
+        // Chroma and luma keys run first in the GPU filter chains; without a
+        // GPU compositor they're applied here to each frame as it's taken
+        #[cfg(feature = "gpu")]
+        let gpu_keys = gpu_recovery.is_available();
+        #[cfg(not(feature = "gpu"))]
+        let gpu_keys = false;
+        let mut cpu_chroma_key = chroma_key.filter(|_| !gpu_keys).map(ChromaKey::new);
+        let mut cpu_luma_key = luma_key.filter(|_| !gpu_keys).map(LumaKey::new);
+        if !gpu_keys {
+            for layer in source_layers.iter_mut() {
+                if let LayerId::Browser(i) = layer.id {
+                    layer.luma_key = overlay_configs[i].luma_key.as_ref().map(LumaKey::new);
+                }
             }
         }
 
+        let ndi_source = state.ndi_source.clone();
+        let transition = config.transition;
+        let state = Arc::new(state);
+        let scheduled_on = state.clone();
+
         // Dedicated render thread — no async overhead, precise frame timing
         let thread = std::thread::Builder::new()
             .name(format!("render-{}", config.name))
             .spawn(move || {
+                crate::realtime::apply_current_thread(crate::realtime::ThreadRole::Render);
                 tracing::info!(
                     "Channel '{}' started ({}x{}@{}fps)",
                     channel_name,
@@ -217,11 +1688,75 @@ impl Channel {
 
                 let mut canvas: RgbaImage =
                     ImageBuffer::from_pixel(width, height, Rgba([0, 0, 0, 255]));
-                let num_browser = browser_layers.len();
+                let num_browser = source_layers
+                    .iter()
+                    .filter(|l| matches!(l.id, LayerId::Browser(_)))
+                    .count();
                 let mut ndi_output = ndi_output;
 
-                let mut last_ndi_frame: Option<RgbaImage> = None;
-                let mut last_browser_frames: Vec<Option<RgbaImage>> = vec![None; num_browser];
+                let mut last_ndi_frame: Option<NdiFrame> = None;
+                let mut last_ndi_uyvy: Option<UyvyFrame> = None;
+                // The frame before the latest, kept for rate adaptation
+                let mut prev_ndi_frame: Option<NdiFrame> = None;
+                let mut prev_ndi_uyvy: Option<UyvyFrame> = None;
+                let mut blended_ndi: Option<BlendedFrame> = None;
+                // NDI source swap: the source shown, and the old one's last frame
+                // while it's crossfaded into the new one
+                let mut shown_source = ndi_source.as_ref().map(|s| s.lock().unwrap().name.clone());
+                let mut held_ndi_frame: Option<NdiFrame> = None;
+                let mut held_ndi_uyvy: Option<UyvyFrame> = None;
+                let mut held_generation: u64 = 0;
+                let mut source_transition: Option<Transition> = None;
+
+                // Layer generations, bumped on every new frame
+                let mut ndi_generation: u64 = 0;
+                // What the canvas currently holds; an identical composite is resent as is
+                let mut last_composite: Option<CompositeKey> = None;
+                // The last output was the input frame itself, not the canvas
+                let mut input_on_air = false;
+                // What the output's buffer holds, for `output_dedup`
+                let mut last_sent: Option<OutputImage> = None;
+                // Config reloads applied so far
+                let mut live_generation: u64 = 0;
+                // Compare view labels, loaded when first shown, and the tally they show
+                #[cfg(feature = "gpu")]
+                let mut source_labels: Option<Option<SourceLabels>> = None;
+                #[cfg(feature = "gpu")]
+                let mut shown_tally = OutputTally::default();
+
+                // Scratch canvas for the downscale overload policy
+                let mut small_canvas: RgbaImage = if overload.policy() == OverloadPolicy::Downscale
+                {
+                    ImageBuffer::from_pixel(
+                        (width / 2).max(1),
+                        (height / 2).max(1),
+                        Rgba([0, 0, 0, 255]),
+                    )
+                } else {
+                    ImageBuffer::new(0, 0)
+                };
+                // UYVY frames converted by the CPU compositor
+                let mut uyvy_cache = compositor::UyvyCache::default();
+                let mut frame_index: u64 = 0;
+                // Frames sent since the channel started: the schedule's timeline
+                let mut timeline: u64 = 0;
+                let mut last_scopes: Option<Instant> = None;
+                let mut warming = warmup != WarmupMode::Off;
+                let warmup_start = Instant::now();
+                let mut live = false;
+                let mut was_down = false;
+                // NDI layer fades: whether the input is on air, and when the last fades began
+                let mut ndi_showing = false;
+                let mut fade_in_from: Option<Instant> = None;
+                let mut fade_out_from: Option<Instant> = None;
+
+                // Render-thread busy time, folded into ChannelUsage once per second
+                let mut usage_window_start = Instant::now();
+                let mut busy_time = Duration::ZERO;
+                // Frames sent and new layer frames in the current stats window
+                let mut window_output: u64 = 0;
+                let mut window_ndi: u64 = 0;
+                let mut window_overlays: Vec<u64> = vec![0; num_browser];
 
                 loop {
                     let frame_start = Instant::now();
@@ -230,61 +1765,603 @@ impl Channel {
                         break;
                     }
 
+                    // Scheduled commands for this frame, before any state is read
+                    let due = schedule.lock().unwrap().advance(timeline);
+                    for scheduled in due {
+                        tracing::info!(
+                            "Channel '{}': running scheduled #{} at frame {}",
+                            channel_name,
+                            scheduled.id,
+                            timeline
+                        );
+                        let channels = std::slice::from_ref(&scheduled_on);
+                        if let Err(e) = crate::control::execute(channels, &scheduled.command) {
+                            tracing::warn!(
+                                "Channel '{}': scheduled #{} failed: {}",
+                                channel_name,
+                                scheduled.id,
+                                e
+                            );
+                        }
+                    }
+
+                    // Layer settings from a config reload
+                    {
+                        let settings = live_settings.lock().unwrap();
+                        if settings.generation != live_generation {
+                            live_generation = settings.generation;
+                            for &(id, opacity, z_index) in &settings.layers {
+                                if id == LayerId::Ndi {
+                                    (ndi_opacity, ndi_z) = (opacity, z_index);
+                                } else if let Some(layer) =
+                                    source_layers.iter_mut().find(|l| l.id == id)
+                                {
+                                    (layer.opacity, layer.z_index) = (opacity, z_index);
+                                }
+                            }
+                            if let (Some(key), Some(cfg)) =
+                                (cpu_chroma_key.as_mut(), settings.chroma_key.as_ref())
+                            {
+                                *key = ChromaKey::new(cfg);
+                            }
+                            for (id, cfg) in &settings.luma_keys {
+                                let key = if *id == LayerId::Ndi {
+                                    cpu_luma_key.as_mut()
+                                } else {
+                                    source_layers
+                                        .iter_mut()
+                                        .find(|l| l.id == *id)
+                                        .and_then(|l| l.luma_key.as_mut())
+                                };
+                                if let Some(key) = key {
+                                    *key = LumaKey::new(cfg);
+                                }
+                            }
+                            // New filter params leave the layers as they were, so
+                            // the last composite can't be reused
+                            #[cfg(feature = "gpu")]
+                            {
+                                gpu_recovery.set_filter_params(&settings.filters);
+                                last_composite = None;
+                            }
+                        }
+                    }
+
+                    // Switched to another source: keep the old one's last frame to crossfade from
+                    if let Some(ref source) = ndi_source {
+                        let source = source.lock().unwrap();
+                        if shown_source.as_ref() != Some(&source.name) {
+                            shown_source = Some(source.name.clone());
+                            source_transition = None;
+                            (held_ndi_frame, held_ndi_uyvy) =
+                                if Transition::start(transition.as_ref(), frame_start).is_some() {
+                                    (last_ndi_frame.clone(), last_ndi_uyvy.clone())
+                                } else {
+                                    (None, None)
+                                };
+                            held_generation = ndi_generation;
+                        }
+                    }
+
                     // Take new frames into buffers
                     if let Some(ref frame_lock) = ndi_latest {
-                        if let Some(img) = take_frame(frame_lock) {
+                        // Captions go on even while an unkeyed fill is held back
+                        let frame = take_frame(frame_lock)
+                            .map(|mut img| {
+                                forward_captions(&mut img.captions, &ndi_captions, &mut ndi_output);
+                                img
+                            })
+                            .and_then(|img| match key_input {
+                                Some(ref mut key) => key.key(img),
+                                None => Some(img),
+                            })
+                            .map(|mut img| {
+                                if let Some(ref chroma_key) = cpu_chroma_key {
+                                    chroma_key.apply(&mut img.image);
+                                }
+                                if let Some(ref luma_key) = cpu_luma_key {
+                                    luma_key.apply(&mut img.image);
+                                }
+                                img
+                            });
+                        if let Some(img) = frame {
+                            reclaim_output(&mut input_on_air, &mut canvas, &mut last_ndi_frame);
+                            rate_adapter.push(img.received_at);
+                            if rate_adapter.enabled() {
+                                prev_ndi_frame = last_ndi_frame.take();
+                                prev_ndi_uyvy = last_ndi_uyvy.take();
+                            }
                             last_ndi_frame = Some(img);
+                            last_ndi_uyvy = None;
+                            ndi_generation += 1;
+                            window_ndi += 1;
                         }
                     }
-                    for (i, (ref frame_lock, _, _)) in browser_layers.iter().enumerate() {
-                        if let Some(img) = take_frame(frame_lock) {
-                            last_browser_frames[i] = Some(img);
+                    if let Some(ref frame_lock) = ndi_latest_uyvy {
+                        if let Some(mut frame) = take_frame(frame_lock) {
+                            forward_captions(&mut frame.captions, &ndi_captions, &mut ndi_output);
+                            reclaim_output(&mut input_on_air, &mut canvas, &mut last_ndi_frame);
+                            rate_adapter.push(frame.received_at);
+                            if rate_adapter.enabled() {
+                                prev_ndi_frame = last_ndi_frame.take();
+                                prev_ndi_uyvy = last_ndi_uyvy.take();
+                            }
+                            last_ndi_uyvy = Some(frame);
+                            last_ndi_frame = None;
+                            ndi_generation += 1;
+                            window_ndi += 1;
+                        }
+                    }
+                    // Standards conversion takes every frame and decides itself when each is shown
+                    if let (Some(queue), Some(converter)) = (&ndi_queue, converter.as_mut()) {
+                        for frame in queue.lock().unwrap().drain(..) {
+                            converter.push(frame);
+                            window_ndi += 1;
+                        }
+                        while let Some(mut img) = converter.next(frame_start) {
+                            forward_captions(&mut img.captions, &ndi_captions, &mut ndi_output);
+                            prev_ndi_frame = last_ndi_frame.take();
+                            last_ndi_frame = Some(img);
+                            ndi_generation += 1;
+                        }
+                    }
+                    // The crossfade starts with the new source's first frame
+                    let holding = held_ndi_frame.is_some() || held_ndi_uyvy.is_some();
+                    if holding && source_transition.is_none() && ndi_generation > held_generation {
+                        source_transition = Transition::start(transition.as_ref(), frame_start);
+                    }
+                    if source_transition.is_some_and(|t| t.finished(frame_start)) {
+                        source_transition = None;
+                        held_ndi_frame = None;
+                        held_ndi_uyvy = None;
+                    }
+                    let source_down = has_ndi_input && ndi_state.lock().unwrap().is_down();
+                    // Fade in on the first frame after startup or a loss, out on loss
+                    if source_down {
+                        if ndi_showing && on_loss != LossPolicy::Hold {
+                            ndi_showing = false;
+                            fade_out_from = Some(frame_start);
+                        }
+                    } else if !ndi_showing
+                        && (last_ndi_frame.is_some() || last_ndi_uyvy.is_some())
+                    {
+                        ndi_showing = true;
+                        fade_in_from = Some(frame_start);
+                        fade_out_from = None;
+                    }
+                    // Lost or timed-out source: once faded out, apply the input's `on_loss` policy
+                    let loss_applied = source_down
+                        && fade_out_from
+                            .is_none_or(|from| frame_start.duration_since(from) >= fade_out);
+                    if loss_applied
+                        && on_loss == LossPolicy::Clear
+                        && (last_ndi_frame.is_some() || last_ndi_uyvy.is_some())
+                    {
+                        reclaim_output(&mut input_on_air, &mut canvas, &mut last_ndi_frame);
+                        last_ndi_frame = None;
+                        last_ndi_uyvy = None;
+                        prev_ndi_frame = None;
+                        prev_ndi_uyvy = None;
+                        rate_adapter.reset();
+                        if let Some(ref mut converter) = converter {
+                            converter.reset();
+                        }
+                        ndi_generation += 1;
+                    }
+                    for layer in source_layers.iter_mut() {
+                        if layer.source.update(frame_index) {
+                            if let (Some(key), Some(img)) =
+                                (&layer.luma_key, layer.source.latest_mut())
+                            {
+                                key.apply(img);
+                            }
+                            if let LayerId::Browser(i) = layer.id {
+                                window_overlays[i] += 1;
+                            }
+                        }
+                    }
+
+                    // Warmup: hold the output until every layer has something to show
+                    if warming {
+                        let ndi_ready = !has_ndi_input || ndi_generation > 0;
+                        let overlays_ready = source_layers.iter().all(|l| l.source.ready());
+                        let timed_out = warmup_timeout.is_some_and(|t| warmup_start.elapsed() >= t);
+                        if ndi_ready && overlays_ready {
+                            tracing::info!(
+                                "Channel '{}': warmup complete after {:.1}s, going live",
+                                channel_name,
+                                warmup_start.elapsed().as_secs_f32()
+                            );
+                        } else if timed_out {
+                            tracing::warn!(
+                                "Channel '{}': going live after {}s warmup timeout (ndi ready: {}, overlays ready: {})",
+                                channel_name,
+                                warmup_start.elapsed().as_secs(),
+                                ndi_ready,
+                                overlays_ready
+                            );
                         }
+                        if (ndi_ready && overlays_ready) || timed_out {
+                            warming = false;
+                            *warming_up_ref.lock().unwrap() = false;
+                        }
+                    }
+                    if !warming && !live {
+                        live = true;
+                        hooks_ref.fire(HookEvent::Started);
+                    }
+                    if source_down != was_down {
+                        was_down = source_down;
+                        hooks_ref.fire(if source_down {
+                            HookEvent::SourceLost
+                        } else {
+                            HookEvent::SourceRestored
+                        });
                     }
 
                     // Build layer refs (no cloning)
-                    let mut layers: Vec<Layer<'_>> = Vec::with_capacity(1 + num_browser);
-                    if let Some(ref img) = last_ndi_frame {
+                    let mut layers: Vec<Layer<'_>> = Vec::with_capacity(1 + source_layers.len());
+                    // Rate adaptation: the previous frame, the latest, or a blend of both
+                    let has_previous = prev_ndi_frame.is_some() || prev_ndi_uyvy.is_some();
+                    let mut pick = match converter {
+                        Some(ref converter) => converter.pick(frame_start),
+                        None if has_previous => rate_adapter.pick(frame_start),
+                        None => Pick::Latest,
+                    };
+                    if let Some(ref t) = source_transition {
+                        // Source crossfade, in place of rate adaptation while it runs
+                        let weight = (t.progress(frame_start) * 256.0) as u16;
+                        blended_ndi = blend_frames(
+                            (&held_ndi_frame, &held_ndi_uyvy),
+                            (&last_ndi_frame, &last_ndi_uyvy),
+                            weight,
+                            blended_ndi.take(),
+                        );
+                        pick = if blended_ndi.is_some() {
+                            Pick::Blend(weight)
+                        } else {
+                            // Different sizes or kinds: cut
+                            source_transition = None;
+                            held_ndi_frame = None;
+                            held_ndi_uyvy = None;
+                            Pick::Latest
+                        };
+                    } else if let Pick::Blend(weight) = pick {
+                        blended_ndi = blend_frames(
+                            (&prev_ndi_frame, &prev_ndi_uyvy),
+                            (&last_ndi_frame, &last_ndi_uyvy),
+                            weight,
+                            blended_ndi.take(),
+                        );
+                        if blended_ndi.is_none() {
+                            pick = Pick::Latest;
+                        }
+                    }
+                    let ndi_fade = if ndi_showing {
+                        fade_progress(fade_in_from, fade_in, frame_start)
+                    } else {
+                        1.0 - fade_progress(fade_out_from, fade_out, frame_start)
+                    };
+                    let ndi_image = match (pick, &blended_ndi) {
+                        (Pick::Blend(_), Some(BlendedFrame::Rgba(image))) => {
+                            Some((LayerImage::Rgba(image), None))
+                        }
+                        (Pick::Blend(_), Some(BlendedFrame::Uyvy(frame))) => {
+                            Some((LayerImage::Uyvy(frame), None))
+                        }
+                        (Pick::Previous, _) => match (&prev_ndi_frame, &prev_ndi_uyvy) {
+                            (Some(frame), _) => {
+                                Some((LayerImage::Rgba(&frame.image), frame.timestamp))
+                            }
+                            (None, Some(frame)) => Some((LayerImage::Uyvy(frame), frame.timestamp)),
+                            (None, None) => None,
+                        },
+                        _ => match (&last_ndi_frame, &last_ndi_uyvy) {
+                            (Some(frame), _) => {
+                                Some((LayerImage::Rgba(&frame.image), frame.timestamp))
+                            }
+                            (None, Some(frame)) => Some((LayerImage::Uyvy(frame), frame.timestamp)),
+                            (None, None) => None,
+                        },
+                    };
+                    if let Some((image, timestamp)) = ndi_image {
                         layers.push(Layer {
-                            image: img,
-                            opacity: ndi_opacity,
+                            image,
+                            opacity: ndi_opacity * ndi_fade,
                             z_index: ndi_z,
-                            source: LayerSource::Ndi,
+                            source: LayerId::Ndi,
+                            shared_key: ndi_source_key
+                                .as_ref()
+                                .map(|key| *key.lock().unwrap())
+                                .zip(timestamp)
+                                .map(|(source, frame)| SharedLayerKey { source, frame }),
+                            // Each input frame can be shown as previous, blended or latest
+                            generation: ndi_generation * 512 + pick.code(),
+                            transform: ndi_transform,
                         });
                     }
-                    for (i, (_, opacity, z_index)) in browser_layers.iter().enumerate() {
-                        if let Some(ref img) = last_browser_frames[i] {
+                    for layer in source_layers.iter_mut() {
+                        // Luma keyed frames differ from what other channels get
+                        let shared_key = layer
+                            .luma_key
+                            .is_none()
+                            .then(|| layer.source.shared_key())
+                            .flatten();
+                        if let Some((img, generation)) = layer.source.frame(frame_start) {
                             layers.push(Layer {
-                                image: img,
-                                opacity: *opacity,
-                                z_index: *z_index,
-                                source: LayerSource::Browser(i),
+                                image: LayerImage::Rgba(img),
+                                opacity: layer.opacity,
+                                z_index: layer.z_index,
+                                source: layer.id,
+                                shared_key,
+                                generation,
+                                transform: layer.transform,
                             });
                         }
                     }
 
-                    if layers.is_empty() {
+                    // Mute/solo from the terminal UI, opacity, groups and scenes from the control API
+                    {
+                        let mut mix = layer_mix.lock().unwrap();
+                        layers.retain_mut(|layer| {
+                            let (opacity, fade) = if layer.source == LayerId::Ndi {
+                                (ndi_opacity, ndi_fade)
+                            } else {
+                                (layer.opacity, 1.0)
+                            };
+                            let configured = (opacity, layer.transform.dst_rect((width, height)));
+                            let Some((opacity, dst)) =
+                                mix.apply(layer.source, configured, fade, frame_start)
+                            else {
+                                return false;
+                            };
+                            layer.opacity = opacity;
+                            layer.transform = layer.transform.with_dst(dst, (width, height));
+                            true
+                        });
+                    }
+
+                    // Forward standalone caption metadata so overlays don't strip it from the chain
+                    if let Some(ref tap) = ndi_captions {
+                        let mut tap = tap.lock().unwrap();
+                        while let Some((data, timecode)) = tap.pending.pop_front() {
+                            ndi_output.send_metadata(data, timecode);
+                            tap.forwarded += 1;
+                        }
+                    }
+
+                    let degraded = overload.effective_policy();
+                    frame_index = frame_index.wrapping_add(1);
+                    let compare_view = *compare.lock().unwrap();
+                    // The compare labels show tally, so a change redraws them
+                    #[cfg(feature = "gpu")]
+                    {
+                        let tally = *output_tally.lock().unwrap();
+                        if tally != shown_tally {
+                            shown_tally = tally;
+                            if compare_view.enabled {
+                                last_composite = None;
+                            }
+                        }
+                    }
+                    let composite_key = CompositeKey::new(&layers, degraded, compare_view);
+                    let animated = filters_animate && degraded != OverloadPolicy::SkipFilters;
+                    // A lone opaque input at the canvas size goes out as received,
+                    // unless the layer shows a blend (e.g. a source crossfade)
+                    let pass_through = !input_filtered
+                        && !rate_adapter.enabled()
+                        && converter.is_none()
+                        && source_transition.is_none()
+                        && pick == Pick::Latest
+                        && !compare_view.enabled
+                        && matches!(
+                            layers.as_slice(),
+                            [Layer {
+                                image: LayerImage::Rgba(image),
+                                opacity,
+                                source: LayerId::Ndi,
+                                transform,
+                                ..
+                            }] if *opacity >= 1.0
+                                && transform.is_identity()
+                                && compositor::covers_canvas(image, (width, height))
+                        );
+
+                    let blank = *blank_ref.lock().unwrap();
+                    let show_slate = blank == Some(BlankMode::Slate)
+                        || *slate_ref.lock().unwrap()
+                        || (loss_applied && on_loss == LossPolicy::Slate)
+                        || (warming && warmup == WarmupMode::Slate);
+
+                    // Sources are only tallied while their picture is in the output
+                    if !input_tallies.is_empty() {
+                        let in_output = !show_slate
+                            && blank.is_none()
+                            && !warming
+                            && layers
+                                .iter()
+                                .any(|l| l.source == LayerId::Ndi && l.opacity > 0.0);
+                        let tally = *output_tally.lock().unwrap();
+                        let tally = InputTally {
+                            on_program: in_output && tally.on_program,
+                            on_preview: in_output && tally.on_preview,
+                        };
+                        for slot in &input_tallies {
+                            *slot.lock().unwrap() = tally;
+                        }
+                    }
+                    if show_slate {
+                        let (slate, shown) = (&slate_image, OutputImage::Slate);
+                        send_output(&mut ndi_output, slate, shown, &mut last_sent, output_dedup);
+                    } else if blank == Some(BlankMode::Black) {
+                        // Blanked: the canvas and last composite stay as they are for resume
+                        let (black, shown) = (&black_image, OutputImage::Black);
+                        send_output(&mut ndi_output, black, shown, &mut last_sent, output_dedup);
+                    } else if warming {
+                        // Nothing has been composited yet, so the canvas is still black
+                        let _ = ndi_output.send_frame(&canvas);
+                        last_sent = None;
+                    } else if layers.is_empty() {
+                        // Everything cleared or muted: black, not the last composite
+                        if last_composite.take().is_some() {
+                            canvas.pixels_mut().for_each(|p| *p = Rgba([0, 0, 0, 255]));
+                        }
+                        input_on_air = false;
                         let _ = ndi_output.send_frame(&canvas);
+                        last_sent = None;
+                    } else if degraded == OverloadPolicy::HalfRate && frame_index.is_multiple_of(2)
+                    {
+                        // Half rate: repeat the previous composite to keep output cadence
+                        let output = last_output(&canvas, &last_ndi_frame, input_on_air);
+                        let shown = OutputImage::Composite;
+                        send_output(&mut ndi_output, output, shown, &mut last_sent, output_dedup);
+                    } else if !animated && last_composite.as_ref() == Some(&composite_key) {
+                        // Nothing changed since the last composite — resend it. Not recorded
+                        // as render time, or the overload policy would flap.
+                        let output = last_output(&canvas, &last_ndi_frame, input_on_air);
+                        let shown = OutputImage::Composite;
+                        send_output(&mut ndi_output, output, shown, &mut last_sent, output_dedup);
+                    } else if pass_through {
+                        // Nothing to blend: send the frame the receive thread
+                        // produced instead of copying it into the canvas first
+                        if let Some(ref frame) = last_ndi_frame {
+                            let _ = ndi_output.send_frame(&frame.image);
+                        }
+                        last_sent = Some(OutputImage::Composite);
+                        input_on_air = true;
+                        overload.record(frame_start.elapsed(), frame_interval, &channel_name);
+                        last_composite = Some(composite_key);
                     } else {
+                        let downscale = degraded == OverloadPolicy::Downscale;
+                        let target = if downscale {
+                            &mut small_canvas
+                        } else {
+                            &mut canvas
+                        };
+
                         #[cfg(feature = "gpu")]
                         {
-                            let used_gpu = if let Some(ref mut gpu) = gpu_compositor {
-                                gpu.composite(&mut canvas, &mut layers)
-                            } else {
-                                false
-                            };
+                            let filters_enabled = degraded != OverloadPolicy::SkipFilters;
+                            let used_gpu = gpu_recovery.composite(
+                                target,
+                                &mut layers,
+                                downscale,
+                                filters_enabled,
+                                &channel_name,
+                            );
                             if !used_gpu {
-                                compositor::composite(&mut canvas, &mut layers);
+                                compositor::composite(target, &mut layers, input_keyed, &mut uyvy_cache);
+                            } else if compare_view.enabled && filters_enabled && !downscale {
+                                // Compare is suspended while the overload policy degrades output
+                                gpu_recovery.composite_compare(
+                                    target,
+                                    &mut layers,
+                                    compare_view.split,
+                                );
+                                let labels = source_labels.get_or_insert_with(|| {
+                                    labels_enabled
+                                        .then(|| {
+                                            SourceLabels::load(
+                                                labels_font.as_deref(),
+                                                height,
+                                                &channel_name,
+                                            )
+                                        })
+                                        .flatten()
+                                });
+                                if let Some(labels) = labels {
+                                    let mix = layer_mix.lock().unwrap();
+                                    let sources: Vec<&str> = label_sources
+                                        .iter()
+                                        .filter(|(source, _)| mix.is_visible(*source))
+                                        .map(|(_, label)| label.as_str())
+                                        .collect();
+                                    let output = output_name.lock().unwrap().clone();
+                                    labels.draw(target, &sources, &output, shown_tally);
+                                }
                             }
                         }
                         #[cfg(not(feature = "gpu"))]
                         {
-                            compositor::composite(&mut canvas, &mut layers);
+                            compositor::composite(target, &mut layers, input_keyed, &mut uyvy_cache);
+                        }
+
+                        if downscale {
+                            upscale_into(&small_canvas, &mut canvas);
                         }
                         let _ = ndi_output.send_frame(&canvas);
+                        last_sent = Some(OutputImage::Composite);
+                        input_on_air = false;
+                        overload.record(frame_start.elapsed(), frame_interval, &channel_name);
+                        last_composite = Some(composite_key);
                     }
 
                     *frames_output.lock().unwrap() += 1;
+                    timeline += 1;
+                    window_output += 1;
+
+                    let on_air = if show_slate {
+                        &slate_image
+                    } else if blank.is_some() {
+                        &black_image
+                    } else {
+                        last_output(&canvas, &last_ndi_frame, input_on_air)
+                    };
+                    if let Some(ref replay) = replay {
+                        replay.push(on_air);
+                    }
+                    if let Some(ref camera) = virtual_camera {
+                        camera.push(on_air);
+                    }
+
+                    // Scopes of what just went out, at a low rate
+                    if let (Some(scopes), Some(cfg)) = (&scopes, &scopes_config) {
+                        let due = last_scopes.is_none_or(|at: Instant| {
+                            at.elapsed() >= Duration::from_millis(cfg.interval_ms)
+                        });
+                        if due {
+                            let waveform_width = if cfg.waveform { cfg.waveform_width } else { 0 };
+                            *scopes.lock().unwrap() =
+                                Some(crate::scopes::measure(on_air, waveform_width));
+                            last_scopes = Some(Instant::now());
+                        }
+                    }
+
+                    busy_time += frame_start.elapsed();
+                    let input_age = match (&last_ndi_frame, &last_ndi_uyvy) {
+                        _ if show_slate || blank.is_some() => None,
+                        (Some(frame), _) => Some(frame.received_at.elapsed()),
+                        (None, Some(frame)) => Some(frame.received_at.elapsed()),
+                        (None, None) => None,
+                    };
+                    minute_stats.frame(frame_start.elapsed(), input_age);
+                    let window = usage_window_start.elapsed();
+                    if window >= stats_interval {
+                        let mut usage = usage_ref.lock().unwrap();
+                        usage.render_cpu_percent =
+                            busy_time.as_secs_f32() / window.as_secs_f32() * 100.0;
+                        #[cfg(feature = "gpu")]
+                        {
+                            usage.gpu_memory_bytes = gpu_recovery.memory_bytes();
+                        }
+                        drop(usage);
+
+                        let rate = |frames: u64| {
+                            (frames as f32 / window.as_secs_f32() * 10.0).round() / 10.0
+                        };
+                        minute_stats.fps(rate(window_output));
+                        *frame_rates_ref.lock().unwrap() = FrameRates {
+                            output: rate(window_output),
+                            ndi: has_ndi_input.then(|| rate(window_ndi)),
+                            overlays: window_overlays.iter().map(|&n| rate(n)).collect(),
+                        };
+                        window_output = 0;
+                        window_ndi = 0;
+                        window_overlays.fill(0);
+
+                        usage_window_start = Instant::now();
+                        busy_time = Duration::ZERO;
+                    }
 
                     // Precise frame timing: macOS timer coalescing causes thread::sleep
                     // to overshoot by 50+ms, so we use small sleep steps + spin finish.
@@ -309,9 +2386,6 @@ impl Channel {
             })
             .expect("Failed to spawn render thread");
 
-        Ok(Self {
-            state: Arc::new(state),
-            _thread: thread,
-        })
+        Ok(Self { state, thread })
     }
 }
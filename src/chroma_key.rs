@@ -0,0 +1,57 @@
+use image::RgbaImage;
+
+use crate::config::ChromaKeyConfig;
+
+/// CPU chroma key, used when the GPU compositor isn't available to run the
+/// built-in shader (`src/shaders/chroma_key.wgsl`). Same math, so a channel
+/// looks the same either way.
+pub struct ChromaKey {
+    /// CbCr of the key color
+    key: [f32; 2],
+    similarity: f32,
+    smoothness: f32,
+    spill: f32,
+}
+
+impl ChromaKey {
+    pub fn new(config: &ChromaKeyConfig) -> Self {
+        Self {
+            key: cbcr(config.rgb()),
+            similarity: config.similarity,
+            smoothness: config.smoothness.max(0.0001),
+            spill: config.spill,
+        }
+    }
+
+    /// Make pixels near the key color transparent, keeping existing alpha
+    /// (letterbox bars stay clear), and desaturate spill near the edges.
+    pub fn apply(&self, img: &mut RgbaImage) {
+        for px in img.pixels_mut() {
+            let mut rgb = [px[0], px[1], px[2]].map(|c| c as f32 / 255.0);
+            let [cb, cr] = cbcr(rgb);
+            let base = (cb - self.key[0]).hypot(cr - self.key[1]) - self.similarity;
+            let mask = (base / self.smoothness).clamp(0.0, 1.0).powf(1.5);
+
+            if self.spill > 0.0 {
+                let luma = 0.2126 * rgb[0] + 0.7152 * rgb[1] + 0.0722 * rgb[2];
+                let keep = (base / self.spill).clamp(0.0, 1.0).powf(1.5);
+                rgb = rgb.map(|c| luma + (c - luma) * keep);
+            }
+
+            px.0 = [
+                (rgb[0] * 255.0).round() as u8,
+                (rgb[1] * 255.0).round() as u8,
+                (rgb[2] * 255.0).round() as u8,
+                (px[3] as f32 * mask).round() as u8,
+            ];
+        }
+    }
+}
+
+/// BT.709 chroma of an RGB color.
+fn cbcr([r, g, b]: [f32; 3]) -> [f32; 2] {
+    [
+        -0.1146 * r - 0.3854 * g + 0.5 * b,
+        0.5 * r - 0.4542 * g - 0.0458 * b,
+    ]
+}
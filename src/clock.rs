@@ -0,0 +1,137 @@
+use anyhow::Result;
+use image::RgbaImage;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio_util::sync::CancellationToken;
+
+use crate::config::{ClockConfig, TimeSource};
+use crate::errors::ErrorSlot;
+use crate::ntp::{self, SharedSync};
+use crate::text::{self, AnchoredText, TextStyle};
+
+/// Clock status info for reporting.
+pub struct ClockState {
+    pub time_source: TimeSource,
+    /// Latest NTP measurement (None for `time_source = "system"`)
+    pub sync: Option<SharedSync>,
+    pub last_error: ErrorSlot,
+}
+
+/// Time-of-day clock layer: formats the time at which each frame is rendered,
+/// to the frame with `%f`.
+pub struct ClockLayer {
+    format: String,
+    utc: bool,
+    frame_rate: u32,
+    sync: Option<SharedSync>,
+    last_error: ErrorSlot,
+    time_source: TimeSource,
+    text: AnchoredText,
+}
+
+impl ClockLayer {
+    pub fn start(
+        cfg: &ClockConfig,
+        width: u32,
+        height: u32,
+        frame_rate: u32,
+        cancel: CancellationToken,
+    ) -> Result<Self> {
+        let font = text::load_font(cfg.font.as_deref().unwrap_or(text::DEFAULT_FONT))?;
+        let last_error = ErrorSlot::default();
+
+        let sync = (cfg.time_source == TimeSource::Ntp).then(|| {
+            let sync = SharedSync::default();
+            ntp::spawn(
+                &cfg.ntp_server,
+                Duration::from_secs(cfg.ntp_interval),
+                sync.clone(),
+                last_error.clone(),
+                cancel,
+            );
+            sync
+        });
+
+        Ok(Self {
+            format: cfg.format.clone(),
+            utc: cfg.utc,
+            frame_rate,
+            sync,
+            last_error,
+            time_source: cfg.time_source,
+            text: AnchoredText::new(
+                font,
+                TextStyle {
+                    size: cfg.font_size,
+                    color: text::parse_color(&cfg.color).unwrap_or([255, 255, 255, 255]),
+                    background: text::parse_color(&cfg.background).unwrap_or([0, 0, 0, 0]),
+                    padding: (cfg.font_size / 4.0) as u32,
+                },
+                cfg.position,
+                cfg.margin,
+                width,
+                height,
+            ),
+        })
+    }
+
+    pub fn state(&self) -> ClockState {
+        ClockState {
+            time_source: self.time_source,
+            sync: self.sync.clone(),
+            last_error: self.last_error.clone(),
+        }
+    }
+
+    /// Returns the clock rendered for the current time and its generation.
+    pub fn frame(&mut self) -> Option<(&RgbaImage, u64)> {
+        // Until the first NTP reply the host clock is shown uncorrected
+        let now = self
+            .sync
+            .as_ref()
+            .and_then(|s| *s.lock().unwrap())
+            .map_or_else(SystemTime::now, |s| s.now());
+        let text = format_time(&self.format, now, self.utc, self.frame_rate);
+        self.text.show(Some(&text))
+    }
+}
+
+/// Expand `%H %M %S %f %Y %m %d %%` for `at`; `%f` is the frame within the second.
+pub fn format_time(format: &str, at: SystemTime, utc: bool, frame_rate: u32) -> String {
+    let since_epoch = at.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let tm = broken_down(since_epoch.as_secs() as libc::time_t, utc);
+    let frame = since_epoch.subsec_nanos() as u64 * frame_rate as u64 / 1_000_000_000;
+
+    let mut out = String::with_capacity(format.len() + 8);
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('H') => out.push_str(&format!("{:02}", tm.tm_hour)),
+            Some('M') => out.push_str(&format!("{:02}", tm.tm_min)),
+            Some('S') => out.push_str(&format!("{:02}", tm.tm_sec)),
+            Some('f') => out.push_str(&format!("{:02}", frame)),
+            Some('Y') => out.push_str(&format!("{:04}", tm.tm_year + 1900)),
+            Some('m') => out.push_str(&format!("{:02}", tm.tm_mon + 1)),
+            Some('d') => out.push_str(&format!("{:02}", tm.tm_mday)),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+/// Calendar fields of a Unix time, in UTC or the host's time zone.
+fn broken_down(secs: libc::time_t, utc: bool) -> libc::tm {
+    unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        if utc {
+            libc::gmtime_r(&secs, &mut tm);
+        } else {
+            libc::localtime_r(&secs, &mut tm);
+        }
+        tm
+    }
+}
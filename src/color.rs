@@ -0,0 +1,350 @@
+use image::RgbaImage;
+use std::time::Instant;
+
+use crate::compositor::Placement;
+use crate::config::{ColorPrimaries, ColorRange, Fit, Roi};
+
+type Matrix = [[f32; 3]; 3];
+
+/// BT.2020 → BT.709 primaries, applied in linear light.
+const BT2020_TO_BT709: Matrix = [
+    [1.6605, -0.5876, -0.0728],
+    [-0.1246, 1.1329, -0.0083],
+    [-0.0182, -0.1006, 1.1187],
+];
+
+/// Display gamma used to linearize for gamut conversion (BT.1886).
+const GAMMA: f32 = 2.4;
+
+/// Size of the linear → encoded lookup table.
+const ENCODE_LUT_SIZE: usize = 4096;
+
+/// Per-input conversion into the pipeline's full-range BT.709/sRGB space.
+///
+/// The NDI SDK decodes every source to RGB with BT.709 coefficients and passes
+/// levels through, so limited-range or BT.601/BT.2020 sources come out washed out
+/// or with shifted hues. Each correction is a lookup table or 3x3 matrix and is
+/// skipped entirely when the input is already full-range BT.709.
+pub struct ColorConversion {
+    range_lut: [u8; 256],
+    /// Undo the SDK's BT.709 decode and redo it with the source's coefficients
+    matrix: Option<Matrix>,
+    gamut: Option<Gamut>,
+}
+
+struct Gamut {
+    to_linear: [f32; 256],
+    matrix: Matrix,
+    encode: Vec<u8>,
+}
+
+impl Gamut {
+    fn bt2020_to_bt709() -> Self {
+        let mut to_linear = [0f32; 256];
+        for (i, v) in to_linear.iter_mut().enumerate() {
+            *v = (i as f32 / 255.0).powf(GAMMA);
+        }
+        let encode = (0..ENCODE_LUT_SIZE)
+            .map(|i| {
+                let linear = i as f32 / (ENCODE_LUT_SIZE - 1) as f32;
+                (linear.powf(1.0 / GAMMA) * 255.0).round() as u8
+            })
+            .collect();
+        Self {
+            to_linear,
+            matrix: BT2020_TO_BT709,
+            encode,
+        }
+    }
+
+    fn apply(&self, rgb: [u8; 3]) -> [u8; 3] {
+        let linear = rgb.map(|c| self.to_linear[c as usize]);
+        let max = (ENCODE_LUT_SIZE - 1) as f32;
+        mul(&self.matrix, linear).map(|c| self.encode[(c.clamp(0.0, 1.0) * max) as usize])
+    }
+}
+
+/// Lookup table mapping a channel of the given range to full range.
+fn range_lut(range: ColorRange) -> [u8; 256] {
+    let mut lut = [0u8; 256];
+    for (i, v) in lut.iter_mut().enumerate() {
+        *v = match range {
+            ColorRange::Full => i as u8,
+            // 16–235 → 0–255
+            ColorRange::Limited => ((i as f32 - 16.0) * 255.0 / 219.0)
+                .round()
+                .clamp(0.0, 255.0) as u8,
+        };
+    }
+    lut
+}
+
+impl ColorConversion {
+    /// Returns None when no conversion is needed.
+    pub fn new(range: ColorRange, primaries: ColorPrimaries) -> Option<Self> {
+        if range == ColorRange::Full && primaries == ColorPrimaries::Bt709 {
+            return None;
+        }
+
+        let matrix = (primaries != ColorPrimaries::Bt709).then(|| {
+            let (kr, kb) = luma_coefficients(primaries);
+            recode_matrix(kr, kb)
+        });
+
+        let gamut = (primaries == ColorPrimaries::Bt2020).then(Gamut::bt2020_to_bt709);
+
+        Some(Self {
+            range_lut: range_lut(range),
+            matrix,
+            gamut,
+        })
+    }
+
+    /// Convert an RGBA image in place. Alpha is left untouched.
+    pub fn apply(&self, img: &mut RgbaImage) {
+        for px in img.pixels_mut() {
+            let mut rgb = [
+                self.range_lut[px.0[0] as usize],
+                self.range_lut[px.0[1] as usize],
+                self.range_lut[px.0[2] as usize],
+            ];
+
+            if let Some(ref m) = self.matrix {
+                let v = rgb.map(|c| c as f32);
+                rgb = mul(m, v).map(|c| c.round().clamp(0.0, 255.0) as u8);
+            }
+
+            if let Some(ref gamut) = self.gamut {
+                rgb = gamut.apply(rgb);
+            }
+
+            px.0[0] = rgb[0];
+            px.0[1] = rgb[1];
+            px.0[2] = rgb[2];
+        }
+    }
+}
+
+/// Packed 4:2:2 frame (U Y0 V Y1 per pixel pair) as received from NDI, carrying
+/// the input's settings so the GPU or CPU can convert it to RGBA on upload.
+#[derive(Clone)]
+pub struct UyvyFrame {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+    pub roi: Option<Roi>,
+    pub fit: Fit,
+    pub range: ColorRange,
+    pub primaries: ColorPrimaries,
+    /// NDI timestamp of the frame, if the sender set one
+    pub timestamp: Option<i64>,
+    pub received_at: Instant,
+    /// Caption metadata that came with the frame (caption passthrough only)
+    pub captions: Option<String>,
+}
+
+impl UyvyFrame {
+    /// Region of the frame to use, clamped to its bounds: (x, y, width, height).
+    pub fn source_rect(&self) -> (u32, u32, u32, u32) {
+        match self.roi {
+            Some(roi) => {
+                let x = roi.x.min(self.width.saturating_sub(1));
+                let y = roi.y.min(self.height.saturating_sub(1));
+                (
+                    x,
+                    y,
+                    roi.width.min(self.width - x).max(1),
+                    roi.height.min(self.height - y).max(1),
+                )
+            }
+            None => (0, 0, self.width, self.height),
+        }
+    }
+
+    /// Where the frame lands on a `width`x`height` canvas under its fit mode.
+    pub fn placement(&self, width: u32, height: u32) -> Placement {
+        Placement::new(self.fit, self.source_rect(), (width, height))
+    }
+
+    /// CPU conversion to RGBA at `img`'s size (nearest-neighbour scaling),
+    /// reusing its buffer. Used when the GPU compositor is unavailable for a
+    /// frame; `luts` must be built for the frame's range and primaries.
+    pub fn to_rgba_into(&self, luts: &UyvyLuts, img: &mut RgbaImage) {
+        let (width, height) = img.dimensions();
+        let Placement {
+            src: (rx, ry, rw, rh),
+            dst: (dx, dy, dw, dh),
+        } = self.placement(width, height);
+        let stride = self.width as usize * 2;
+
+        // Pixels outside the placement (letterbox bars) stay transparent
+        img.fill(0);
+        let rows = img
+            .chunks_exact_mut(width as usize * 4)
+            .enumerate()
+            .skip(dy as usize)
+            .take(dh as usize);
+        for (y, row) in rows {
+            let sy = (ry + (y as u32 - dy) * rh / dh) as usize;
+            let Some(src_row) = self.data.get(sy * stride..(sy + 1) * stride) else {
+                continue;
+            };
+            let placed = &mut row[dx as usize * 4..(dx + dw) as usize * 4];
+            for (x, px) in placed.chunks_exact_mut(4).enumerate() {
+                let sx = (rx + x as u32 * rw / dw) as usize;
+                let pair = (sx & !1) * 2;
+                let Some(bytes) = src_row.get(pair..pair + 4) else {
+                    continue;
+                };
+                let luma = if sx & 1 == 0 { bytes[1] } else { bytes[3] };
+                px.copy_from_slice(&luts.pixel(luma, bytes[0], bytes[2]));
+            }
+        }
+    }
+}
+
+/// Lookup tables converting a UYVY source's video-range samples to full-range
+/// BT.709 RGBA, built once per range and primaries instead of doing the matrix
+/// math per pixel.
+pub struct UyvyLuts {
+    pub range: ColorRange,
+    pub primaries: ColorPrimaries,
+    /// Contributions of each sample to R, G and B in 16.16 fixed point, 0–255 scale
+    luma: [i32; 256],
+    cr_to_r: [i32; 256],
+    cb_to_g: [i32; 256],
+    cr_to_g: [i32; 256],
+    cb_to_b: [i32; 256],
+    range_lut: [u8; 256],
+    gamut: Option<Gamut>,
+}
+
+impl UyvyLuts {
+    pub fn new(range: ColorRange, primaries: ColorPrimaries) -> Self {
+        let (kr, kb) = luma_coefficients(primaries);
+        let m = ycbcr_to_rgb(kr, kb);
+        let table = |f: &dyn Fn(f32) -> f32| {
+            let mut lut = [0i32; 256];
+            for (i, v) in lut.iter_mut().enumerate() {
+                *v = (f(i as f32) * 255.0 * 65536.0).round() as i32;
+            }
+            lut
+        };
+        let chroma = |i: f32| (i - 128.0) / 224.0;
+        Self {
+            range,
+            primaries,
+            luma: table(&|i| (i - 16.0) / 219.0),
+            cr_to_r: table(&|i| m[0][2] * chroma(i)),
+            cb_to_g: table(&|i| m[1][1] * chroma(i)),
+            cr_to_g: table(&|i| m[1][2] * chroma(i)),
+            cb_to_b: table(&|i| m[2][1] * chroma(i)),
+            range_lut: range_lut(range),
+            gamut: (primaries == ColorPrimaries::Bt2020).then(Gamut::bt2020_to_bt709),
+        }
+    }
+
+    fn pixel(&self, y: u8, cb: u8, cr: u8) -> [u8; 4] {
+        let (cb, cr) = (cb as usize, cr as usize);
+        let l = self.luma[y as usize];
+        let channel = |v: i32| self.range_lut[((v + 32768) >> 16).clamp(0, 255) as usize];
+        let mut rgb = [
+            channel(l + self.cr_to_r[cr]),
+            channel(l + self.cb_to_g[cb] + self.cr_to_g[cr]),
+            channel(l + self.cb_to_b[cb]),
+        ];
+        if let Some(ref gamut) = self.gamut {
+            rgb = gamut.apply(rgb);
+        }
+        [rgb[0], rgb[1], rgb[2], 255]
+    }
+}
+
+/// Convert RGBA to packed 4:2:2 (U Y0 V Y1 per pixel pair), BT.709 video range,
+/// as NDI receivers expect. Each pair shares the average of its two chroma
+/// samples, so the width must be even. With `alpha`, the frame's alpha follows as a plane of its own (UYVA).
+pub fn rgba_to_uyvy(src: &[u8], width: u32, height: u32, alpha: bool, dst: &mut [u8]) {
+    let (kr, kb) = luma_coefficients(ColorPrimaries::Bt709);
+    let to_ycbcr = rgb_to_ycbcr(kr, kb);
+    let (width, height) = (width as usize, height as usize);
+    let stride = width * 2;
+    let (packed, alpha_plane) = dst.split_at_mut(stride * height);
+
+    let encode = |px: &[u8]| mul(&to_ycbcr, [px[0], px[1], px[2]].map(|c| c as f32 / 255.0));
+    for (src_row, dst_row) in src
+        .chunks_exact(width * 4)
+        .zip(packed.chunks_exact_mut(stride))
+    {
+        for (pair, out) in src_row.chunks_exact(8).zip(dst_row.chunks_exact_mut(4)) {
+            let (first, second) = (encode(&pair[..4]), encode(&pair[4..]));
+            let cb = (first[1] + second[1]) / 2.0;
+            let cr = (first[2] + second[2]) / 2.0;
+            out[0] = (128.0 + cb * 224.0).round().clamp(16.0, 240.0) as u8;
+            out[1] = (16.0 + first[0] * 219.0).round().clamp(16.0, 235.0) as u8;
+            out[2] = (128.0 + cr * 224.0).round().clamp(16.0, 240.0) as u8;
+            out[3] = (16.0 + second[0] * 219.0).round().clamp(16.0, 235.0) as u8;
+        }
+    }
+
+    if alpha {
+        for (a, px) in alpha_plane.iter_mut().zip(src.chunks_exact(4)) {
+            *a = px[3];
+        }
+    }
+}
+
+/// Luma coefficients (Kr, Kb) of a YCbCr encoding.
+pub fn luma_coefficients(primaries: ColorPrimaries) -> (f32, f32) {
+    match primaries {
+        ColorPrimaries::Bt709 => (0.2126, 0.0722),
+        ColorPrimaries::Bt601 => (0.299, 0.114),
+        ColorPrimaries::Bt2020 => (0.2627, 0.0593),
+    }
+}
+
+fn mul(m: &Matrix, v: [f32; 3]) -> [f32; 3] {
+    [
+        m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2],
+        m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2],
+        m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2],
+    ]
+}
+
+/// RGB → YCbCr matrix for the given luma coefficients.
+fn rgb_to_ycbcr(kr: f32, kb: f32) -> Matrix {
+    let kg = 1.0 - kr - kb;
+    [
+        [kr, kg, kb],
+        [-kr / (2.0 * (1.0 - kb)), -kg / (2.0 * (1.0 - kb)), 0.5],
+        [0.5, -kg / (2.0 * (1.0 - kr)), -kb / (2.0 * (1.0 - kr))],
+    ]
+}
+
+/// YCbCr → RGB matrix for the given luma coefficients.
+fn ycbcr_to_rgb(kr: f32, kb: f32) -> Matrix {
+    let kg = 1.0 - kr - kb;
+    [
+        [1.0, 0.0, 2.0 * (1.0 - kr)],
+        [
+            1.0,
+            -2.0 * kb * (1.0 - kb) / kg,
+            -2.0 * kr * (1.0 - kr) / kg,
+        ],
+        [1.0, 2.0 * (1.0 - kb), 0.0],
+    ]
+}
+
+/// Matrix that re-encodes RGB decoded with BT.709 coefficients as if it had
+/// been decoded with (kr, kb): back to YCbCr via BT.709, then forward again.
+fn recode_matrix(kr: f32, kb: f32) -> Matrix {
+    let (kr_709, kb_709) = luma_coefficients(ColorPrimaries::Bt709);
+    let to_ycbcr = rgb_to_ycbcr(kr_709, kb_709);
+    let to_rgb = ycbcr_to_rgb(kr, kb);
+    let mut out = [[0f32; 3]; 3];
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, cell) in row.iter_mut().enumerate() {
+            *cell = (0..3).map(|k| to_rgb[i][k] * to_ycbcr[k][j]).sum();
+        }
+    }
+    out
+}
@@ -1,23 +1,411 @@
 use image::RgbaImage;
+use std::borrow::Cow;
 
-/// Identifies which pre-compiled filter chain to use for a layer.
+use crate::color::{UyvyFrame, UyvyLuts};
+use crate::config::Fit;
+use crate::source::LayerId;
+
+/// Pixel rectangle: (x, y, width, height).
+pub type Rect = (u32, u32, u32, u32);
+
+/// Where a source lands on the canvas under a fit mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    /// The part of the source that is shown
+    pub src: Rect,
+    /// The part of the canvas it is scaled into; the rest stays transparent
+    pub dst: Rect,
+}
+
+impl Placement {
+    /// Place the `src` region of a source onto a `width`x`height` canvas.
+    pub fn new(fit: Fit, src: Rect, (width, height): (u32, u32)) -> Self {
+        let full = (0, 0, width, height);
+        let (sx, sy, sw, sh) = src;
+        if sw == 0 || sh == 0 || width == 0 || height == 0 {
+            return Self { src, dst: full };
+        }
+        // Compare aspect ratios without dividing
+        let (sw64, sh64, w64, h64) = (sw as u64, sh as u64, width as u64, height as u64);
+        let wider = sw64 * h64 > w64 * sh64;
+        match fit {
+            Fit::Stretch => Self { src, dst: full },
+            Fit::Contain => {
+                let (w, h) = if wider {
+                    (width, (sh64 * w64 / sw64) as u32)
+                } else {
+                    ((sw64 * h64 / sh64) as u32, height)
+                };
+                let (w, h) = (w.clamp(1, width), h.clamp(1, height));
+                Self {
+                    src,
+                    dst: ((width - w) / 2, (height - h) / 2, w, h),
+                }
+            }
+            Fit::Cover => {
+                let (w, h) = if wider {
+                    ((w64 * sh64 / h64) as u32, sh)
+                } else {
+                    (sw, (h64 * sw64 / w64) as u32)
+                };
+                let (w, h) = (w.clamp(1, sw), h.clamp(1, sh));
+                Self {
+                    src: (sx + (sw - w) / 2, sy + (sh - h) / 2, w, h),
+                    dst: full,
+                }
+            }
+        }
+    }
+
+    /// Whether the source leaves part of the canvas uncovered.
+    pub fn letterboxed(&self, (width, height): (u32, u32)) -> bool {
+        self.dst != (0, 0, width, height)
+    }
+}
+
+/// Whether an image can stand in for the whole canvas: it has the canvas size
+/// and is opaque in the top-left corner, which letterbox bars never are.
+pub fn covers_canvas(image: &RgbaImage, (width, height): (u32, u32)) -> bool {
+    image.dimensions() == (width, height) && image.as_raw().get(3) == Some(&255)
+}
+
+/// Where a layer's image goes on the canvas: its `crop`, in image pixels,
+/// scaled into the `dst` area of the canvas. Without either, the whole image
+/// covers the whole canvas.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Transform {
+    pub crop: Option<Rect>,
+    pub dst: Option<Rect>,
+    pub warp: Option<Warp>,
+}
+
+impl Transform {
+    /// A `dst` covering the whole `canvas` is left out, so such layers still
+    /// count as full-canvas ones.
+    pub fn new(crop: Option<Rect>, dst: Option<Rect>, (width, height): (u32, u32)) -> Self {
+        Self {
+            crop,
+            dst: dst.filter(|&dst| dst != (0, 0, width, height)),
+            warp: None,
+        }
+    }
+
+    /// The same layer scaled into `dst` instead.
+    pub fn with_dst(self, dst: Rect, canvas: (u32, u32)) -> Self {
+        Self {
+            warp: self.warp,
+            ..Self::new(self.crop, Some(dst), canvas)
+        }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.crop.is_none() && self.dst.is_none() && self.warp.is_none()
+    }
+
+    /// The canvas area the layer is drawn into.
+    pub fn dst_rect(&self, (width, height): (u32, u32)) -> Rect {
+        self.dst.unwrap_or((0, 0, width, height))
+    }
+
+    /// `image` as a full-canvas layer: cropped, scaled into place (nearest
+    /// neighbour) and transparent around it. Borrowed when it already is one.
+    pub fn apply<'a>(&self, image: &'a RgbaImage, canvas: (u32, u32)) -> Cow<'a, RgbaImage> {
+        let (image_w, image_h) = image.dimensions();
+        let crop = self.crop.and_then(|(x, y, w, h)| {
+            let (x, y) = (x.min(image_w), y.min(image_h));
+            let (w, h) = (w.min(image_w - x), h.min(image_h - y));
+            (w > 0 && h > 0 && (x, y, w, h) != (0, 0, image_w, image_h)).then_some((x, y, w, h))
+        });
+        let image: Cow<RgbaImage> = match crop {
+            Some((x, y, w, h)) => {
+                Cow::Owned(image::imageops::crop_imm(image, x, y, w, h).to_image())
+            }
+            None => Cow::Borrowed(image),
+        };
+        if let Some(warp) = self.warp {
+            return Cow::Owned(warp.apply(&image, self.dst_rect(canvas), canvas));
+        }
+        let (x, y, w, h) = self.dst_rect(canvas);
+        let image = if image.dimensions() != (w, h) {
+            Cow::Owned(image::imageops::resize(
+                &*image,
+                w,
+                h,
+                image::imageops::FilterType::Nearest,
+            ))
+        } else {
+            image
+        };
+        if self.dst.is_none() {
+            return image;
+        }
+        let mut placed = RgbaImage::new(canvas.0, canvas.1);
+        image::imageops::replace(&mut placed, &*image, x as i64, y as i64);
+        Cow::Owned(placed)
+    }
+}
+
+/// Rotation and shear of a layer, in degrees. The layer is turned and sheared
+/// around its centre, then scaled so its bounding box fills its canvas area,
+/// e.g. a horizontal ticker turned 90° to run up the edge of the screen.
 #[derive(Debug, Clone, Copy)]
-pub enum LayerSource {
-    Ndi,
-    Browser(#[allow(dead_code)] usize),
+pub struct Warp {
+    pub rotation: f32,
+    pub skew_x: f32,
+    pub skew_y: f32,
+}
+
+// Compared bitwise, so warps can key composites and GPU uploads
+impl PartialEq for Warp {
+    fn eq(&self, other: &Self) -> bool {
+        self.bits() == other.bits()
+    }
+}
+
+impl Eq for Warp {}
+
+impl std::hash::Hash for Warp {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.bits().hash(state);
+    }
+}
+
+impl Warp {
+    /// None when it leaves the layer as it is.
+    pub fn new(rotation: f32, skew_x: f32, skew_y: f32) -> Option<Self> {
+        let warp = Self {
+            rotation: rotation.rem_euclid(360.0),
+            skew_x,
+            skew_y,
+        };
+        (warp.rotation != 0.0 || skew_x != 0.0 || skew_y != 0.0).then_some(warp)
+    }
+
+    fn bits(&self) -> [u32; 3] {
+        [self.rotation, self.skew_x, self.skew_y].map(f32::to_bits)
+    }
+
+    /// `image` turned, sheared and scaled into the `dst` area of an otherwise
+    /// transparent canvas, sampled bilinearly.
+    fn apply(&self, image: &RgbaImage, dst: Rect, (width, height): (u32, u32)) -> RgbaImage {
+        let mut placed = RgbaImage::new(width, height);
+        let (src_w, src_h) = (image.width() as f32, image.height() as f32);
+        // Shear, then rotate (clockwise, as y points down)
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let (kx, ky) = (
+            self.skew_x.to_radians().tan(),
+            self.skew_y.to_radians().tan(),
+        );
+        let m = [
+            [cos - sin * ky, cos * kx - sin],
+            [sin + cos * ky, sin * kx + cos],
+        ];
+        // Scale the bounding box of the turned image onto `dst`
+        let (half_w, half_h) = (src_w / 2.0, src_h / 2.0);
+        let extent = |row: [f32; 2]| (row[0] * half_w).abs() + (row[1] * half_h).abs();
+        let (x, y, w, h) = dst;
+        let (scale_x, scale_y) = (
+            w as f32 / (2.0 * extent(m[0])),
+            h as f32 / (2.0 * extent(m[1])),
+        );
+        let m = [
+            [m[0][0] * scale_x, m[0][1] * scale_x],
+            [m[1][0] * scale_y, m[1][1] * scale_y],
+        ];
+        let det = m[0][0] * m[1][1] - m[0][1] * m[1][0];
+        if !det.is_normal() {
+            return placed;
+        }
+        let inv = [
+            [m[1][1] / det, -m[0][1] / det],
+            [-m[1][0] / det, m[0][0] / det],
+        ];
+        let (center_x, center_y) = (x as f32 + w as f32 / 2.0, y as f32 + h as f32 / 2.0);
+        for py in y..(y + h).min(height) {
+            for px in x..(x + w).min(width) {
+                let (qx, qy) = (px as f32 + 0.5 - center_x, py as f32 + 0.5 - center_y);
+                let u = inv[0][0] * qx + inv[0][1] * qy + half_w - 0.5;
+                let v = inv[1][0] * qx + inv[1][1] * qy + half_h - 0.5;
+                if let Some(pixel) = sample_bilinear(image, u, v) {
+                    placed.put_pixel(px, py, pixel);
+                }
+            }
+        }
+        placed
+    }
+}
+
+/// The colour at pixel position (`u`, `v`) of `image`, transparent outside it,
+/// weighted by alpha so edges don't darken. None where it's fully transparent.
+fn sample_bilinear(image: &RgbaImage, u: f32, v: f32) -> Option<image::Rgba<u8>> {
+    let (w, h) = (image.width() as f32, image.height() as f32);
+    if u <= -1.0 || v <= -1.0 || u >= w || v >= h {
+        return None;
+    }
+    let (x0, y0) = (u.floor(), v.floor());
+    let (fx, fy) = (u - x0, v - y0);
+    let mut sum = [0.0f32; 4];
+    for (dx, dy, weight) in [
+        (0.0, 0.0, (1.0 - fx) * (1.0 - fy)),
+        (1.0, 0.0, fx * (1.0 - fy)),
+        (0.0, 1.0, (1.0 - fx) * fy),
+        (1.0, 1.0, fx * fy),
+    ] {
+        let (sx, sy) = (x0 + dx, y0 + dy);
+        if sx < 0.0 || sy < 0.0 || sx >= w || sy >= h {
+            continue;
+        }
+        let p = image.get_pixel(sx as u32, sy as u32);
+        let alpha = p[3] as f32 * weight;
+        sum[0] += p[0] as f32 * alpha;
+        sum[1] += p[1] as f32 * alpha;
+        sum[2] += p[2] as f32 * alpha;
+        sum[3] += alpha;
+    }
+    if sum[3] < 0.5 {
+        return None;
+    }
+    Some(image::Rgba([
+        (sum[0] / sum[3]).round() as u8,
+        (sum[1] / sum[3]).round() as u8,
+        (sum[2] / sum[3]).round() as u8,
+        sum[3].round() as u8,
+    ]))
+}
+
+/// Pixel data of a layer. UYVY frames are converted to RGBA by the compositor.
+#[derive(Clone, Copy)]
+pub enum LayerImage<'a> {
+    Rgba(&'a RgbaImage),
+    Uyvy(&'a UyvyFrame),
+}
+
+/// Identifies one frame of a layer source that several channels may composite,
+/// so the GPU compositor can upload it once and share the texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SharedLayerKey {
+    /// Hash of the source and everything that affects its pixels (size, crop, color)
+    pub source: u64,
+    /// Frame identity within the source (e.g. the NDI timestamp)
+    pub frame: i64,
 }
 
 pub struct Layer<'a> {
-    pub image: &'a RgbaImage,
+    pub image: LayerImage<'a>,
     pub opacity: f32,
     pub z_index: i32,
+    pub source: LayerId,
+    /// Set when other channels may show the identical frame
     #[allow(dead_code)]
-    pub source: LayerSource,
+    pub shared_key: Option<SharedLayerKey>,
+    /// Bumped whenever the layer's pixels change, so unchanged layers can be skipped
+    pub generation: u64,
+    /// Crop and placement on the canvas; UYVY frames are cropped by their own `roi`
+    pub transform: Transform,
+}
+
+/// UYVY layers converted to RGBA by the CPU compositor, kept until their
+/// generation changes so a repeated frame isn't converted again.
+#[derive(Default)]
+pub struct UyvyCache {
+    entries: Vec<UyvyEntry>,
+    luts: Vec<UyvyLuts>,
+}
+
+struct UyvyEntry {
+    source: LayerId,
+    generation: u64,
+    image: RgbaImage,
+    /// Drawn this frame; entries not drawn are dropped afterwards
+    used: bool,
+}
+
+impl UyvyCache {
+    /// The layer's frame as RGBA at `width`x`height`, converted only when the
+    /// layer's generation or size changed.
+    fn convert(
+        &mut self,
+        layer: &Layer<'_>,
+        frame: &UyvyFrame,
+        width: u32,
+        height: u32,
+    ) -> &RgbaImage {
+        let hit = self.entries.iter().position(|e| {
+            !e.used
+                && e.source == layer.source
+                && e.generation == layer.generation
+                && e.image.dimensions() == (width, height)
+        });
+        let index = match hit {
+            Some(index) => index,
+            None => {
+                let luts = match self
+                    .luts
+                    .iter()
+                    .position(|l| l.range == frame.range && l.primaries == frame.primaries)
+                {
+                    Some(i) => &self.luts[i],
+                    None => {
+                        self.luts.push(UyvyLuts::new(frame.range, frame.primaries));
+                        self.luts.last().unwrap()
+                    }
+                };
+                // Convert into the buffer of the layer's previous frame when there is one
+                let reuse = self
+                    .entries
+                    .iter()
+                    .position(|e| !e.used && e.source == layer.source);
+                let index = match reuse {
+                    Some(index) => index,
+                    None => {
+                        self.entries.push(UyvyEntry {
+                            source: layer.source,
+                            generation: 0,
+                            image: RgbaImage::new(0, 0),
+                            used: false,
+                        });
+                        self.entries.len() - 1
+                    }
+                };
+                let entry = &mut self.entries[index];
+                if entry.image.dimensions() != (width, height) {
+                    entry.image = RgbaImage::new(width, height);
+                }
+                frame.to_rgba_into(luts, &mut entry.image);
+                entry.generation = layer.generation;
+                index
+            }
+        };
+        let entry = &mut self.entries[index];
+        entry.used = true;
+        &entry.image
+    }
+
+    /// Drop entries of layers not drawn this frame.
+    fn end_frame(&mut self) {
+        self.entries.retain(|e| e.used);
+        for entry in &mut self.entries {
+            entry.used = false;
+        }
+    }
+}
+
+/// Sort layers into draw order: ascending z_index, ties broken by layer source
+/// so the result never depends on the order layers were collected in.
+pub fn sort_layers(layers: &mut [Layer<'_>]) {
+    layers.sort_by_key(|l| (l.z_index, l.source.tie_break()));
 }
 
 /// Composite layers onto a caller-owned canvas (reused across frames).
 /// Canvas is cleared to opaque black, then layers are blended by z_index order.
-pub fn composite(canvas: &mut RgbaImage, layers: &mut [Layer<'_>]) {
+/// With `input_keyed` the NDI layer has per-pixel alpha from a key, so it's
+/// blended even when it looks opaque. UYVY layers are converted through `uyvy`.
+pub fn composite(
+    canvas: &mut RgbaImage,
+    layers: &mut [Layer<'_>],
+    input_keyed: bool,
+    uyvy: &mut UyvyCache,
+) {
     let (width, height) = canvas.dimensions();
 
     // Clear canvas to opaque black
@@ -29,37 +417,50 @@ pub fn composite(canvas: &mut RgbaImage, layers: &mut [Layer<'_>]) {
         pixel[3] = 255;
     }
 
-    layers.sort_by_key(|l| l.z_index);
+    sort_layers(layers);
 
     // Fast path: single opaque layer at matching size — just copy
-    if layers.len() == 1 && layers[0].opacity >= 1.0 {
-        let (sw, sh) = layers[0].image.dimensions();
-        if sw == width && sh == height {
-            buf.copy_from_slice(layers[0].image.as_raw().as_slice());
+    if let [Layer {
+        image: LayerImage::Rgba(image),
+        opacity,
+        transform,
+        ..
+    }] = layers
+    {
+        if !input_keyed
+            && *opacity >= 1.0
+            && transform.is_identity()
+            && covers_canvas(image, (width, height))
+        {
+            buf.copy_from_slice(image.as_raw().as_slice());
             return;
         }
     }
 
     for layer in layers.iter() {
-        blend_layer(canvas, &layer.image, layer.opacity, width, height);
+        match layer.image {
+            LayerImage::Rgba(image) => blend_layer(canvas, image, layer.opacity, layer.transform),
+            LayerImage::Uyvy(frame) => {
+                let (_, _, w, h) = layer.transform.dst_rect((width, height));
+                let image = uyvy.convert(layer, frame, w, h);
+                let transform = Transform {
+                    crop: None,
+                    ..layer.transform
+                };
+                blend_layer(canvas, image, layer.opacity, transform);
+            }
+        }
     }
+    uyvy.end_frame();
 }
 
 /// Blend a source layer onto the destination using Porter-Duff "over" with opacity.
-fn blend_layer(dst: &mut RgbaImage, src: &RgbaImage, opacity: f32, width: u32, height: u32) {
-    let (sw, sh) = src.dimensions();
-
+fn blend_layer(dst: &mut RgbaImage, src: &RgbaImage, opacity: f32, transform: Transform) {
     if opacity <= 0.0 {
         return;
     }
-
-    if sw == width && sh == height {
-        blend_direct(dst, src, opacity);
-    } else {
-        let scaled =
-            image::imageops::resize(src, width, height, image::imageops::FilterType::Nearest);
-        blend_direct(dst, &scaled, opacity);
-    }
+    let src = transform.apply(src, dst.dimensions());
+    blend_direct(dst, &src, opacity);
 }
 
 /// Integer-based pixel-by-pixel alpha blend (src over dst) with opacity multiplier.
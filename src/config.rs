@@ -1,105 +1,2501 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use crate::source::LayerId;
 
 #[derive(Debug, Deserialize)]
 pub struct Config {
     #[serde(default)]
     pub settings: Settings,
+    /// Friendly names for NDI sources (`[sources] cam1 = "STUDIO-PC (Camera 1)"`)
+    #[serde(default)]
+    pub sources: HashMap<String, String>,
     #[serde(default)]
     pub channel: Vec<ChannelConfig>,
+    /// Channels created for every NDI source matching a pattern (`[[auto_channel]]`)
+    #[serde(default)]
+    pub auto_channel: Vec<AutoChannelConfig>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Settings {
     #[serde(default)]
-    pub status_port: u16,
-    #[serde(default = "default_log_level")]
-    pub log_level: String,
+    pub status_port: u16,
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+    #[serde(default)]
+    pub realtime: RealtimeConfig,
+    /// Default emergency slate for all channels (`[settings.slate]`)
+    #[serde(default)]
+    pub slate: SlateConfig,
+    /// Compositing path; `--compositor` overrides it
+    #[serde(default)]
+    pub compositor: CompositorMode,
+    /// Live terminal status; when false (or with `--quiet`) status is logged instead
+    #[serde(default = "default_true")]
+    pub terminal_status: bool,
+    /// Seconds between logged status summaries in quiet mode (0 = never)
+    #[serde(default = "default_status_log_interval")]
+    pub status_log_interval: u64,
+    /// How often CPU, memory and render-time stats are sampled, in milliseconds
+    #[serde(default = "default_stats_interval_ms")]
+    pub stats_interval_ms: u64,
+    /// Hours of per-minute channel stats kept for `/stats` (0 = none)
+    #[serde(default = "default_stats_history_hours")]
+    pub stats_history_hours: u32,
+    /// Channels started at the same time at startup
+    #[serde(default = "default_startup_concurrency")]
+    pub startup_concurrency: usize,
+    /// What a channel that fails to start at startup does to the mixer
+    #[serde(default)]
+    pub on_channel_error: ChannelErrorPolicy,
+    /// Seconds between start attempts of failed channels (`on_channel_error = "retry"`)
+    #[serde(default = "default_channel_retry_delay")]
+    pub channel_retry_delay: u64,
+    /// Silence sent on outputs that have no audio (`[settings.silent_audio]`)
+    #[serde(default)]
+    pub silent_audio: SilentAudioConfig,
+    /// Blank browser tabs opened ahead of time for overlays (`[settings.page_pool]`)
+    #[serde(default)]
+    pub page_pool: PagePoolConfig,
+    /// NDI source discovery timing (`[settings.discovery]`)
+    #[serde(default)]
+    pub discovery: DiscoveryConfig,
+    /// Unix socket accepting newline-delimited JSON commands
+    #[serde(default)]
+    pub control_socket: Option<String>,
+    /// Access tokens, rate limits and audit log for control requests
+    #[serde(default)]
+    pub control_api: ControlApiConfig,
+    /// Set by `--dry-run`: write PNG snapshots here instead of creating NDI senders
+    #[serde(skip)]
+    pub snapshot_dir: Option<PathBuf>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            status_port: 0,
+            log_level: "info".to_string(),
+            realtime: RealtimeConfig::default(),
+            slate: SlateConfig::default(),
+            compositor: CompositorMode::default(),
+            terminal_status: true,
+            status_log_interval: default_status_log_interval(),
+            stats_interval_ms: default_stats_interval_ms(),
+            stats_history_hours: default_stats_history_hours(),
+            startup_concurrency: default_startup_concurrency(),
+            on_channel_error: ChannelErrorPolicy::default(),
+            channel_retry_delay: default_channel_retry_delay(),
+            silent_audio: SilentAudioConfig::default(),
+            page_pool: PagePoolConfig::default(),
+            discovery: DiscoveryConfig::default(),
+            control_socket: None,
+            control_api: ControlApiConfig::default(),
+            snapshot_dir: None,
+        }
+    }
+}
+
+/// Access control for the control API (`[settings.control_api]`).
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ControlApiConfig {
+    /// When any are set, control requests need `Authorization: Bearer <token>`
+    #[serde(default)]
+    pub tokens: Vec<ApiToken>,
+    /// JSON Lines file every control request is appended to
+    #[serde(default)]
+    pub audit_log: Option<String>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct ApiToken {
+    /// Who the token belongs to, as recorded in the audit log
+    pub name: String,
+    pub token: String,
+    /// Control requests per minute (0 = unlimited)
+    #[serde(default = "default_rate_limit")]
+    pub rate_limit: u32,
+}
+
+impl std::fmt::Debug for ApiToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiToken")
+            .field("name", &self.name)
+            .field("token", &"***")
+            .field("rate_limit", &self.rate_limit)
+            .finish()
+    }
+}
+
+fn default_rate_limit() -> u32 {
+    60
+}
+
+/// Browser tabs created ahead of the overlays that use them.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct PagePoolConfig {
+    /// Tabs opened at browser launch (default: one per overlay of the enabled channels)
+    #[serde(default)]
+    pub prewarm: Option<usize>,
+    /// Blank tabs kept ready after startup, for seamless reloads and channels started later
+    #[serde(default = "default_spare_pages")]
+    pub spare: usize,
+}
+
+impl Default for PagePoolConfig {
+    fn default() -> Self {
+        Self {
+            prewarm: None,
+            spare: default_spare_pages(),
+        }
+    }
+}
+
+fn default_spare_pages() -> usize {
+    1
+}
+
+/// How the shared NDI finders look for sources.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct DiscoveryConfig {
+    /// Milliseconds a discovery cycle waits for source announcements; cycles
+    /// end early when the source list changes
+    #[serde(default = "default_search_ms")]
+    pub search_ms: u64,
+    /// Milliseconds between checks of inputs still searching (for cancellation
+    /// and `source_timeout`) when no announcement wakes them
+    #[serde(default = "default_poll_ms")]
+    pub poll_ms: u64,
+}
+
+impl Default for DiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            search_ms: default_search_ms(),
+            poll_ms: default_poll_ms(),
+        }
+    }
+}
+
+fn default_search_ms() -> u64 {
+    2000
+}
+
+fn default_poll_ms() -> u64 {
+    1000
+}
+
+/// Silent audio for receivers that misbehave with video-only streams.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct SilentAudioConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_silent_sample_rate")]
+    pub sample_rate: u32,
+    #[serde(default = "default_silent_channels")]
+    pub channels: u32,
+}
+
+impl Default for SilentAudioConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sample_rate: default_silent_sample_rate(),
+            channels: default_silent_channels(),
+        }
+    }
+}
+
+fn default_silent_sample_rate() -> u32 {
+    48000
+}
+
+fn default_silent_channels() -> u32 {
+    2
+}
+
+/// What happens when a channel fails to start at startup.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelErrorPolicy {
+    /// Stop the mixer
+    #[default]
+    Abort,
+    /// Run without the channel; it's listed as failed until enabled again
+    Skip,
+    /// Run without the channel and keep trying to start it
+    Retry,
+}
+
+/// Which compositor channels use.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum CompositorMode {
+    /// GPU when the feature is built and an adapter is found, else CPU
+    #[default]
+    Auto,
+    Cpu,
+    /// Fail at startup instead of falling back to CPU
+    Gpu,
+}
+
+/// Full-screen image/message that replaces program output while active.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SlateConfig {
+    /// Image scaled to fill the frame (drawn over `background`)
+    #[serde(default)]
+    pub image: Option<String>,
+    /// Text centered on the slate
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default = "default_slate_background")]
+    pub background: String,
+    #[serde(default = "default_caption_color")]
+    pub color: String,
+    /// TTF/OTF font path (defaults to Arial on macOS)
+    #[serde(default)]
+    pub font: Option<String>,
+    #[serde(default = "default_slate_font_size")]
+    pub font_size: f32,
+    /// Terminal key that toggles this slate (global: all channels)
+    #[serde(default)]
+    pub hotkey: Option<char>,
+}
+
+impl Default for SlateConfig {
+    fn default() -> Self {
+        Self {
+            image: None,
+            message: None,
+            background: default_slate_background(),
+            color: default_caption_color(),
+            font: None,
+            font_size: default_slate_font_size(),
+            hotkey: None,
+        }
+    }
+}
+
+fn default_slate_background() -> String {
+    "#000000".to_string()
+}
+
+fn default_slate_font_size() -> f32 {
+    72.0
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThreadPriority {
+    #[default]
+    Default,
+    /// Elevated priority (nice -10 on Linux, user-interactive QoS on macOS)
+    High,
+    /// Realtime scheduling (SCHED_FIFO / SCHED_RR) — usually needs privileges
+    Realtime,
+}
+
+/// Thread priority and core pinning for the video path (`[settings.realtime]`).
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RealtimeConfig {
+    #[serde(default)]
+    pub render_priority: ThreadPriority,
+    #[serde(default)]
+    pub ndi_priority: ThreadPriority,
+    /// Cores to pin render threads to (round-robin across channels)
+    #[serde(default)]
+    pub render_cores: Vec<usize>,
+    /// Cores to pin NDI receive/send threads to (round-robin)
+    #[serde(default)]
+    pub ndi_cores: Vec<usize>,
+}
+
+fn default_log_level() -> String {
+    "info".to_string()
+}
+
+fn default_status_log_interval() -> u64 {
+    60
+}
+
+fn default_stats_interval_ms() -> u64 {
+    1000
+}
+
+fn default_stats_history_hours() -> u32 {
+    24
+}
+
+fn default_startup_concurrency() -> usize {
+    4
+}
+
+fn default_channel_retry_delay() -> u64 {
+    30
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FilterConfig {
+    pub shader: String,
+    #[serde(default)]
+    pub params: HashMap<String, f32>,
+    /// Extra images (LUTs, noise, masks) bound to the shader in group 1
+    #[serde(default)]
+    pub textures: Vec<String>,
+    /// Multiplier for the shader's `time` uniform (0.5 = half speed)
+    #[serde(default = "default_time_scale")]
+    pub time_scale: f32,
+    /// Hold `time` at zero so time-based effects stay still
+    #[serde(default)]
+    #[allow(dead_code)]
+    pub paused: bool,
+    /// Run the filter as several passes, e.g. downsample/upsample for a wide blur.
+    /// Empty = one full-size pass of `shader`.
+    #[serde(default)]
+    pub passes: Vec<FilterPassConfig>,
+    /// WGSL source of a built-in filter, used instead of reading `shader`
+    #[serde(skip)]
+    pub builtin: Option<&'static str>,
+}
+
+fn default_time_scale() -> f32 {
+    1.0
+}
+
+/// One pass of a multi-pass filter. Each pass reads the previous pass's output
+/// and shares the filter's params and textures.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FilterPassConfig {
+    /// Shader for this pass (defaults to the filter's `shader`)
+    #[serde(default)]
+    pub shader: Option<String>,
+    /// Function to run in the pass's shader; only read by the GPU compositor
+    #[serde(default = "default_entry_point")]
+    #[cfg_attr(not(feature = "gpu"), allow(dead_code))]
+    pub entry_point: String,
+    /// Output size relative to the layer (0.5 = half width and height)
+    #[serde(default = "default_pass_scale")]
+    pub scale: f32,
+}
+
+fn default_entry_point() -> String {
+    "main".to_string()
+}
+
+fn default_pass_scale() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChannelConfig {
+    pub name: String,
+    /// Disabled channels stay configured but don't run until enabled via the API
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    pub output_name: String,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default = "default_frame_rate")]
+    pub frame_rate: u32,
+    /// Display aspect ratio sent with output frames, "16:9" or a number (default: square pixels)
+    #[serde(default)]
+    pub aspect_ratio: Option<String>,
+    /// Pixel aspect ratio (e.g. 1.333 for anamorphic 1440x1080); alternative to `aspect_ratio`
+    #[serde(default)]
+    pub pixel_aspect_ratio: Option<f32>,
+    pub ndi_input: Option<NdiInputConfig>,
+    /// Standards conversion: the channel only re-times its NDI input to `frame_rate`
+    #[serde(default)]
+    pub conversion: Option<ConversionConfig>,
+    /// NDI source to take the channel's audio from (may differ from the video source)
+    #[serde(default)]
+    pub audio_source: Option<String>,
+    /// Alias `audio_source` was given as (set when resolving `[sources]`)
+    #[serde(skip)]
+    pub audio_source_alias: Option<String>,
+    /// Mix the audio of several NDI sources (instead of `audio_source`)
+    #[serde(default)]
+    pub audio: Option<AudioMixConfig>,
+    /// Legacy single overlay (backwards compat with `[channel.browser_overlay]`)
+    #[serde(default)]
+    browser_overlay: Option<BrowserOverlayConfig>,
+    /// Multiple overlays (`[[channel.browser_overlays]]`)
+    #[serde(default)]
+    browser_overlays: Vec<BrowserOverlayConfig>,
+    /// Still images (logos, bugs) loaded once at startup
+    #[serde(default)]
+    pub image_overlays: Vec<ImageOverlayConfig>,
+    /// Lines of text (labels, simple clocks) rendered without a browser
+    #[serde(default)]
+    pub text_overlays: Vec<TextOverlayConfig>,
+    /// Starting opacity and visibility of the groups layers name in `group`
+    #[serde(default)]
+    pub layer_groups: Vec<LayerGroupConfig>,
+    /// Named sets of layer states, switched through the control API
+    #[serde(default)]
+    pub scene: Vec<SceneConfig>,
+    /// How scene switches and NDI source swaps are rendered (default: cut)
+    #[serde(default)]
+    pub transition: Option<TransitionConfig>,
+    /// Channel-level post-processing filters (applied after all layers composited)
+    #[serde(default)]
+    pub filters: Vec<FilterConfig>,
+    /// What to do when compositing consistently exceeds the frame interval
+    #[serde(default)]
+    pub overload: OverloadConfig,
+    /// Repeat the last sent frame instead of converting it again when the
+    /// composited output hasn't changed
+    #[serde(default)]
+    pub output_dedup: bool,
+    /// Pixel format the output is sent in
+    #[serde(default)]
+    pub output_pixel_format: OutputPixelFormat,
+    /// Compositing path for this channel; `auto` follows `[settings] compositor`
+    #[serde(default)]
+    pub compositor: CompositorMode,
+    /// Split-screen A/B view for tuning filters on the live output
+    #[serde(default)]
+    pub compare: Option<CompareConfig>,
+    /// Histograms (and optionally a waveform) of the output at `/channels/{name}/scopes`
+    #[serde(default)]
+    pub scopes: Option<ScopesConfig>,
+    /// Rolling buffer of the last output frames, dumped to disk on request
+    #[serde(default)]
+    pub replay: Option<ReplayConfig>,
+    /// Also send the output to a local virtual webcam (v4l2loopback, Linux)
+    #[serde(default)]
+    pub virtual_camera: Option<VirtualCameraConfig>,
+    /// Burned-in captions from an SRT/VTT file or a live WebSocket feed
+    #[serde(default)]
+    pub captions: Option<CaptionsConfig>,
+    /// Built-in time-of-day clock, optionally NTP-disciplined
+    #[serde(default)]
+    pub clock: Option<ClockConfig>,
+    /// Countdown or stopwatch, started and paused through the control API
+    #[serde(default)]
+    pub timer: Option<TimerConfig>,
+    /// Per-channel slate, replacing `[settings.slate]` for this channel
+    #[serde(default)]
+    pub slate: Option<SlateConfig>,
+    /// What to output at startup until every layer is ready
+    #[serde(default)]
+    pub warmup: WarmupMode,
+    /// Seconds to wait for layers before going live anyway (0 = wait indefinitely)
+    #[serde(default = "default_warmup_timeout")]
+    pub warmup_timeout: u64,
+    /// Commands or webhooks fired on channel lifecycle events
+    #[serde(default)]
+    pub hooks: Vec<HookConfig>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    /// The channel has started outputting (after warmup, if any)
+    Started,
+    /// The NDI input was lost or timed out
+    SourceLost,
+    /// The NDI input came back after being lost
+    SourceRestored,
+    /// The channel is stopping: mixer shutdown, the channel was disabled, or an
+    /// auto channel's source went away
+    Stopped,
+}
+
+impl HookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookEvent::Started => "started",
+            HookEvent::SourceLost => "source_lost",
+            HookEvent::SourceRestored => "source_restored",
+            HookEvent::Stopped => "stopped",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HookConfig {
+    pub event: HookEvent,
+    /// Shell command to run (`sh -c`)
+    #[serde(default)]
+    pub exec: Option<String>,
+    /// URL to POST a JSON event to
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarmupMode {
+    /// Go live immediately, layers appear as they arrive
+    #[default]
+    Off,
+    /// Output black until all overlays have loaded and the NDI input has sent a frame
+    Black,
+    /// Show the channel's slate until then
+    Slate,
+}
+
+fn default_warmup_timeout() -> u64 {
+    30
+}
+
+impl ChannelConfig {
+    /// The slate this channel shows: its own if configured, else the global one.
+    pub fn slate_or<'a>(&'a self, global: &'a SlateConfig) -> &'a SlateConfig {
+        self.slate.as_ref().unwrap_or(global)
+    }
+
+    /// Returns all browser overlays, merging legacy single `browser_overlay` with `browser_overlays`.
+    pub fn all_browser_overlays(&self) -> Vec<&BrowserOverlayConfig> {
+        let mut all: Vec<&BrowserOverlayConfig> = Vec::new();
+        if let Some(ref single) = self.browser_overlay {
+            all.push(single);
+        }
+        all.extend(self.browser_overlays.iter());
+        all
+    }
+
+    /// Take the settings a running channel can change in place from `from`:
+    /// layer opacity and z_index, layer group settings, filter params, chroma
+    /// and luma key settings, overlay css and audio mix levels. Layers, filters
+    /// and audio sources are matched by position, groups by name; everything
+    /// else is left as it is.
+    /// Returns whether any of them changed.
+    pub fn copy_live_settings(&mut self, from: &ChannelConfig) -> bool {
+        fn set<T: PartialEq + Clone>(to: &mut T, from: &T, changed: &mut bool) {
+            *changed |= to != from;
+            *to = from.clone();
+        }
+        fn params(to: &mut [FilterConfig], from: &[FilterConfig], changed: &mut bool) {
+            for (to, from) in to.iter_mut().zip(from) {
+                set(&mut to.params, &from.params, changed);
+            }
+        }
+        let mut changed = false;
+        if let (Some(to), Some(from)) = (self.ndi_input.as_mut(), from.ndi_input.as_ref()) {
+            set(&mut to.opacity, &from.opacity, &mut changed);
+            set(&mut to.z_index, &from.z_index, &mut changed);
+            if let (Some(to), Some(from)) = (to.chroma_key.as_mut(), from.chroma_key.as_ref()) {
+                set(to, from, &mut changed);
+            }
+            if let (Some(to), Some(from)) = (to.luma_key.as_mut(), from.luma_key.as_ref()) {
+                set(to, from, &mut changed);
+            }
+            params(&mut to.filters, &from.filters, &mut changed);
+        }
+        let overlays = self
+            .browser_overlay
+            .iter_mut()
+            .chain(self.browser_overlays.iter_mut());
+        for (to, from) in overlays.zip(from.all_browser_overlays()) {
+            set(&mut to.opacity, &from.opacity, &mut changed);
+            set(&mut to.z_index, &from.z_index, &mut changed);
+            set(&mut to.css, &from.css, &mut changed);
+            set(&mut to.audio_gain_db, &from.audio_gain_db, &mut changed);
+            if let (Some(to), Some(from)) = (to.luma_key.as_mut(), from.luma_key.as_ref()) {
+                set(to, from, &mut changed);
+            }
+            params(&mut to.filters, &from.filters, &mut changed);
+        }
+        for (to, from) in self.image_overlays.iter_mut().zip(&from.image_overlays) {
+            set(&mut to.opacity, &from.opacity, &mut changed);
+            set(&mut to.z_index, &from.z_index, &mut changed);
+        }
+        for (to, from) in self.text_overlays.iter_mut().zip(&from.text_overlays) {
+            set(&mut to.opacity, &from.opacity, &mut changed);
+            set(&mut to.z_index, &from.z_index, &mut changed);
+        }
+        for to in self.layer_groups.iter_mut() {
+            if let Some(from) = from.layer_groups.iter().find(|g| g.name == to.name) {
+                set(&mut to.opacity, &from.opacity, &mut changed);
+                set(&mut to.visible, &from.visible, &mut changed);
+            }
+        }
+        params(&mut self.filters, &from.filters, &mut changed);
+        if let (Some(to), Some(from)) = (self.compare.as_mut(), from.compare.as_ref()) {
+            params(&mut to.filters, &from.filters, &mut changed);
+        }
+        if let (Some(to), Some(from)) = (self.audio.as_mut(), from.audio.as_ref()) {
+            set(&mut to.master_db, &from.master_db, &mut changed);
+            for (to, from) in to.sources.iter_mut().zip(&from.sources) {
+                set(&mut to.gain_db, &from.gain_db, &mut changed);
+                set(&mut to.mute, &from.mute, &mut changed);
+            }
+        }
+        changed
+    }
+
+    /// The channel's audio mix: `[channel.audio]`, or the default mix when
+    /// only overlays have audio. None without any mixed audio.
+    pub fn audio_mix(&self) -> Option<AudioMixConfig> {
+        match self.audio {
+            Some(ref audio) => Some(audio.clone()),
+            None => self
+                .all_browser_overlays()
+                .iter()
+                .any(|b| b.audio)
+                .then(AudioMixConfig::default),
+        }
+    }
+
+    /// Picture aspect ratio for the output's frame metadata, if configured.
+    /// None leaves it to the SDK (square pixels).
+    pub fn picture_aspect_ratio(&self) -> anyhow::Result<Option<f32>> {
+        let ratio = match (&self.aspect_ratio, self.pixel_aspect_ratio) {
+            (Some(_), Some(_)) => {
+                anyhow::bail!("set either aspect_ratio or pixel_aspect_ratio, not both")
+            }
+            (Some(ratio), None) => parse_aspect_ratio(ratio)
+                .ok_or_else(|| anyhow::anyhow!("invalid aspect_ratio '{}'", ratio))?,
+            (None, Some(par)) => par * self.width as f32 / self.height as f32,
+            (None, None) => return Ok(None),
+        };
+        if !ratio.is_finite() || ratio <= 0.0 {
+            anyhow::bail!("aspect ratio must be > 0");
+        }
+        Ok(Some(ratio))
+    }
+
+    /// Replace source aliases from `[sources]` (see `Config::resolve_sources`).
+    fn resolve_sources(&mut self, sources: &HashMap<String, String>) {
+        if let Some(ref mut input) = self.ndi_input {
+            if let Some(name) = sources.get(&input.source) {
+                input.alias = Some(std::mem::replace(&mut input.source, name.clone()));
+            }
+            if let Some(ref mut key) = input.key_source {
+                if let Some(name) = sources.get(key.as_str()) {
+                    input.key_alias = Some(std::mem::replace(key, name.clone()));
+                }
+            }
+        }
+        if let Some(ref mut source) = self.audio_source {
+            if let Some(name) = sources.get(source.as_str()) {
+                self.audio_source_alias = Some(std::mem::replace(source, name.clone()));
+            }
+        }
+        for input in self.audio.iter_mut().flat_map(|a| a.sources.iter_mut()) {
+            if let Some(name) = sources.get(&input.source) {
+                input.alias = Some(std::mem::replace(&mut input.source, name.clone()));
+            }
+        }
+    }
+
+    /// Checks that only involve this channel's own settings.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Some(ref slate) = self.slate {
+            validate_slate(slate, &format!("Channel '{}'", self.name))?;
+        }
+        if self.width == 0 || self.height == 0 {
+            anyhow::bail!("Channel '{}': width and height must be > 0", self.name);
+        }
+        if self.frame_rate == 0 {
+            anyhow::bail!("Channel '{}': frame_rate must be > 0", self.name);
+        }
+        for hook in &self.hooks {
+            match (&hook.exec, &hook.url) {
+                (Some(_), None) => {}
+                (None, Some(url)) if url.starts_with("http://") => {}
+                (None, Some(url)) => anyhow::bail!(
+                    "Channel '{}': hook url must be http:// (got '{}')",
+                    self.name,
+                    url
+                ),
+                _ => anyhow::bail!(
+                    "Channel '{}': each hook needs exactly one of exec or url",
+                    self.name
+                ),
+            }
+        }
+        if let Err(e) = self.picture_aspect_ratio() {
+            anyhow::bail!("Channel '{}': {}", self.name, e);
+        }
+        if matches!(
+            self.output_pixel_format,
+            OutputPixelFormat::Uyvy | OutputPixelFormat::Uyva
+        ) && self.width % 2 != 0
+        {
+            anyhow::bail!(
+                "Channel '{}': output_pixel_format uyvy/uyva needs an even width",
+                self.name
+            );
+        }
+        if self.overload.trigger_frames == 0 || self.overload.recover_frames == 0 {
+            anyhow::bail!(
+                "Channel '{}': overload trigger_frames and recover_frames must be > 0",
+                self.name
+            );
+        }
+        if !(self.overload.recover_ratio > 0.0 && self.overload.recover_ratio <= 1.0) {
+            anyhow::bail!(
+                "Channel '{}': overload recover_ratio must be in (0.0, 1.0]",
+                self.name
+            );
+        }
+        if let Some(ref ndi) = self.ndi_input {
+            if !(0.0..=1.0).contains(&ndi.opacity) {
+                anyhow::bail!("Channel '{}': ndi_input opacity must be 0.0–1.0", self.name);
+            }
+            if ndi.roi.is_some_and(|roi| roi.width == 0 || roi.height == 0) {
+                anyhow::bail!(
+                    "Channel '{}': ndi_input roi width and height must be > 0",
+                    self.name
+                );
+            }
+            self.validate_placement(ndi.placement.as_ref(), "ndi_input")?;
+            if let Some(ref key) = ndi.key_source {
+                if key.trim().is_empty() || *key == ndi.source {
+                    anyhow::bail!(
+                        "Channel '{}': ndi_input key_source must name another source than the fill",
+                        self.name
+                    );
+                }
+            }
+            if let Some(ref key) = ndi.chroma_key {
+                if crate::text::parse_color(&key.color).is_none() {
+                    anyhow::bail!(
+                        "Channel '{}': invalid chroma_key color '{}' (use #RRGGBB)",
+                        self.name,
+                        key.color
+                    );
+                }
+                if !(0.0..=1.0).contains(&key.similarity) {
+                    anyhow::bail!(
+                        "Channel '{}': chroma_key similarity must be 0.0–1.0",
+                        self.name
+                    );
+                }
+                if !(key.smoothness >= 0.0 && key.spill >= 0.0) {
+                    anyhow::bail!(
+                        "Channel '{}': chroma_key smoothness and spill must be >= 0",
+                        self.name
+                    );
+                }
+            }
+            if let Some(ref key) = ndi.luma_key {
+                key.validate(&self.name, "ndi_input")?;
+            }
+            for filter in &ndi.filters {
+                validate_filter(filter, &self.name, "ndi_input")?;
+            }
+        }
+        for filter in &self.filters {
+            validate_filter(filter, &self.name, "channel")?;
+        }
+        if let Some(ref conversion) = self.conversion {
+            let Some(ref ndi) = self.ndi_input else {
+                anyhow::bail!("Channel '{}': conversion needs an ndi_input", self.name);
+            };
+            if self.layers().len() > 1 {
+                anyhow::bail!(
+                    "Channel '{}': a conversion channel carries only its ndi_input, no other layers",
+                    self.name
+                );
+            }
+            if ndi.key_source.is_some() || ndi.rate_adaptation != RateAdaptation::Latest {
+                anyhow::bail!(
+                    "Channel '{}': conversion can't be combined with key_source or rate_adaptation",
+                    self.name
+                );
+            }
+            if !(1..=8).contains(&conversion.delay_frames) {
+                anyhow::bail!(
+                    "Channel '{}': conversion delay_frames must be 1–8",
+                    self.name
+                );
+            }
+        }
+        if let Some(ref scopes) = self.scopes {
+            if scopes.interval_ms == 0 {
+                anyhow::bail!("Channel '{}': scopes interval_ms must be > 0", self.name);
+            }
+            if scopes.waveform && scopes.waveform_width == 0 {
+                anyhow::bail!("Channel '{}': scopes waveform_width must be > 0", self.name);
+            }
+        }
+        if let Some(ref replay) = self.replay {
+            if replay.seconds == 0 || replay.max_memory_mb == 0 {
+                anyhow::bail!(
+                    "Channel '{}': replay seconds and max_memory_mb must be > 0",
+                    self.name
+                );
+            }
+        }
+        if let Some(ref camera) = self.virtual_camera {
+            if !cfg!(target_os = "linux") {
+                anyhow::bail!(
+                    "Channel '{}': virtual_camera needs v4l2loopback, which is only available on Linux",
+                    self.name
+                );
+            }
+            if camera.device.is_empty() {
+                anyhow::bail!("Channel '{}': virtual_camera device is empty", self.name);
+            }
+        }
+        if let Some(ref compare) = self.compare {
+            if !(0.0..=1.0).contains(&compare.split) {
+                anyhow::bail!("Channel '{}': compare split must be 0.0–1.0", self.name);
+            }
+            for filter in &compare.filters {
+                validate_filter(filter, &self.name, "compare")?;
+            }
+        }
+        if let Some(ref audio) = self.audio {
+            if self.audio_source.is_some() {
+                anyhow::bail!(
+                    "Channel '{}': set either audio_source or [channel.audio], not both",
+                    self.name
+                );
+            }
+            if audio.sources.is_empty() && !self.all_browser_overlays().iter().any(|b| b.audio) {
+                anyhow::bail!(
+                    "Channel '{}': audio needs at least one source or an overlay with audio",
+                    self.name
+                );
+            }
+            validate_audio_mix(audio, &self.name)?;
+        }
+        let overlay_audio = self.all_browser_overlays().into_iter().filter(|b| b.audio);
+        for browser in overlay_audio {
+            if self.audio_source.is_some() {
+                anyhow::bail!(
+                    "Channel '{}': overlay audio can't be combined with audio_source; \
+                     list the source under [channel.audio] instead",
+                    self.name
+                );
+            }
+            if !crate::audio_mixer::GAIN_RANGE.contains(&browser.audio_gain_db) {
+                anyhow::bail!(
+                    "Channel '{}': browser overlay audio_gain_db must be -96–24",
+                    self.name
+                );
+            }
+        }
+        if let Some(ref captions) = self.captions {
+            validate_captions(captions, &self.name)?;
+        }
+        if let Some(ref clock) = self.clock {
+            validate_clock(clock, &self.name)?;
+        }
+        for text in &self.text_overlays {
+            if text.text.is_empty() {
+                anyhow::bail!(
+                    "Channel '{}': text overlay text must not be empty",
+                    self.name
+                );
+            }
+            validate_time_format(&text.text, "HMSfYmd", "text overlay", &self.name)?;
+            validate_text_style(
+                text.font.as_deref(),
+                [&text.color, &text.background],
+                text.font_size,
+                text.opacity,
+                "text overlay",
+                &self.name,
+            )?;
+        }
+        if let Some(ref timer) = self.timer {
+            validate_timer(timer, &self.name)?;
+        }
+        for browser in self.all_browser_overlays() {
+            if browser.width == 0 || browser.height == 0 {
+                anyhow::bail!(
+                    "Channel '{}': browser overlay width and height must be > 0",
+                    self.name
+                );
+            }
+            if !(0.0..=1.0).contains(&browser.opacity) {
+                anyhow::bail!(
+                    "Channel '{}': browser overlay opacity must be 0.0–1.0",
+                    self.name
+                );
+            }
+            if browser
+                .crop
+                .is_some_and(|crop| !crop.fits((browser.width, browser.height)))
+            {
+                anyhow::bail!(
+                    "Channel '{}': browser overlay crop must be non-empty and within its {}x{} viewport",
+                    self.name,
+                    browser.width,
+                    browser.height
+                );
+            }
+            self.validate_placement(browser.placement.as_ref(), "browser overlay")?;
+            if !browser.rotation.is_finite() {
+                anyhow::bail!(
+                    "Channel '{}': browser overlay rotation must be a number of degrees",
+                    self.name
+                );
+            }
+            if [browser.skew_x, browser.skew_y]
+                .iter()
+                .any(|skew| !(-80.0..=80.0).contains(skew))
+            {
+                anyhow::bail!(
+                    "Channel '{}': browser overlay skew_x and skew_y must be -80–80 degrees",
+                    self.name
+                );
+            }
+            if let Some(ref file) = browser.css_file {
+                if !Path::new(file).exists() {
+                    anyhow::bail!(
+                        "Channel '{}': browser overlay css_file not found: {}",
+                        self.name,
+                        file
+                    );
+                }
+            }
+            if browser.basic_auth.is_some() && browser.bearer_token.is_some() {
+                anyhow::bail!(
+                    "Channel '{}': browser overlay can't use both basic_auth and bearer_token",
+                    self.name
+                );
+            }
+            if let Some(ref file) = browser.js_file {
+                if !Path::new(file).exists() {
+                    anyhow::bail!(
+                        "Channel '{}': browser overlay js_file not found: {}",
+                        self.name,
+                        file
+                    );
+                }
+            }
+            if let Some(ref data) = browser.data {
+                match (&data.file, &data.url) {
+                    (Some(_), Some(_)) | (None, None) => anyhow::bail!(
+                        "Channel '{}': browser overlay data needs exactly one of file or url",
+                        self.name
+                    ),
+                    (None, Some(url)) if !url.starts_with("http://") => anyhow::bail!(
+                        "Channel '{}': browser overlay data url must be http://: {}",
+                        self.name,
+                        url
+                    ),
+                    _ => {}
+                }
+                if data.interval_ms < 100 {
+                    anyhow::bail!(
+                        "Channel '{}': browser overlay data interval_ms must be >= 100",
+                        self.name
+                    );
+                }
+                let mut chars = data.global.chars();
+                let identifier = chars
+                    .next()
+                    .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+                    && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+                if !identifier {
+                    anyhow::bail!(
+                        "Channel '{}': browser overlay data global must be a JavaScript identifier: {}",
+                        self.name,
+                        data.global
+                    );
+                }
+            }
+            if browser.frame_divisor == 0 {
+                anyhow::bail!(
+                    "Channel '{}': browser overlay frame_divisor must be >= 1",
+                    self.name
+                );
+            }
+            if let Some(ref budget) = browser.cpu_budget {
+                if budget.max_percent <= 0.0 {
+                    anyhow::bail!(
+                        "Channel '{}': browser overlay cpu_budget max_percent must be > 0",
+                        self.name
+                    );
+                }
+                if budget.throttle_rate < 1.0 {
+                    anyhow::bail!(
+                        "Channel '{}': browser overlay cpu_budget throttle_rate must be >= 1",
+                        self.name
+                    );
+                }
+            }
+            if browser.capture_mode == CaptureMode::Screenshot && browser.refresh_interval_ms == 0 {
+                anyhow::bail!(
+                    "Channel '{}': browser overlay refresh_interval_ms must be > 0 with capture_mode = \"screenshot\"",
+                    self.name
+                );
+            }
+            if browser.on_load_failure == OverlayLoadFailure::Continue && browser.retry_delay == 0 {
+                anyhow::bail!(
+                    "Channel '{}': browser overlay retry_delay must be > 0 with on_load_failure = \"continue\"",
+                    self.name
+                );
+            }
+            if let Some(ref key) = browser.luma_key {
+                key.validate(&self.name, "browser_overlay")?;
+            }
+            for filter in &browser.filters {
+                validate_filter(filter, &self.name, "browser_overlay")?;
+            }
+        }
+        for image in &self.image_overlays {
+            if !Path::new(&image.path).exists() {
+                anyhow::bail!(
+                    "Channel '{}': image overlay not found: {}",
+                    self.name,
+                    image.path
+                );
+            }
+            if !(0.0..=1.0).contains(&image.opacity) {
+                anyhow::bail!(
+                    "Channel '{}': image overlay opacity must be 0.0–1.0",
+                    self.name
+                );
+            }
+            if image.width == Some(0) || image.height == Some(0) {
+                anyhow::bail!(
+                    "Channel '{}': image overlay width and height must be > 0",
+                    self.name
+                );
+            }
+        }
+        let groups: Vec<Option<&str>> = self.layers().iter().map(|l| l.group).collect();
+        if groups.iter().flatten().any(|g| g.is_empty()) {
+            anyhow::bail!("Channel '{}': layer_group must not be empty", self.name);
+        }
+        for (i, group) in self.layer_groups.iter().enumerate() {
+            if self.layer_groups[..i].iter().any(|g| g.name == group.name) {
+                anyhow::bail!(
+                    "Channel '{}': layer group '{}' is listed twice",
+                    self.name,
+                    group.name
+                );
+            }
+            // Catches typos: a group only exists through its layers
+            if !groups.contains(&Some(group.name.as_str())) {
+                anyhow::bail!(
+                    "Channel '{}': no layer is in layer group '{}'",
+                    self.name,
+                    group.name
+                );
+            }
+            if !(0.0..=1.0).contains(&group.opacity) {
+                anyhow::bail!(
+                    "Channel '{}': layer group '{}' opacity must be 0.0–1.0",
+                    self.name,
+                    group.name
+                );
+            }
+        }
+        let layer_ids: Vec<LayerId> = self.layers().iter().map(|l| l.id).collect();
+        for (i, scene) in self.scene.iter().enumerate() {
+            if scene.name.trim().is_empty() {
+                anyhow::bail!("Channel '{}': scene name must not be empty", self.name);
+            }
+            if self.scene[..i].iter().any(|s| s.name == scene.name) {
+                anyhow::bail!(
+                    "Channel '{}': scene '{}' is listed twice",
+                    self.name,
+                    scene.name
+                );
+            }
+            for layer in &scene.layers {
+                if !layer_ids.contains(&layer.id) {
+                    anyhow::bail!(
+                        "Channel '{}': scene '{}' lists layer {}, which the channel doesn't have",
+                        self.name,
+                        scene.name,
+                        layer.id
+                    );
+                }
+                if layer.opacity.is_some_and(|o| !(0.0..=1.0).contains(&o)) {
+                    anyhow::bail!(
+                        "Channel '{}': scene '{}' opacity of {} must be 0.0–1.0",
+                        self.name,
+                        scene.name,
+                        layer.id
+                    );
+                }
+                self.validate_placement(
+                    layer.placement.as_ref(),
+                    &format!("scene '{}' layer {}", scene.name, layer.id),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_placement(&self, placement: Option<&Roi>, layer: &str) -> anyhow::Result<()> {
+        if placement.is_some_and(|placement| !placement.fits((self.width, self.height))) {
+            anyhow::bail!(
+                "Channel '{}': {} placement must be non-empty and within the {}x{} canvas",
+                self.name,
+                layer,
+                self.width,
+                self.height
+            );
+        }
+        Ok(())
+    }
+
+    /// Every layer of the channel, in tie-break order.
+    pub fn layers(&self) -> Vec<ConfiguredLayer<'_>> {
+        let mut layers = Vec::new();
+        if let Some(ref input) = self.ndi_input {
+            layers.push(ConfiguredLayer::new(
+                LayerId::Ndi,
+                input.z_index,
+                input.opacity,
+                &input.layer_group,
+            ));
+        }
+        for (i, overlay) in self.all_browser_overlays().into_iter().enumerate() {
+            layers.push(ConfiguredLayer::new(
+                LayerId::Browser(i),
+                overlay.z_index,
+                overlay.opacity,
+                &overlay.layer_group,
+            ));
+        }
+        for (i, image) in self.image_overlays.iter().enumerate() {
+            layers.push(ConfiguredLayer::new(
+                LayerId::Image(i),
+                image.z_index,
+                image.opacity,
+                &image.layer_group,
+            ));
+        }
+        for (i, text) in self.text_overlays.iter().enumerate() {
+            layers.push(ConfiguredLayer::new(
+                LayerId::Text(i),
+                text.z_index,
+                text.opacity,
+                &text.layer_group,
+            ));
+        }
+        if let Some(ref captions) = self.captions {
+            layers.push(ConfiguredLayer::new(
+                LayerId::Captions,
+                captions.z_index,
+                captions.opacity,
+                &captions.layer_group,
+            ));
+        }
+        if let Some(ref clock) = self.clock {
+            layers.push(ConfiguredLayer::new(
+                LayerId::Clock,
+                clock.z_index,
+                clock.opacity,
+                &clock.layer_group,
+            ));
+        }
+        if let Some(ref timer) = self.timer {
+            layers.push(ConfiguredLayer::new(
+                LayerId::Timer,
+                timer.z_index,
+                timer.opacity,
+                &timer.layer_group,
+            ));
+        }
+        layers
+    }
+}
+
+/// A layer as configured, whatever its kind.
+pub struct ConfiguredLayer<'a> {
+    pub id: LayerId,
+    pub z_index: i32,
+    pub opacity: f32,
+    pub group: Option<&'a str>,
+}
+
+impl<'a> ConfiguredLayer<'a> {
+    fn new(id: LayerId, z_index: i32, opacity: f32, group: &'a Option<String>) -> Self {
+        Self {
+            id,
+            z_index,
+            opacity,
+            group: group.as_deref(),
+        }
+    }
+}
+
+/// Source name used to check an auto channel template at load time.
+const EXAMPLE_SOURCE: &str = "EXAMPLE-PC (Example)";
+
+/// Template for channels created automatically, one per NDI source whose name
+/// matches `source`. Channels are added as sources appear and removed once a
+/// source has been gone for `linger` seconds.
+#[derive(Debug, Deserialize)]
+pub struct AutoChannelConfig {
+    /// Source name pattern: `*` matches any run of characters, `?` any one
+    pub source: String,
+    /// NDI groups to discover sources in (comma-separated; default: public)
+    #[serde(default)]
+    pub groups: Option<String>,
+    /// Channel name; `{source}` and `{stream}` are filled in per source
+    #[serde(default = "default_auto_channel_name")]
+    pub name: String,
+    /// NDI output name, with the same placeholders as `name`
+    pub output_name: String,
+    /// Seconds a source may be missing before its channel is stopped
+    #[serde(default = "default_auto_channel_linger")]
+    pub linger: u64,
+    /// Everything else a `[[channel]]` takes; strings may use the placeholders
+    #[serde(default)]
+    pub template: toml::Table,
+}
+
+fn default_auto_channel_name() -> String {
+    "{source}".to_string()
+}
+
+fn default_auto_channel_linger() -> u64 {
+    10
+}
+
+impl AutoChannelConfig {
+    /// Build the channel for one discovered source. `{source}` is the full NDI
+    /// name, `{stream}` the part in parentheses ("CAM 1" of "STUDIO-PC (CAM 1)").
+    pub fn instantiate(
+        &self,
+        source: &str,
+        sources: &HashMap<String, String>,
+    ) -> anyhow::Result<ChannelConfig> {
+        let stream = stream_name(source);
+        let fill = |s: &str| s.replace("{source}", source).replace("{stream}", stream);
+
+        let mut table = self.template.clone();
+        for (_, value) in table.iter_mut() {
+            fill_placeholders(value, &fill);
+        }
+        table.insert("name".into(), fill(&self.name).into());
+        table.insert("output_name".into(), fill(&self.output_name).into());
+        let input = table
+            .entry("ndi_input")
+            .or_insert_with(|| toml::Table::new().into());
+        let toml::Value::Table(input) = input else {
+            anyhow::bail!("template ndi_input must be a table");
+        };
+        input.insert("source".into(), source.into());
+        if let Some(ref groups) = self.groups {
+            input.insert("groups".into(), groups.as_str().into());
+        }
+
+        let mut channel: ChannelConfig = toml::Value::Table(table).try_into()?;
+        channel.resolve_sources(sources);
+        channel.validate()?;
+        Ok(channel)
+    }
+
+    fn validate(&self, sources: &HashMap<String, String>) -> anyhow::Result<()> {
+        let scope = format!("auto_channel '{}'", self.source);
+        if self.source.trim().is_empty() {
+            anyhow::bail!("auto_channel: source pattern must not be empty");
+        }
+        for (field, value) in [("name", &self.name), ("output_name", &self.output_name)] {
+            if !value.contains("{source}") && !value.contains("{stream}") {
+                anyhow::bail!(
+                    "{}: {} must contain {{source}} or {{stream}} to be unique per source",
+                    scope,
+                    field
+                );
+            }
+            if self.template.contains_key(field) {
+                anyhow::bail!(
+                    "{}: set {} on the auto_channel, not in its template",
+                    scope,
+                    field
+                );
+            }
+        }
+        let channel = self
+            .instantiate(EXAMPLE_SOURCE, sources)
+            .map_err(|e| anyhow::anyhow!("{}: {}", scope, e))?;
+        if channel.slate.as_ref().is_some_and(|s| s.hotkey.is_some()) {
+            anyhow::bail!("{}: templates can't bind slate hotkeys", scope);
+        }
+        if !channel.enabled {
+            anyhow::bail!("{}: templates can't set enabled = false", scope);
+        }
+        Ok(())
+    }
+}
+
+/// Replace placeholders in every string of a template value.
+fn fill_placeholders(value: &mut toml::Value, fill: &impl Fn(&str) -> String) {
+    match value {
+        toml::Value::String(s) => *s = fill(s),
+        toml::Value::Array(items) => items.iter_mut().for_each(|v| fill_placeholders(v, fill)),
+        toml::Value::Table(table) => table
+            .iter_mut()
+            .for_each(|(_, v)| fill_placeholders(v, fill)),
+        _ => {}
+    }
+}
+
+/// The stream part of an NDI name ("CAM 1" of "STUDIO-PC (CAM 1)").
+pub fn stream_name(source: &str) -> &str {
+    source
+        .split_once(" (")
+        .and_then(|(_, rest)| rest.strip_suffix(')'))
+        .unwrap_or(source)
+}
+
+/// Shell-style match where `*` is any run of characters and `?` any one.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let (pattern, name): (Vec<char>, Vec<char>) =
+        (pattern.chars().collect(), name.chars().collect());
+    let (mut p, mut n) = (0, 0);
+    // Where the last `*` was, and how much of the name it has taken so far
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((sp, sn)) => {
+                    p = sp + 1;
+                    n = sn + 1;
+                    star = Some((sp, sn + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+fn default_frame_rate() -> u32 {
+    30
+}
+
+/// "16:9" or "1.778".
+fn parse_aspect_ratio(ratio: &str) -> Option<f32> {
+    match ratio.split_once(':') {
+        Some((w, h)) => Some(w.trim().parse::<f32>().ok()? / h.trim().parse::<f32>().ok()?),
+        None => ratio.trim().parse().ok(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverloadPolicy {
+    /// Keep rendering at full quality and let the channel fall behind
+    #[default]
+    None,
+    /// Bypass all shader filters while overloaded
+    SkipFilters,
+    /// Composite every other frame and repeat the previous one in between
+    HalfRate,
+    /// Composite at half resolution and upscale to the output size
+    Downscale,
+}
+
+impl OverloadPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OverloadPolicy::None => "none",
+            OverloadPolicy::SkipFilters => "skip_filters",
+            OverloadPolicy::HalfRate => "half_rate",
+            OverloadPolicy::Downscale => "downscale",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OverloadConfig {
+    #[serde(default)]
+    pub policy: OverloadPolicy,
+    /// Consecutive over-budget frames before the policy engages
+    #[serde(default = "default_overload_trigger_frames")]
+    pub trigger_frames: u32,
+    /// Consecutive healthy frames before the policy disengages
+    #[serde(default = "default_overload_recover_frames")]
+    pub recover_frames: u32,
+    /// A frame counts as healthy when render time < recover_ratio * frame interval
+    #[serde(default = "default_overload_recover_ratio")]
+    pub recover_ratio: f32,
+}
+
+impl Default for OverloadConfig {
+    fn default() -> Self {
+        Self {
+            policy: OverloadPolicy::None,
+            trigger_frames: default_overload_trigger_frames(),
+            recover_frames: default_overload_recover_frames(),
+            recover_ratio: default_overload_recover_ratio(),
+        }
+    }
+}
+
+fn default_overload_trigger_frames() -> u32 {
+    30
+}
+
+fn default_overload_recover_frames() -> u32 {
+    150
+}
+
+fn default_overload_recover_ratio() -> f32 {
+    0.7
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorRange {
+    /// 0–255 levels, used as-is
+    #[default]
+    Full,
+    /// 16–235 video levels, expanded to full range
+    Limited,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorPrimaries {
+    #[default]
+    Bt709,
+    /// SD sources (BT.601 coefficients)
+    Bt601,
+    /// UHD/HDR-era sources (BT.2020 coefficients and gamut, mapped to BT.709)
+    Bt2020,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveFormat {
+    /// The NDI SDK converts to RGBA on the receive thread
+    #[default]
+    Rgba,
+    /// Receive native UYVY and convert to RGBA on the GPU (falls back to RGBA without a GPU)
+    Uyvy,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputPixelFormat {
+    /// 8-bit BGRA with alpha
+    #[default]
+    Bgra,
+    /// 8-bit BGR, alpha ignored by receivers
+    Bgrx,
+    /// Packed 4:2:2 YCbCr (BT.709, video range), no alpha
+    Uyvy,
+    /// UYVY followed by a full-resolution alpha plane
+    Uyva,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateAdaptation {
+    /// Show the most recent frame (repeats fall where arrival jitter puts them)
+    #[default]
+    Latest,
+    /// One frame behind, show the input frame nearest each output frame
+    Cadence,
+    /// One frame behind, crossfade between the two nearest input frames
+    Blend,
+}
+
+/// Pixel rectangle: a crop of a source, or an area of the canvas.
+#[derive(Debug, Clone, Copy, Hash, Deserialize, Serialize)]
+pub struct Roi {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Roi {
+    pub fn rect(&self) -> (u32, u32, u32, u32) {
+        (self.x, self.y, self.width, self.height)
+    }
+
+    /// Whether the rectangle is non-empty and lies within `width`x`height`.
+    pub fn fits(&self, (width, height): (u32, u32)) -> bool {
+        self.width > 0
+            && self.height > 0
+            && self.x as u64 + self.width as u64 <= width as u64
+            && self.y as u64 + self.height as u64 <= height as u64
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NdiInputConfig {
+    pub source: String,
+    /// Alias `source` was given as (set when resolving `[sources]`)
+    #[serde(skip)]
+    pub alias: Option<String>,
+    /// NDI groups to search for the source (comma-separated; default: public)
+    #[serde(default)]
+    pub groups: Option<String>,
+    /// Source whose luma keys this one, for CG systems that send separate key
+    /// and fill (`source` is the fill)
+    #[serde(default)]
+    pub key_source: Option<String>,
+    /// Alias `key_source` was given as
+    #[serde(skip)]
+    pub key_alias: Option<String>,
+    /// The fill arrives multiplied by the key (shaped fill)
+    #[serde(default)]
+    pub fill_premultiplied: bool,
+    /// Use only this region of the source (cropped before resizing)
+    #[serde(default, alias = "crop")]
+    pub roi: Option<Roi>,
+    /// Canvas area the source is scaled into (default: the whole canvas)
+    #[serde(default)]
+    pub placement: Option<Roi>,
+    /// How a source whose aspect ratio differs from the channel's fills the canvas
+    #[serde(default)]
+    pub fit: Fit,
+    /// Pixel format requested from the NDI SDK
+    #[serde(default)]
+    pub receive_format: ReceiveFormat,
+    /// How frames of a source slower than the channel are scheduled
+    #[serde(default)]
+    pub rate_adaptation: RateAdaptation,
+    /// Signal range the source actually sends
+    #[serde(default)]
+    pub range: ColorRange,
+    /// Color encoding of the source; converted to BT.709 for compositing
+    #[serde(default)]
+    pub primaries: ColorPrimaries,
+    #[serde(default)]
+    pub z_index: i32,
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+    #[serde(default)]
+    pub layer_group: Option<String>,
+    /// Seconds to wait for the source to appear before treating it as lost (0 = wait forever)
+    #[serde(default)]
+    pub source_timeout: u64,
+    /// What the channel shows while the source is lost or timed out
+    #[serde(default)]
+    pub on_loss: LossPolicy,
+    /// Milliseconds to fade the input in when its first frame arrives (0 = cut)
+    #[serde(default)]
+    pub fade_in_ms: u64,
+    /// Milliseconds to fade the input out when the source is lost, before
+    /// `on_loss` takes effect (0 = cut; unused with `on_loss = "hold"`)
+    #[serde(default)]
+    pub fade_out_ms: u64,
+    /// Key out a green or blue screen (runs ahead of `filters`)
+    #[serde(default)]
+    pub chroma_key: Option<ChromaKeyConfig>,
+    /// Key by brightness, e.g. white-on-black graphics (after the chroma key)
+    #[serde(default)]
+    pub luma_key: Option<LumaKeyConfig>,
+    #[serde(default)]
+    pub filters: Vec<FilterConfig>,
+    /// Forward closed-caption metadata (CEA-608/708) from the input to the output
+    #[serde(default = "default_true")]
+    pub caption_passthrough: bool,
+    /// Include the most recent caption payload in the status API
+    #[serde(default)]
+    pub expose_captions: bool,
+}
+
+impl NdiInputConfig {
+    /// The input's GPU filter chain: the chroma key and luma key, if any,
+    /// then `filters`.
+    pub fn filter_chain(&self) -> Vec<FilterConfig> {
+        self.chroma_key
+            .iter()
+            .map(ChromaKeyConfig::filter)
+            .chain(self.luma_key.iter().map(LumaKeyConfig::filter))
+            .chain(self.filters.iter().cloned())
+            .collect()
+    }
+}
+
+/// Built-in chroma key, measured as distance from the key color in the CbCr plane.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ChromaKeyConfig {
+    /// Screen color, `#RRGGBB`
+    #[serde(default = "default_key_color")]
+    pub color: String,
+    /// Colors this close to the key become fully transparent (0.0–1.0)
+    #[serde(default = "default_key_similarity")]
+    pub similarity: f32,
+    /// Distance beyond `similarity` over which edges fade back in
+    #[serde(default = "default_key_smoothness")]
+    pub smoothness: f32,
+    /// Desaturates key color reflected onto the subject near the edge (0 = off)
+    #[serde(default = "default_key_spill")]
+    pub spill: f32,
+}
+
+fn default_key_color() -> String {
+    "#00FF00".to_string()
+}
+
+fn default_key_similarity() -> f32 {
+    0.4
+}
+
+fn default_key_smoothness() -> f32 {
+    0.08
+}
+
+fn default_key_spill() -> f32 {
+    0.1
+}
+
+impl ChromaKeyConfig {
+    /// Key color as RGB in 0.0–1.0 (green if it doesn't parse; validated at load).
+    pub fn rgb(&self) -> [f32; 3] {
+        let [r, g, b, _] = crate::text::parse_color(&self.color).unwrap_or([0, 255, 0, 255]);
+        [r, g, b].map(|c| c as f32 / 255.0)
+    }
+
+    /// The key as a built-in GPU filter. Params are packed by name, so the
+    /// shader reads them as key_b, key_g, key_r, similarity, smoothness, spill.
+    pub fn filter(&self) -> FilterConfig {
+        let [r, g, b] = self.rgb();
+        FilterConfig {
+            shader: "chroma_key (built-in)".to_string(),
+            params: HashMap::from([
+                ("key_r".to_string(), r),
+                ("key_g".to_string(), g),
+                ("key_b".to_string(), b),
+                ("similarity".to_string(), self.similarity),
+                ("smoothness".to_string(), self.smoothness),
+                ("spill".to_string(), self.spill),
+            ]),
+            textures: Vec::new(),
+            time_scale: 1.0,
+            paused: true,
+            passes: Vec::new(),
+            builtin: Some(include_str!("shaders/chroma_key.wgsl")),
+        }
+    }
+}
+
+/// JSON handed to a browser overlay's page whenever it changes, as a global
+/// and in a `message` event.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DataBindingConfig {
+    /// Local JSON file
+    #[serde(default)]
+    pub file: Option<String>,
+    /// http:// URL answering with JSON, e.g. a local API
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Milliseconds between polls
+    #[serde(default = "default_data_interval_ms")]
+    pub interval_ms: u64,
+    /// Name of the `window` property the page reads the data from
+    #[serde(default = "default_data_global")]
+    pub global: String,
+}
+
+impl DataBindingConfig {
+    /// The file or URL polled, for logs.
+    pub fn source(&self) -> &str {
+        self.file
+            .as_deref()
+            .or(self.url.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+fn default_data_interval_ms() -> u64 {
+    1000
+}
+
+fn default_data_global() -> String {
+    "ndimixerData".to_string()
+}
+
+/// Built-in luma key: dark pixels become transparent (bright ones with `invert`).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LumaKeyConfig {
+    /// Luma at or below which pixels are fully transparent (0.0–1.0)
+    #[serde(default = "default_luma_threshold")]
+    pub threshold: f32,
+    /// Luma range above `threshold` over which pixels fade back in
+    #[serde(default = "default_luma_softness")]
+    pub softness: f32,
+    /// Key out bright pixels instead, e.g. black-on-white graphics
+    #[serde(default)]
+    pub invert: bool,
+}
+
+fn default_luma_threshold() -> f32 {
+    0.1
+}
+
+fn default_luma_softness() -> f32 {
+    0.1
+}
+
+impl LumaKeyConfig {
+    fn validate(&self, channel: &str, layer: &str) -> anyhow::Result<()> {
+        if !(0.0..=1.0).contains(&self.threshold) {
+            anyhow::bail!(
+                "Channel '{}': {} luma_key threshold must be 0.0–1.0",
+                channel,
+                layer
+            );
+        }
+        if self.softness.is_nan() || self.softness < 0.0 {
+            anyhow::bail!(
+                "Channel '{}': {} luma_key softness must be >= 0",
+                channel,
+                layer
+            );
+        }
+        Ok(())
+    }
+
+    /// The key as a built-in GPU filter. Params are packed by name, so the
+    /// shader reads them as invert, softness, threshold.
+    pub fn filter(&self) -> FilterConfig {
+        FilterConfig {
+            shader: "luma_key (built-in)".to_string(),
+            params: HashMap::from([
+                ("invert".to_string(), if self.invert { 1.0 } else { 0.0 }),
+                ("softness".to_string(), self.softness),
+                ("threshold".to_string(), self.threshold),
+            ]),
+            textures: Vec::new(),
+            time_scale: 1.0,
+            paused: true,
+            passes: Vec::new(),
+            builtin: Some(include_str!("shaders/luma_key.wgsl")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LossPolicy {
+    /// Keep showing the last received frame
+    #[default]
+    Hold,
+    /// Drop the NDI layer; the other layers stay on air
+    Clear,
+    /// Show the channel's slate
+    Slate,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Fit {
+    /// Scale to the canvas size, distorting other aspect ratios
+    #[default]
+    Stretch,
+    /// Scale to fit inside the canvas, leaving transparent bars
+    Contain,
+    /// Scale to fill the canvas, cropping what overhangs
+    Cover,
+}
+
+impl Fit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Fit::Stretch => "stretch",
+            Fit::Contain => "contain",
+            Fit::Cover => "cover",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReloadMode {
+    /// Reload the page in place (the layer may briefly blank)
+    #[default]
+    Reload,
+    /// Load a second page in the background and swap once it's ready
+    Seamless,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlayLoadFailure {
+    /// Abort startup if the page can't be loaded after all retries
+    #[default]
+    Fail,
+    /// Start the channel without the overlay and keep retrying in the background
+    Continue,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureMode {
+    /// Screencast for moving content plus periodic screenshots for correct transparency
+    #[default]
+    Dual,
+    /// Periodic screenshots only — no screencast frames, so no transparency flicker
+    Screenshot,
+}
+
+/// HTTP basic auth credentials for an overlay URL.
+#[derive(Clone, Deserialize)]
+pub struct BasicAuth {
+    pub user: String,
+    pub password: String,
+}
+
+impl std::fmt::Debug for BasicAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BasicAuth")
+            .field("user", &self.user)
+            .field("password", &"***")
+            .finish()
+    }
+}
+
+/// A line of text drawn with a native font rasterizer.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TextOverlayConfig {
+    /// `{channel}` and `{output}` names, plus the clock's `%` time fields
+    pub text: String,
+    /// Expand `%` fields in UTC instead of the host's local time
+    #[serde(default)]
+    pub utc: bool,
+    #[serde(default)]
+    pub font: Option<String>,
+    #[serde(default = "default_clock_font_size")]
+    pub font_size: f32,
+    #[serde(default = "default_caption_color")]
+    pub color: String,
+    #[serde(default = "default_caption_background")]
+    pub background: String,
+    #[serde(default)]
+    pub position: TextPosition,
+    #[serde(default = "default_clock_margin")]
+    pub margin: u32,
+    #[serde(default = "default_clock_z_index")]
+    pub z_index: i32,
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+    #[serde(default)]
+    pub layer_group: Option<String>,
+}
+
+/// Settings of a named layer group; applied on top of each member's own opacity.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LayerGroupConfig {
+    pub name: String,
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+    #[serde(default = "default_true")]
+    pub visible: bool,
+}
+
+/// A named set of layer states. While it's active only the layers it lists
+/// are drawn, with its opacity and placement where it sets them.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SceneConfig {
+    pub name: String,
+    #[serde(default)]
+    pub layers: Vec<SceneLayerConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SceneLayerConfig {
+    /// Layer ID, as the control API takes it ("ndi", "overlay:0", ...)
+    pub id: LayerId,
+    /// Replaces the layer's configured opacity
+    #[serde(default)]
+    pub opacity: Option<f32>,
+    /// Canvas area the layer is scaled into (default: where it's configured)
+    #[serde(default)]
+    pub placement: Option<Roi>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct TransitionConfig {
+    #[serde(default)]
+    pub kind: TransitionKind,
+    /// Length of the transition
+    #[serde(default = "default_transition_ms")]
+    pub duration_ms: u64,
+    #[serde(default)]
+    pub easing: Easing,
+}
+
+fn default_transition_ms() -> u64 {
+    500
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransitionKind {
+    /// Switch on the next frame
+    Cut,
+    /// Fade layers and the NDI input from the old state to the new
+    #[default]
+    Crossfade,
+}
+
+/// How a transition's progress is spread over its duration.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Easing {
+    Linear,
+    /// Starts slow
+    EaseIn,
+    /// Ends slow
+    EaseOut,
+    /// Starts and ends slow
+    #[default]
+    EaseInOut,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImageOverlayConfig {
+    /// PNG or JPEG file
+    pub path: String,
+    /// Position of the image's top-left corner on the canvas
+    #[serde(default)]
+    pub x: i32,
+    #[serde(default)]
+    pub y: i32,
+    /// Scale to this size (default: the image's own size)
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
+    #[serde(default = "default_z_index_overlay")]
+    pub z_index: i32,
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+    #[serde(default)]
+    pub layer_group: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BrowserOverlayConfig {
+    pub url: String,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default = "default_z_index_overlay")]
+    pub z_index: i32,
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+    #[serde(default)]
+    pub layer_group: Option<String>,
+    /// Part of the page shown, in viewport pixels (default: all of it)
+    #[serde(default)]
+    pub crop: Option<Roi>,
+    /// Canvas area the page is scaled into (default: the whole canvas)
+    #[serde(default)]
+    pub placement: Option<Roi>,
+    /// Degrees clockwise the page is turned before it's scaled into `placement`
+    #[serde(default)]
+    pub rotation: f32,
+    /// Degrees the page is sheared horizontally, and vertically
+    #[serde(default)]
+    pub skew_x: f32,
+    #[serde(default)]
+    pub skew_y: f32,
+    #[serde(default)]
+    pub css: String,
+    /// Capture the page's audio and mix it into the channel's output
+    #[serde(default)]
+    pub audio: bool,
+    /// Level of the page's audio in the channel's mix, in dB
+    #[serde(default)]
+    pub audio_gain_db: f32,
+    /// Stylesheet file injected after `css` and re-injected whenever it changes
+    #[serde(default)]
+    pub css_file: Option<String>,
+    /// Script run in every document of the page before its own scripts
+    #[serde(default)]
+    pub js_file: Option<String>,
+    /// JSON polled from a file or URL and handed to the page
+    #[serde(default)]
+    pub data: Option<DataBindingConfig>,
+    /// Credentials answered to HTTP basic auth challenges
+    #[serde(default)]
+    pub basic_auth: Option<BasicAuth>,
+    /// Sent as `Authorization: Bearer <token>` with the page's requests
+    #[serde(default)]
+    pub bearer_token: Option<String>,
+    #[serde(default)]
+    pub reload_interval: u64,
+    #[serde(default)]
+    pub reload_mode: ReloadMode,
+    /// Seconds to wait for the initial navigation (0 = no limit)
+    #[serde(default = "default_navigation_timeout")]
+    pub navigation_timeout: u64,
+    /// Extra navigation attempts before the load counts as failed
+    #[serde(default = "default_navigation_retries")]
+    pub navigation_retries: u32,
+    /// Seconds between navigation attempts
+    #[serde(default = "default_retry_delay")]
+    pub retry_delay: u64,
+    #[serde(default)]
+    pub on_load_failure: OverlayLoadFailure,
+    #[serde(default)]
+    pub capture_mode: CaptureMode,
+    /// Milliseconds between direct screenshots (0 = only the initial one)
+    #[serde(default = "default_refresh_interval_ms")]
+    pub refresh_interval_ms: u64,
+    /// Update the layer every Nth channel frame (1 = every frame)
+    #[serde(default = "default_frame_divisor")]
+    pub frame_divisor: u32,
+    /// Cap on the page's CPU use, enforced with Chromium's CPU throttling
+    #[serde(default)]
+    pub cpu_budget: Option<CpuBudgetConfig>,
+    /// Key by brightness, e.g. white-on-black graphics (runs ahead of `filters`)
+    #[serde(default)]
+    pub luma_key: Option<LumaKeyConfig>,
+    #[serde(default)]
+    pub filters: Vec<FilterConfig>,
+}
+
+impl BrowserOverlayConfig {
+    /// The overlay's GPU filter chain: the luma key, if any, then `filters`.
+    pub fn filter_chain(&self) -> Vec<FilterConfig> {
+        self.luma_key
+            .iter()
+            .map(LumaKeyConfig::filter)
+            .chain(self.filters.iter().cloned())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CpuBudgetConfig {
+    /// Main-thread CPU the page may use, in percent of one core
+    pub max_percent: f32,
+    /// Slowdown factor applied while the page is over budget
+    #[serde(default = "default_throttle_rate")]
+    pub throttle_rate: f64,
+    /// Also freeze the page like a background tab, one check at a time,
+    /// while it stays over budget despite throttling
+    #[serde(default)]
+    pub freeze: bool,
+    /// Seconds under half the budget before throttling is lifted
+    #[serde(default = "default_budget_recover_seconds")]
+    pub recover_seconds: u64,
+}
+
+fn default_throttle_rate() -> f64 {
+    4.0
+}
+
+fn default_budget_recover_seconds() -> u64 {
+    10
+}
+
+/// Standards conversion of a channel's NDI input to the channel's frame rate.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConversionConfig {
+    #[serde(default)]
+    pub method: ConversionMethod,
+    /// Input frames the output runs behind the input, to absorb arrival jitter
+    #[serde(default = "default_conversion_delay")]
+    pub delay_frames: u32,
+}
+
+fn default_conversion_delay() -> u32 {
+    2
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConversionMethod {
+    /// Show the input frame nearest each output frame (e.g. 3:2 for 24 → 60 fps)
+    #[default]
+    Cadence,
+    /// Crossfade between the two input frames around each output frame
+    Blend,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompareConfig {
+    /// Start with the split view on; it can be switched at runtime either way
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Divider position as a fraction of the width; the channel's filters show left of it
+    #[serde(default = "default_compare_split")]
+    pub split: f32,
+    /// Channel filter chain for the right side (layer filters still apply);
+    /// empty = the right side without any filters
+    #[serde(default)]
+    pub filters: Vec<FilterConfig>,
+    /// Draw source names with tally borders over the compare view
+    #[serde(default = "default_true")]
+    #[cfg_attr(not(feature = "gpu"), allow(dead_code))]
+    pub labels: bool,
+    /// Font for the labels (default: Arial)
+    #[serde(default)]
+    #[cfg_attr(not(feature = "gpu"), allow(dead_code))]
+    pub font: Option<String>,
+}
+
+fn default_compare_split() -> f32 {
+    0.5
+}
+
+/// Audio mixer: several NDI sources (and overlays with `audio`) summed with
+/// per-source gain.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AudioMixConfig {
+    /// Level applied to the whole mix, in dB
+    #[serde(default)]
+    pub master_db: f32,
+    #[serde(default = "default_silent_sample_rate")]
+    pub sample_rate: u32,
+    #[serde(default = "default_silent_channels")]
+    pub channels: u32,
+    /// `[[channel.audio.sources]]`
+    #[serde(default)]
+    pub sources: Vec<AudioMixSourceConfig>,
 }
 
-impl Default for Settings {
+impl Default for AudioMixConfig {
     fn default() -> Self {
         Self {
-            status_port: 0,
-            log_level: "info".to_string(),
+            master_db: 0.0,
+            sample_rate: default_silent_sample_rate(),
+            channels: default_silent_channels(),
+            sources: Vec::new(),
         }
     }
 }
 
-fn default_log_level() -> String {
-    "info".to_string()
+#[derive(Debug, Clone, Deserialize)]
+pub struct AudioMixSourceConfig {
+    /// NDI source name or `[sources]` alias (substring match)
+    pub source: String,
+    /// Alias `source` was given as (set when resolving `[sources]`)
+    #[serde(skip)]
+    pub alias: Option<String>,
+    /// NDI groups to search for the source (comma-separated; default: public)
+    #[serde(default)]
+    pub groups: Option<String>,
+    #[serde(default)]
+    pub gain_db: f32,
+    #[serde(default)]
+    pub mute: bool,
+}
+
+fn validate_audio_mix(audio: &AudioMixConfig, channel: &str) -> anyhow::Result<()> {
+    let range = crate::audio_mixer::GAIN_RANGE;
+    if !(8000..=192000).contains(&audio.sample_rate) {
+        anyhow::bail!(
+            "Channel '{}': audio sample_rate must be 8000–192000",
+            channel
+        );
+    }
+    if !(1..=16).contains(&audio.channels) {
+        anyhow::bail!("Channel '{}': audio channels must be 1–16", channel);
+    }
+    if !range.contains(&audio.master_db) {
+        anyhow::bail!("Channel '{}': audio master_db must be -96–24", channel);
+    }
+    for source in &audio.sources {
+        if source.source.trim().is_empty() {
+            anyhow::bail!("Channel '{}': audio source name is empty", channel);
+        }
+        if !range.contains(&source.gain_db) {
+            anyhow::bail!(
+                "Channel '{}': audio source '{}' gain_db must be -96–24",
+                channel,
+                source.source
+            );
+        }
+    }
+    Ok(())
 }
 
 #[derive(Debug, Clone, Deserialize)]
-pub struct FilterConfig {
-    pub shader: String,
+pub struct ScopesConfig {
+    /// Milliseconds between measurements of the output
+    #[serde(default = "default_scopes_interval_ms")]
+    pub interval_ms: u64,
+    /// Also build a luma waveform image
     #[serde(default)]
-    pub params: HashMap<String, f32>,
+    pub waveform: bool,
+    /// Waveform columns (capped at the output width)
+    #[serde(default = "default_waveform_width")]
+    pub waveform_width: u32,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ChannelConfig {
-    pub name: String,
-    pub output_name: String,
-    pub width: u32,
-    pub height: u32,
-    #[serde(default = "default_frame_rate")]
-    pub frame_rate: u32,
-    pub ndi_input: Option<NdiInputConfig>,
-    /// Legacy single overlay (backwards compat with `[channel.browser_overlay]`)
+fn default_scopes_interval_ms() -> u64 {
+    1000
+}
+
+fn default_waveform_width() -> u32 {
+    512
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReplayConfig {
+    /// Seconds of output to keep
+    #[serde(default = "default_replay_seconds")]
+    pub seconds: u64,
+    /// Memory cap for the buffered frames; shortens the buffer if `seconds` doesn't fit
+    #[serde(default = "default_replay_max_memory_mb")]
+    pub max_memory_mb: u64,
+    /// Directory replay files are written to
+    #[serde(default = "default_replay_dir")]
+    pub dir: String,
+}
+
+fn default_replay_seconds() -> u64 {
+    10
+}
+
+fn default_replay_max_memory_mb() -> u64 {
+    1024
+}
+
+fn default_replay_dir() -> String {
+    "replays".to_string()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VirtualCameraConfig {
+    /// v4l2loopback device node, e.g. `/dev/video10`
+    pub device: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptionPosition {
+    Top,
+    #[default]
+    Bottom,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CaptionsConfig {
+    /// SRT or WebVTT file, timed from channel start
     #[serde(default)]
-    browser_overlay: Option<BrowserOverlayConfig>,
-    /// Multiple overlays (`[[channel.browser_overlays]]`)
+    pub file: Option<String>,
+    /// Live caption feed (`ws://` / `wss://`); each message replaces the caption
     #[serde(default)]
-    browser_overlays: Vec<BrowserOverlayConfig>,
-    /// Channel-level post-processing filters (applied after all layers composited)
+    pub websocket: Option<String>,
+    /// TTF/OTF font path (defaults to Arial on macOS)
     #[serde(default)]
-    pub filters: Vec<FilterConfig>,
+    pub font: Option<String>,
+    #[serde(default = "default_caption_font_size")]
+    pub font_size: f32,
+    #[serde(default = "default_caption_color")]
+    pub color: String,
+    #[serde(default = "default_caption_background")]
+    pub background: String,
+    #[serde(default)]
+    pub position: CaptionPosition,
+    /// Distance from the top/bottom edge in pixels
+    #[serde(default = "default_caption_margin")]
+    pub margin: u32,
+    #[serde(default = "default_caption_z_index")]
+    pub z_index: i32,
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+    #[serde(default)]
+    pub layer_group: Option<String>,
+    /// Shift file cues later (positive) or earlier (negative)
+    #[serde(default)]
+    pub offset_ms: i64,
+    /// How long a live caption stays up without a new message
+    #[serde(default = "default_caption_live_hold_ms")]
+    pub live_hold_ms: u64,
 }
 
-impl ChannelConfig {
-    /// Returns all browser overlays, merging legacy single `browser_overlay` with `browser_overlays`.
-    pub fn all_browser_overlays(&self) -> Vec<&BrowserOverlayConfig> {
-        let mut all: Vec<&BrowserOverlayConfig> = Vec::new();
-        if let Some(ref single) = self.browser_overlay {
-            all.push(single);
-        }
-        all.extend(self.browser_overlays.iter());
-        all
-    }
+fn default_caption_font_size() -> f32 {
+    42.0
 }
 
-fn default_frame_rate() -> u32 {
-    30
+fn default_caption_color() -> String {
+    "#FFFFFF".to_string()
 }
 
-#[derive(Debug, Deserialize)]
-pub struct NdiInputConfig {
-    pub source: String,
+fn default_caption_background() -> String {
+    "#000000B0".to_string()
+}
+
+fn default_caption_margin() -> u32 {
+    60
+}
+
+fn default_caption_z_index() -> i32 {
+    100
+}
+
+fn default_caption_live_hold_ms() -> u64 {
+    4000
+}
+
+/// Where a single-line text layer (clock, timer) sits on the canvas.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextPosition {
+    TopLeft,
+    Top,
+    #[default]
+    TopRight,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+/// Where a clock layer takes the time of day from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeSource {
+    /// The host clock, as disciplined by the OS (NTP daemon, or PTP via ptp4l/phc2sys)
+    #[default]
+    System,
+    /// The host clock corrected by an offset measured against `ntp_server`
+    Ntp,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClockConfig {
+    /// `%H %M %S` time, `%f` frame within the second, `%Y %m %d` date, `%%` a literal %
+    #[serde(default = "default_clock_format")]
+    pub format: String,
+    /// Show UTC instead of the host's local time
     #[serde(default)]
+    pub utc: bool,
+    #[serde(default)]
+    pub time_source: TimeSource,
+    /// NTP server (`host` or `host:port`) for `time_source = "ntp"`
+    #[serde(default = "default_ntp_server")]
+    pub ntp_server: String,
+    /// Seconds between NTP queries
+    #[serde(default = "default_ntp_interval")]
+    pub ntp_interval: u64,
+    /// TTF/OTF font path (defaults to Arial on macOS)
+    #[serde(default)]
+    pub font: Option<String>,
+    #[serde(default = "default_clock_font_size")]
+    pub font_size: f32,
+    #[serde(default = "default_caption_color")]
+    pub color: String,
+    #[serde(default = "default_caption_background")]
+    pub background: String,
+    #[serde(default)]
+    pub position: TextPosition,
+    /// Distance from the canvas edges in pixels
+    #[serde(default = "default_clock_margin")]
+    pub margin: u32,
+    #[serde(default = "default_clock_z_index")]
     pub z_index: i32,
     #[serde(default = "default_opacity")]
     pub opacity: f32,
     #[serde(default)]
-    pub filters: Vec<FilterConfig>,
+    pub layer_group: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct BrowserOverlayConfig {
-    pub url: String,
-    pub width: u32,
-    pub height: u32,
-    #[serde(default = "default_z_index_overlay")]
+fn default_clock_format() -> String {
+    "%H:%M:%S:%f".to_string()
+}
+
+fn default_ntp_server() -> String {
+    "pool.ntp.org".to_string()
+}
+
+fn default_ntp_interval() -> u64 {
+    64
+}
+
+fn default_clock_font_size() -> f32 {
+    48.0
+}
+
+fn default_clock_margin() -> u32 {
+    40
+}
+
+fn default_clock_z_index() -> i32 {
+    110
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimerMode {
+    /// Count down from `duration` to zero
+    #[default]
+    Countdown,
+    /// Count up from zero
+    Stopwatch,
+}
+
+/// What a countdown shows once it reaches zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimerEnd {
+    /// Stay at zero
+    #[default]
+    Hold,
+    /// Remove the timer from the output
+    Hide,
+    /// Keep counting past zero, shown with a leading `-`
+    Overrun,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TimerConfig {
+    #[serde(default)]
+    pub mode: TimerMode,
+    /// Countdown length in seconds
+    #[serde(default = "default_timer_duration")]
+    pub duration: u64,
+    /// `%H %M %S` and `%f` (frame within the second); the largest unit shown carries the rest
+    #[serde(default = "default_timer_format")]
+    pub format: String,
+    /// Start running when the channel starts instead of waiting for `start_timer`
+    #[serde(default)]
+    pub autostart: bool,
+    #[serde(default)]
+    pub on_end: TimerEnd,
+    /// TTF/OTF font path (defaults to Arial on macOS)
+    #[serde(default)]
+    pub font: Option<String>,
+    #[serde(default = "default_timer_font_size")]
+    pub font_size: f32,
+    #[serde(default = "default_caption_color")]
+    pub color: String,
+    #[serde(default = "default_caption_background")]
+    pub background: String,
+    #[serde(default = "default_timer_position")]
+    pub position: TextPosition,
+    /// Distance from the canvas edges in pixels
+    #[serde(default = "default_clock_margin")]
+    pub margin: u32,
+    #[serde(default = "default_timer_z_index")]
     pub z_index: i32,
     #[serde(default = "default_opacity")]
     pub opacity: f32,
     #[serde(default)]
-    pub css: String,
-    #[serde(default)]
-    pub reload_interval: u64,
-    #[serde(default)]
-    pub filters: Vec<FilterConfig>,
+    pub layer_group: Option<String>,
+}
+
+fn default_timer_duration() -> u64 {
+    300
+}
+
+fn default_timer_format() -> String {
+    "%M:%S".to_string()
+}
+
+fn default_timer_font_size() -> f32 {
+    96.0
+}
+
+fn default_timer_position() -> TextPosition {
+    TextPosition::Bottom
+}
+
+fn default_timer_z_index() -> i32 {
+    105
+}
+
+fn default_true() -> bool {
+    true
 }
 
 fn default_opacity() -> f32 {
@@ -110,6 +2506,31 @@ fn default_z_index_overlay() -> i32 {
     1
 }
 
+fn default_navigation_timeout() -> u64 {
+    30
+}
+
+fn default_navigation_retries() -> u32 {
+    2
+}
+
+fn default_retry_delay() -> u64 {
+    5
+}
+
+fn default_refresh_interval_ms() -> u64 {
+    2000
+}
+
+fn default_frame_divisor() -> u32 {
+    1
+}
+
+/// Custom textures per filter (bindings 1..=8 of group 1, after the sampler).
+pub const MAX_FILTER_TEXTURES: usize = 8;
+/// Float params per filter (the shader's params uniform).
+pub const MAX_FILTER_PARAMS: usize = 16;
+
 fn validate_filter(filter: &FilterConfig, channel: &str, layer: &str) -> anyhow::Result<()> {
     if !Path::new(&filter.shader).exists() {
         anyhow::bail!(
@@ -119,73 +2540,378 @@ fn validate_filter(filter: &FilterConfig, channel: &str, layer: &str) -> anyhow:
             filter.shader
         );
     }
-    if filter.params.len() > 16 {
+    if filter.params.len() > MAX_FILTER_PARAMS {
+        anyhow::bail!(
+            "Channel '{}': {} filter has {} params (max {})",
+            channel,
+            layer,
+            filter.params.len(),
+            MAX_FILTER_PARAMS
+        );
+    }
+    if filter.textures.len() > MAX_FILTER_TEXTURES {
         anyhow::bail!(
-            "Channel '{}': {} filter has {} params (max 16)",
+            "Channel '{}': {} filter has {} textures (max {})",
             channel,
             layer,
-            filter.params.len()
+            filter.textures.len(),
+            MAX_FILTER_TEXTURES
+        );
+    }
+    for texture in &filter.textures {
+        if !Path::new(texture).exists() {
+            anyhow::bail!(
+                "Channel '{}': {} filter texture not found: {}",
+                channel,
+                layer,
+                texture
+            );
+        }
+    }
+    for pass in &filter.passes {
+        if let Some(ref shader) = pass.shader {
+            if !Path::new(shader).exists() {
+                anyhow::bail!(
+                    "Channel '{}': {} filter pass shader not found: {}",
+                    channel,
+                    layer,
+                    shader
+                );
+            }
+        }
+        if !(pass.scale > 0.0 && pass.scale <= 1.0) {
+            anyhow::bail!(
+                "Channel '{}': {} filter pass scale must be in (0, 1], got {}",
+                channel,
+                layer,
+                pass.scale
+            );
+        }
+    }
+    if filter.passes.last().is_some_and(|p| p.scale != 1.0) {
+        anyhow::bail!(
+            "Channel '{}': {} filter's last pass must have scale = 1.0",
+            channel,
+            layer
+        );
+    }
+    if !filter.time_scale.is_finite() {
+        anyhow::bail!(
+            "Channel '{}': {} filter time_scale must be a finite number",
+            channel,
+            layer
         );
     }
     Ok(())
 }
 
+fn validate_slate(slate: &SlateConfig, scope: &str) -> anyhow::Result<()> {
+    if let Some(ref image) = slate.image {
+        if !Path::new(image).exists() {
+            anyhow::bail!("{}: slate image not found: {}", scope, image);
+        }
+    }
+    if slate.message.is_some() {
+        let font = slate.font.as_deref().unwrap_or(crate::text::DEFAULT_FONT);
+        if !Path::new(font).exists() {
+            anyhow::bail!("{}: slate font not found: {}", scope, font);
+        }
+    }
+    for color in [&slate.color, &slate.background] {
+        if crate::text::parse_color(color).is_none() {
+            anyhow::bail!(
+                "{}: invalid slate color '{}' (use #RRGGBB or #RRGGBBAA)",
+                scope,
+                color
+            );
+        }
+    }
+    if slate.font_size <= 0.0 {
+        anyhow::bail!("{}: slate font_size must be > 0", scope);
+    }
+    Ok(())
+}
+
+/// Check that `format` only uses `%` specifiers from `allowed` (plus `%%`).
+fn validate_time_format(
+    format: &str,
+    allowed: &str,
+    layer: &str,
+    channel: &str,
+) -> anyhow::Result<()> {
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+        match chars.next() {
+            Some(c) if c == '%' || allowed.contains(c) => {}
+            Some(c) => anyhow::bail!(
+                "Channel '{}': unknown {} format specifier '%{}' (use {} or %%)",
+                channel,
+                layer,
+                c,
+                allowed
+                    .chars()
+                    .map(|c| format!("%{}", c))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+            None => anyhow::bail!(
+                "Channel '{}': {} format ends with a lone '%'",
+                channel,
+                layer
+            ),
+        }
+    }
+    Ok(())
+}
+
+/// Font, colors, size and opacity shared by the native text layers.
+fn validate_text_style(
+    font: Option<&str>,
+    colors: [&str; 2],
+    font_size: f32,
+    opacity: f32,
+    layer: &str,
+    channel: &str,
+) -> anyhow::Result<()> {
+    let font = font.unwrap_or(crate::text::DEFAULT_FONT);
+    if !Path::new(font).exists() {
+        anyhow::bail!("Channel '{}': {} font not found: {}", channel, layer, font);
+    }
+    for color in colors {
+        if crate::text::parse_color(color).is_none() {
+            anyhow::bail!(
+                "Channel '{}': invalid {} color '{}' (use #RRGGBB or #RRGGBBAA)",
+                channel,
+                layer,
+                color
+            );
+        }
+    }
+    if font_size <= 0.0 {
+        anyhow::bail!("Channel '{}': {} font_size must be > 0", channel, layer);
+    }
+    if !(0.0..=1.0).contains(&opacity) {
+        anyhow::bail!("Channel '{}': {} opacity must be 0.0–1.0", channel, layer);
+    }
+    Ok(())
+}
+
+fn validate_timer(timer: &TimerConfig, channel: &str) -> anyhow::Result<()> {
+    validate_time_format(&timer.format, "HMSf", "timer", channel)?;
+    if timer.mode == TimerMode::Countdown && timer.duration == 0 {
+        anyhow::bail!("Channel '{}': timer duration must be > 0", channel);
+    }
+    validate_text_style(
+        timer.font.as_deref(),
+        [&timer.color, &timer.background],
+        timer.font_size,
+        timer.opacity,
+        "timer",
+        channel,
+    )
+}
+
+fn validate_clock(clock: &ClockConfig, channel: &str) -> anyhow::Result<()> {
+    validate_time_format(&clock.format, "HMSfYmd", "clock", channel)?;
+    if clock.time_source == TimeSource::Ntp {
+        if clock.ntp_server.trim().is_empty() {
+            anyhow::bail!("Channel '{}': clock ntp_server must not be empty", channel);
+        }
+        if clock.ntp_interval == 0 {
+            anyhow::bail!("Channel '{}': clock ntp_interval must be > 0", channel);
+        }
+    }
+    validate_text_style(
+        clock.font.as_deref(),
+        [&clock.color, &clock.background],
+        clock.font_size,
+        clock.opacity,
+        "clock",
+        channel,
+    )
+}
+
+fn validate_captions(captions: &CaptionsConfig, channel: &str) -> anyhow::Result<()> {
+    match (&captions.file, &captions.websocket) {
+        (Some(file), None) => {
+            if !Path::new(file).exists() {
+                anyhow::bail!("Channel '{}': captions file not found: {}", channel, file);
+            }
+        }
+        (None, Some(url)) => {
+            if !(url.starts_with("ws://") || url.starts_with("wss://")) {
+                anyhow::bail!(
+                    "Channel '{}': captions websocket must be a ws:// or wss:// URL (got '{}')",
+                    channel,
+                    url
+                );
+            }
+        }
+        _ => anyhow::bail!(
+            "Channel '{}': captions need exactly one of `file` or `websocket`",
+            channel
+        ),
+    }
+    let font = captions
+        .font
+        .as_deref()
+        .unwrap_or(crate::text::DEFAULT_FONT);
+    if !Path::new(font).exists() {
+        anyhow::bail!("Channel '{}': captions font not found: {}", channel, font);
+    }
+    for color in [&captions.color, &captions.background] {
+        if crate::text::parse_color(color).is_none() {
+            anyhow::bail!(
+                "Channel '{}': invalid captions color '{}' (use #RRGGBB or #RRGGBBAA)",
+                channel,
+                color
+            );
+        }
+    }
+    if captions.font_size <= 0.0 {
+        anyhow::bail!("Channel '{}': captions font_size must be > 0", channel);
+    }
+    if !(0.0..=1.0).contains(&captions.opacity) {
+        anyhow::bail!("Channel '{}': captions opacity must be 0.0–1.0", channel);
+    }
+    Ok(())
+}
+
 impl Config {
     pub fn load(path: &Path) -> anyhow::Result<Self> {
         let content = std::fs::read_to_string(path)
             .map_err(|e| anyhow::anyhow!("Failed to read config file {}: {}", path.display(), e))?;
-        let config: Config = toml::from_str(&content)
+        let mut config: Config = toml::from_str(&content)
             .map_err(|e| anyhow::anyhow!("Failed to parse config file: {}", e))?;
+        config.resolve_sources();
         config.validate()?;
         Ok(config)
     }
 
+    /// Replace source aliases with the NDI names they stand for, remembering the
+    /// alias for display. Names that aren't aliases are used as NDI names directly.
+    fn resolve_sources(&mut self) {
+        for ch in &mut self.channel {
+            ch.resolve_sources(&self.sources);
+        }
+    }
+
     fn validate(&self) -> anyhow::Result<()> {
-        if self.channel.is_empty() {
-            anyhow::bail!("At least one channel must be defined");
+        if self.channel.is_empty() && self.auto_channel.is_empty() {
+            anyhow::bail!("At least one channel or auto_channel must be defined");
         }
-        for ch in &self.channel {
-            if ch.width == 0 || ch.height == 0 {
-                anyhow::bail!("Channel '{}': width and height must be > 0", ch.name);
-            }
-            if ch.frame_rate == 0 {
-                anyhow::bail!("Channel '{}': frame_rate must be > 0", ch.name);
-            }
-            if let Some(ref ndi) = ch.ndi_input {
-                if !(0.0..=1.0).contains(&ndi.opacity) {
-                    anyhow::bail!("Channel '{}': ndi_input opacity must be 0.0–1.0", ch.name);
-                }
-                for filter in &ndi.filters {
-                    validate_filter(filter, &ch.name, "ndi_input")?;
-                }
-            }
-            for filter in &ch.filters {
-                validate_filter(filter, &ch.name, "channel")?;
+        for (alias, name) in &self.sources {
+            if name.trim().is_empty() {
+                anyhow::bail!("sources: alias '{}' has an empty NDI source name", alias);
             }
-            for browser in ch.all_browser_overlays() {
-                if browser.width == 0 || browser.height == 0 {
+        }
+        let realtime = &self.settings.realtime;
+        if let Some(cores) = core_affinity::get_core_ids() {
+            for core in realtime.render_cores.iter().chain(&realtime.ndi_cores) {
+                if !cores.iter().any(|c| c.id == *core) {
                     anyhow::bail!(
-                        "Channel '{}': browser overlay width and height must be > 0",
-                        ch.name
+                        "settings.realtime: core {} does not exist (available: 0–{})",
+                        core,
+                        cores.len().saturating_sub(1)
                     );
                 }
-                if !(0.0..=1.0).contains(&browser.opacity) {
+            }
+        }
+        validate_slate(&self.settings.slate, "settings.slate")?;
+        let tokens = &self.settings.control_api.tokens;
+        for (i, token) in tokens.iter().enumerate() {
+            if token.name.is_empty() || token.token.is_empty() {
+                anyhow::bail!("settings.control_api: tokens need a name and a token");
+            }
+            if tokens[..i]
+                .iter()
+                .any(|t| t.name == token.name || t.token == token.token)
+            {
+                anyhow::bail!(
+                    "settings.control_api: duplicate token name or value ('{}')",
+                    token.name
+                );
+            }
+        }
+        if !(250..=60_000).contains(&self.settings.stats_interval_ms) {
+            anyhow::bail!("settings: stats_interval_ms must be 250–60000");
+        }
+        if self.settings.stats_history_hours > 168 {
+            anyhow::bail!("settings: stats_history_hours must be 0–168");
+        }
+        if self.settings.startup_concurrency == 0 {
+            anyhow::bail!("settings: startup_concurrency must be >= 1");
+        }
+        if self.settings.channel_retry_delay == 0 {
+            anyhow::bail!("settings: channel_retry_delay must be >= 1");
+        }
+        let discovery = &self.settings.discovery;
+        if !(100..=60_000).contains(&discovery.search_ms) {
+            anyhow::bail!("settings.discovery: search_ms must be 100–60000");
+        }
+        if !(50..=60_000).contains(&discovery.poll_ms) {
+            anyhow::bail!("settings.discovery: poll_ms must be 50–60000");
+        }
+        let silent = &self.settings.silent_audio;
+        if silent.enabled {
+            if !(8000..=192000).contains(&silent.sample_rate) {
+                anyhow::bail!("settings.silent_audio: sample_rate must be 8000–192000");
+            }
+            if !(1..=16).contains(&silent.channels) {
+                anyhow::bail!("settings.silent_audio: channels must be 1–16");
+            }
+        }
+        for auto in &self.auto_channel {
+            auto.validate(&self.sources)?;
+        }
+        let mut hotkeys: Vec<char> = self.settings.slate.hotkey.into_iter().collect();
+        for ch in &self.channel {
+            ch.validate()?;
+            if let Some(key) = ch.slate.as_ref().and_then(|s| s.hotkey) {
+                if hotkeys.contains(&key) {
                     anyhow::bail!(
-                        "Channel '{}': browser overlay opacity must be 0.0–1.0",
-                        ch.name
+                        "Channel '{}': slate hotkey '{}' is already in use",
+                        ch.name,
+                        key
                     );
                 }
-                for filter in &browser.filters {
-                    validate_filter(filter, &ch.name, "browser_overlay")?;
-                }
+                hotkeys.push(key);
             }
         }
         Ok(())
     }
 
+    /// Whether any channel, or auto channel template, sets `compositor = "gpu"`,
+    /// which needs a GPU context even with the mixer pinned to the CPU.
+    pub fn channels_require_gpu(&self) -> bool {
+        self.channel
+            .iter()
+            .any(|ch| ch.compositor == CompositorMode::Gpu)
+            || self
+                .auto_channel
+                .iter()
+                .any(|rule| rule.template.get("compositor").and_then(|v| v.as_str()) == Some("gpu"))
+    }
+
+    /// Browser overlays of the channels enabled at startup.
+    pub fn enabled_browser_overlays(&self) -> usize {
+        self.channel
+            .iter()
+            .filter(|ch| ch.enabled)
+            .map(|ch| ch.all_browser_overlays().len())
+            .sum()
+    }
+
     pub fn has_browser_overlays(&self) -> bool {
         self.channel
             .iter()
             .any(|ch| !ch.all_browser_overlays().is_empty())
+            || self.auto_channel.iter().any(|auto| {
+                auto.instantiate(EXAMPLE_SOURCE, &self.sources)
+                    .is_ok_and(|ch| !ch.all_browser_overlays().is_empty())
+            })
     }
 }
@@ -0,0 +1,762 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::audio_mixer::GAIN_RANGE;
+use crate::browser::{self, Interaction};
+use crate::channel::{BlankMode, ChannelRegistry, ChannelState};
+use crate::config::Roi;
+use crate::schedule::{format_timecode, parse_timecode};
+use crate::source::LayerId;
+use crate::timer::Timer;
+use crate::transition::Transition;
+
+/// Operator actions, shared by the HTTP API, the line-based control stream and
+/// terminal hotkeys.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Command {
+    /// Put the slate on air (one channel, or all when `channel` is omitted)
+    Slate {
+        #[serde(default)]
+        channel: Option<String>,
+    },
+    /// Take the slate off air
+    ClearSlate {
+        #[serde(default)]
+        channel: Option<String>,
+    },
+    /// Slate on if any targeted channel is live, otherwise clear it
+    ToggleSlate {
+        #[serde(default)]
+        channel: Option<String>,
+    },
+    /// Hold a channel's output on black (or the slate) without touching its layers
+    BlankOutput {
+        channel: String,
+        #[serde(default)]
+        mode: BlankMode,
+    },
+    /// Put a blanked channel's program back on air
+    ResumeOutput { channel: String },
+    /// Restart the filter clock so time-based effects begin from zero
+    ResetFilterClock {
+        #[serde(default)]
+        channel: Option<String>,
+    },
+    /// Zero frame, overload and caption counters
+    ResetCounters {
+        #[serde(default)]
+        channel: Option<String>,
+    },
+    /// Override a layer's opacity: `layer` is its ID (see `LayerId`). With
+    /// `duration_ms`, it ramps there instead of snapping.
+    SetOpacity {
+        channel: String,
+        layer: String,
+        opacity: f32,
+        #[serde(default)]
+        duration_ms: Option<u64>,
+    },
+    /// Move a layer to an area of the canvas, ramping there over `duration_ms`;
+    /// back where it's configured when `placement` is omitted
+    SetPlacement {
+        channel: String,
+        layer: String,
+        #[serde(default)]
+        placement: Option<Roi>,
+        #[serde(default)]
+        duration_ms: Option<u64>,
+    },
+    /// Change a layer group's opacity or visibility; omitted fields stay as they
+    /// are. With `duration_ms`, the opacity ramps instead of snapping.
+    SetLayerGroup {
+        channel: String,
+        group: String,
+        #[serde(default)]
+        opacity: Option<f32>,
+        #[serde(default)]
+        visible: Option<bool>,
+        #[serde(default)]
+        duration_ms: Option<u64>,
+    },
+    /// Switch a channel to one of its `[[channel.scene]]` layer sets
+    SetScene { channel: String, scene: String },
+    /// Change a level of the channel's audio mixer: a source's gain and mute
+    /// by index, or the master level when `source` is omitted
+    SetAudioLevel {
+        channel: String,
+        #[serde(default)]
+        source: Option<usize>,
+        #[serde(default)]
+        gain_db: Option<f32>,
+        #[serde(default)]
+        mute: Option<bool>,
+    },
+    /// Point a browser overlay at another URL (until its next reload)
+    Navigate {
+        channel: String,
+        overlay: usize,
+        url: String,
+    },
+    /// Send a click, keystroke or scroll to a browser overlay
+    Interact {
+        channel: String,
+        overlay: usize,
+        input: Interaction,
+    },
+    /// Start a disabled channel's pipeline
+    Enable { channel: String },
+    /// Stop a channel's pipeline, keeping it in the configuration
+    Disable { channel: String },
+    /// Change a channel's output resolution, restarting its pipeline
+    SetResolution {
+        channel: String,
+        width: u32,
+        height: u32,
+    },
+    /// Rename a channel's NDI output, keeping its pipeline running
+    SetOutputName {
+        channel: String,
+        output_name: String,
+    },
+    /// Switch a channel's NDI input to another source (NDI name or `[sources]`
+    /// alias), keeping its pipeline running
+    SetInputSource { channel: String, source: String },
+    /// Show a channel's filters on the left of the output and the compare
+    /// chain (or no filters) on the right; `split` moves the divider
+    SetCompare {
+        channel: String,
+        enabled: bool,
+        #[serde(default)]
+        split: Option<f32>,
+    },
+    /// Write a channel's replay buffer to disk
+    DumpReplay { channel: String },
+    /// Start or resume a channel's timer (all timers when `channel` is omitted)
+    StartTimer {
+        #[serde(default)]
+        channel: Option<String>,
+    },
+    /// Pause a timer, keeping its count
+    PauseTimer {
+        #[serde(default)]
+        channel: Option<String>,
+    },
+    /// Stop a timer and return it to the start; `duration` (seconds) sets a new countdown length
+    ResetTimer {
+        #[serde(default)]
+        channel: Option<String>,
+        #[serde(default)]
+        duration: Option<u64>,
+    },
+    /// Run `command` on a channel just before the given output frame, by
+    /// number or as `HH:MM:SS:FF` timecode since the channel started
+    Schedule {
+        channel: String,
+        #[serde(default)]
+        frame: Option<u64>,
+        #[serde(default)]
+        timecode: Option<String>,
+        command: Box<Command>,
+    },
+    /// Drop a scheduled command by id, or all of a channel's when `id` is omitted
+    Unschedule {
+        channel: String,
+        #[serde(default)]
+        id: Option<u64>,
+    },
+}
+
+impl Command {
+    /// The channel a command names, if any.
+    fn channel(&self) -> Option<&str> {
+        match self {
+            Command::Slate { channel }
+            | Command::ClearSlate { channel }
+            | Command::ToggleSlate { channel }
+            | Command::ResetFilterClock { channel }
+            | Command::ResetCounters { channel }
+            | Command::StartTimer { channel }
+            | Command::PauseTimer { channel }
+            | Command::ResetTimer { channel, .. } => channel.as_deref(),
+            Command::SetOpacity { channel, .. }
+            | Command::SetPlacement { channel, .. }
+            | Command::SetLayerGroup { channel, .. }
+            | Command::SetScene { channel, .. }
+            | Command::SetAudioLevel { channel, .. }
+            | Command::Navigate { channel, .. }
+            | Command::Interact { channel, .. }
+            | Command::Enable { channel }
+            | Command::Disable { channel }
+            | Command::SetResolution { channel, .. }
+            | Command::SetOutputName { channel, .. }
+            | Command::SetInputSource { channel, .. }
+            | Command::SetCompare { channel, .. }
+            | Command::DumpReplay { channel }
+            | Command::BlankOutput { channel, .. }
+            | Command::ResumeOutput { channel }
+            | Command::Schedule { channel, .. }
+            | Command::Unschedule { channel, .. } => Some(channel),
+        }
+    }
+
+    /// Whether the render thread can run the command: it only changes state
+    /// the next frame reads, without waiting on pages or restarting the channel.
+    fn schedulable(&self) -> bool {
+        !matches!(
+            self,
+            Command::Navigate { .. }
+                | Command::Interact { .. }
+                | Command::Enable { .. }
+                | Command::Disable { .. }
+                | Command::SetResolution { .. }
+                | Command::SetOutputName { .. }
+                | Command::SetInputSource { .. }
+                | Command::DumpReplay { .. }
+                | Command::Schedule { .. }
+                | Command::Unschedule { .. }
+        )
+    }
+}
+
+/// Run any command, including the ones that drive overlay pages or start and
+/// stop channels.
+pub async fn dispatch(registry: &ChannelRegistry, command: &Command) -> Result<()> {
+    let channels = &registry.snapshot();
+    match command {
+        Command::Enable { channel } => registry.set_enabled(channel, true).await,
+        Command::Disable { channel } => registry.set_enabled(channel, false).await,
+        Command::SetResolution {
+            channel,
+            width,
+            height,
+        } => registry.set_resolution(channel, *width, *height).await,
+        Command::SetOutputName {
+            channel,
+            output_name,
+        } => registry.set_output_name(channel, output_name).await,
+        Command::SetInputSource { channel, source } => {
+            registry.set_input_source(channel, source).await
+        }
+        Command::DumpReplay { channel } => {
+            let ch = target_channels(channels, Some(channel))?[0];
+            let replay = ch
+                .replay
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("Channel '{}' has no replay buffer", channel))?;
+            replay.dump().await.map(|_| ())
+        }
+        Command::Navigate {
+            channel,
+            overlay,
+            url,
+        } => {
+            let (page, _) = overlay_page(channels, channel, *overlay)?;
+            tracing::info!("Channel '{}': overlay {} → {}", channel, overlay, url);
+            browser::navigate(&page, url).await
+        }
+        Command::Interact {
+            channel,
+            overlay,
+            input,
+        } => {
+            let (page, viewport) = overlay_page(channels, channel, *overlay)?;
+            tracing::info!(
+                "Channel '{}': overlay {} interaction {:?}",
+                channel,
+                overlay,
+                input
+            );
+            browser::interact(&page, viewport, input).await
+        }
+        _ => execute(channels, command),
+    }
+}
+
+/// Run a command against the running channels.
+pub fn execute(channels: &[Arc<ChannelState>], command: &Command) -> Result<()> {
+    match command {
+        Command::SetOpacity {
+            channel,
+            layer,
+            opacity,
+            duration_ms,
+        } => set_opacity(channels, channel, layer, *opacity, *duration_ms),
+        Command::SetPlacement {
+            channel,
+            layer,
+            placement,
+            duration_ms,
+        } => set_placement(channels, channel, layer, *placement, *duration_ms),
+        Command::SetLayerGroup {
+            channel,
+            group,
+            opacity,
+            visible,
+            duration_ms,
+        } => set_layer_group(channels, channel, group, *opacity, *visible, *duration_ms),
+        Command::SetScene { channel, scene } => set_scene(channels, channel, scene),
+        Command::SetAudioLevel {
+            channel,
+            source,
+            gain_db,
+            mute,
+        } => set_audio_level(channels, channel, *source, *gain_db, *mute),
+        Command::Navigate { .. }
+        | Command::Interact { .. }
+        | Command::Enable { .. }
+        | Command::Disable { .. }
+        | Command::SetResolution { .. }
+        | Command::SetOutputName { .. }
+        | Command::SetInputSource { .. }
+        | Command::DumpReplay { .. } => {
+            anyhow::bail!("This command must go through `dispatch`")
+        }
+        Command::Slate { channel } => set_slate(channels, channel.as_deref(), Some(true)),
+        Command::ClearSlate { channel } => set_slate(channels, channel.as_deref(), Some(false)),
+        Command::ToggleSlate { channel } => set_slate(channels, channel.as_deref(), None),
+        Command::BlankOutput { channel, mode } => set_blank(channels, channel, Some(*mode)),
+        Command::ResumeOutput { channel } => set_blank(channels, channel, None),
+        Command::ResetFilterClock { channel } => reset_filter_clock(channels, channel.as_deref()),
+        Command::ResetCounters { channel } => reset_counters(channels, channel.as_deref()),
+        Command::SetCompare {
+            channel,
+            enabled,
+            split,
+        } => set_compare(channels, channel, *enabled, *split),
+        Command::Schedule {
+            channel,
+            frame,
+            timecode,
+            command,
+        } => schedule(channels, channel, *frame, timecode.as_deref(), command).map(|_| ()),
+        Command::Unschedule { channel, id } => unschedule(channels, channel, *id),
+        Command::StartTimer { channel } => {
+            control_timer(channels, channel.as_deref(), "started", |t| t.start())
+        }
+        Command::PauseTimer { channel } => {
+            control_timer(channels, channel.as_deref(), "paused", |t| t.pause())
+        }
+        Command::ResetTimer { channel, duration } => {
+            control_timer(channels, channel.as_deref(), "reset", |t| {
+                t.reset(duration.map(Duration::from_secs))
+            })
+        }
+    }
+}
+
+/// Resolve a command's channel target: a single named channel, or all of them.
+pub fn target_channels<'a>(
+    channels: &'a [Arc<ChannelState>],
+    name: Option<&str>,
+) -> Result<Vec<&'a Arc<ChannelState>>> {
+    match name {
+        Some(name) => channels
+            .iter()
+            .find(|ch| ch.name == name)
+            .map(|ch| vec![ch])
+            .ok_or_else(|| anyhow::anyhow!("Unknown channel '{}'", name)),
+        None => Ok(channels.iter().collect()),
+    }
+}
+
+/// The live tab of a channel's browser overlay, with its viewport size.
+pub fn overlay_page(
+    channels: &[Arc<ChannelState>],
+    name: &str,
+    index: usize,
+) -> Result<(chromiumoxide::Page, (u32, u32))> {
+    let ch = target_channels(channels, Some(name))?[0];
+    let overlay = ch
+        .browser_overlays
+        .get(index)
+        .ok_or_else(|| anyhow::anyhow!("Channel '{}' has no browser overlay {}", name, index))?;
+    let page = overlay
+        .page
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| anyhow::anyhow!("Browser overlay {} is not loaded", overlay.url))?;
+    Ok((page, overlay.viewport))
+}
+
+/// The channel's layer with ID `layer`.
+fn channel_layer(ch: &ChannelState, layer: &str) -> Result<LayerId> {
+    layer
+        .parse::<LayerId>()
+        .ok()
+        .filter(|source| ch.layers.iter().any(|l| l.source == *source))
+        .ok_or_else(|| anyhow::anyhow!("Channel '{}' has no layer '{}'", ch.name, layer))
+}
+
+/// A ramp over `duration_ms` with the easing of the channel's transition;
+/// None (snap) without a duration.
+fn ramp(ch: &ChannelState, duration_ms: Option<u64>) -> Option<Transition> {
+    let easing = ch.transition.map(|t| t.easing).unwrap_or_default();
+    duration_ms
+        .filter(|&ms| ms > 0)
+        .map(|ms| Transition::new(Duration::from_millis(ms), easing, Instant::now()))
+}
+
+/// " over 500 ms" for the logs of ramped changes.
+fn over(duration_ms: Option<u64>) -> String {
+    match duration_ms {
+        Some(ms) if ms > 0 => format!(" over {} ms", ms),
+        _ => String::new(),
+    }
+}
+
+fn set_opacity(
+    channels: &[Arc<ChannelState>],
+    name: &str,
+    layer: &str,
+    opacity: f32,
+    duration_ms: Option<u64>,
+) -> Result<()> {
+    if !(0.0..=1.0).contains(&opacity) {
+        anyhow::bail!("opacity must be 0.0–1.0");
+    }
+    let ch = target_channels(channels, Some(name))?[0];
+    let source = channel_layer(ch, layer)?;
+    let ramp = ramp(ch, duration_ms);
+    ch.layer_mix
+        .lock()
+        .unwrap()
+        .set_opacity(source, opacity, ramp);
+    tracing::info!(
+        "Channel '{}': {} opacity {:.2}{}",
+        name,
+        layer,
+        opacity,
+        over(duration_ms)
+    );
+    Ok(())
+}
+
+fn set_placement(
+    channels: &[Arc<ChannelState>],
+    name: &str,
+    layer: &str,
+    placement: Option<Roi>,
+    duration_ms: Option<u64>,
+) -> Result<()> {
+    let ch = target_channels(channels, Some(name))?[0];
+    let source = channel_layer(ch, layer)?;
+    if placement.is_some_and(|p| !p.fits((ch.width, ch.height))) {
+        anyhow::bail!(
+            "placement must be a non-empty area within the {}x{} canvas",
+            ch.width,
+            ch.height
+        );
+    }
+    let ramp = ramp(ch, duration_ms);
+    ch.layer_mix
+        .lock()
+        .unwrap()
+        .set_placement(source, placement.map(|p| p.rect()), ramp);
+    match placement {
+        Some(p) => tracing::info!(
+            "Channel '{}': {} placed at {}x{}+{}+{}{}",
+            name,
+            layer,
+            p.width,
+            p.height,
+            p.x,
+            p.y,
+            over(duration_ms)
+        ),
+        None => tracing::info!("Channel '{}': {} placement reset", name, layer),
+    }
+    Ok(())
+}
+
+fn set_scene(channels: &[Arc<ChannelState>], name: &str, scene: &str) -> Result<()> {
+    let ch = target_channels(channels, Some(name))?[0];
+    let config = ch
+        .scenes
+        .iter()
+        .find(|s| s.name == scene)
+        .ok_or_else(|| anyhow::anyhow!("Channel '{}' has no scene '{}'", name, scene))?;
+    let transition = Transition::start(ch.transition.as_ref(), Instant::now());
+    ch.layer_mix.lock().unwrap().set_scene(config, transition);
+    tracing::info!("Channel '{}': scene '{}'", name, scene);
+    Ok(())
+}
+
+fn set_layer_group(
+    channels: &[Arc<ChannelState>],
+    name: &str,
+    group: &str,
+    opacity: Option<f32>,
+    visible: Option<bool>,
+    duration_ms: Option<u64>,
+) -> Result<()> {
+    if opacity.is_some_and(|o| !(0.0..=1.0).contains(&o)) {
+        anyhow::bail!("opacity must be 0.0–1.0");
+    }
+    let ch = target_channels(channels, Some(name))?[0];
+    let ramp = ramp(ch, duration_ms);
+    let mut mix = ch.layer_mix.lock().unwrap();
+    let layer_group = mix
+        .groups
+        .iter_mut()
+        .find(|g| g.name == group)
+        .ok_or_else(|| anyhow::anyhow!("Channel '{}' has no layer group '{}'", name, group))?;
+    if let Some(opacity) = opacity {
+        layer_group.opacity.retarget(opacity, ramp, Instant::now());
+    }
+    if let Some(visible) = visible {
+        layer_group.visible = visible;
+    }
+    tracing::info!(
+        "Channel '{}': layer group '{}' opacity {:.2}{}{}",
+        name,
+        group,
+        layer_group.opacity.target(),
+        over(duration_ms.filter(|_| opacity.is_some())),
+        if layer_group.visible { "" } else { " (hidden)" }
+    );
+    Ok(())
+}
+
+fn set_audio_level(
+    channels: &[Arc<ChannelState>],
+    name: &str,
+    source: Option<usize>,
+    gain_db: Option<f32>,
+    mute: Option<bool>,
+) -> Result<()> {
+    let ch = target_channels(channels, Some(name))?[0];
+    let mix = ch
+        .audio_mix
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Channel '{}' has no audio mixer", name))?;
+    if gain_db.is_some_and(|db| !GAIN_RANGE.contains(&db)) {
+        anyhow::bail!("gain_db must be -96–24");
+    }
+    let mut levels = mix.levels.lock().unwrap();
+    match source {
+        Some(index) => {
+            let level = levels.sources.get_mut(index).ok_or_else(|| {
+                anyhow::anyhow!("Channel '{}' has no audio source {}", name, index)
+            })?;
+            if let Some(db) = gain_db {
+                level.gain_db = db;
+            }
+            if let Some(mute) = mute {
+                level.mute = mute;
+            }
+            tracing::info!(
+                "Channel '{}': audio source {} at {:+.1} dB{}",
+                name,
+                index,
+                level.gain_db,
+                if level.mute { " (muted)" } else { "" }
+            );
+        }
+        None => {
+            if mute.is_some() {
+                anyhow::bail!("mute applies to a source; give `source`");
+            }
+            if let Some(db) = gain_db {
+                levels.master_db = db;
+            }
+            tracing::info!(
+                "Channel '{}': audio master at {:+.1} dB",
+                name,
+                levels.master_db
+            );
+        }
+    }
+    Ok(())
+}
+
+fn reset_filter_clock(channels: &[Arc<ChannelState>], name: Option<&str>) -> Result<()> {
+    for ch in target_channels(channels, name)? {
+        *ch.filter_clock.lock().unwrap() = Instant::now();
+        tracing::info!("Channel '{}': filter clock reset", ch.name);
+    }
+    Ok(())
+}
+
+fn reset_counters(channels: &[Arc<ChannelState>], name: Option<&str>) -> Result<()> {
+    for ch in target_channels(channels, name)? {
+        *ch.frames_output.lock().unwrap() = 0;
+        if let Some(ref repeated) = ch.frames_repeated {
+            *repeated.lock().unwrap() = 0;
+        }
+        *ch.ndi_frames_received.lock().unwrap() = 0;
+        *ch.ndi_last_error.lock().unwrap() = None;
+        *ch.gpu_fallback.lock().unwrap() = None;
+        if let Some(ref health) = ch.gpu_health {
+            let mut health = health.lock().unwrap();
+            health.fallback_frames = 0;
+            health.reinit_attempts = 0;
+        }
+        ch.overload.lock().unwrap().engaged_count = 0;
+        if let Some(ref audio) = ch.audio_input {
+            *audio.frames_received.lock().unwrap() = 0;
+        }
+        for source in ch.audio_mix.iter().flat_map(|m| &m.sources) {
+            *source.frames_received.lock().unwrap() = 0;
+        }
+        for overlay in &ch.browser_overlays {
+            overlay.capture.lock().unwrap().reset_counters();
+            if let Some(ref budget) = overlay.cpu_budget {
+                budget.lock().unwrap().throttled_count = 0;
+            }
+            *overlay.last_error.lock().unwrap() = None;
+        }
+        if let Some(ref clock) = ch.clock {
+            *clock.last_error.lock().unwrap() = None;
+        }
+        if let Some(ref tap) = ch.cc_passthrough {
+            let mut tap = tap.lock().unwrap();
+            tap.received = 0;
+            tap.forwarded = 0;
+        }
+        *ch.counters_since.lock().unwrap() = Instant::now();
+        tracing::info!("Channel '{}': counters reset", ch.name);
+    }
+    Ok(())
+}
+
+fn set_compare(
+    channels: &[Arc<ChannelState>],
+    name: &str,
+    enabled: bool,
+    split: Option<f32>,
+) -> Result<()> {
+    let ch = target_channels(channels, Some(name))?[0];
+    if ch.gpu_health.is_none() {
+        anyhow::bail!(
+            "Channel '{}' has no GPU compositor, so there are no filters to compare",
+            name
+        );
+    }
+    if let Some(split) = split {
+        if !(0.0..=1.0).contains(&split) {
+            anyhow::bail!("Compare split must be 0.0–1.0");
+        }
+    }
+    let mut compare = ch.compare.lock().unwrap();
+    compare.enabled = enabled;
+    if let Some(split) = split {
+        compare.split = split;
+    }
+    tracing::info!(
+        "Channel '{}': compare view {} (split {:.2})",
+        name,
+        if enabled { "on" } else { "off" },
+        compare.split
+    );
+    Ok(())
+}
+
+/// Apply `action` to the timer of one channel, or of every channel that has one.
+fn control_timer(
+    channels: &[Arc<ChannelState>],
+    name: Option<&str>,
+    verb: &str,
+    action: impl Fn(&mut Timer),
+) -> Result<()> {
+    let mut found = false;
+    for ch in target_channels(channels, name)? {
+        match ch.timer {
+            Some(ref timer) => {
+                action(&mut timer.lock().unwrap());
+                found = true;
+                tracing::info!("Channel '{}': timer {}", ch.name, verb);
+            }
+            None if name.is_some() => anyhow::bail!("Channel '{}' has no timer", ch.name),
+            None => {}
+        }
+    }
+    if !found {
+        anyhow::bail!("No channel has a timer");
+    }
+    Ok(())
+}
+
+/// Queue a command on a channel's render timeline, returning its id. Commands
+/// that don't name a channel act on the scheduling channel only.
+pub fn schedule(
+    channels: &[Arc<ChannelState>],
+    name: &str,
+    frame: Option<u64>,
+    timecode: Option<&str>,
+    command: &Command,
+) -> Result<u64> {
+    let ch = target_channels(channels, Some(name))?[0];
+    if !command.schedulable() {
+        anyhow::bail!("This command can't be scheduled");
+    }
+    if command.channel().is_some_and(|c| c != name) {
+        anyhow::bail!("A scheduled command can only act on the channel it is scheduled on");
+    }
+    let frame = match (frame, timecode) {
+        (Some(frame), None) => frame,
+        (None, Some(timecode)) => parse_timecode(timecode, ch.frame_rate)?,
+        _ => anyhow::bail!("Give either a frame or a timecode"),
+    };
+    let id = ch.schedule.lock().unwrap().add(frame, command.clone())?;
+    tracing::info!(
+        "Channel '{}': scheduled #{} at {} (frame {}): {:?}",
+        name,
+        id,
+        format_timecode(frame, ch.frame_rate),
+        frame,
+        command
+    );
+    Ok(id)
+}
+
+fn unschedule(channels: &[Arc<ChannelState>], name: &str, id: Option<u64>) -> Result<()> {
+    let ch = target_channels(channels, Some(name))?[0];
+    let mut schedule = ch.schedule.lock().unwrap();
+    match id {
+        Some(id) if !schedule.cancel(id) => {
+            anyhow::bail!("Channel '{}' has no scheduled command {}", name, id)
+        }
+        Some(id) => tracing::info!("Channel '{}': scheduled #{} cancelled", name, id),
+        None => {
+            schedule.clear();
+            tracing::info!("Channel '{}': schedule cleared", name);
+        }
+    }
+    Ok(())
+}
+
+fn set_blank(channels: &[Arc<ChannelState>], name: &str, mode: Option<BlankMode>) -> Result<()> {
+    let ch = target_channels(channels, Some(name))?[0];
+    let mut blank = ch.output_blank.lock().unwrap();
+    if *blank != mode {
+        match mode {
+            Some(mode) => {
+                tracing::warn!("Channel '{}': output blanked to {}", ch.name, mode.as_str())
+            }
+            None => tracing::warn!("Channel '{}': output resumed", ch.name),
+        }
+    }
+    *blank = mode;
+    Ok(())
+}
+
+fn set_slate(channels: &[Arc<ChannelState>], name: Option<&str>, on: Option<bool>) -> Result<()> {
+    let targets = target_channels(channels, name)?;
+    let on = on.unwrap_or_else(|| targets.iter().any(|ch| !*ch.slate_active.lock().unwrap()));
+    for ch in targets {
+        let mut active = ch.slate_active.lock().unwrap();
+        if *active != on {
+            tracing::warn!(
+                "Channel '{}': slate {}",
+                ch.name,
+                if on { "ON AIR" } else { "cleared" }
+            );
+        }
+        *active = on;
+    }
+    Ok(())
+}
@@ -0,0 +1,160 @@
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio_util::sync::CancellationToken;
+
+use crate::audit::{AuditEntry, AuditLog};
+use crate::channel::ChannelRegistry;
+use crate::control::{self, Command};
+
+/// Run one JSON command and return the reply, `{"ok": true}` or
+/// `{"ok": false, "error": "..."}`, auditing it as coming in `via`.
+pub async fn run_line(
+    line: &str,
+    channels: &ChannelRegistry,
+    via: &str,
+    client: &str,
+    remote: Option<String>,
+    audit: Option<&AuditLog>,
+) -> serde_json::Value {
+    let result = match serde_json::from_str::<Command>(line) {
+        Ok(command) => control::dispatch(channels, &command).await,
+        Err(e) => Err(anyhow::anyhow!("invalid command: {}", e)),
+    };
+    answer(line, result, via, client, remote, audit)
+}
+
+/// Refuse one JSON command without running it, e.g. over a rate limit, and
+/// return the reply `{"ok": false, "error": error}`; audited like `run_line`.
+pub fn reject_line(
+    line: &str,
+    error: &str,
+    via: &str,
+    client: &str,
+    remote: Option<String>,
+    audit: Option<&AuditLog>,
+) -> serde_json::Value {
+    answer(
+        line,
+        Err(anyhow::anyhow!("{}", error)),
+        via,
+        client,
+        remote,
+        audit,
+    )
+}
+
+/// Audit a command's outcome and turn it into its reply.
+fn answer(
+    line: &str,
+    result: anyhow::Result<()>,
+    via: &str,
+    client: &str,
+    remote: Option<String>,
+    audit: Option<&AuditLog>,
+) -> serde_json::Value {
+    if let Some(audit) = audit {
+        let body: Option<serde_json::Value> = serde_json::from_str(line).ok();
+        audit.record(&AuditEntry {
+            via,
+            client,
+            remote,
+            request: body
+                .as_ref()
+                .and_then(|b| b["action"].as_str())
+                .unwrap_or("invalid")
+                .to_string(),
+            body,
+            ok: result.is_ok(),
+            status: None,
+            error: result.as_ref().err().map(|e| e.to_string()),
+        });
+    }
+    match result {
+        Ok(()) => serde_json::json!({ "ok": true }),
+        Err(e) => serde_json::json!({ "ok": false, "error": e.to_string() }),
+    }
+}
+
+/// Read newline-delimited JSON commands and answer each with one JSON line,
+/// `{"ok": true}` or `{"ok": false, "error": "..."}`. Blank lines are ignored.
+async fn serve_lines<R, W>(
+    reader: R,
+    mut writer: W,
+    channels: &ChannelRegistry,
+    cancel: &CancellationToken,
+    via: &str,
+    audit: Option<&AuditLog>,
+) -> std::io::Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut lines = BufReader::new(reader).lines();
+    loop {
+        let line = tokio::select! {
+            _ = cancel.cancelled() => break,
+            line = lines.next_line() => line?,
+        };
+        let Some(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let reply = run_line(&line, channels, via, "anonymous", None, audit).await;
+        writer.write_all(format!("{}\n", reply).as_bytes()).await?;
+        writer.flush().await?;
+    }
+    Ok(())
+}
+
+/// Accept commands on stdin, replying on stdout, until stdin closes.
+pub async fn serve_stdin(
+    channels: ChannelRegistry,
+    audit: Option<Arc<AuditLog>>,
+    cancel: CancellationToken,
+) {
+    tracing::info!("Control: reading commands from stdin");
+    let (stdin, stdout) = (tokio::io::stdin(), tokio::io::stdout());
+    if let Err(e) = serve_lines(stdin, stdout, &channels, &cancel, "stdin", audit.as_deref()).await
+    {
+        tracing::error!("Control stdin error: {}", e);
+    }
+}
+
+/// Accept commands on a Unix domain socket, one connection per client.
+#[cfg(unix)]
+pub async fn serve_socket(
+    path: &str,
+    channels: ChannelRegistry,
+    audit: Option<Arc<AuditLog>>,
+    cancel: CancellationToken,
+) -> anyhow::Result<()> {
+    // A socket left behind by an earlier run would make bind fail
+    use std::os::unix::fs::FileTypeExt;
+    if std::fs::metadata(path).is_ok_and(|m| m.file_type().is_socket()) {
+        std::fs::remove_file(path)?;
+    }
+    let listener = tokio::net::UnixListener::bind(path)
+        .map_err(|e| anyhow::anyhow!("control socket {}: {}", path, e))?;
+    tracing::info!("Control socket: {}", path);
+
+    loop {
+        let stream = tokio::select! {
+            _ = cancel.cancelled() => break,
+            accepted = listener.accept() => accepted?.0,
+        };
+        let channels = channels.clone();
+        let audit = audit.clone();
+        let cancel = cancel.clone();
+        tokio::spawn(async move {
+            let (reader, writer) = stream.into_split();
+            let audit = audit.as_deref();
+            if let Err(e) = serve_lines(reader, writer, &channels, &cancel, "socket", audit).await {
+                tracing::debug!("Control socket client error: {}", e);
+            }
+        });
+    }
+
+    let _ = std::fs::remove_file(path);
+    Ok(())
+}
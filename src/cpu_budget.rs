@@ -0,0 +1,225 @@
+use chromiumoxide::cdp::browser_protocol::emulation::SetCpuThrottlingRateParams;
+use chromiumoxide::cdp::browser_protocol::page::{
+    SetWebLifecycleStateParams, SetWebLifecycleStateState,
+};
+use chromiumoxide::cdp::browser_protocol::performance::{
+    EnableParams, EnableTimeDomain, GetMetricsParams,
+};
+use chromiumoxide::cdp::browser_protocol::target::TargetId;
+use chromiumoxide::Page;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::config::CpuBudgetConfig;
+
+/// How often page CPU is measured.
+const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+/// Consecutive checks over budget before throttling engages.
+const TRIGGER_CHECKS: u32 = 2;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetState {
+    #[default]
+    Normal,
+    /// Chromium CPU throttling applied
+    Throttled,
+    /// Frozen like a background tab for one check interval
+    Frozen,
+}
+
+/// CPU budget state of one overlay, shared with status reporting.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CpuBudgetStatus {
+    pub max_percent: f32,
+    /// Main-thread CPU of the page over the last check, in percent of one core
+    pub cpu_percent: f32,
+    pub state: BudgetState,
+    /// Number of times throttling has engaged
+    pub throttled_count: u64,
+}
+
+/// Watch the overlay's current tab until cancelled, throttling it while it
+/// exceeds `max_percent`.
+///
+/// Hysteresis: throttling engages after `TRIGGER_CHECKS` consecutive checks
+/// over budget and is lifted once the page has stayed under half the budget
+/// for `recover_seconds`. With `freeze`, a page still over budget while
+/// throttled is frozen for one check interval, then thawed and measured again.
+pub fn spawn(
+    cfg: &CpuBudgetConfig,
+    url: &str,
+    page: Arc<Mutex<Option<Page>>>,
+    status: Arc<Mutex<CpuBudgetStatus>>,
+    cancel: CancellationToken,
+) {
+    let mut governor = Governor {
+        cfg: cfg.clone(),
+        url: url.to_string(),
+        status,
+        target: None,
+        baseline: None,
+        over_checks: 0,
+        under_since: None,
+    };
+    tokio::spawn(async move {
+        let mut timer = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = timer.tick() => {}
+            }
+            // The tab changes on seamless reloads; None until the page loads
+            let current = page.lock().unwrap().clone();
+            if let Some(current) = current {
+                governor.check(&current).await;
+            }
+        }
+    });
+}
+
+struct Governor {
+    cfg: CpuBudgetConfig,
+    url: String,
+    status: Arc<Mutex<CpuBudgetStatus>>,
+    /// Tab being measured
+    target: Option<TargetId>,
+    /// Main-thread task time (seconds) at the previous check
+    baseline: Option<(f64, Instant)>,
+    over_checks: u32,
+    under_since: Option<Instant>,
+}
+
+impl Governor {
+    fn state(&self) -> BudgetState {
+        self.status.lock().unwrap().state
+    }
+
+    fn set_state(&self, state: BudgetState) {
+        self.status.lock().unwrap().state = state;
+    }
+
+    async fn check(&mut self, page: &Page) {
+        if self.target.as_ref() != Some(page.target_id()) {
+            self.attach(page).await;
+            return;
+        }
+
+        // A frozen page has only been held for this interval: thaw it and measure afresh
+        if self.state() == BudgetState::Frozen {
+            let _ = page
+                .execute(SetWebLifecycleStateParams::new(
+                    SetWebLifecycleStateState::Active,
+                ))
+                .await;
+            self.set_state(BudgetState::Throttled);
+            self.baseline = task_seconds(page).await.map(|t| (t, Instant::now()));
+            return;
+        }
+
+        let Some(task) = task_seconds(page).await else {
+            return;
+        };
+        let now = Instant::now();
+        let Some((last_task, last_at)) = self.baseline.replace((task, now)) else {
+            return;
+        };
+        let wall = now.duration_since(last_at).as_secs_f64();
+        let percent = ((task - last_task).max(0.0) / wall.max(0.001) * 100.0) as f32;
+        self.status.lock().unwrap().cpu_percent = (percent * 10.0).round() / 10.0;
+
+        let max = self.cfg.max_percent;
+        match self.state() {
+            BudgetState::Normal => {
+                self.over_checks = if percent > max {
+                    self.over_checks + 1
+                } else {
+                    0
+                };
+                if self.over_checks >= TRIGGER_CHECKS {
+                    self.over_checks = 0;
+                    tracing::warn!(
+                        "Browser overlay {}: using {:.0}% CPU (budget {:.0}%), throttling {}x",
+                        self.url,
+                        percent,
+                        max,
+                        self.cfg.throttle_rate
+                    );
+                    self.throttle(page, self.cfg.throttle_rate).await;
+                    let mut status = self.status.lock().unwrap();
+                    status.state = BudgetState::Throttled;
+                    status.throttled_count += 1;
+                }
+            }
+            BudgetState::Throttled if percent > max => {
+                self.under_since = None;
+                if self.cfg.freeze {
+                    tracing::debug!(
+                        "Browser overlay {}: {:.0}% CPU while throttled, freezing",
+                        self.url,
+                        percent
+                    );
+                    let _ = page
+                        .execute(SetWebLifecycleStateParams::new(
+                            SetWebLifecycleStateState::Frozen,
+                        ))
+                        .await;
+                    self.set_state(BudgetState::Frozen);
+                }
+            }
+            BudgetState::Throttled if percent < max / 2.0 => {
+                let since = *self.under_since.get_or_insert(now);
+                if since.elapsed() >= Duration::from_secs(self.cfg.recover_seconds) {
+                    self.under_since = None;
+                    tracing::info!(
+                        "Browser overlay {}: back under its CPU budget, throttling lifted",
+                        self.url
+                    );
+                    self.throttle(page, 1.0).await;
+                    self.set_state(BudgetState::Normal);
+                }
+            }
+            BudgetState::Throttled => self.under_since = None,
+            BudgetState::Frozen => {}
+        }
+    }
+
+    /// Start measuring a new tab, carrying over throttling that was in effect.
+    async fn attach(&mut self, page: &Page) {
+        // Thread time, so throttled (suspended) time isn't counted as CPU
+        let enable = EnableParams::builder()
+            .time_domain(EnableTimeDomain::ThreadTicks)
+            .build();
+        if let Err(e) = page.execute(enable).await {
+            tracing::debug!("Browser overlay {}: can't measure CPU: {}", self.url, e);
+            return;
+        }
+        self.target = Some(page.target_id().clone());
+        self.baseline = task_seconds(page).await.map(|t| (t, Instant::now()));
+        self.over_checks = 0;
+        self.under_since = None;
+        if self.state() != BudgetState::Normal {
+            self.throttle(page, self.cfg.throttle_rate).await;
+            self.set_state(BudgetState::Throttled);
+        }
+    }
+
+    async fn throttle(&self, page: &Page, rate: f64) {
+        if let Err(e) = page.execute(SetCpuThrottlingRateParams::new(rate)).await {
+            tracing::warn!("Browser overlay {}: CPU throttling failed: {}", self.url, e);
+        }
+    }
+}
+
+/// Total main-thread task time of the page so far, in seconds.
+async fn task_seconds(page: &Page) -> Option<f64> {
+    let metrics = page.execute(GetMetricsParams::default()).await.ok()?;
+    metrics
+        .result
+        .metrics
+        .iter()
+        .find(|m| m.name == "TaskDuration")
+        .map(|m| m.value)
+}
@@ -0,0 +1,88 @@
+use anyhow::Result;
+use chromiumoxide::Page;
+use serde_json::Value;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+
+use crate::config::DataBindingConfig;
+
+/// Longest a data URL may take to answer before the poll counts as failed.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Poll the binding's file or URL until `cancel`, publishing each value that
+/// differs from the last.
+///
+/// Failures are logged once until the source reads again; the page keeps the
+/// last good value meanwhile.
+pub fn spawn(cfg: &DataBindingConfig, cancel: CancellationToken) -> watch::Receiver<Option<Value>> {
+    let (tx, rx) = watch::channel(None);
+    let cfg = cfg.clone();
+    tokio::spawn(async move {
+        let client = reqwest::Client::new();
+        let mut timer = tokio::time::interval(Duration::from_millis(cfg.interval_ms));
+        timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut failing = false;
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = timer.tick() => {}
+            }
+            match read(&client, &cfg).await {
+                Ok(value) => {
+                    if failing {
+                        tracing::info!("Overlay data {}: reading again", cfg.source());
+                        failing = false;
+                    }
+                    tx.send_if_modified(|current| {
+                        if current.as_ref() == Some(&value) {
+                            return false;
+                        }
+                        *current = Some(value);
+                        true
+                    });
+                }
+                Err(e) => {
+                    if !failing {
+                        tracing::warn!("Overlay data {}: {}", cfg.source(), e);
+                        failing = true;
+                    }
+                }
+            }
+        }
+    });
+    rx
+}
+
+async fn read(client: &reqwest::Client, cfg: &DataBindingConfig) -> Result<Value> {
+    if let Some(ref path) = cfg.file {
+        return Ok(serde_json::from_str(
+            &tokio::fs::read_to_string(path).await?,
+        )?);
+    }
+    let Some(ref url) = cfg.url else {
+        anyhow::bail!("no file or url");
+    };
+    let response = client.get(url).timeout(FETCH_TIMEOUT).send().await?;
+    if !response.status().is_success() {
+        anyhow::bail!("{} returned {}", url, response.status());
+    }
+    Ok(response.json().await?)
+}
+
+/// Hand `data` to the page: set it as `window[global]`, then post it to the
+/// window as `{ type: "ndimixer:data", data }` for pages listening for updates.
+pub async fn inject(page: &Page, global: &str, data: &Value) {
+    let js = format!(
+        r#"
+            (() => {{
+                const data = {data};
+                window[{global}] = data;
+                window.postMessage({{ type: 'ndimixer:data', data }}, '*');
+            }})();
+            "#,
+        data = data,
+        global = serde_json::to_string(global).unwrap_or_default()
+    );
+    let _ = page.evaluate(js).await;
+}
@@ -0,0 +1,58 @@
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Failure classes reported in `/status`, stable for monitoring to alert on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// The NDI source didn't appear within `source_timeout`
+    NdiSourceNotFound,
+    /// A connected NDI source stopped sending video
+    NdiSourceLost,
+    /// The NDI receiver reported an error
+    NdiReceiveFailed,
+    /// The NDI input thread gave up (receiver creation or search failed)
+    NdiInputFailed,
+    /// An overlay page failed to load, after retries
+    BrowserLoadFailed,
+    /// Capturing an overlay page failed and capture stopped
+    BrowserCaptureFailed,
+    /// No GPU compositor for a channel with shader filters; filters are skipped
+    GpuUnavailable,
+    /// A GPU composite couldn't be read back; the frame was composited on the CPU
+    #[cfg_attr(not(feature = "gpu"), allow(dead_code))]
+    GpuReadbackFailed,
+    /// A clock layer's NTP query failed; the previous offset stays in use
+    NtpSyncFailed,
+}
+
+/// The most recent error of one component.
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorReport {
+    pub code: ErrorCode,
+    pub message: String,
+    /// Unix time of the latest occurrence, in seconds
+    pub timestamp: u64,
+    /// Errors of this component since startup or the last counter reset
+    pub count: u64,
+}
+
+/// A component's last error, shared with status reporting.
+pub type ErrorSlot = Arc<Mutex<Option<ErrorReport>>>;
+
+/// Record an error in a component's slot, replacing the previous one.
+pub fn record(slot: &Mutex<Option<ErrorReport>>, code: ErrorCode, message: impl ToString) {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut slot = slot.lock().unwrap();
+    let count = slot.as_ref().map_or(0, |r| r.count) + 1;
+    *slot = Some(ErrorReport {
+        code,
+        message: message.to_string(),
+        timestamp,
+        count,
+    });
+}
@@ -0,0 +1,212 @@
+use axum::response::sse::Event;
+use futures::{Stream, StreamExt};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::channel::{AudioInputState, BlankMode, ChannelRegistry, ChannelState};
+
+/// What `/events` tracks of a channel between polls.
+#[derive(Clone, PartialEq)]
+struct ChannelSnapshot {
+    ndi_state: Option<&'static str>,
+    /// The NDI input's format, e.g. `1920x1080 59.94p`
+    ndi_format: Option<String>,
+    /// State of the audio source, then of each mixed audio source
+    audio: Vec<&'static str>,
+    /// Per overlay: loaded, error
+    overlays: Vec<(bool, Option<String>)>,
+    slate: bool,
+    blank: Option<BlankMode>,
+    warming_up: bool,
+}
+
+impl ChannelSnapshot {
+    fn of(ch: &ChannelState) -> Self {
+        Self {
+            ndi_state: ch
+                .ndi_source
+                .as_ref()
+                .map(|_| ch.ndi_state.lock().unwrap().as_str()),
+            ndi_format: ch.ndi_format.lock().unwrap().as_ref().map(|f| f.summary()),
+            audio: audio_inputs(ch)
+                .map(|a| a.state.lock().unwrap().as_str())
+                .collect(),
+            overlays: ch
+                .browser_overlays
+                .iter()
+                .map(|b| (*b.loaded.lock().unwrap(), b.error.lock().unwrap().clone()))
+                .collect(),
+            slate: *ch.slate_active.lock().unwrap(),
+            blank: *ch.output_blank.lock().unwrap(),
+            warming_up: *ch.warming_up.lock().unwrap(),
+        }
+    }
+}
+
+/// The channel's audio source, then its mixed audio sources.
+fn audio_inputs(ch: &ChannelState) -> impl Iterator<Item = &AudioInputState> {
+    ch.audio_input
+        .iter()
+        .chain(ch.audio_mix.iter().flat_map(|m| &m.sources))
+}
+
+#[derive(Serialize)]
+struct FrameCounters {
+    name: String,
+    frames_output: u64,
+    output_fps: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ndi_frames_received: Option<u64>,
+}
+
+/// A state change, sent as an SSE event on `/events` and a message on `/ws`.
+pub struct StatusEvent {
+    pub name: &'static str,
+    pub data: serde_json::Value,
+}
+
+fn event(name: &'static str, data: serde_json::Value) -> StatusEvent {
+    StatusEvent { name, data }
+}
+
+/// What a client has been told about each channel so far.
+#[derive(Default)]
+pub struct Tracker {
+    previous: HashMap<String, ChannelSnapshot>,
+}
+
+impl Tracker {
+    /// Events for what changed since the last poll. Channels not seen before
+    /// are reported in full, so the first poll sends the current state.
+    pub fn poll(&mut self, channels: &[Arc<ChannelState>]) -> Vec<StatusEvent> {
+        changes(channels, &mut self.previous)
+    }
+}
+
+fn changes(
+    channels: &[Arc<ChannelState>],
+    previous: &mut HashMap<String, ChannelSnapshot>,
+) -> Vec<StatusEvent> {
+    let mut events = Vec::new();
+
+    previous.retain(|name, _| {
+        let running = channels.iter().any(|ch| ch.name == *name);
+        if !running {
+            events.push(event(
+                "channel",
+                serde_json::json!({ "channel": name, "running": false }),
+            ));
+        }
+        running
+    });
+
+    for ch in channels {
+        let now = ChannelSnapshot::of(ch);
+        let before = previous.get(&ch.name);
+        if before.is_none() {
+            events.push(event(
+                "channel",
+                serde_json::json!({ "channel": ch.name, "running": true }),
+            ));
+        }
+        if before.map(|b| b.ndi_state) != Some(now.ndi_state) {
+            if let Some(state) = now.ndi_state {
+                events.push(event(
+                    "ndi",
+                    serde_json::json!({ "channel": ch.name, "state": state }),
+                ));
+            }
+        }
+        if before.map(|b| &b.ndi_format) != Some(&now.ndi_format) {
+            if let Some(ref format) = now.ndi_format {
+                events.push(event(
+                    "ndi_format",
+                    serde_json::json!({ "channel": ch.name, "format": format }),
+                ));
+            }
+        }
+        for ((state, input), i) in now.audio.iter().zip(audio_inputs(ch)).zip(0..) {
+            if before.and_then(|b| b.audio.get(i)) != Some(state) {
+                events.push(event(
+                    "audio",
+                    serde_json::json!({
+                        "channel": ch.name,
+                        "source": input.source,
+                        "state": state,
+                    }),
+                ));
+            }
+        }
+        for (i, overlay) in now.overlays.iter().enumerate() {
+            if before.and_then(|b| b.overlays.get(i)) != Some(overlay) {
+                events.push(event(
+                    "overlay",
+                    serde_json::json!({
+                        "channel": ch.name,
+                        "index": i,
+                        "url": ch.browser_overlays[i].url,
+                        "loaded": overlay.0,
+                        "error": overlay.1,
+                    }),
+                ));
+            }
+        }
+        if before.map(|b| b.slate) != Some(now.slate) {
+            events.push(event(
+                "slate",
+                serde_json::json!({ "channel": ch.name, "on": now.slate }),
+            ));
+        }
+        if before.map(|b| b.blank) != Some(now.blank) {
+            events.push(event(
+                "blank",
+                serde_json::json!({ "channel": ch.name, "mode": now.blank }),
+            ));
+        }
+        if before.map(|b| b.warming_up) != Some(now.warming_up) {
+            events.push(event(
+                "warmup",
+                serde_json::json!({ "channel": ch.name, "warming_up": now.warming_up }),
+            ));
+        }
+        previous.insert(ch.name.clone(), now);
+    }
+
+    // Counters move every frame, so they go out as a snapshot on every poll
+    let counters: Vec<FrameCounters> = channels
+        .iter()
+        .map(|ch| FrameCounters {
+            name: ch.name.clone(),
+            frames_output: *ch.frames_output.lock().unwrap(),
+            output_fps: ch.frame_rates.lock().unwrap().output,
+            ndi_frames_received: ch
+                .ndi_source
+                .as_ref()
+                .map(|_| *ch.ndi_frames_received.lock().unwrap()),
+        })
+        .collect();
+    events.push(event("frames", serde_json::json!({ "channels": counters })));
+    events
+}
+
+/// Poll the channels every `interval` and turn what changed into events.
+pub fn stream(
+    channels: ChannelRegistry,
+    interval: Duration,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    let mut timer = tokio::time::interval(interval);
+    timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let state = (channels, timer, Tracker::default());
+    futures::stream::unfold(state, |(channels, mut timer, mut tracker)| async move {
+        timer.tick().await;
+        let events = tracker
+            .poll(&channels.snapshot())
+            .into_iter()
+            .map(|e| Ok(Event::default().event(e.name).data(e.data.to_string())));
+        Some((futures::stream::iter(events), (channels, timer, tracker)))
+    })
+    .flatten()
+}
@@ -1,11 +1,16 @@
 use image::RgbaImage;
-use std::sync::Arc;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use wgpu::util::DeviceExt;
 
-use crate::compositor::{Layer, LayerSource};
-use crate::config::FilterConfig;
-use crate::gpu_context::GpuContext;
+use crate::color::UyvyFrame;
+use crate::compositor::{Layer, LayerImage, SharedLayerKey, Transform};
+use crate::config::{ColorPrimaries, ColorRange, FilterConfig};
+use crate::gpu_context::{GpuContext, SharedTexture};
+use crate::source::LayerId;
 
 /// Uniform buffer matching the WGSL Params struct (16-byte aligned).
 #[repr(C)]
@@ -28,17 +33,153 @@ struct FilterUniforms {
     params: [f32; 16],
 }
 
+/// Uniform buffer matching the WGSL Params struct in uyvy.wgsl.
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct UyvyParams {
+    out_width: u32,
+    out_height: u32,
+    src_width: u32,
+    src_height: u32,
+    roi_x: u32,
+    roi_y: u32,
+    roi_w: u32,
+    roi_h: u32,
+    dst_x: u32,
+    dst_y: u32,
+    dst_w: u32,
+    dst_h: u32,
+    kr: f32,
+    kb: f32,
+    expand_range: u32,
+    bt2020_gamut: u32,
+}
+
 struct CachedTexture {
     texture: wgpu::Texture,
     view: wgpu::TextureView,
     width: u32,
     height: u32,
+    /// Layer, generation and transform currently held, when it can be reused as is
+    content: Option<(LayerId, u64, Transform)>,
+}
+
+struct UyvySource {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    width: u32,
+    height: u32,
+    /// Conversion parameters, rewritten for each frame
+    params: wgpu::Buffer,
+    /// Bind group for the target last converted into
+    bind_group: Option<(wgpu::TextureView, wgpu::BindGroup)>,
 }
 
 struct CompiledFilter {
-    pipeline: wgpu::ComputePipeline,
+    /// Position in the configured chain (filters that failed to compile are skipped)
+    index: usize,
+    /// At least one; all but the last write to their own (possibly downsampled) target
+    passes: Vec<CompiledPass>,
     packed_params: [f32; 16],
     param_count: f32,
+    time_scale: f32,
+    paused: bool,
+    /// Custom textures (bind group 1), when the filter declares any
+    textures: Option<FilterTextures>,
+}
+
+struct CompiledPass {
+    pipeline: wgpu::ComputePipeline,
+    /// Intermediate output; None for the last pass
+    target: Option<PassTarget>,
+}
+
+/// Intermediate pass output (the view keeps its texture alive).
+struct PassTarget {
+    view: wgpu::TextureView,
+    width: u32,
+    height: u32,
+}
+
+struct FilterTextures {
+    bind_group: wgpu::BindGroup,
+    /// Kept alive for the bind group; counted in memory usage
+    textures: Vec<wgpu::Texture>,
+}
+
+/// Load a filter's custom textures and bind them with the shared sampler.
+fn load_filter_textures(
+    ctx: &GpuContext,
+    paths: &[String],
+    layout: &wgpu::BindGroupLayout,
+) -> anyhow::Result<FilterTextures> {
+    let mut textures = Vec::with_capacity(paths.len());
+    for path in paths {
+        let image = image::open(path)
+            .map_err(|e| anyhow::anyhow!("Failed to load filter texture '{}': {}", path, e))?
+            .to_rgba8();
+        let (width, height) = image.dimensions();
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(path),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            // Unorm, not sRGB: LUTs and masks hold data, not display colors
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        ctx.queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            image.as_raw(),
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(width * 4),
+                rows_per_image: Some(height),
+            },
+            size,
+        );
+        textures.push(texture);
+    }
+
+    let views: Vec<wgpu::TextureView> = textures
+        .iter()
+        .map(|t| t.create_view(&Default::default()))
+        .collect();
+    let mut entries = vec![wgpu::BindGroupEntry {
+        binding: 0,
+        resource: wgpu::BindingResource::Sampler(&ctx.filter_sampler),
+    }];
+    entries.extend(
+        views
+            .iter()
+            .enumerate()
+            .map(|(i, view)| wgpu::BindGroupEntry {
+                binding: i as u32 + 1,
+                resource: wgpu::BindingResource::TextureView(view),
+            }),
+    );
+    let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("filter_textures"),
+        layout,
+        entries: &entries,
+    });
+
+    Ok(FilterTextures {
+        bind_group,
+        textures,
+    })
 }
 
 /// Per-channel GPU compositor. Owns ping-pong textures, staging buffer,
@@ -51,6 +192,8 @@ pub struct GpuCompositor {
     pong_view: wgpu::TextureView,
     staging: wgpu::Buffer,
     layer_cache: Vec<Option<CachedTexture>>,
+    /// Packed UYVY sources (half width) per layer slot, present once a UYVY frame was uploaded
+    uyvy_sources: Vec<Option<UyvySource>>,
     width: u32,
     height: u32,
     padded_row: u32,
@@ -62,44 +205,139 @@ pub struct GpuCompositor {
     ndi_filters: Vec<CompiledFilter>,
     browser_filters: Vec<Vec<CompiledFilter>>,
     channel_filters: Vec<CompiledFilter>,
-    start_time: Instant,
+    filters_enabled: bool,
+    /// Zero point of the filter `time` uniform, shared with the channel so it can be reset
+    filter_clock: Arc<Mutex<Instant>>,
+}
+
+/// Pack params alphabetically into the uniform array.
+fn pack_params(params: &HashMap<String, f32>) -> [f32; 16] {
+    let mut packed = [0.0f32; 16];
+    let mut keys: Vec<&String> = params.keys().collect();
+    keys.sort();
+    for (j, key) in keys.iter().enumerate().take(16) {
+        packed[j] = params[*key];
+    }
+    packed
+}
+
+/// Take new params for compiled filters from the configs they were compiled from.
+fn update_params(compiled: &mut [CompiledFilter], configs: &[FilterConfig]) {
+    for filter in compiled {
+        if let Some(cfg) = configs.get(filter.index) {
+            filter.packed_params = pack_params(&cfg.params);
+            filter.param_count = cfg.params.len() as f32;
+        }
+    }
 }
 
 fn compile_filters(
     ctx: &GpuContext,
     configs: &[FilterConfig],
     label_prefix: &str,
+    width: u32,
+    height: u32,
 ) -> Vec<CompiledFilter> {
     let mut compiled = Vec::new();
-    for (i, cfg) in configs.iter().enumerate() {
-        let label = format!("{}_filter_{}", label_prefix, i);
-        let source = match std::fs::read_to_string(&cfg.shader) {
-            Ok(s) => s,
-            Err(e) => {
-                tracing::error!("Failed to read filter shader '{}': {}", cfg.shader, e);
-                continue;
-            }
+    'filters: for (i, cfg) in configs.iter().enumerate() {
+        let texture_layout =
+            (!cfg.textures.is_empty()).then(|| ctx.filter_texture_layout(cfg.textures.len()));
+        let textures = match texture_layout {
+            Some(ref layout) => match load_filter_textures(ctx, &cfg.textures, layout) {
+                Ok(textures) => Some(textures),
+                Err(e) => {
+                    tracing::error!("Skipping filter '{}': {}", cfg.shader, e);
+                    continue;
+                }
+            },
+            None => None,
+        };
+
+        // (shader, entry point, scale) per pass; a plain filter is one full-size pass
+        let pass_configs: Vec<(&str, &str, f32)> = if cfg.passes.is_empty() {
+            vec![(cfg.shader.as_str(), "main", 1.0)]
+        } else {
+            cfg.passes
+                .iter()
+                .map(|p| {
+                    (
+                        p.shader.as_deref().unwrap_or(&cfg.shader),
+                        p.entry_point.as_str(),
+                        p.scale,
+                    )
+                })
+                .collect()
         };
-        match ctx.compile_filter_pipeline(&label, &source) {
-            Ok(pipeline) => {
-                // Pack params alphabetically into array
-                let mut packed_params = [0.0f32; 16];
-                let mut keys: Vec<&String> = cfg.params.keys().collect();
-                keys.sort();
-                for (j, key) in keys.iter().enumerate().take(16) {
-                    packed_params[j] = cfg.params[*key];
+
+        let mut passes = Vec::with_capacity(pass_configs.len());
+        for (p, &(shader, entry_point, scale)) in pass_configs.iter().enumerate() {
+            let label = format!("{}_filter_{}_pass_{}", label_prefix, i, p);
+            let source = match cfg.builtin {
+                Some(source) => Ok(source.to_string()),
+                None => std::fs::read_to_string(shader),
+            };
+            let source = match source {
+                Ok(s) => s,
+                Err(e) => {
+                    tracing::error!("Failed to read filter shader '{}': {}", shader, e);
+                    continue 'filters;
                 }
-                compiled.push(CompiledFilter {
-                    pipeline,
-                    packed_params,
-                    param_count: cfg.params.len() as f32,
+            };
+            let pipeline = match ctx.compile_filter_pipeline(
+                &label,
+                &source,
+                entry_point,
+                texture_layout.as_ref(),
+            ) {
+                Ok(pipeline) => pipeline,
+                Err(e) => {
+                    tracing::error!("Failed to compile filter shader '{}': {}", shader, e);
+                    continue 'filters;
+                }
+            };
+            // The last pass writes straight into the filter chain's ping-pong texture
+            let target = (p + 1 < pass_configs.len()).then(|| {
+                let pass_width = ((width as f32 * scale).round() as u32).max(1);
+                let pass_height = ((height as f32 * scale).round() as u32).max(1);
+                let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+                    label: Some(&label),
+                    size: wgpu::Extent3d {
+                        width: pass_width,
+                        height: pass_height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: wgpu::TextureFormat::Rgba8Unorm,
+                    usage: wgpu::TextureUsages::TEXTURE_BINDING
+                        | wgpu::TextureUsages::STORAGE_BINDING,
+                    view_formats: &[],
                 });
-                tracing::info!("Compiled filter shader: {}", cfg.shader);
-            }
-            Err(e) => {
-                tracing::error!("Failed to compile filter shader '{}': {}", cfg.shader, e);
-            }
+                PassTarget {
+                    view: texture.create_view(&Default::default()),
+                    width: pass_width,
+                    height: pass_height,
+                }
+            });
+            passes.push(CompiledPass { pipeline, target });
         }
+
+        compiled.push(CompiledFilter {
+            index: i,
+            passes,
+            packed_params: pack_params(&cfg.params),
+            param_count: cfg.params.len() as f32,
+            time_scale: cfg.time_scale,
+            paused: cfg.paused,
+            textures,
+        });
+        tracing::info!(
+            "Compiled filter shader: {} ({} pass{})",
+            cfg.shader,
+            pass_configs.len(),
+            if pass_configs.len() == 1 { "" } else { "es" }
+        );
     }
     compiled
 }
@@ -112,6 +350,7 @@ impl GpuCompositor {
         ndi_filter_configs: &[FilterConfig],
         browser_filter_configs: &[Vec<FilterConfig>],
         channel_filter_configs: &[FilterConfig],
+        filter_clock: Arc<Mutex<Instant>>,
     ) -> Self {
         let device = &ctx.device;
 
@@ -156,13 +395,14 @@ impl GpuCompositor {
         });
 
         // Compile filter shaders
-        let ndi_filters = compile_filters(&ctx, ndi_filter_configs, "ndi");
+        let ndi_filters = compile_filters(&ctx, ndi_filter_configs, "ndi", width, height);
         let browser_filters: Vec<Vec<CompiledFilter>> = browser_filter_configs
             .iter()
             .enumerate()
-            .map(|(i, cfgs)| compile_filters(&ctx, cfgs, &format!("browser_{}", i)))
+            .map(|(i, cfgs)| compile_filters(&ctx, cfgs, &format!("browser_{}", i), width, height))
             .collect();
-        let channel_filters = compile_filters(&ctx, channel_filter_configs, "channel");
+        let channel_filters =
+            compile_filters(&ctx, channel_filter_configs, "channel", width, height);
 
         // Allocate filter ping-pong textures only if any filters exist
         let has_filters = !ndi_filters.is_empty()
@@ -193,6 +433,7 @@ impl GpuCompositor {
             pong_view,
             staging,
             layer_cache: Vec::new(),
+            uyvy_sources: Vec::new(),
             width,
             height,
             padded_row,
@@ -203,12 +444,68 @@ impl GpuCompositor {
             ndi_filters,
             browser_filters,
             channel_filters,
-            start_time: Instant::now(),
+            filters_enabled: true,
+            filter_clock,
+        }
+    }
+
+    /// Approximate GPU memory held by this compositor's textures and buffers.
+    pub fn memory_bytes(&self) -> u64 {
+        let frame = self.width as u64 * self.height as u64 * 4;
+        let filter_textures = if self.filter_a.is_some() { 2 } else { 0 };
+        let layer_textures = self.layer_cache.iter().flatten().count() as u64;
+        let uyvy_bytes: u64 = self
+            .uyvy_sources
+            .iter()
+            .flatten()
+            .map(|u| u.width as u64 * u.height as u64 * 4)
+            .sum();
+        let all_filters = || {
+            self.ndi_filters
+                .iter()
+                .chain(self.browser_filters.iter().flatten())
+                .chain(self.channel_filters.iter())
+        };
+        let custom_bytes: u64 = all_filters()
+            .filter_map(|f| f.textures.as_ref())
+            .flat_map(|t| t.textures.iter())
+            .map(|t| t.width() as u64 * t.height() as u64 * 4)
+            .sum();
+        let pass_bytes: u64 = all_filters()
+            .flat_map(|f| f.passes.iter())
+            .filter_map(|p| p.target.as_ref())
+            .map(|t| t.width as u64 * t.height as u64 * 4)
+            .sum();
+        frame * (2 + filter_textures + layer_textures)
+            + uyvy_bytes
+            + custom_bytes
+            + pass_bytes
+            + self.staging.size()
+    }
+
+    /// Update filter params in place, from filter chains shaped like the ones
+    /// the compositor was built with.
+    pub fn set_params(
+        &mut self,
+        ndi: &[FilterConfig],
+        browser: &[Vec<FilterConfig>],
+        channel: &[FilterConfig],
+    ) {
+        update_params(&mut self.ndi_filters, ndi);
+        for (compiled, configs) in self.browser_filters.iter_mut().zip(browser) {
+            update_params(compiled, configs);
         }
+        update_params(&mut self.channel_filters, channel);
+    }
+
+    /// Enable or bypass all filter chains (used by the overload policy).
+    pub fn set_filters_enabled(&mut self, enabled: bool) {
+        self.filters_enabled = enabled;
     }
 
     /// Apply a chain of filters to a source texture using filter_a/filter_b ping-pong.
     /// The source is first copied into filter_a, then filters alternate between a→b and b→a.
+    /// Multi-pass filters run their intermediate passes through their own targets.
     /// Returns whether filter_a holds the result (true) or filter_b (false).
     fn apply_filters(
         &self,
@@ -222,7 +519,7 @@ impl GpuCompositor {
         let fb_view = self.filter_b_view.as_ref().unwrap();
         let fa_tex = self.filter_a.as_ref().unwrap();
 
-        let time = self.start_time.elapsed().as_secs_f32();
+        let elapsed = self.filter_clock.lock().unwrap().elapsed().as_secs_f32();
 
         // Copy source → filter_a
         encoder.copy_texture_to_texture(
@@ -248,56 +545,84 @@ impl GpuCompositor {
         let mut a_is_input = true;
 
         for filter in filters {
-            let uniforms = FilterUniforms {
-                time,
-                width: self.width as f32,
-                height: self.height as f32,
-                param_count: filter.param_count,
-                params: filter.packed_params,
+            let time = if filter.paused {
+                0.0
+            } else {
+                elapsed * filter.time_scale
             };
-
-            let uniform_buf =
-                self.ctx
-                    .device
-                    .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                        label: None,
-                        contents: bytemuck::bytes_of(&uniforms),
-                        usage: wgpu::BufferUsages::UNIFORM,
-                    });
-
             let (input_view, output_view) = if a_is_input {
                 (fa_view, fb_view)
             } else {
                 (fb_view, fa_view)
             };
 
-            let bg = self.ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: None,
-                layout: &self.ctx.filter_layout,
-                entries: &[
-                    wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: wgpu::BindingResource::TextureView(input_view),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 1,
-                        resource: wgpu::BindingResource::TextureView(output_view),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 2,
-                        resource: uniform_buf.as_entire_binding(),
-                    },
-                ],
-            });
+            // Each pass reads the previous one's output; the last writes the chain target
+            let mut pass_input = input_view;
+            for filter_pass in &filter.passes {
+                let (pass_output, width, height, groups_x, groups_y) = match filter_pass.target {
+                    Some(ref t) => (
+                        &t.view,
+                        t.width,
+                        t.height,
+                        t.width.div_ceil(16),
+                        t.height.div_ceil(16),
+                    ),
+                    None => (output_view, self.width, self.height, dispatch_x, dispatch_y),
+                };
+
+                let uniforms = FilterUniforms {
+                    time,
+                    width: width as f32,
+                    height: height as f32,
+                    param_count: filter.param_count,
+                    params: filter.packed_params,
+                };
+
+                let uniform_buf =
+                    self.ctx
+                        .device
+                        .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                            label: None,
+                            contents: bytemuck::bytes_of(&uniforms),
+                            usage: wgpu::BufferUsages::UNIFORM,
+                        });
+
+                let bg = self
+                    .ctx
+                    .device
+                    .create_bind_group(&wgpu::BindGroupDescriptor {
+                        label: None,
+                        layout: &self.ctx.filter_layout,
+                        entries: &[
+                            wgpu::BindGroupEntry {
+                                binding: 0,
+                                resource: wgpu::BindingResource::TextureView(pass_input),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 1,
+                                resource: wgpu::BindingResource::TextureView(pass_output),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 2,
+                                resource: uniform_buf.as_entire_binding(),
+                            },
+                        ],
+                    });
 
-            {
-                let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
-                    label: None,
-                    timestamp_writes: None,
-                });
-                pass.set_pipeline(&filter.pipeline);
-                pass.set_bind_group(0, &bg, &[]);
-                pass.dispatch_workgroups(dispatch_x, dispatch_y, 1);
+                {
+                    let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                        label: None,
+                        timestamp_writes: None,
+                    });
+                    pass.set_pipeline(&filter_pass.pipeline);
+                    pass.set_bind_group(0, &bg, &[]);
+                    if let Some(ref textures) = filter.textures {
+                        pass.set_bind_group(1, &textures.bind_group, &[]);
+                    }
+                    pass.dispatch_workgroups(groups_x, groups_y, 1);
+                }
+
+                pass_input = pass_output;
             }
 
             a_is_input = !a_is_input;
@@ -312,16 +637,29 @@ impl GpuCompositor {
     /// Composite layers onto canvas using GPU compute shaders.
     /// Returns true on success. On failure, caller should fall back to CPU.
     pub fn composite(&mut self, canvas: &mut RgbaImage, layers: &mut [Layer<'_>]) -> bool {
-        layers.sort_by_key(|l| l.z_index);
+        crate::compositor::sort_layers(layers);
 
         let dispatch_x = (self.width + 15) / 16;
         let dispatch_y = (self.height + 15) / 16;
 
-        // Upload all layer textures first (needs &mut self)
+        // Upload all layer textures first (needs &mut self). Unfiltered layers that
+        // other channels may also show come from the context-wide shared cache.
+        let mut shared: Vec<Option<Arc<SharedTexture>>> = Vec::with_capacity(layers.len());
         for (i, layer) in layers.iter().enumerate() {
-            if layer.opacity > 0.0 {
-                self.upload_layer(i, layer.image);
-            }
+            let unfiltered = self.layer_filters(layer.source).is_empty();
+            let texture = match layer.shared_key.filter(|_| unfiltered) {
+                _ if layer.opacity <= 0.0 => None,
+                Some(key) => Some(self.upload_shared(i, key, layer.image, layer.transform)),
+                None => {
+                    // Filters write back into the cached texture, so only unfiltered
+                    // layers can keep last frame's upload
+                    let content =
+                        unfiltered.then_some((layer.source, layer.generation, layer.transform));
+                    self.upload_layer(i, layer.image, layer.transform, content);
+                    None
+                }
+            };
+            shared.push(texture);
         }
 
         // Now borrow ctx immutably for the rest
@@ -336,17 +674,7 @@ impl GpuCompositor {
                 continue;
             }
 
-            let filters = match layer.source {
-                LayerSource::Ndi => &self.ndi_filters,
-                LayerSource::Browser(idx) => {
-                    if idx < self.browser_filters.len() {
-                        &self.browser_filters[idx]
-                    } else {
-                        continue;
-                    }
-                }
-            };
-
+            let filters = self.layer_filters(layer.source);
             if filters.is_empty() {
                 continue;
             }
@@ -417,8 +745,10 @@ impl GpuCompositor {
         });
 
         {
-            let mut pass =
-                encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None, timestamp_writes: None });
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: None,
+                timestamp_writes: None,
+            });
             pass.set_pipeline(&self.ctx.clear_pipeline);
             pass.set_bind_group(0, &clear_bg, &[]);
             pass.dispatch_workgroups(dispatch_x, dispatch_y, 1);
@@ -432,7 +762,10 @@ impl GpuCompositor {
                 continue;
             }
 
-            let layer_view = &self.layer_cache[i].as_ref().unwrap().view;
+            let layer_view = match shared[i] {
+                Some(ref texture) => &texture.view,
+                None => &self.layer_cache[i].as_ref().unwrap().view,
+            };
 
             let params = BlendParams {
                 opacity: layer.opacity,
@@ -476,8 +809,10 @@ impl GpuCompositor {
             });
 
             {
-                let mut pass = encoder
-                    .begin_compute_pass(&wgpu::ComputePassDescriptor { label: None, timestamp_writes: None });
+                let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                    label: None,
+                    timestamp_writes: None,
+                });
                 pass.set_pipeline(&self.ctx.blend_pipeline);
                 pass.set_bind_group(0, &blend_bg, &[]);
                 pass.dispatch_workgroups(dispatch_x, dispatch_y, 1);
@@ -487,12 +822,8 @@ impl GpuCompositor {
         }
 
         // Step 3: Apply channel-level post-processing filters
-        if !self.channel_filters.is_empty() {
-            let composited_tex = if ping_is_src {
-                &self.ping
-            } else {
-                &self.pong
-            };
+        if !self.channel_filters.is_empty() && self.filters_enabled {
+            let composited_tex = if ping_is_src { &self.ping } else { &self.pong };
 
             let result_in_a = self.apply_filters(
                 &mut encoder,
@@ -509,11 +840,7 @@ impl GpuCompositor {
                 self.filter_b.as_ref().unwrap()
             };
 
-            let dest_tex = if ping_is_src {
-                &self.ping
-            } else {
-                &self.pong
-            };
+            let dest_tex = if ping_is_src { &self.ping } else { &self.pong };
 
             encoder.copy_texture_to_texture(
                 wgpu::TexelCopyTextureInfo {
@@ -537,11 +864,7 @@ impl GpuCompositor {
         }
 
         // Step 4: Copy result to staging buffer
-        let result_tex = if ping_is_src {
-            &self.ping
-        } else {
-            &self.pong
-        };
+        let result_tex = if ping_is_src { &self.ping } else { &self.pong };
 
         encoder.copy_texture_to_buffer(
             wgpu::TexelCopyTextureInfo {
@@ -603,14 +926,83 @@ impl GpuCompositor {
         }
     }
 
-    /// Upload layer image to a cached GPU texture, resizing on CPU if needed.
-    fn upload_layer(&mut self, index: usize, image: &RgbaImage) {
-        let (img_w, img_h) = image.dimensions();
+    /// Filters that apply to a layer source (empty when none or bypassed).
+    fn layer_filters(&self, source: LayerId) -> &[CompiledFilter] {
+        if !self.filters_enabled {
+            return &[];
+        }
+        match source {
+            LayerId::Ndi => &self.ndi_filters,
+            LayerId::Browser(idx) => self
+                .browser_filters
+                .get(idx)
+                .map(Vec::as_slice)
+                .unwrap_or(&[]),
+            LayerId::Image(_)
+            | LayerId::Text(_)
+            | LayerId::Captions
+            | LayerId::Clock
+            | LayerId::Timer => &[],
+        }
+    }
+
+    /// Get the context-wide texture for a shared layer frame, uploading it only if
+    /// no other channel already has this frame.
+    fn upload_shared(
+        &mut self,
+        index: usize,
+        key: SharedLayerKey,
+        image: LayerImage<'_>,
+        transform: Transform,
+    ) -> Arc<SharedTexture> {
+        while self.uyvy_sources.len() <= index {
+            self.uyvy_sources.push(None);
+        }
+
+        // Channels compositing at a different size or place need their own copy
+        let mut hasher = DefaultHasher::new();
+        (key.source, self.width, self.height, transform).hash(&mut hasher);
+        let key = SharedLayerKey {
+            source: hasher.finish(),
+            ..key
+        };
+
+        let ctx = self.ctx.clone();
+        let (width, height) = (self.width, self.height);
+        ctx.shared_layer(key, width, height, |target| match image {
+            LayerImage::Rgba(image) => {
+                write_rgba(&ctx, &target.texture, image, transform, width, height)
+            }
+            LayerImage::Uyvy(frame) => convert_uyvy(
+                &ctx,
+                &mut self.uyvy_sources[index],
+                frame,
+                &target.view,
+                transform,
+                width,
+                height,
+            ),
+        })
+    }
 
+    /// Upload layer image to a cached GPU texture. RGBA images are cropped, resized
+    /// and placed on CPU if needed; UYVY frames are converted (and cropped/scaled)
+    /// by a compute pass.
+    /// Skipped when `content` matches what the texture already holds.
+    fn upload_layer(
+        &mut self,
+        index: usize,
+        image: LayerImage<'_>,
+        transform: Transform,
+        content: Option<(LayerId, u64, Transform)>,
+    ) {
         // Ensure cache has enough slots
         while self.layer_cache.len() <= index {
             self.layer_cache.push(None);
         }
+        while self.uyvy_sources.len() <= index {
+            self.uyvy_sources.push(None);
+        }
 
         // Recreate texture if dimensions don't match canvas
         let needs_recreate = match &self.layer_cache[index] {
@@ -619,7 +1011,8 @@ impl GpuCompositor {
         };
 
         if needs_recreate {
-            // COPY_SRC needed so we can copy layer texture → filter_a for filtering
+            // COPY_SRC needed so we can copy layer texture → filter_a for filtering,
+            // STORAGE_BINDING so UYVY conversion can write into it
             let texture = self.ctx.device.create_texture(&wgpu::TextureDescriptor {
                 label: Some("layer"),
                 size: wgpu::Extent3d {
@@ -632,6 +1025,7 @@ impl GpuCompositor {
                 dimension: wgpu::TextureDimension::D2,
                 format: wgpu::TextureFormat::Rgba8Unorm,
                 usage: wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::STORAGE_BINDING
                     | wgpu::TextureUsages::COPY_DST
                     | wgpu::TextureUsages::COPY_SRC,
                 view_formats: &[],
@@ -642,41 +1036,220 @@ impl GpuCompositor {
                 view,
                 width: self.width,
                 height: self.height,
+                content: None,
             });
         }
 
-        // Resize on CPU if layer doesn't match canvas (same as CPU compositor)
-        let upload_data: std::borrow::Cow<[u8]> = if img_w == self.width && img_h == self.height {
-            std::borrow::Cow::Borrowed(image.as_raw())
-        } else {
-            let resized = image::imageops::resize(
+        let cached = self.layer_cache[index].as_mut().unwrap();
+        if content.is_some() && cached.content == content {
+            return;
+        }
+        cached.content = content;
+
+        let cached = self.layer_cache[index].as_ref().unwrap();
+        match image {
+            LayerImage::Rgba(image) => write_rgba(
+                &self.ctx,
+                &cached.texture,
                 image,
+                transform,
                 self.width,
                 self.height,
-                image::imageops::FilterType::Nearest,
-            );
-            std::borrow::Cow::Owned(resized.into_raw())
-        };
+            ),
+            LayerImage::Uyvy(frame) => convert_uyvy(
+                &self.ctx,
+                &mut self.uyvy_sources[index],
+                frame,
+                &cached.view,
+                transform,
+                self.width,
+                self.height,
+            ),
+        }
+    }
+}
 
-        let cached = self.layer_cache[index].as_ref().unwrap();
-        self.ctx.queue.write_texture(
-            wgpu::TexelCopyTextureInfo {
-                texture: &cached.texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
-            },
-            &upload_data,
-            wgpu::TexelCopyBufferLayout {
-                offset: 0,
-                bytes_per_row: Some(self.width * 4),
-                rows_per_image: Some(self.height),
-            },
-            wgpu::Extent3d {
-                width: self.width,
-                height: self.height,
+/// Write an RGBA image into a layer texture, transforming it on CPU if it isn't
+/// a full-canvas layer already.
+fn write_rgba(
+    ctx: &GpuContext,
+    texture: &wgpu::Texture,
+    image: &RgbaImage,
+    transform: Transform,
+    width: u32,
+    height: u32,
+) {
+    // Same as the CPU compositor
+    let upload = transform.apply(image, (width, height));
+
+    ctx.queue.write_texture(
+        wgpu::TexelCopyTextureInfo {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        upload.as_raw(),
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(width * 4),
+            rows_per_image: Some(height),
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+}
+
+/// Upload a packed UYVY frame into `source` and convert it into `target`.
+fn convert_uyvy(
+    ctx: &GpuContext,
+    source: &mut Option<UyvySource>,
+    frame: &UyvyFrame,
+    target: &wgpu::TextureView,
+    transform: Transform,
+    width: u32,
+    height: u32,
+) {
+    // One rgba8 texel per pixel pair
+    let src_w = frame.width.div_ceil(2);
+    let src_h = frame.height;
+    if frame.data.len() < (src_w * 4 * src_h) as usize {
+        tracing::warn!(
+            "UYVY frame too small for {}x{}, skipping",
+            frame.width,
+            frame.height
+        );
+        return;
+    }
+
+    let device = &ctx.device;
+    let needs_source = match source {
+        Some(u) => u.width != src_w || u.height != src_h,
+        None => true,
+    };
+    if needs_source {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("uyvy_source"),
+            size: wgpu::Extent3d {
+                width: src_w,
+                height: src_h,
                 depth_or_array_layers: 1,
             },
-        );
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&Default::default());
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("uyvy_params"),
+            size: std::mem::size_of::<UyvyParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        *source = Some(UyvySource {
+            texture,
+            view,
+            width: src_w,
+            height: src_h,
+            params,
+            bind_group: None,
+        });
+    }
+    let source = source.as_mut().unwrap();
+
+    ctx.queue.write_texture(
+        wgpu::TexelCopyTextureInfo {
+            texture: &source.texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        &frame.data,
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(src_w * 4),
+            rows_per_image: Some(src_h),
+        },
+        wgpu::Extent3d {
+            width: src_w,
+            height: src_h,
+            depth_or_array_layers: 1,
+        },
+    );
+
+    // Fitted into the layer's area, then moved there
+    let (area_x, area_y, area_w, area_h) = transform.dst_rect((width, height));
+    let placement = frame.placement(area_w, area_h);
+    let (roi_x, roi_y, roi_w, roi_h) = placement.src;
+    let (dst_x, dst_y, dst_w, dst_h) = placement.dst;
+    let (dst_x, dst_y) = (area_x + dst_x, area_y + dst_y);
+    let (kr, kb) = crate::color::luma_coefficients(frame.primaries);
+    let params = UyvyParams {
+        out_width: width,
+        out_height: height,
+        src_width: frame.width,
+        src_height: frame.height,
+        roi_x,
+        roi_y,
+        roi_w,
+        roi_h,
+        dst_x,
+        dst_y,
+        dst_w,
+        dst_h,
+        kr,
+        kb,
+        expand_range: (frame.range == ColorRange::Limited) as u32,
+        bt2020_gamut: (frame.primaries == ColorPrimaries::Bt2020) as u32,
+    };
+    ctx.queue
+        .write_buffer(&source.params, 0, bytemuck::bytes_of(&params));
+
+    // Rebuilt only when the target changes (new size, or another shared texture)
+    if source
+        .bind_group
+        .as_ref()
+        .is_none_or(|(view, _)| view != target)
+    {
+        let bg = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &ctx.filter_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&source.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(target),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: source.params.as_entire_binding(),
+                },
+            ],
+        });
+        source.bind_group = Some((target.clone(), bg));
+    }
+    let (_, bg) = source.bind_group.as_ref().unwrap();
+
+    let mut encoder =
+        device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: None,
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&ctx.uyvy_pipeline);
+        pass.set_bind_group(0, bg, &[]);
+        pass.dispatch_workgroups(width.div_ceil(16), height.div_ceil(16), 1);
     }
+    // Submitted before the composite encoder, so queue order keeps it first
+    ctx.queue.submit(std::iter::once(encoder.finish()));
 }
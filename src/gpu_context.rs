@@ -1,8 +1,41 @@
-use std::sync::Arc;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::compositor::SharedLayerKey;
+
+/// Shared cache entries unused for this long are dropped (source went away).
+const SHARED_LAYER_TTL: Duration = Duration::from_secs(5);
+
+/// Recent frames kept per shared source, so channels a frame or two apart
+/// (frame divisors, rate adaptation, previous-frame picks) still share uploads.
+const SHARED_LAYER_FRAMES: usize = 3;
+
+/// A layer texture shared across channels. Never modified after upload, so holders
+/// must not run filters on it in place.
+pub struct SharedTexture {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub width: u32,
+    pub height: u32,
+}
+
+struct SharedFrame {
+    frame: i64,
+    texture: Arc<SharedTexture>,
+}
+
+/// A source's most recent uploads, oldest first.
+struct SharedEntry {
+    frames: VecDeque<SharedFrame>,
+    last_used: Instant,
+}
 
 /// Shared GPU state: device, queue, and compiled compute pipelines.
 /// Created once at startup, wrapped in Arc, passed to each channel.
 pub struct GpuContext {
+    /// The adapter the device was created on
+    pub adapter: wgpu::AdapterInfo,
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
     pub blend_pipeline: wgpu::ComputePipeline,
@@ -11,6 +44,12 @@ pub struct GpuContext {
     pub clear_layout: wgpu::BindGroupLayout,
     pub filter_layout: wgpu::BindGroupLayout,
     pub filter_pipeline_layout: wgpu::PipelineLayout,
+    /// UYVY → RGBA conversion (shares the filter bind group layout)
+    pub uyvy_pipeline: wgpu::ComputePipeline,
+    /// Linear, repeating sampler bound alongside custom filter textures
+    pub filter_sampler: wgpu::Sampler,
+    /// Layer textures shared by channels consuming the same source, keyed by source
+    shared_layers: Mutex<HashMap<u64, SharedEntry>>,
 }
 
 impl GpuContext {
@@ -95,11 +134,12 @@ impl GpuContext {
             ],
         });
 
-        let clear_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("clear_pl"),
-            bind_group_layouts: &[&clear_layout],
-            immediate_size: 0,
-        });
+        let clear_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("clear_pl"),
+                bind_group_layouts: &[&clear_layout],
+                immediate_size: 0,
+            });
 
         let clear_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
             label: Some("clear"),
@@ -157,11 +197,12 @@ impl GpuContext {
             ],
         });
 
-        let blend_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("blend_pl"),
-            bind_group_layouts: &[&blend_layout],
-            immediate_size: 0,
-        });
+        let blend_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("blend_pl"),
+                bind_group_layouts: &[&blend_layout],
+                immediate_size: 0,
+            });
 
         let blend_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
             label: Some("blend"),
@@ -209,15 +250,40 @@ impl GpuContext {
             ],
         });
 
-        let filter_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("filter_pl"),
-            bind_group_layouts: &[&filter_layout],
-            immediate_size: 0,
+        let filter_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("filter_pl"),
+                bind_group_layouts: &[&filter_layout],
+                immediate_size: 0,
+            });
+
+        let uyvy_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("uyvy.wgsl"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/uyvy.wgsl").into()),
+        });
+
+        let uyvy_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("uyvy"),
+            layout: Some(&filter_pipeline_layout),
+            module: &uyvy_shader,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        let filter_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("filter_sampler"),
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
         });
 
         tracing::info!("GPU compute compositor initialized");
 
         Some(Arc::new(Self {
+            adapter: adapter.get_info(),
             device,
             queue,
             blend_pipeline,
@@ -226,29 +292,176 @@ impl GpuContext {
             clear_layout,
             filter_layout,
             filter_pipeline_layout,
+            uyvy_pipeline,
+            filter_sampler,
+            shared_layers: Mutex::new(HashMap::new()),
         }))
     }
 
-    /// Compile a filter compute shader from WGSL source code.
+    /// Bind group layout for a filter's custom textures (group 1): a sampler at
+    /// binding 0, then `count` textures from binding 1.
+    pub fn filter_texture_layout(&self, count: usize) -> wgpu::BindGroupLayout {
+        let mut entries = vec![wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+        }];
+        entries.extend((0..count).map(|i| wgpu::BindGroupLayoutEntry {
+            binding: i as u32 + 1,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        }));
+
+        self.device
+            .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("filter_textures_bgl"),
+                entries: &entries,
+            })
+    }
+
+    /// Compile a filter compute shader from WGSL source code. Filters with custom
+    /// textures pass their texture layout, which becomes bind group 1.
     pub fn compile_filter_pipeline(
         &self,
         label: &str,
         wgsl_source: &str,
+        entry_point: &str,
+        texture_layout: Option<&wgpu::BindGroupLayout>,
     ) -> Result<wgpu::ComputePipeline, String> {
-        let module = self.device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some(label),
-            source: wgpu::ShaderSource::Wgsl(wgsl_source.into()),
+        // Catch WGSL and layout errors here instead of in the device's error handler
+        let scope = self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+        let module = self
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(label),
+                source: wgpu::ShaderSource::Wgsl(wgsl_source.into()),
+            });
+
+        let textured_layout = texture_layout.map(|textures| {
+            self.device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some(label),
+                    bind_group_layouts: &[&self.filter_layout, textures],
+                    immediate_size: 0,
+                })
         });
 
-        let pipeline = self.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
-            label: Some(label),
-            layout: Some(&self.filter_pipeline_layout),
-            module: &module,
-            entry_point: Some("main"),
-            compilation_options: Default::default(),
-            cache: None,
+        let pipeline = self
+            .device
+            .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(label),
+                layout: Some(
+                    textured_layout
+                        .as_ref()
+                        .unwrap_or(&self.filter_pipeline_layout),
+                ),
+                module: &module,
+                entry_point: Some(entry_point),
+                compilation_options: Default::default(),
+                cache: None,
+            });
+
+        match pollster::block_on(scope.pop()) {
+            Some(e) => Err(e.to_string()),
+            None => Ok(pipeline),
+        }
+    }
+
+    /// Get the shared texture for a source frame, uploading it via `upload` only
+    /// if no channel has done so yet. The source's oldest texture is reused when
+    /// no channel still holds it, otherwise a fresh one is allocated so in-flight
+    /// composites keep their frame. The upload runs outside the cache lock, so
+    /// channels uploading other sources don't wait for it.
+    pub fn shared_layer(
+        &self,
+        key: SharedLayerKey,
+        width: u32,
+        height: u32,
+        upload: impl FnOnce(&SharedTexture),
+    ) -> Arc<SharedTexture> {
+        let fits = |t: &SharedTexture| t.width == width && t.height == height;
+        let reusable = {
+            let mut cache = self.shared_layers.lock().unwrap();
+            let now = Instant::now();
+            if let Some(entry) = cache.get_mut(&key.source) {
+                if let Some(hit) = entry
+                    .frames
+                    .iter()
+                    .find(|f| f.frame == key.frame && fits(&f.texture))
+                {
+                    entry.last_used = now;
+                    return hit.texture.clone();
+                }
+            }
+
+            cache.retain(|_, e| now.duration_since(e.last_used) < SHARED_LAYER_TTL);
+
+            cache.get_mut(&key.source).and_then(|entry| {
+                let oldest = entry.frames.front()?;
+                (entry.frames.len() == SHARED_LAYER_FRAMES
+                    && Arc::strong_count(&oldest.texture) == 1
+                    && fits(&oldest.texture))
+                .then(|| entry.frames.pop_front())
+                .flatten()
+                .map(|f| f.texture)
+            })
+        };
+        let texture = reusable.unwrap_or_else(|| {
+            let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("shared_layer"),
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::STORAGE_BINDING
+                    | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            });
+            let view = texture.create_view(&Default::default());
+            Arc::new(SharedTexture {
+                texture,
+                view,
+                width,
+                height,
+            })
         });
 
-        Ok(pipeline)
+        // Published only once uploaded, so no other channel sees it without its data
+        upload(&texture);
+
+        let mut cache = self.shared_layers.lock().unwrap();
+        let entry = cache.entry(key.source).or_insert_with(|| SharedEntry {
+            frames: VecDeque::with_capacity(SHARED_LAYER_FRAMES),
+            last_used: Instant::now(),
+        });
+        entry.last_used = Instant::now();
+        // Another channel may have uploaded the same frame meanwhile; keep one copy
+        if !entry
+            .frames
+            .iter()
+            .any(|f| f.frame == key.frame && fits(&f.texture))
+        {
+            if entry.frames.len() == SHARED_LAYER_FRAMES {
+                entry.frames.pop_front();
+            }
+            entry.frames.push_back(SharedFrame {
+                frame: key.frame,
+                texture: texture.clone(),
+            });
+        }
+        texture
     }
 }
@@ -0,0 +1,367 @@
+use serde::Serialize;
+#[cfg(feature = "gpu")]
+use std::sync::mpsc::{Receiver, TryRecvError};
+#[cfg(feature = "gpu")]
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "gpu")]
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "gpu")]
+use crate::compositor::Layer;
+#[cfg(feature = "gpu")]
+use crate::config::{ChannelConfig, FilterConfig};
+#[cfg(feature = "gpu")]
+use crate::errors::{record, ErrorCode, ErrorSlot};
+#[cfg(feature = "gpu")]
+use crate::gpu_compositor::GpuCompositor;
+#[cfg(feature = "gpu")]
+use crate::gpu_context::GpuContext;
+
+/// Snapshot of a channel's GPU compositing health, shared with status reporting.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GpuHealth {
+    /// Frames composited on the CPU because the GPU failed or was backed off
+    pub fallback_frames: u64,
+    /// GPU failures in a row; reset by the next frame composited on the GPU
+    pub consecutive_failures: u32,
+    /// GPU attempts are suspended after repeated failures
+    pub backing_off: bool,
+    /// GPU context re-initializations attempted since startup
+    pub reinit_attempts: u64,
+}
+
+/// GPU failures in a row before GPU attempts are suspended.
+#[cfg(feature = "gpu")]
+const FAILURES_BEFORE_BACKOFF: u32 = 3;
+#[cfg(feature = "gpu")]
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+#[cfg(feature = "gpu")]
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// A context rebuilt this recently is reused by other recovering channels
+/// instead of building another; a lost device fails every channel at once.
+#[cfg(feature = "gpu")]
+const SHARE_REBUILT_WITHIN: Duration = Duration::from_secs(10);
+
+/// A channel's GPU compositors: full resolution, plus half resolution for the
+/// downscale overload policy and the compare view's alternate filter chain.
+#[cfg(feature = "gpu")]
+pub struct Compositors {
+    pub full: GpuCompositor,
+    pub small: Option<GpuCompositor>,
+    pub compare: Option<GpuCompositor>,
+}
+
+/// A channel's filter chains, for updating filter params in place.
+#[cfg(feature = "gpu")]
+#[derive(Debug, Clone, Default)]
+pub struct FilterChains {
+    pub ndi: Vec<FilterConfig>,
+    pub overlays: Vec<Vec<FilterConfig>>,
+    pub channel: Vec<FilterConfig>,
+    /// The compare view's channel chain (None = the right side is unfiltered)
+    pub compare: Option<Vec<FilterConfig>>,
+}
+
+#[cfg(feature = "gpu")]
+impl FilterChains {
+    pub fn of(config: &ChannelConfig) -> Self {
+        Self {
+            ndi: config
+                .ndi_input
+                .as_ref()
+                .map(|c| c.filter_chain())
+                .unwrap_or_default(),
+            overlays: config
+                .all_browser_overlays()
+                .iter()
+                .map(|c| c.filter_chain())
+                .collect(),
+            channel: config.filters.clone(),
+            compare: config
+                .compare
+                .as_ref()
+                .map(|c| c.filters.clone())
+                .filter(|f| !f.is_empty()),
+        }
+    }
+}
+
+/// Builds a channel's compositors on a GPU context.
+#[cfg(feature = "gpu")]
+pub type BuildCompositors = Arc<dyn Fn(&Arc<GpuContext>) -> Compositors + Send + Sync>;
+
+/// Runs a channel's GPU compositors and recovers them when they fail.
+///
+/// A failed frame is composited on the CPU and the GPU retried on the next one.
+/// After `FAILURES_BEFORE_BACKOFF` failures in a row GPU attempts are suspended;
+/// when the backoff ends a fresh GPU context is created and the compositors are
+/// rebuilt on it. The backoff doubles (up to `MAX_BACKOFF`) each time recovery
+/// doesn't stick and resets once a frame composites on the GPU again. The
+/// context and compositors are built on a background thread while frames keep
+/// going to the CPU, and channels recovering together share one fresh context.
+#[cfg(feature = "gpu")]
+pub struct GpuRecovery {
+    compositors: Option<Compositors>,
+    build: BuildCompositors,
+    backoff: Duration,
+    retry_at: Option<Instant>,
+    /// Compositors being rebuilt on a background thread (None if that failed)
+    rebuilding: Option<Receiver<Option<Compositors>>>,
+    status: Arc<Mutex<GpuHealth>>,
+    last_error: ErrorSlot,
+    /// Right side of the compare view, composited separately
+    compare_canvas: image::RgbaImage,
+    /// Filter params from a config reload, re-applied to rebuilt compositors
+    filter_params: Option<FilterChains>,
+}
+
+#[cfg(feature = "gpu")]
+impl GpuRecovery {
+    /// `ctx` is None when the mixer runs without a GPU; compositing then always
+    /// falls to the CPU and no recovery is attempted.
+    pub fn new(
+        ctx: Option<&Arc<GpuContext>>,
+        build: BuildCompositors,
+        status: Arc<Mutex<GpuHealth>>,
+        last_error: ErrorSlot,
+    ) -> Self {
+        Self {
+            compositors: ctx.map(&*build),
+            build,
+            backoff: INITIAL_BACKOFF,
+            retry_at: None,
+            rebuilding: None,
+            status,
+            last_error,
+            compare_canvas: image::RgbaImage::new(0, 0),
+            filter_params: None,
+        }
+    }
+
+    /// Update every compositor's filter params, keeping the compiled shaders.
+    pub fn set_filter_params(&mut self, filters: &FilterChains) {
+        self.filter_params = Some(filters.clone());
+        self.apply_filter_params();
+    }
+
+    fn apply_filter_params(&mut self) {
+        let (Some(c), Some(f)) = (self.compositors.as_mut(), self.filter_params.as_ref()) else {
+            return;
+        };
+        c.full.set_params(&f.ndi, &f.overlays, &f.channel);
+        if let Some(ref mut small) = c.small {
+            small.set_params(&f.ndi, &f.overlays, &f.channel);
+        }
+        if let (Some(compare), Some(filters)) = (c.compare.as_mut(), f.compare.as_ref()) {
+            compare.set_params(&f.ndi, &f.overlays, filters);
+        }
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.compositors.is_some()
+    }
+
+    /// Composite on the GPU. Returns false when the frame must be composited on
+    /// the CPU instead.
+    pub fn composite(
+        &mut self,
+        canvas: &mut image::RgbaImage,
+        layers: &mut [Layer<'_>],
+        downscale: bool,
+        filters_enabled: bool,
+        channel: &str,
+    ) -> bool {
+        if let Some(retry_at) = self.retry_at {
+            if Instant::now() < retry_at {
+                self.status.lock().unwrap().fallback_frames += 1;
+                return false;
+            }
+            self.retry_at = None;
+            self.reinit(channel);
+        }
+        if self.rebuilding.is_some() && !self.finish_reinit(channel) {
+            self.status.lock().unwrap().fallback_frames += 1;
+            return false;
+        }
+
+        let gpu = match self.compositors.as_mut() {
+            Some(c) if downscale => c.small.as_mut(),
+            Some(c) => Some(&mut c.full),
+            None => None,
+        };
+        let Some(gpu) = gpu else {
+            return false;
+        };
+
+        gpu.set_filters_enabled(filters_enabled);
+        if gpu.composite(canvas, layers) {
+            let mut status = self.status.lock().unwrap();
+            if status.consecutive_failures > 0 {
+                if status.consecutive_failures >= FAILURES_BEFORE_BACKOFF {
+                    tracing::info!("Channel '{}': GPU compositing recovered", channel);
+                }
+                status.consecutive_failures = 0;
+                self.backoff = INITIAL_BACKOFF;
+            }
+            return true;
+        }
+
+        record(
+            &self.last_error,
+            ErrorCode::GpuReadbackFailed,
+            "GPU readback failed; frame composited on the CPU",
+        );
+        let mut status = self.status.lock().unwrap();
+        status.fallback_frames += 1;
+        status.consecutive_failures += 1;
+        if status.consecutive_failures >= FAILURES_BEFORE_BACKOFF {
+            tracing::warn!(
+                "Channel '{}': {} GPU failures in a row, compositing on CPU for {}s before re-initializing the GPU",
+                channel,
+                status.consecutive_failures,
+                self.backoff.as_secs()
+            );
+            drop(status);
+            self.back_off();
+        }
+        false
+    }
+
+    /// Compare view: composite the layers again — through the compare filter
+    /// chain, or without filters when there is none — and show that right of
+    /// `split` (a fraction of the width), with a divider line. `canvas` must
+    /// already hold the full-resolution GPU composite.
+    pub fn composite_compare(
+        &mut self,
+        canvas: &mut image::RgbaImage,
+        layers: &mut [Layer<'_>],
+        split: f32,
+    ) {
+        let Some(compositors) = self.compositors.as_mut() else {
+            return;
+        };
+        let (width, height) = canvas.dimensions();
+        if self.compare_canvas.dimensions() != (width, height) {
+            self.compare_canvas = image::RgbaImage::new(width, height);
+        }
+        let composited = match compositors.compare.as_mut() {
+            Some(gpu) => gpu.composite(&mut self.compare_canvas, layers),
+            None => {
+                compositors.full.set_filters_enabled(false);
+                let composited = compositors.full.composite(&mut self.compare_canvas, layers);
+                compositors.full.set_filters_enabled(true);
+                composited
+            }
+        };
+        // A failed readback just leaves the frame fully filtered
+        if !composited {
+            return;
+        }
+
+        let divider = ((width as f32 * split).round() as u32).min(width);
+        let row_bytes = width as usize * 4;
+        let reference = self.compare_canvas.as_raw();
+        for (y, row) in canvas.chunks_exact_mut(row_bytes).enumerate() {
+            let start = y * row_bytes + divider as usize * 4;
+            row[divider as usize * 4..].copy_from_slice(&reference[start..(y + 1) * row_bytes]);
+        }
+        for x in divider.saturating_sub(1)..(divider + 1).min(width) {
+            for y in 0..height {
+                canvas.put_pixel(x, y, image::Rgba([255, 255, 255, 255]));
+            }
+        }
+    }
+
+    /// GPU memory held by the compositors.
+    pub fn memory_bytes(&self) -> Option<u64> {
+        self.compositors.as_ref().map(|c| {
+            c.full.memory_bytes()
+                + c.small.as_ref().map_or(0, |g| g.memory_bytes())
+                + c.compare.as_ref().map_or(0, |g| g.memory_bytes())
+        })
+    }
+
+    fn back_off(&mut self) {
+        self.retry_at = Some(Instant::now() + self.backoff);
+        self.backoff = (self.backoff * 2).min(MAX_BACKOFF);
+        self.status.lock().unwrap().backing_off = true;
+    }
+
+    /// Start building the compositors on a fresh GPU context, off the render
+    /// thread (see `finish_reinit`).
+    fn reinit(&mut self, channel: &str) {
+        {
+            let mut status = self.status.lock().unwrap();
+            status.backing_off = false;
+            status.reinit_attempts += 1;
+        }
+        // Release the old device's resources before creating a new one
+        self.compositors = None;
+
+        let build = self.build.clone();
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::Builder::new()
+            .name(format!("gpu-reinit-{}", channel))
+            .spawn(move || {
+                let _ = tx.send(fresh_context().map(|ctx| build(&ctx)));
+            })
+            .expect("Failed to spawn GPU re-initialization thread");
+        self.rebuilding = Some(rx);
+    }
+
+    /// Take the rebuilt compositors once the background build is done. False
+    /// while it runs, or if it failed.
+    fn finish_reinit(&mut self, channel: &str) -> bool {
+        let Some(ref rebuilding) = self.rebuilding else {
+            return true;
+        };
+        let compositors = match rebuilding.try_recv() {
+            Err(TryRecvError::Empty) => return false,
+            Ok(compositors) => compositors,
+            Err(TryRecvError::Disconnected) => None,
+        };
+        self.rebuilding = None;
+        match compositors {
+            Some(compositors) => {
+                self.compositors = Some(compositors);
+                self.apply_filter_params();
+                tracing::info!("Channel '{}': GPU context re-initialized", channel);
+                true
+            }
+            None => {
+                tracing::warn!(
+                    "Channel '{}': GPU re-initialization failed, compositing on CPU for {}s",
+                    channel,
+                    self.backoff.as_secs()
+                );
+                record(
+                    &self.last_error,
+                    ErrorCode::GpuUnavailable,
+                    "GPU re-initialization failed; frames are composited on the CPU",
+                );
+                self.back_off();
+                false
+            }
+        }
+    }
+}
+
+/// A fresh GPU context for a recovering channel. Blocks while it's created,
+/// so it runs off the render threads; channels asking while one is built, or
+/// within `SHARE_REBUILT_WITHIN` after, get the same one (or the same failure).
+#[cfg(feature = "gpu")]
+fn fresh_context() -> Option<Arc<GpuContext>> {
+    type Rebuilt = Option<(Instant, Option<Arc<GpuContext>>)>;
+    static REBUILT: Mutex<Rebuilt> = Mutex::new(None);
+
+    // Held during the build, so channels asking meanwhile wait for its result
+    let mut rebuilt = REBUILT.lock().unwrap();
+    if let Some((built_at, ref ctx)) = *rebuilt {
+        if built_at.elapsed() < SHARE_REBUILT_WITHIN {
+            return ctx.clone();
+        }
+    }
+    let ctx = GpuContext::try_new();
+    *rebuilt = Some((Instant::now(), ctx.clone()));
+    ctx
+}
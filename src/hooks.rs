@@ -0,0 +1,144 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::runtime::Handle;
+
+use crate::config::{HookConfig, HookEvent};
+
+/// Longest a hook command or webhook may take before it's abandoned.
+const HOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Body POSTed to webhook URLs.
+#[derive(Serialize)]
+struct HookPayload<'a> {
+    channel: &'a str,
+    output_name: &'a str,
+    event: HookEvent,
+    /// Unix time in seconds
+    timestamp: u64,
+}
+
+/// A channel's lifecycle hooks: shell commands and webhooks run on events.
+///
+/// Hooks run on the tokio runtime so the render thread never waits on them;
+/// failures are logged and never affect the channel.
+pub struct Hooks {
+    channel: String,
+    /// Changes when the output is renamed at runtime
+    output_name: Mutex<String>,
+    hooks: Vec<HookConfig>,
+    runtime: Handle,
+}
+
+impl Hooks {
+    /// Must be called from within the tokio runtime.
+    pub fn new(channel: &str, output_name: &str, hooks: &[HookConfig]) -> Self {
+        Self {
+            channel: channel.to_string(),
+            output_name: Mutex::new(output_name.to_string()),
+            hooks: hooks.to_vec(),
+            runtime: Handle::current(),
+        }
+    }
+
+    pub fn set_output_name(&self, output_name: &str) {
+        *self.output_name.lock().unwrap() = output_name.to_string();
+    }
+
+    /// Start the hooks for an event in the background.
+    pub fn fire(&self, event: HookEvent) {
+        if !self.hooks.iter().any(|h| h.event == event) {
+            return;
+        }
+        let hooks = self.clone_for(event);
+        self.runtime.spawn(async move { hooks.run(event).await });
+    }
+
+    /// Run the hooks for an event and wait for them (used at shutdown).
+    pub async fn run(&self, event: HookEvent) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let output_name = self.output_name.lock().unwrap().clone();
+        let payload = HookPayload {
+            channel: &self.channel,
+            output_name: &output_name,
+            event,
+            timestamp,
+        };
+
+        let runs = self
+            .hooks
+            .iter()
+            .filter(|h| h.event == event)
+            .map(|hook| async {
+                let result = match (&hook.exec, &hook.url) {
+                    (Some(cmd), _) => self.exec(cmd, &output_name, event).await,
+                    (None, Some(url)) => post(url, &payload).await,
+                    (None, None) => Ok(()),
+                };
+                if let Err(e) = result {
+                    tracing::warn!(
+                        "Channel '{}': {} hook failed: {}",
+                        self.channel,
+                        event.as_str(),
+                        e
+                    );
+                }
+            });
+        futures::future::join_all(runs).await;
+    }
+
+    async fn exec(&self, cmd: &str, output_name: &str, event: HookEvent) -> anyhow::Result<()> {
+        let mut child = tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg(cmd)
+            .env("NDIMIXER_CHANNEL", &self.channel)
+            .env("NDIMIXER_OUTPUT", output_name)
+            .env("NDIMIXER_EVENT", event.as_str())
+            .kill_on_drop(true)
+            .spawn()?;
+        let status = tokio::time::timeout(HOOK_TIMEOUT, child.wait())
+            .await
+            .map_err(|_| anyhow::anyhow!("'{}' timed out after {:?}", cmd, HOOK_TIMEOUT))??;
+        if !status.success() {
+            anyhow::bail!("'{}' exited with {}", cmd, status);
+        }
+        tracing::debug!(
+            "Channel '{}': ran {} hook '{}'",
+            self.channel,
+            event.as_str(),
+            cmd
+        );
+        Ok(())
+    }
+
+    /// Copy of the hooks for one event, to move into a task.
+    fn clone_for(&self, event: HookEvent) -> Self {
+        Self {
+            channel: self.channel.clone(),
+            output_name: Mutex::new(self.output_name.lock().unwrap().clone()),
+            hooks: self
+                .hooks
+                .iter()
+                .filter(|h| h.event == event)
+                .cloned()
+                .collect(),
+            runtime: self.runtime.clone(),
+        }
+    }
+}
+
+async fn post(url: &str, payload: &HookPayload<'_>) -> anyhow::Result<()> {
+    let response = reqwest::Client::new()
+        .post(url)
+        .json(payload)
+        .timeout(HOOK_TIMEOUT)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        anyhow::bail!("{} returned {}", url, response.status());
+    }
+    Ok(())
+}
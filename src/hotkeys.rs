@@ -0,0 +1,76 @@
+use std::io::Read;
+
+use crate::channel::ChannelRegistry;
+use crate::control::{self, Command};
+
+/// Restores the terminal mode changed by `spawn` when dropped.
+pub struct TerminalGuard {
+    #[cfg(unix)]
+    original: Option<libc::termios>,
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(ref original) = self.original {
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, original);
+            }
+        }
+    }
+}
+
+/// Listen for single-key commands on stdin. On Unix terminals, canonical mode and
+/// echo are turned off so keys act immediately while Ctrl+C still works; elsewhere
+/// a key takes effect after Enter.
+pub fn spawn(bindings: Vec<(char, Command)>, channels: ChannelRegistry) -> TerminalGuard {
+    let guard = TerminalGuard {
+        #[cfg(unix)]
+        original: enable_cbreak(),
+    };
+
+    std::thread::Builder::new()
+        .name("hotkeys".to_string())
+        .spawn(move || {
+            let mut stdin = std::io::stdin();
+            let mut buf = [0u8; 16];
+            while let Ok(n) = stdin.read(&mut buf) {
+                if n == 0 {
+                    break;
+                }
+                for key in String::from_utf8_lossy(&buf[..n]).chars() {
+                    let Some((_, command)) = bindings.iter().find(|(k, _)| *k == key) else {
+                        continue;
+                    };
+                    if let Err(e) = control::execute(&channels.snapshot(), command) {
+                        tracing::warn!("Hotkey '{}': {}", key, e);
+                    }
+                }
+            }
+        })
+        .expect("Failed to spawn hotkey thread");
+
+    guard
+}
+
+/// Switch stdin to cbreak mode, returning the previous settings to restore.
+#[cfg(unix)]
+fn enable_cbreak() -> Option<libc::termios> {
+    unsafe {
+        if libc::isatty(libc::STDIN_FILENO) == 0 {
+            return None;
+        }
+        let mut original: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(libc::STDIN_FILENO, &mut original) != 0 {
+            return None;
+        }
+        let mut cbreak = original;
+        cbreak.c_lflag &= !(libc::ICANON | libc::ECHO);
+        cbreak.c_cc[libc::VMIN] = 1;
+        cbreak.c_cc[libc::VTIME] = 0;
+        if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &cbreak) != 0 {
+            return None;
+        }
+        Some(original)
+    }
+}
@@ -0,0 +1,29 @@
+use anyhow::Result;
+use image::RgbaImage;
+
+use crate::config::ImageOverlayConfig;
+
+/// Load an image overlay and place it on a transparent canvas of the channel's
+/// size, once, so compositing it costs no more than any other full-frame layer
+/// and the GPU compositor can keep it as a texture.
+pub fn load(cfg: &ImageOverlayConfig, width: u32, height: u32) -> Result<RgbaImage> {
+    let img = image::open(&cfg.path)
+        .map_err(|e| anyhow::anyhow!("Failed to load image overlay {}: {}", cfg.path, e))?
+        .to_rgba8();
+    let size = (
+        cfg.width.unwrap_or(img.width()),
+        cfg.height.unwrap_or(img.height()),
+    );
+    let img = if size != img.dimensions() {
+        image::imageops::resize(&img, size.0, size.1, image::imageops::FilterType::Triangle)
+    } else {
+        img
+    };
+    if img.dimensions() == (width, height) && cfg.x == 0 && cfg.y == 0 {
+        return Ok(img);
+    }
+
+    let mut canvas = RgbaImage::new(width, height);
+    image::imageops::replace(&mut canvas, &img, cfg.x as i64, cfg.y as i64);
+    Ok(canvas)
+}
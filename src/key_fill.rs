@@ -0,0 +1,62 @@
+use image::RgbaImage;
+use std::collections::VecDeque;
+
+use crate::ndi_input::{NdiFrame, NdiInput};
+
+/// Key frames kept to pair with fill frames by timestamp.
+const KEY_HISTORY: usize = 4;
+
+/// The key half of an external key/fill pair: a second NDI input whose luma
+/// becomes the alpha of the fill, as traditional CG systems send them.
+pub struct KeyInput {
+    input: NdiInput,
+    /// Recent key frames, oldest first
+    recent: VecDeque<NdiFrame>,
+    fill_premultiplied: bool,
+}
+
+impl KeyInput {
+    pub fn new(input: NdiInput, fill_premultiplied: bool) -> Self {
+        Self {
+            input,
+            recent: VecDeque::with_capacity(KEY_HISTORY),
+            fill_premultiplied,
+        }
+    }
+
+    /// Key a fill frame with the key frame of the same timestamp, or the latest
+    /// one when the two senders don't stamp their frames alike. None until the
+    /// first key frame arrives, so an unkeyed fill never goes on air.
+    pub fn key(&mut self, mut fill: NdiFrame) -> Option<NdiFrame> {
+        if let Some(frame) = self.input.latest_frame.lock().unwrap().take() {
+            if self.recent.len() == KEY_HISTORY {
+                self.recent.pop_front();
+            }
+            self.recent.push_back(frame);
+        }
+        let key = fill
+            .timestamp
+            .and_then(|t| self.recent.iter().rev().find(|k| k.timestamp == Some(t)))
+            .or_else(|| self.recent.back())?;
+        apply_key(&mut fill.image, &key.image, self.fill_premultiplied);
+        Some(fill)
+    }
+}
+
+/// Set each fill pixel's alpha to the key's BT.709 luma, times the fill's own
+/// alpha so letterbox bars stay clear. Both frames are processed to the canvas
+/// size with the same crop and fit, so they line up pixel for pixel.
+fn apply_key(fill: &mut RgbaImage, key: &RgbaImage, premultiplied: bool) {
+    if fill.dimensions() != key.dimensions() {
+        return;
+    }
+    for (f, k) in fill.pixels_mut().zip(key.pixels()) {
+        let luma = (54 * k[0] as u32 + 183 * k[1] as u32 + 19 * k[2] as u32) >> 8;
+        if premultiplied && luma > 0 {
+            for c in 0..3 {
+                f[c] = (f[c] as u32 * 255 / luma).min(255) as u8;
+            }
+        }
+        f[3] = (luma * f[3] as u32 / 255) as u8;
+    }
+}
@@ -0,0 +1,84 @@
+use ab_glyph::FontArc;
+use image::RgbaImage;
+
+use crate::ndi_output::OutputTally;
+use crate::text::{self, TextStyle};
+
+const PROGRAM: [u8; 4] = [220, 30, 30, 255];
+const PREVIEW: [u8; 4] = [30, 190, 60, 255];
+const IDLE: [u8; 4] = [90, 90, 90, 255];
+
+/// Source names with tally borders, drawn over the compare view so an
+/// operator can tell what is in the picture and whether it is on air.
+pub struct SourceLabels {
+    font: FontArc,
+    style: TextStyle,
+    border: u32,
+    margin: u32,
+}
+
+impl SourceLabels {
+    /// Labels sized for a canvas `height` pixels high. None (with a warning)
+    /// when the font can't be loaded.
+    pub fn load(font: Option<&str>, height: u32, channel: &str) -> Option<Self> {
+        let path = font.unwrap_or(text::DEFAULT_FONT);
+        let font = text::load_font(path)
+            .inspect_err(|e| {
+                tracing::warn!("Channel '{}': compare labels disabled: {}", channel, e)
+            })
+            .ok()?;
+        let size = (height as f32 / 40.0).max(12.0);
+        Some(Self {
+            font,
+            style: TextStyle {
+                size,
+                color: [255, 255, 255, 255],
+                background: [0, 0, 0, 200],
+                padding: (size / 3.0) as u32,
+            },
+            border: (size / 6.0).max(2.0) as u32,
+            margin: (size / 2.0) as u32,
+        })
+    }
+
+    /// Draw `sources` (top of the stack first) down the left edge, then the
+    /// output and its receiver count. Borders are red while a receiver has the
+    /// output on program, green on preview and grey otherwise.
+    pub fn draw(&self, canvas: &mut RgbaImage, sources: &[&str], output: &str, tally: OutputTally) {
+        let color = if tally.on_program {
+            PROGRAM
+        } else if tally.on_preview {
+            PREVIEW
+        } else {
+            IDLE
+        };
+        let receivers = format!(
+            "{} → {} receiver{}",
+            output,
+            tally.connections,
+            if tally.connections == 1 { "" } else { "s" }
+        );
+        let (margin, border) = (self.margin as i32, self.border as i32);
+        let mut y = margin + border;
+        for label in sources.iter().copied().chain([receivers.as_str()]) {
+            let lines = vec![label.to_string()];
+            let (w, h) = text::block_size(&self.font, &self.style, &lines);
+            let x = margin + border;
+            text::draw_lines(canvas, &self.font, &self.style, &lines, x, y);
+            // Outline around the box, outside it so the text stays readable
+            let outer_w = w + self.border * 2;
+            text::fill_rect(canvas, x - border, y - border, outer_w, self.border, color);
+            text::fill_rect(
+                canvas,
+                x - border,
+                y + h as i32,
+                outer_w,
+                self.border,
+                color,
+            );
+            text::fill_rect(canvas, x - border, y, self.border, h, color);
+            text::fill_rect(canvas, x + w as i32, y, self.border, h, color);
+            y += h as i32 + border * 2 + margin / 2;
+        }
+    }
+}
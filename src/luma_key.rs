@@ -0,0 +1,35 @@
+use image::RgbaImage;
+
+use crate::config::LumaKeyConfig;
+
+/// CPU luma key, used when the GPU compositor isn't available to run the
+/// built-in shader (`src/shaders/luma_key.wgsl`).
+pub struct LumaKey {
+    threshold: f32,
+    softness: f32,
+    invert: bool,
+}
+
+impl LumaKey {
+    pub fn new(config: &LumaKeyConfig) -> Self {
+        Self {
+            threshold: config.threshold,
+            softness: config.softness.max(0.0001),
+            invert: config.invert,
+        }
+    }
+
+    /// Make dark pixels (bright ones when inverted) transparent, keeping
+    /// existing alpha.
+    pub fn apply(&self, img: &mut RgbaImage) {
+        for px in img.pixels_mut() {
+            let mut luma =
+                (0.2126 * px[0] as f32 + 0.7152 * px[1] as f32 + 0.0722 * px[2] as f32) / 255.0;
+            if self.invert {
+                luma = 1.0 - luma;
+            }
+            let mask = ((luma - self.threshold) / self.softness).clamp(0.0, 1.0);
+            px[3] = (px[3] as f32 * mask).round() as u8;
+        }
+    }
+}
@@ -1,21 +1,68 @@
+mod audio;
+mod audio_mixer;
+mod audit;
 mod browser;
+mod capabilities;
+mod captions;
 mod channel;
+mod chroma_key;
+mod clock;
+mod color;
 mod compositor;
 mod config;
+mod control;
+mod control_stream;
+mod cpu_budget;
+mod data_binding;
+mod errors;
+mod events;
 #[cfg(feature = "gpu")]
 mod gpu_compositor;
 #[cfg(feature = "gpu")]
 mod gpu_context;
+mod gpu_recovery;
+mod hooks;
+mod hotkeys;
+mod image_overlay;
+mod key_fill;
+#[cfg(feature = "gpu")]
+mod labels;
+mod luma_key;
 mod ndi_input;
 mod ndi_output;
+mod ntp;
+mod overload;
+mod preflight;
+mod provision;
+mod rate_adapt;
+mod realtime;
+mod reload;
+mod replay;
+mod resources;
+mod runtime_info;
+mod schedule;
+mod scopes;
+mod slate;
+mod source;
+mod stats_history;
 mod status;
+mod status_page;
+mod supervisor;
+mod text;
+mod text_overlay;
+mod timer;
+mod transition;
+mod tui;
+mod virtual_camera;
 
 use clap::Parser;
+use std::collections::HashMap;
+use std::io::IsTerminal;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio_util::sync::CancellationToken;
 
-use channel::ChannelState;
+use channel::{ChannelRegistry, ChannelState};
 
 #[derive(Parser)]
 #[command(
@@ -31,12 +78,52 @@ struct Cli {
     /// List available NDI sources and exit
     #[arg(long)]
     list_sources: bool,
+
+    /// Compositing path, overriding `compositor` in [settings]
+    #[arg(long, value_enum)]
+    compositor: Option<config::CompositorMode>,
+
+    /// Run preflight checks (config, NDI runtime, GPU, Chromium, shaders, port) and exit
+    #[arg(long)]
+    check: bool,
+
+    /// Print plain status lines instead of the interactive dashboard
+    #[arg(long)]
+    no_tui: bool,
+
+    /// No live terminal status; log a one-line summary per channel periodically (for systemd, Docker)
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// Read newline-delimited JSON commands on stdin and reply on stdout (implies --quiet)
+    #[arg(long)]
+    control_stdin: bool,
+
+    /// Run inputs, overlays and compositing without NDI outputs, writing PNG snapshots instead
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Directory for --dry-run snapshots (one PNG per output, rewritten every 5 seconds)
+    #[arg(long, default_value = "snapshots")]
+    snapshot_dir: PathBuf,
+
+    /// Print one line of JSON with the resolved channels, compositor, GPU adapter and
+    /// listening ports once started, then keep running
+    #[arg(long)]
+    print_runtime_json: bool,
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
+    // Handle --check before NDI init so a missing runtime is reported, not fatal
+    if cli.check {
+        let report = preflight::check(&cli.config, cli.compositor);
+        report.print();
+        return report.into_result();
+    }
+
     // Initialize NDI (needed for --list-sources before config is loaded)
     let ndi = grafton_ndi::NDI::new()?;
 
@@ -59,15 +146,35 @@ async fn main() -> anyhow::Result<()> {
     }
 
     // Load config
-    let config = config::Config::load(&cli.config)?;
+    let mut config = config::Config::load(&cli.config)?;
+    let config_watcher = reload::Watcher::new(&cli.config, &config);
+    if cli.dry_run {
+        config.settings.snapshot_dir = Some(cli.snapshot_dir.clone());
+    }
+
+    // The dashboard takes over the terminal, so logs go to its log pane instead
+    let quiet = cli.quiet || cli.control_stdin || !config.settings.terminal_status;
+    let use_tui = !quiet && !cli.no_tui && std::io::stdout().is_terminal();
+    let log_tail = tui::LogTail::default();
 
     // Initialize logging with level from config
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(&config.settings.log_level)),
-        )
-        .init();
+    let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(&config.settings.log_level));
+    if use_tui {
+        tracing_subscriber::fmt()
+            .with_env_filter(env_filter)
+            .with_ansi(false)
+            .with_writer(log_tail.clone())
+            .init();
+    } else if cli.control_stdin {
+        // stdout carries command replies
+        tracing_subscriber::fmt()
+            .with_env_filter(env_filter)
+            .with_writer(std::io::stderr)
+            .init();
+    } else {
+        tracing_subscriber::fmt().with_env_filter(env_filter).init();
+    }
 
     tracing::info!(
         "NDI Mixer v{} starting with {} channel{}",
@@ -75,63 +182,254 @@ async fn main() -> anyhow::Result<()> {
         config.channel.len(),
         if config.channel.len() == 1 { "" } else { "s" }
     );
+    if !config.auto_channel.is_empty() {
+        tracing::info!(
+            "{} auto channel rule{} will provision channels as sources appear",
+            config.auto_channel.len(),
+            if config.auto_channel.len() == 1 {
+                ""
+            } else {
+                "s"
+            }
+        );
+    }
+
+    if let Some(ref dir) = config.settings.snapshot_dir {
+        tracing::warn!(
+            "Dry run: no NDI outputs will be created, snapshots go to {}",
+            dir.display()
+        );
+    }
+
+    realtime::init(config.settings.realtime.clone());
+
+    // Catch a missing browser or a taken port before any channel starts
+    let report = preflight::startup(&config);
+    report.log();
+    report.into_result()?;
 
     let cancel = CancellationToken::new();
 
     // Launch shared browser if any channel needs it
     let shared_browser = if config.has_browser_overlays() {
         tracing::info!("Launching headless browser for overlays...");
-        Some(browser::SharedBrowser::launch().await?)
+        let pool = config.settings.page_pool;
+        let prewarm = pool
+            .prewarm
+            .unwrap_or_else(|| config.enabled_browser_overlays());
+        Some(browser::SharedBrowser::launch(prewarm, pool.spare).await?)
     } else {
         None
     };
 
-    // Initialize GPU compositor if feature enabled
+    // Initialize GPU compositor if feature enabled and not pinned to CPU (or a
+    // channel pins itself to the GPU); channels get the resolved mode through
+    // their settings
+    let compositor = cli.compositor.unwrap_or(config.settings.compositor);
+    config.settings.compositor = compositor;
     #[cfg(feature = "gpu")]
-    let gpu_ctx = gpu_context::GpuContext::try_new();
+    let gpu_ctx = match compositor {
+        config::CompositorMode::Auto => gpu_context::GpuContext::try_new(),
+        config::CompositorMode::Cpu if config.channels_require_gpu() => {
+            tracing::info!(
+                "Compositor pinned to CPU, except for channels with compositor = \"gpu\""
+            );
+            gpu_context::GpuContext::try_new()
+        }
+        config::CompositorMode::Cpu => {
+            tracing::info!("Compositor pinned to CPU");
+            None
+        }
+        config::CompositorMode::Gpu => {
+            Some(gpu_context::GpuContext::try_new().ok_or_else(|| {
+                anyhow::anyhow!("GPU compositor requested, but no usable GPU adapter was found")
+            })?)
+        }
+    };
     #[cfg(not(feature = "gpu"))]
-    let gpu_ctx: Option<std::sync::Arc<()>> = None;
+    let gpu_ctx: Option<std::sync::Arc<()>> = match compositor {
+        config::CompositorMode::Gpu => anyhow::bail!(
+            "GPU compositor requested, but this build lacks the `gpu` feature (rebuild with --features gpu)"
+        ),
+        _ => None,
+    };
 
-    // Start channels
-    let mut channels = Vec::new();
+    // Slate hotkeys: the global key toggles every channel, channel keys just their own
+    let mut hotkey_bindings: Vec<(char, control::Command)> = Vec::new();
+    if let Some(key) = config.settings.slate.hotkey {
+        hotkey_bindings.push((key, control::Command::ToggleSlate { channel: None }));
+    }
     for ch_config in &config.channel {
-        let ch = channel::Channel::start(
-            ch_config,
-            &ndi,
-            shared_browser.as_ref().map(|b| b.browser()),
-            gpu_ctx.clone(),
-            cancel.clone(),
-        )
-        .await?;
-        channels.push(ch);
+        if let Some(key) = ch_config.slate.as_ref().and_then(|s| s.hotkey) {
+            hotkey_bindings.push((
+                key,
+                control::Command::ToggleSlate {
+                    channel: Some(ch_config.name.clone()),
+                },
+            ));
+        }
+    }
+    // Start enabled channels; the supervisor enables and disables them later on
+    let channel_states = ChannelRegistry::default();
+    let launcher = supervisor::Launcher {
+        settings: config.settings.clone(),
+        ndi: ndi.clone(),
+        inputs: ndi_input::InputManager::new(&ndi, config.settings.discovery),
+        overlays: shared_browser
+            .as_ref()
+            .map(|b| browser::OverlayManager::new(b.pages())),
+        gpu_ctx: gpu_ctx.clone(),
+        channels: channel_states.clone(),
+        cancel: cancel.clone(),
+    };
+    config_watcher.spawn(channel_states.clone(), cancel.clone());
+    let runtime_channels: Vec<runtime_info::ChannelInfo> = if cli.print_runtime_json {
+        config
+            .channel
+            .iter()
+            .map(runtime_info::ChannelInfo::of)
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let auto_channels = config.auto_channel.len();
+    let mut supervisor = supervisor::Supervisor::new(
+        std::mem::take(&mut config.channel),
+        config.sources.clone(),
+        launcher.clone(),
+    );
+    supervisor.start_enabled().await?;
+    tokio::spawn(supervisor.run(channel_states.requests()));
+
+    // Auto channels join and leave the registry as their sources come and go
+    if !config.auto_channel.is_empty() {
+        let provisioner = provision::Provisioner {
+            rules: std::mem::take(&mut config.auto_channel),
+            sources: config.sources.clone(),
+            launcher,
+        };
+        tokio::spawn(provisioner.run());
     }
 
-    // Collect Arc<ChannelState> for shared access
-    let channel_states: Vec<Arc<ChannelState>> =
-        channels.iter().map(|ch| ch.state.clone()).collect();
+    // Sample process CPU and memory for status reporting
+    let process_usage = Arc::new(std::sync::Mutex::new(resources::ProcessUsage::default()));
+    resources::spawn_sampler(
+        process_usage.clone(),
+        std::time::Duration::from_millis(config.settings.stats_interval_ms),
+        cancel.clone(),
+    );
 
     // Determine compositor mode label
     let compositor_mode: &str;
     #[cfg(feature = "gpu")]
     {
-        compositor_mode = if gpu_ctx.is_some() { "gpu" } else { "cpu" };
+        compositor_mode = if gpu_ctx.is_some() && compositor != config::CompositorMode::Cpu {
+            "gpu"
+        } else {
+            "cpu"
+        };
     }
     #[cfg(not(feature = "gpu"))]
     {
         compositor_mode = "cpu";
     }
 
+    // Audit log of control requests, shared by the HTTP API and the control stream
+    let audit = match config.settings.control_api.audit_log {
+        Some(ref path) => Some(Arc::new(audit::AuditLog::open(path)?)),
+        None => None,
+    };
+
+    // What this instance supports, for remote controllers
+    #[cfg(feature = "gpu")]
+    let gpu_info = gpu_ctx.as_ref().map(|ctx| capabilities::GpuInfo {
+        adapter: ctx.adapter.name.clone(),
+        backend: ctx.adapter.backend.to_str().to_string(),
+        driver: ctx.adapter.driver.clone(),
+    });
+    #[cfg(not(feature = "gpu"))]
+    let gpu_info = None;
+    let chromium_version = match shared_browser {
+        Some(ref browser) => browser.version().await,
+        None => None,
+    };
+    let capabilities = capabilities::Capabilities::new(
+        compositor_mode,
+        gpu_info,
+        grafton_ndi::NDI::version().ok(),
+        chromium_version,
+    );
+
     // Start HTTP status endpoint if configured
     let status_port = config.settings.status_port;
     if status_port > 0 {
+        let capabilities = capabilities.clone();
         let states_for_http = channel_states.clone();
         let compositor_str = compositor_mode.to_string();
+        let usage_for_http = process_usage.clone();
+        let control_api = config.settings.control_api.clone();
+        let audit = audit.clone();
         tokio::spawn(async move {
-            if let Err(e) = status::serve_http(states_for_http, &compositor_str, status_port).await {
+            if let Err(e) = status::serve_http(
+                states_for_http,
+                &compositor_str,
+                usage_for_http,
+                status_port,
+                &control_api,
+                audit,
+                capabilities,
+            )
+            .await
+            {
                 tracing::error!("Status HTTP server error: {}", e);
             }
         });
-        println!("Status: http://localhost:{}/status", status_port);
+        if !cli.control_stdin {
+            println!("Status: http://localhost:{}/status", status_port);
+        }
+    }
+
+    // Line-based control for setups without HTTP
+    if cli.control_stdin {
+        tokio::spawn(control_stream::serve_stdin(
+            channel_states.clone(),
+            audit.clone(),
+            cancel.clone(),
+        ));
+    }
+    if let Some(ref path) = config.settings.control_socket {
+        #[cfg(unix)]
+        {
+            let path = path.clone();
+            let states = channel_states.clone();
+            let audit = audit.clone();
+            let cancel = cancel.clone();
+            tokio::spawn(async move {
+                if let Err(e) = control_stream::serve_socket(&path, states, audit, cancel).await {
+                    tracing::error!("Control socket error: {}", e);
+                }
+            });
+        }
+        #[cfg(not(unix))]
+        tracing::warn!(
+            "control_socket {} ignored: Unix sockets are not supported here",
+            path
+        );
+    }
+
+    if cli.print_runtime_json {
+        runtime_info::RuntimeInfo::new(
+            &cli.config,
+            runtime_channels,
+            auto_channels,
+            capabilities,
+            runtime_info::Listen {
+                status_port: (status_port > 0).then_some(status_port),
+                control_socket: config.settings.control_socket.clone(),
+                control_stdin: cli.control_stdin,
+            },
+        )
+        .print();
     }
 
     // Ctrl+C handler
@@ -142,36 +440,162 @@ async fn main() -> anyhow::Result<()> {
         cancel_clone.cancel();
     });
 
+    if use_tui {
+        let dashboard = tui::Dashboard {
+            channels: channel_states.clone(),
+            compositor: compositor_mode.to_string(),
+            usage: process_usage.clone(),
+            status_port,
+            hotkeys: hotkey_bindings,
+            log: log_tail,
+        };
+        let tui_cancel = cancel.clone();
+        tokio::task::spawn_blocking(move || tui::run(dashboard, tui_cancel)).await??;
+        run_stopped_hooks(&channel_states).await;
+        println!("NDI Mixer stopped.");
+        return Ok(());
+    }
+
+    // Stdin belongs to the control stream when --control-stdin is set
+    let _terminal_guard = (!hotkey_bindings.is_empty() && !cli.control_stdin)
+        .then(|| hotkeys::spawn(hotkey_bindings, channel_states.clone()));
+
+    if quiet {
+        log_status_periodically(
+            &channel_states,
+            config.settings.status_log_interval,
+            &cancel,
+        )
+        .await;
+        run_stopped_hooks(&channel_states).await;
+        tracing::info!("NDI Mixer stopped.");
+        return Ok(());
+    }
+
     // Print status periodically until cancelled
     loop {
         if cancel.is_cancelled() {
             break;
         }
-        print_terminal_status(&channel_states, compositor_mode);
+        let usage = process_usage.lock().unwrap().clone();
+        print_terminal_status(&channel_states.snapshot(), compositor_mode, &usage);
         tokio::time::sleep(std::time::Duration::from_secs(1)).await;
     }
 
+    run_stopped_hooks(&channel_states).await;
     println!("\nNDI Mixer stopped.");
     Ok(())
 }
 
-fn print_terminal_status(channels: &[Arc<ChannelState>], compositor: &str) {
+/// Run every channel's `stopped` hooks and wait for them before exiting.
+async fn run_stopped_hooks(channels: &ChannelRegistry) {
+    futures::future::join_all(
+        channels
+            .snapshot()
+            .iter()
+            .map(|ch| ch.hooks.run(config::HookEvent::Stopped)),
+    )
+    .await;
+}
+
+/// Quiet mode: log one status line per channel every `interval` seconds until cancelled.
+async fn log_status_periodically(
+    channels: &ChannelRegistry,
+    interval: u64,
+    cancel: &CancellationToken,
+) {
+    if interval == 0 {
+        cancel.cancelled().await;
+        return;
+    }
+    let interval = std::time::Duration::from_secs(interval);
+    // Keyed by name: auto channels may come and go between summaries
+    let mut last_frames: HashMap<String, u64> = channels
+        .snapshot()
+        .iter()
+        .map(|ch| (ch.name.clone(), *ch.frames_output.lock().unwrap()))
+        .collect();
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = tokio::time::sleep(interval) => {}
+        }
+        let channels = channels.snapshot();
+        last_frames.retain(|name, _| channels.iter().any(|ch| ch.name == *name));
+        for ch in &channels {
+            let frames = *ch.frames_output.lock().unwrap();
+            let last = last_frames.entry(ch.name.clone()).or_insert(0);
+            let fps = frames.saturating_sub(*last) as f64 / interval.as_secs_f64();
+            *last = frames;
+
+            let ndi = match ch.ndi_source {
+                Some(ref src) => format!(
+                    "{} ({})",
+                    src.lock().unwrap().label(),
+                    ch.ndi_state.lock().unwrap().as_str()
+                ),
+                None => "-".to_string(),
+            };
+            let loaded = ch
+                .browser_overlays
+                .iter()
+                .filter(|b| *b.loaded.lock().unwrap())
+                .count();
+            let overload = if ch.overload.lock().unwrap().active {
+                format!(" overload={}", ch.overload_policy.as_str())
+            } else {
+                String::new()
+            };
+            let slate = if *ch.slate_active.lock().unwrap() {
+                " SLATE"
+            } else if ch.output_blank.lock().unwrap().is_some() {
+                " BLANK"
+            } else if *ch.warming_up.lock().unwrap() {
+                " WARMUP"
+            } else {
+                ""
+            };
+            tracing::info!(
+                "Status '{}': ndi={} overlays={}/{} fps={:.1} frames={}{}{}",
+                ch.name,
+                ndi,
+                loaded,
+                ch.browser_overlays.len(),
+                fps,
+                frames,
+                overload,
+                slate
+            );
+        }
+    }
+}
+
+fn print_terminal_status(
+    channels: &[Arc<ChannelState>],
+    compositor: &str,
+    usage: &resources::ProcessUsage,
+) {
     print!("\x1b[2J\x1b[H"); // Clear screen, cursor to top
     println!(
-        "NDI Mixer v{} — {} channel{} active ({})\n",
+        "NDI Mixer v{} — {} channel{} active ({}) — CPU {:.0}%, RSS {} MB\n",
         env!("CARGO_PKG_VERSION"),
         channels.len(),
         if channels.len() == 1 { "" } else { "s" },
-        compositor.to_uppercase()
+        compositor.to_uppercase(),
+        usage.cpu_percent,
+        usage.rss_bytes / (1024 * 1024)
     );
 
     for ch in channels {
         let ndi_status = if let Some(ref src) = ch.ndi_source {
-            let connected = *ch.ndi_connected.lock().unwrap();
-            if connected {
-                format!("NDI: \x1b[32m+\x1b[0m {}", src)
-            } else {
-                format!("NDI: \x1b[33m~\x1b[0m {}", src)
+            let src = src.lock().unwrap().label().to_string();
+            match *ch.ndi_state.lock().unwrap() {
+                ndi_input::SourceState::Connected => format!("NDI: \x1b[32m+\x1b[0m {}", src),
+                ndi_input::SourceState::Searching => format!("NDI: \x1b[33m~\x1b[0m {}", src),
+                ndi_input::SourceState::Lost => format!("NDI: \x1b[31mx\x1b[0m {} (lost)", src),
+                ndi_input::SourceState::TimedOut => {
+                    format!("NDI: \x1b[31mx\x1b[0m {} (not found)", src)
+                }
             }
         } else {
             "NDI: -".to_string()
@@ -185,8 +609,18 @@ fn print_terminal_status(channels: &[Arc<ChannelState>], compositor: &str) {
                 .iter()
                 .filter(|b| *b.loaded.lock().unwrap())
                 .count();
+            let failed_count = ch
+                .browser_overlays
+                .iter()
+                .filter(|b| b.error.lock().unwrap().is_some())
+                .count();
             let total = ch.browser_overlays.len();
-            if loaded_count == total {
+            if failed_count > 0 {
+                format!(
+                    "Browser: \x1b[31mx\x1b[0m {}/{} failed",
+                    failed_count, total
+                )
+            } else if loaded_count == total {
                 if total == 1 {
                     "Browser: \x1b[32m+\x1b[0m loaded".to_string()
                 } else {
@@ -201,17 +635,54 @@ fn print_terminal_status(channels: &[Arc<ChannelState>], compositor: &str) {
         };
 
         let frames = *ch.frames_output.lock().unwrap();
+        let rates = ch.frame_rates.lock().unwrap().clone();
+        // Incoming layer rates next to the output's, to spot which source stalls
+        let mut layer_rates = Vec::new();
+        if let Some(fps) = rates.ndi {
+            layer_rates.push(format!("ndi {:.1}", fps));
+        }
+        for fps in &rates.overlays {
+            layer_rates.push(format!("web {:.1}", fps));
+        }
+        let layer_rates = if layer_rates.is_empty() {
+            String::new()
+        } else {
+            format!(" <- {}", layer_rates.join(", "))
+        };
+
+        let overload_status = if ch.overload.lock().unwrap().active {
+            format!(
+                "  \x1b[33m[overload: {}]\x1b[0m",
+                ch.overload_policy.as_str()
+            )
+        } else {
+            String::new()
+        };
+
+        let slate_status = if *ch.slate_active.lock().unwrap() {
+            "  \x1b[41;97m SLATE \x1b[0m"
+        } else if ch.output_blank.lock().unwrap().is_some() {
+            "  \x1b[40;97m BLANK \x1b[0m"
+        } else if *ch.warming_up.lock().unwrap() {
+            "  \x1b[36m[warming up]\x1b[0m"
+        } else {
+            ""
+        };
 
         println!(
-            "  {:<16} {}  |  {}  |  Out: {} ({}x{}@{}) [{}f]",
+            "  {:<16} {}  |  {}  |  Out: {} ({}x{}@{}) [{:.1}fps{}, {}f]{}{}",
             ch.name,
             ndi_status,
             browser_status,
-            ch.output_name,
+            ch.output_name.lock().unwrap(),
             ch.width,
             ch.height,
             ch.frame_rate,
-            frames
+            rates.output,
+            layer_rates,
+            frames,
+            overload_status,
+            slate_status
         );
     }
     println!();
@@ -1,160 +1,1067 @@
 use anyhow::Result;
 use grafton_ndi::{
-    Finder, FinderOptions, Receiver, ReceiverColorFormat, ReceiverOptions, Source, NDI,
+    Finder, FinderOptions, PixelFormat, Receiver, ReceiverColorFormat, ReceiverOptions, ScanType,
+    Source, Tally, VideoFrame, NDI,
 };
 use image::{ImageBuffer, RgbaImage};
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Condvar, Mutex, Weak};
+use std::time::{Duration, Instant};
 use tokio_util::sync::CancellationToken;
 
+use crate::color::{ColorConversion, UyvyFrame};
+use crate::compositor::{Placement, Rect};
+use crate::config::{ColorPrimaries, ColorRange, DiscoveryConfig, Fit, Roi};
+use crate::errors::{record, ErrorCode, ErrorSlot};
+
+/// XML elements that carry closed captions in NDI metadata.
+const CAPTION_ELEMENTS: &[&str] = &["<C608", "<C708", "<CEA608", "<CEA708", "<ndi_captions"];
+
+/// Max caption packets held for the output before the oldest are dropped.
+const CAPTION_QUEUE_LIMIT: usize = 64;
+
+/// How long a connected source may go without sending video before it counts as lost.
+const SOURCE_LOST_AFTER: Duration = Duration::from_secs(3);
+
+/// Max frames queued for a render thread that takes every frame.
+const QUEUE_FRAMES: usize = 8;
+
+/// Where an input is in its source's lifecycle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SourceState {
+    /// Not found yet
+    #[default]
+    Searching,
+    /// Receiving frames
+    Connected,
+    /// Was connected, but frames stopped or the receiver failed
+    Lost,
+    /// Not found within `source_timeout`; still searching
+    TimedOut,
+}
+
+impl SourceState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SourceState::Searching => "searching",
+            SourceState::Connected => "connected",
+            SourceState::Lost => "lost",
+            SourceState::TimedOut => "timed_out",
+        }
+    }
+
+    /// Lost or timed out: the channel applies its `on_loss` policy.
+    pub fn is_down(&self) -> bool {
+        matches!(self, SourceState::Lost | SourceState::TimedOut)
+    }
+}
+
+/// Tally a channel passes upstream to the source of its input.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct InputTally {
+    pub on_program: bool,
+    pub on_preview: bool,
+}
+
+impl InputTally {
+    /// On program or preview for either tally, e.g. of two channels taking one source.
+    fn merge(self, other: Self) -> Self {
+        Self {
+            on_program: self.on_program || other.on_program,
+            on_preview: self.on_preview || other.on_preview,
+        }
+    }
+}
+
+/// Closed-caption metadata picked off the input. Captions sent as standalone
+/// metadata frames wait here to be forwarded the same way; captions carried
+/// by a video frame travel with it (see `NdiFrame::captions`).
+#[derive(Debug, Default)]
+pub struct CaptionTap {
+    /// Standalone caption metadata frames, with their timecode
+    pub pending: VecDeque<(String, i64)>,
+    pub received: u64,
+    pub forwarded: u64,
+    pub last: Option<String>,
+}
+
+/// An input's video format as received, before crop and resize.
+#[derive(Debug, Clone, Serialize)]
+pub struct InputFormat {
+    pub width: u32,
+    pub height: u32,
+    /// Frames per second; an interlaced frame carries two fields
+    pub frame_rate: f64,
+    /// progressive, interleaved, field0 or field1
+    pub scan: String,
+    /// As delivered by the NDI SDK, which depends on `receive_format`
+    pub pixel_format: String,
+}
+
+impl InputFormat {
+    fn of(frame: &VideoFrame) -> Self {
+        let frame_rate = if frame.frame_rate_d > 0 {
+            let fps = frame.frame_rate_n as f64 / frame.frame_rate_d as f64;
+            (fps * 1000.0).round() / 1000.0
+        } else {
+            0.0
+        };
+        Self {
+            width: frame.width as u32,
+            height: frame.height as u32,
+            frame_rate,
+            scan: format!("{:?}", frame.scan_type).to_lowercase(),
+            pixel_format: format!("{:?}", frame.pixel_format).to_lowercase(),
+        }
+    }
+
+    /// Whether the source runs at a noticeably different rate than `output_fps`
+    /// (more than 2% off, so 29.97 on a 30 fps channel doesn't count).
+    pub fn rate_mismatch(&self, output_fps: u32) -> bool {
+        self.frame_rate > 0.0 && (self.frame_rate / output_fps as f64 - 1.0).abs() > 0.02
+    }
+
+    /// Short form for logs and the dashboard, e.g. `1920x1080 29.97p`.
+    pub fn summary(&self) -> String {
+        let scan = if self.scan == "progressive" { "p" } else { "i" };
+        format!("{}x{} {}{}", self.width, self.height, self.frame_rate, scan)
+    }
+}
+
+/// What identifies a format change between frames.
+type FormatKey = (i32, i32, i32, i32, PixelFormat, ScanType);
+
+/// A received frame, already cropped, resized and color converted.
+#[derive(Clone)]
+pub struct NdiFrame {
+    pub image: RgbaImage,
+    /// NDI timestamp of the frame, if the sender set one
+    pub timestamp: Option<i64>,
+    pub received_at: Instant,
+    /// Caption metadata that came with the frame (caption passthrough only)
+    pub captions: Option<String>,
+}
+
+/// How received frames are processed before they reach the render thread.
+#[derive(Clone)]
+pub struct FrameProcessing {
+    /// Output size frames are resized to (the channel canvas)
+    pub width: u32,
+    pub height: u32,
+    pub roi: Option<Roi>,
+    /// Placement of sources whose aspect ratio differs from the canvas
+    pub fit: Fit,
+    pub range: ColorRange,
+    pub primaries: ColorPrimaries,
+    /// Request native UYVY and leave conversion to the GPU compositor
+    pub uyvy: bool,
+    /// The channel's frame rate, to warn about sources running at another rate
+    pub frame_rate: u32,
+    /// Queue every frame in `NdiInput::queue` instead of keeping only the
+    /// latest (for standards conversion)
+    pub queue: bool,
+}
+
+impl FrameProcessing {
+    /// Hash of a source name and these settings. Inputs with equal keys produce
+    /// identical frames, so their GPU uploads can be shared.
+    fn source_key(&self, source_name: &str) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        source_name.hash(&mut hasher);
+        self.width.hash(&mut hasher);
+        self.height.hash(&mut hasher);
+        self.roi.hash(&mut hasher);
+        self.fit.hash(&mut hasher);
+        self.range.hash(&mut hasher);
+        self.primaries.hash(&mut hasher);
+        self.uyvy.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// Where the receive thread reports its progress for status.
+struct InputReport {
+    state: Arc<Mutex<SourceState>>,
+    frames_received: Arc<Mutex<u64>>,
+    last_error: ErrorSlot,
+    format: Arc<Mutex<Option<InputFormat>>>,
+}
+
+/// A channel's NDI input. Its slots outlive source switches, so the render
+/// thread and status keep reading the same ones.
+#[derive(Clone)]
 pub struct NdiInput {
-    pub latest_frame: Arc<Mutex<Option<RgbaImage>>>,
-    pub connected: Arc<Mutex<bool>>,
+    pub latest_frame: Arc<Mutex<Option<NdiFrame>>>,
+    /// Frames received as UYVY (only in UYVY mode; sources with alpha still arrive as RGBA)
+    pub latest_uyvy: Arc<Mutex<Option<UyvyFrame>>>,
+    /// Every frame since the render thread last looked, oldest first (only
+    /// with `FrameProcessing::queue`; then `latest_frame` stays empty)
+    pub queue: Option<Arc<Mutex<VecDeque<NdiFrame>>>>,
+    pub state: Arc<Mutex<SourceState>>,
     pub frames_received: Arc<Mutex<u64>>,
-    _thread: std::thread::JoinHandle<()>,
+    pub last_error: ErrorSlot,
+    /// Format of the most recent frame received
+    pub format: Arc<Mutex<Option<InputFormat>>>,
+    /// Present when caption passthrough is enabled
+    pub captions: Option<Arc<Mutex<CaptionTap>>>,
+    /// Identifies this input's frames across channels (see `FrameProcessing::source_key`)
+    pub source_key: Arc<Mutex<u64>>,
+    /// Set by the channel, and sent on to the source by the receiver
+    pub tally: Arc<Mutex<InputTally>>,
+    /// The receiver this input gets its frames from
+    pub share: InputShare,
+    manager: InputManager,
+    subscription: Arc<Mutex<Subscription>>,
+}
+
+/// What an input is subscribed to, kept to subscribe again on a source switch.
+struct Subscription {
+    source: String,
+    groups: Option<String>,
+    processing: FrameProcessing,
+    source_timeout: Option<Duration>,
+    /// The channel's token
+    channel: CancellationToken,
+    /// Ends the current subscription; a child of `channel`
+    cancel: CancellationToken,
 }
 
 impl NdiInput {
-    pub fn start(
-        ndi: &NDI,
-        source_name: &str,
-        target_width: u32,
-        target_height: u32,
-        cancel: CancellationToken,
-    ) -> Result<Self> {
-        let latest_frame: Arc<Mutex<Option<RgbaImage>>> = Arc::new(Mutex::new(None));
-        let connected: Arc<Mutex<bool>> = Arc::new(Mutex::new(false));
-        let frames_received: Arc<Mutex<u64>> = Arc::new(Mutex::new(0));
-
-        let frame_ref = latest_frame.clone();
-        let connected_ref = connected.clone();
-        let frames_ref = frames_received.clone();
-        let name = source_name.to_string();
-        let ndi = ndi.clone();
-
-        let thread = std::thread::Builder::new()
-            .name(format!("ndi-in-{}", source_name))
-            .spawn(move || {
-                if let Err(e) = receive_loop(
-                    &ndi,
-                    &name,
-                    target_width,
-                    target_height,
-                    frame_ref,
-                    connected_ref,
-                    frames_ref,
-                    cancel,
-                ) {
-                    tracing::error!("NDI input '{}' error: {}", name, e);
-                }
-            })
-            .expect("Failed to spawn NDI input thread");
-
-        Ok(Self {
-            latest_frame,
-            connected,
-            frames_received,
-            _thread: thread,
-        })
+    /// Move the input to another source in the same groups. The old
+    /// subscription ends at once, stopping its receiver unless other channels
+    /// still take it, and a new search starts. The last frame of the old source
+    /// stays in place until the new one delivers.
+    pub fn switch_source(&self, source_name: &str) {
+        let mut subscription = self.subscription.lock().unwrap();
+        if subscription.source == source_name {
+            return;
+        }
+        subscription.cancel.cancel();
+        // Waits out a delivery in progress, so nothing of the old source follows
+        self.share
+            .receiver()
+            .subscribers
+            .lock()
+            .unwrap()
+            .retain(|s| !s.cancel.is_cancelled());
+        tracing::info!(
+            "NDI input: switching from '{}' to '{}'",
+            subscription.source,
+            source_name
+        );
+        subscription.source = source_name.to_string();
+        subscription.cancel = subscription.channel.child_token();
+        *self.state.lock().unwrap() = SourceState::Searching;
+        self.manager.attach(self, &subscription);
     }
 }
 
-fn receive_loop(
-    ndi: &NDI,
-    source_name: &str,
-    target_width: u32,
-    target_height: u32,
-    latest_frame: Arc<Mutex<Option<RgbaImage>>>,
-    connected: Arc<Mutex<bool>>,
-    frames_received: Arc<Mutex<u64>>,
+/// Inputs with equal keys are served by one receiver.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ReceiverKey {
+    source: String,
+    groups: Option<String>,
+    uyvy: bool,
+}
+
+/// Hands out NDI inputs, opening one receiver per source no matter how many
+/// channels take it. Each channel subscribes with its own size, crop and color
+/// settings; channels with identical settings also share the processed frame.
+#[derive(Clone)]
+pub struct InputManager {
+    ndi: NDI,
+    finder: SourceFinder,
+    receivers: Arc<Mutex<HashMap<ReceiverKey, Arc<SharedReceiver>>>>,
+}
+
+/// A channel's view of the receiver behind its input, which changes when the
+/// input switches source.
+#[derive(Clone)]
+pub struct InputShare(Arc<Mutex<Arc<SharedReceiver>>>);
+
+impl InputShare {
+    /// Channels currently taking frames from the receiver.
+    pub fn channels(&self) -> usize {
+        self.receiver().subscribers.lock().unwrap().len()
+    }
+
+    fn receiver(&self) -> Arc<SharedReceiver> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+#[derive(Default)]
+struct SharedReceiver {
+    subscribers: Mutex<Vec<Subscriber>>,
+    /// Format of the most recent frame, for channels subscribing later
+    format: Mutex<Option<InputFormat>>,
+}
+
+/// One channel's input on a shared receiver.
+struct Subscriber {
+    processing: FrameProcessing,
+    color: Option<ColorConversion>,
+    source_key: u64,
+    latest_frame: Arc<Mutex<Option<NdiFrame>>>,
+    latest_uyvy: Arc<Mutex<Option<UyvyFrame>>>,
+    queue: Option<Arc<Mutex<VecDeque<NdiFrame>>>>,
+    report: InputReport,
+    captions: Option<Arc<Mutex<CaptionTap>>>,
+    tally: Arc<Mutex<InputTally>>,
+    source_timeout: Option<Duration>,
+    subscribed_at: Instant,
+    roi_warned: bool,
+    /// The subscription ends when it's cancelled (see `Subscription::cancel`)
     cancel: CancellationToken,
-) -> Result<()> {
-    tracing::info!("NDI input: searching for source '{}'...", source_name);
+}
 
-    // Find the source (blocking search on this dedicated thread)
-    let source = find_source(ndi, source_name, &cancel)?;
-    tracing::info!("NDI input: found source '{}'", source_name);
+impl InputManager {
+    pub fn new(ndi: &NDI, discovery: DiscoveryConfig) -> Self {
+        Self {
+            ndi: ndi.clone(),
+            finder: SourceFinder::new(ndi, discovery),
+            receivers: Arc::default(),
+        }
+    }
 
-    // Create receiver with RGBA color format
-    let recv_opts = ReceiverOptions::builder(source)
-        .color(ReceiverColorFormat::RGBX_RGBA)
-        .build();
-    let receiver = Receiver::new(ndi, &recv_opts)?;
+    pub fn ndi(&self) -> &NDI {
+        &self.ndi
+    }
 
-    *connected.lock().unwrap() = true;
+    /// The finders every input searches with.
+    pub fn finder(&self) -> &SourceFinder {
+        &self.finder
+    }
 
-    loop {
-        if cancel.is_cancelled() {
-            break;
+    /// Subscribe to a source, starting a receiver for it unless one is already
+    /// running. The subscription ends when `cancel` is cancelled; the receiver
+    /// stops with its last subscriber.
+    pub fn subscribe(
+        &self,
+        source_name: &str,
+        groups: Option<&str>,
+        processing: FrameProcessing,
+        source_timeout: Option<Duration>,
+        caption_passthrough: bool,
+        cancel: CancellationToken,
+    ) -> NdiInput {
+        let subscription = Subscription {
+            source: source_name.to_string(),
+            groups: groups.map(str::to_string),
+            processing,
+            source_timeout,
+            cancel: cancel.child_token(),
+            channel: cancel,
+        };
+        let input = NdiInput {
+            latest_frame: Arc::default(),
+            latest_uyvy: Arc::default(),
+            queue: subscription.processing.queue.then(Arc::default),
+            state: Arc::new(Mutex::new(SourceState::Searching)),
+            frames_received: Arc::default(),
+            last_error: ErrorSlot::default(),
+            format: Arc::default(),
+            captions: caption_passthrough.then(|| Arc::new(Mutex::new(CaptionTap::default()))),
+            source_key: Arc::default(),
+            tally: Arc::default(),
+            // Set by `attach`
+            share: InputShare(Arc::default()),
+            manager: self.clone(),
+            subscription: Arc::new(Mutex::new(subscription)),
+        };
+        self.attach(&input, &input.subscription.lock().unwrap());
+        input
+    }
+
+    /// Add `input` to the receiver for its subscription's source.
+    fn attach(&self, input: &NdiInput, subscription: &Subscription) {
+        let source_name = subscription.source.as_str();
+        let processing = subscription.processing.clone();
+        let key = ReceiverKey {
+            source: subscription.source.clone(),
+            groups: subscription.groups.clone(),
+            uyvy: processing.uyvy,
+        };
+        let mut receivers = self.receivers.lock().unwrap();
+        let (shared, started) = match receivers.get(&key) {
+            Some(shared) => (shared.clone(), false),
+            None => {
+                let shared: Arc<SharedReceiver> = Arc::default();
+                receivers.insert(key.clone(), shared.clone());
+                (shared, true)
+            }
+        };
+
+        *input.format.lock().unwrap() = shared.format.lock().unwrap().clone();
+        let source_key = processing.source_key(source_name);
+        *input.source_key.lock().unwrap() = source_key;
+        *input.share.0.lock().unwrap() = shared.clone();
+        let subscriber = Subscriber {
+            color: ColorConversion::new(processing.range, processing.primaries),
+            source_key,
+            processing,
+            latest_frame: input.latest_frame.clone(),
+            latest_uyvy: input.latest_uyvy.clone(),
+            queue: input.queue.clone(),
+            report: InputReport {
+                state: input.state.clone(),
+                frames_received: input.frames_received.clone(),
+                last_error: input.last_error.clone(),
+                format: input.format.clone(),
+            },
+            captions: input.captions.clone(),
+            tally: input.tally.clone(),
+            source_timeout: subscription.source_timeout,
+            subscribed_at: Instant::now(),
+            roi_warned: false,
+            cancel: subscription.cancel.clone(),
+        };
+        shared.subscribers.lock().unwrap().push(subscriber);
+        drop(receivers);
+
+        if started {
+            let manager = self.clone();
+            std::thread::Builder::new()
+                .name(format!("ndi-in-{}", source_name))
+                .spawn(move || manager.run_receiver(key, shared))
+                .expect("Failed to spawn NDI input thread");
+        } else {
+            tracing::info!(
+                "NDI input: sharing the receiver for '{}' with another channel",
+                source_name
+            );
         }
+    }
 
-        // Poll for a video frame with short timeout
-        match receiver.capture_video_timeout(Duration::from_millis(100)) {
-            Ok(Some(frame)) => {
-                let w = frame.width as u32;
-                let h = frame.height as u32;
-
-                if let Some(img) = ImageBuffer::from_raw(w, h, frame.data.clone()) {
-                    // Resize to target dimensions once on this thread, not per-render-frame
-                    let img = if w != target_width || h != target_height {
-                        image::imageops::resize(
-                            &img,
-                            target_width,
-                            target_height,
-                            image::imageops::FilterType::Nearest,
-                        )
-                    } else {
-                        img
-                    };
-                    *latest_frame.lock().unwrap() = Some(img);
-                    *frames_received.lock().unwrap() += 1;
+    fn run_receiver(&self, key: ReceiverKey, shared: Arc<SharedReceiver>) {
+        crate::realtime::apply_current_thread(crate::realtime::ThreadRole::Ndi);
+        let result = self
+            .connect(&key, &shared)
+            .and_then(|receiver| match receiver {
+                Some(receiver) => self.receive_loop(&receiver, &key, &shared),
+                None => Ok(()),
+            });
+        if let Err(e) = result {
+            tracing::error!("NDI input '{}' error: {}", key.source, e);
+            for subscriber in shared.subscribers.lock().unwrap().iter() {
+                record(&subscriber.report.last_error, ErrorCode::NdiInputFailed, &e);
+            }
+        }
+        // Channels subscribing from now on get a fresh receiver
+        let mut receivers = self.receivers.lock().unwrap();
+        if receivers.get(&key).is_some_and(|r| Arc::ptr_eq(r, &shared)) {
+            receivers.remove(&key);
+        }
+    }
+
+    /// Drop subscribers whose channel stopped. Returns false once none are
+    /// left, after retiring the receiver so new subscribers start another.
+    fn prune(&self, key: &ReceiverKey, shared: &Arc<SharedReceiver>) -> bool {
+        let mut receivers = self.receivers.lock().unwrap();
+        let mut subscribers = shared.subscribers.lock().unwrap();
+        subscribers.retain(|s| !s.cancel.is_cancelled());
+        if !subscribers.is_empty() {
+            return true;
+        }
+        if receivers.get(key).is_some_and(|r| Arc::ptr_eq(r, shared)) {
+            receivers.remove(key);
+        }
+        false
+    }
+
+    /// Find the source and open a receiver for it; None if every subscriber
+    /// left first. Subscribers whose `source_timeout` passes are marked
+    /// `TimedOut`, but the search goes on.
+    fn connect(&self, key: &ReceiverKey, shared: &Arc<SharedReceiver>) -> Result<Option<Receiver>> {
+        tracing::info!("NDI input: searching for source '{}'...", key.source);
+        let found = self.finder.find(&key.source, key.groups.as_deref(), || {
+            if !self.prune(key, shared) {
+                return false;
+            }
+            for subscriber in shared.subscribers.lock().unwrap().iter() {
+                subscriber.check_timeout(&key.source);
+            }
+            true
+        });
+        let Some(source) = found else {
+            return Ok(None);
+        };
+        tracing::info!("NDI input: found source '{}'", key.source);
+
+        // RGBA: the SDK converts. UYVY: native 4:2:2 for opaque frames (converted on
+        // the GPU), RGBA for frames with alpha.
+        let color_format = if key.uyvy {
+            ReceiverColorFormat::UYVY_RGBA
+        } else {
+            ReceiverColorFormat::RGBX_RGBA
+        };
+        let recv_opts = ReceiverOptions::builder(source).color(color_format).build();
+        Ok(Some(Receiver::new(&self.ndi, &recv_opts)?))
+    }
+
+    fn receive_loop(
+        &self,
+        receiver: &Receiver,
+        key: &ReceiverKey,
+        shared: &Arc<SharedReceiver>,
+    ) -> Result<()> {
+        let source_name = key.source.as_str();
+        let mut format_key: Option<FormatKey> = None;
+        // Connected once the first frame arrives
+        let mut last_frame_at: Option<Instant> = None;
+        let mut sent_tally: Option<InputTally> = None;
+
+        while self.prune(key, shared) {
+            // Tally of every channel taking the source, sent on when it changes
+            let tally = shared
+                .subscribers
+                .lock()
+                .unwrap()
+                .iter()
+                .fold(InputTally::default(), |tally, s| {
+                    tally.merge(*s.tally.lock().unwrap())
+                });
+            if sent_tally != Some(tally) {
+                sent_tally = Some(tally);
+                if let Err(e) = receiver.set_tally(&Tally::new(tally.on_program, tally.on_preview))
+                {
+                    tracing::warn!("NDI input '{}': sending tally failed: {}", source_name, e);
                 }
             }
-            Ok(None) => {
-                // Timeout, no frame available — brief yield
-                std::thread::sleep(Duration::from_millis(1));
+
+            // Captions sent as standalone metadata frames
+            let wants_captions = shared
+                .subscribers
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|s| s.captions.is_some());
+            if wants_captions {
+                while let Ok(Some(meta)) = receiver.capture_metadata_timeout(Duration::ZERO) {
+                    for subscriber in shared.subscribers.lock().unwrap().iter() {
+                        if let Some(ref tap) = subscriber.captions {
+                            push_caption(tap, &meta.data, meta.timecode);
+                        }
+                    }
+                }
             }
-            Err(e) => {
-                tracing::warn!("NDI receive error: {}", e);
-                *connected.lock().unwrap() = false;
-                std::thread::sleep(Duration::from_secs(1));
+
+            // Poll for a video frame with short timeout
+            match receiver.capture_video_timeout(Duration::from_millis(100)) {
+                Ok(Some(frame)) => {
+                    let timestamp = frame_timestamp(frame.timestamp);
+                    let received_at = Instant::now();
+                    last_frame_at = Some(received_at);
+                    let mut subscribers = shared.subscribers.lock().unwrap();
+
+                    let frame_format = (
+                        frame.width,
+                        frame.height,
+                        frame.frame_rate_n,
+                        frame.frame_rate_d,
+                        frame.pixel_format,
+                        frame.scan_type,
+                    );
+                    if format_key != Some(frame_format) {
+                        let changed = format_key.replace(frame_format).is_some();
+                        let format = InputFormat::of(&frame);
+                        match shared.format.lock().unwrap().as_ref() {
+                            // Laptops switching displays and the like change size mid-stream;
+                            // every frame is placed anew, so this only needs reporting
+                            Some(previous) if changed => {
+                                tracing::info!(
+                                    "NDI input '{}': format changed from {} {} to {} {}",
+                                    source_name,
+                                    previous.summary(),
+                                    previous.pixel_format,
+                                    format.summary(),
+                                    format.pixel_format
+                                );
+                            }
+                            _ => tracing::info!(
+                                "NDI input '{}': receiving {} {}",
+                                source_name,
+                                format.summary(),
+                                format.pixel_format
+                            ),
+                        }
+                        for subscriber in subscribers.iter() {
+                            if format.rate_mismatch(subscriber.processing.frame_rate) {
+                                tracing::warn!(
+                                    "NDI input '{}': source runs at {} fps but the channel outputs {} fps",
+                                    source_name,
+                                    format.frame_rate,
+                                    subscriber.processing.frame_rate
+                                );
+                            }
+                            *subscriber.report.format.lock().unwrap() = Some(format.clone());
+                        }
+                        *shared.format.lock().unwrap() = Some(format);
+                    }
+
+                    // Frames processed for earlier subscribers, by source key
+                    let mut processed: Vec<(u64, RgbaImage)> = Vec::new();
+                    let sharing = subscribers.len() > 1;
+                    for subscriber in subscribers.iter_mut() {
+                        mark_connected(&subscriber.report.state, source_name);
+                        if subscriber.deliver(
+                            &frame,
+                            timestamp,
+                            received_at,
+                            sharing.then_some(&mut processed),
+                            source_name,
+                        ) {
+                            *subscriber.report.frames_received.lock().unwrap() += 1;
+                        }
+                    }
+                }
+                Ok(None) => {
+                    if last_frame_at.is_some_and(|at| at.elapsed() >= SOURCE_LOST_AFTER) {
+                        for subscriber in shared.subscribers.lock().unwrap().iter() {
+                            if mark_lost(&subscriber.report.state, source_name) {
+                                record(
+                                    &subscriber.report.last_error,
+                                    ErrorCode::NdiSourceLost,
+                                    format!(
+                                        "no video from '{}' for {}s",
+                                        source_name,
+                                        SOURCE_LOST_AFTER.as_secs()
+                                    ),
+                                );
+                            }
+                        }
+                    }
+                    // Timeout, no frame available — brief yield
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+                Err(e) => {
+                    tracing::warn!("NDI receive error: {}", e);
+                    for subscriber in shared.subscribers.lock().unwrap().iter() {
+                        mark_lost(&subscriber.report.state, source_name);
+                        record(
+                            &subscriber.report.last_error,
+                            ErrorCode::NdiReceiveFailed,
+                            &e,
+                        );
+                    }
+                    std::thread::sleep(Duration::from_secs(1));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Subscriber {
+    /// Mark the input timed out once `source_timeout` has passed without the source.
+    fn check_timeout(&self, source_name: &str) {
+        let Some(timeout) = self.source_timeout else {
+            return;
+        };
+        let mut state = self.report.state.lock().unwrap();
+        if *state != SourceState::Searching || self.subscribed_at.elapsed() < timeout {
+            return;
+        }
+        *state = SourceState::TimedOut;
+        tracing::warn!(
+            "NDI input: source '{}' not found after {}s, still searching",
+            source_name,
+            timeout.as_secs()
+        );
+        record(
+            &self.report.last_error,
+            ErrorCode::NdiSourceNotFound,
+            format!(
+                "source '{}' not found within {}s",
+                source_name,
+                timeout.as_secs()
+            ),
+        );
+    }
+
+    /// Process a received frame for this channel and hand it over. `processed`
+    /// holds frames already processed for other subscribers when the receiver
+    /// is shared. Returns false if the frame was skipped.
+    fn deliver(
+        &mut self,
+        frame: &VideoFrame,
+        timestamp: Option<i64>,
+        received_at: Instant,
+        processed: Option<&mut Vec<(u64, RgbaImage)>>,
+        source_name: &str,
+    ) -> bool {
+        let (w, h) = (frame.width as u32, frame.height as u32);
+        // Captions embedded in per-frame metadata stay with the frame
+        let captions = match (&self.captions, &frame.metadata) {
+            (Some(tap), Some(meta)) if carries_captions(meta) => {
+                note_caption(&mut tap.lock().unwrap(), meta);
+                Some(meta.clone())
+            }
+            _ => None,
+        };
+
+        // UYVY goes to the GPU untouched; crop, scale and color happen in one pass
+        if frame.pixel_format == PixelFormat::UYVY {
+            let mut latest = self.latest_uyvy.lock().unwrap();
+            // Captions of a frame the render thread never took ride on this one
+            let captions = join_captions(latest.take().and_then(|f| f.captions), captions);
+            *latest = Some(UyvyFrame {
+                width: w,
+                height: h,
+                data: frame.data.clone(),
+                roi: self.processing.roi,
+                fit: self.processing.fit,
+                range: self.processing.range,
+                primaries: self.processing.primaries,
+                timestamp,
+                received_at,
+                captions,
+            });
+            return true;
+        }
+
+        let image = match processed {
+            Some(processed) => match processed.iter().find(|(key, _)| *key == self.source_key) {
+                Some((_, image)) => image.clone(),
+                None => {
+                    let Some(image) = self.process(frame, source_name) else {
+                        return false;
+                    };
+                    processed.push((self.source_key, image.clone()));
+                    image
+                }
+            },
+            None => match self.process(frame, source_name) {
+                Some(image) => image,
+                None => return false,
+            },
+        };
+        let mut frame = NdiFrame {
+            image,
+            timestamp,
+            received_at,
+            captions,
+        };
+        // Captions of frames the render thread never takes ride on the next one
+        match self.queue {
+            Some(ref queue) => {
+                let mut queue = queue.lock().unwrap();
+                // A stalled render thread loses the oldest frames, not the newest
+                if queue.len() >= QUEUE_FRAMES {
+                    let dropped = queue.pop_front().and_then(|f| f.captions);
+                    match queue.front_mut() {
+                        Some(next) => next.captions = join_captions(dropped, next.captions.take()),
+                        None => frame.captions = join_captions(dropped, frame.captions),
+                    }
+                }
+                queue.push_back(frame);
+            }
+            None => {
+                let mut latest = self.latest_frame.lock().unwrap();
+                frame.captions =
+                    join_captions(latest.take().and_then(|f| f.captions), frame.captions);
+                *latest = Some(frame);
             }
         }
+        true
+    }
+
+    /// Crop, resize and color convert an RGBA frame for this channel.
+    fn process(&mut self, frame: &VideoFrame, source_name: &str) -> Option<RgbaImage> {
+        let (w, h) = (frame.width as u32, frame.height as u32);
+        let target = (self.processing.width, self.processing.height);
+
+        let region = match self.processing.roi {
+            Some(ref roi) => match roi_rect(roi, w, h) {
+                Some(rect) => rect,
+                None => {
+                    if !self.roi_warned {
+                        tracing::warn!(
+                            "NDI input '{}': roi is outside the {}x{} source",
+                            source_name,
+                            w,
+                            h
+                        );
+                        self.roi_warned = true;
+                    }
+                    return None;
+                }
+            },
+            None => (0, 0, w, h),
+        };
+        // Placed per frame, so a source changing size mid-stream is fitted anew
+        let placement = Placement::new(self.processing.fit, region, target);
+
+        // Crop first so the resize only touches the pixels we keep
+        let (_, _, crop_w, crop_h) = placement.src;
+        let data = if placement.src == (0, 0, w, h) {
+            frame.data.clone()
+        } else {
+            crop_rgba(&frame.data, w, placement.src)?
+        };
+
+        let img: RgbaImage = ImageBuffer::from_raw(crop_w, crop_h, data)?;
+        // Resize to target dimensions once on this thread, not per-render-frame
+        let (_, _, dst_w, dst_h) = placement.dst;
+        let mut img = if (crop_w, crop_h) != (dst_w, dst_h) {
+            image::imageops::resize(&img, dst_w, dst_h, image::imageops::FilterType::Nearest)
+        } else {
+            img
+        };
+        // Convert after resizing so it runs on the fewest pixels
+        if let Some(ref color) = self.color {
+            color.apply(&mut img);
+        }
+        if placement.letterboxed(target) {
+            let mut boxed = RgbaImage::new(target.0, target.1);
+            let (x, y, _, _) = placement.dst;
+            image::imageops::replace(&mut boxed, &img, x as i64, y as i64);
+            img = boxed;
+        }
+        Some(img)
+    }
+}
+
+fn mark_connected(state: &Mutex<SourceState>, source_name: &str) {
+    let mut state = state.lock().unwrap();
+    if *state == SourceState::Lost {
+        tracing::info!("NDI input: source '{}' is back", source_name);
+    }
+    *state = SourceState::Connected;
+}
+
+/// Returns true if the source was not already lost.
+fn mark_lost(state: &Mutex<SourceState>, source_name: &str) -> bool {
+    let mut state = state.lock().unwrap();
+    if *state == SourceState::Lost {
+        return false;
     }
+    tracing::warn!("NDI input: lost source '{}'", source_name);
+    *state = SourceState::Lost;
+    true
+}
 
-    Ok(())
+/// NDI reports 0 or i64::MAX when the sender didn't timestamp the frame.
+fn frame_timestamp(timestamp: i64) -> Option<i64> {
+    (timestamp != 0 && timestamp != i64::MAX).then_some(timestamp)
 }
 
-fn find_source(ndi: &NDI, source_name: &str, cancel: &CancellationToken) -> Result<Source> {
-    let finder_opts = FinderOptions::builder().show_local_sources(true).build();
-    let finder = Finder::new(ndi, &finder_opts)?;
+/// The region of interest clamped to the frame bounds. None if nothing of the
+/// region is inside the frame.
+fn roi_rect(roi: &Roi, width: u32, height: u32) -> Option<Rect> {
+    let x = roi.x.min(width);
+    let y = roi.y.min(height);
+    let crop_w = roi.width.min(width - x);
+    let crop_h = roi.height.min(height - y);
+    (crop_w > 0 && crop_h > 0).then_some((x, y, crop_w, crop_h))
+}
+
+/// Copy the `rect` region out of an RGBA frame `width` pixels wide.
+fn crop_rgba(data: &[u8], width: u32, (x, y, crop_w, crop_h): Rect) -> Option<Vec<u8>> {
+    let stride = width as usize * 4;
+    let row_len = crop_w as usize * 4;
+    let mut out = Vec::with_capacity(row_len * crop_h as usize);
+    for row in y..y + crop_h {
+        let start = row as usize * stride + x as usize * 4;
+        out.extend_from_slice(data.get(start..start + row_len)?);
+    }
+    Some(out)
+}
+
+fn carries_captions(metadata: &str) -> bool {
+    CAPTION_ELEMENTS.iter().any(|tag| metadata.contains(tag))
+}
+
+fn note_caption(tap: &mut CaptionTap, metadata: &str) {
+    tap.received += 1;
+    tap.last = Some(metadata.to_string());
+}
+
+/// Queue a standalone metadata frame for the output if it carries closed
+/// captions. The payload is forwarded untouched so 608/708 byte data survives intact.
+fn push_caption(tap: &Mutex<CaptionTap>, metadata: &str, timecode: i64) {
+    if !carries_captions(metadata) {
+        return;
+    }
+    let mut tap = tap.lock().unwrap();
+    if tap.pending.len() >= CAPTION_QUEUE_LIMIT {
+        tap.pending.pop_front();
+    }
+    tap.pending.push_back((metadata.to_string(), timecode));
+    note_caption(&mut tap, metadata);
+}
+
+/// Captions of two frames in order, as one metadata payload.
+fn join_captions(earlier: Option<String>, later: Option<String>) -> Option<String> {
+    match (earlier, later) {
+        (Some(earlier), Some(later)) => Some(earlier + &later),
+        (earlier, later) => earlier.or(later),
+    }
+}
+
+pub fn find_source(
+    finder: &SourceFinder,
+    source_name: &str,
+    groups: Option<&str>,
+    cancel: &CancellationToken,
+) -> Result<Source> {
+    finder
+        .find(source_name, groups, || !cancel.is_cancelled())
+        .ok_or_else(|| {
+            anyhow::anyhow!("Cancelled while searching for NDI source '{}'", source_name)
+        })
+}
+
+/// Runs one NDI finder per group list, each on its own thread, and shares
+/// what it sees: any number of inputs searching the same groups cause the
+/// discovery traffic of a single finder. A finder stops once nothing watches it.
+#[derive(Clone)]
+pub struct SourceFinder {
+    ndi: NDI,
+    config: DiscoveryConfig,
+    lists: Arc<Mutex<HashMap<Option<String>, Weak<SourceList>>>>,
+}
+
+/// What one group list's finder currently sees.
+struct SourceList {
+    /// None until the first discovery cycle ends
+    sources: Mutex<Option<Vec<Source>>>,
+    updated: Condvar,
+}
+
+/// The sources visible in one group list, kept current while this is held.
+pub struct SourceWatch {
+    list: Arc<SourceList>,
+    poll: Duration,
+}
+
+impl SourceWatch {
+    /// Sources seen in the latest discovery cycle; None before the first ended.
+    pub fn sources(&self) -> Option<Vec<Source>> {
+        self.list.sources.lock().unwrap().clone()
+    }
+
+    /// Block until the next discovery cycle ends, or `poll_ms` at most.
+    pub fn wait(&self) {
+        let sources = self.list.sources.lock().unwrap();
+        let _ = self.list.updated.wait_timeout(sources, self.poll);
+    }
+}
+
+impl SourceFinder {
+    pub fn new(ndi: &NDI, config: DiscoveryConfig) -> Self {
+        Self {
+            ndi: ndi.clone(),
+            config,
+            lists: Arc::default(),
+        }
+    }
+
+    /// Watch the sources in `groups` (default: public), starting a finder
+    /// for them unless one is already running.
+    pub fn watch(&self, groups: Option<&str>) -> SourceWatch {
+        let poll = Duration::from_millis(self.config.poll_ms);
+        let key = groups.map(str::to_string);
+        let mut lists = self.lists.lock().unwrap();
+        if let Some(list) = lists.get(&key).and_then(Weak::upgrade) {
+            return SourceWatch { list, poll };
+        }
+
+        let list = Arc::new(SourceList {
+            sources: Mutex::default(),
+            updated: Condvar::new(),
+        });
+        lists.insert(key.clone(), Arc::downgrade(&list));
+        let ndi = self.ndi.clone();
+        let weak = Arc::downgrade(&list);
+        let search = Duration::from_millis(self.config.search_ms);
+        std::thread::Builder::new()
+            .name(format!("ndi-find-{}", groups.unwrap_or("public")))
+            .spawn(move || discover(&ndi, key.as_deref(), &weak, search))
+            .expect("Failed to spawn NDI finder thread");
+        SourceWatch { list, poll }
+    }
+
+    /// Wait for a source whose name contains `name`. `keep_waiting` runs
+    /// between checks, at least every `poll_ms`; the search ends with None
+    /// once it returns false.
+    pub fn find(
+        &self,
+        name: &str,
+        groups: Option<&str>,
+        mut keep_waiting: impl FnMut() -> bool,
+    ) -> Option<Source> {
+        let watch = self.watch(groups);
+        loop {
+            let sources = watch.sources().unwrap_or_default();
+            if let Some(source) = sources.into_iter().find(|s| s.name.contains(name)) {
+                tracing::info!("NDI input: '{}' matched source '{}'", name, source.name);
+                return Some(source);
+            }
+            if !keep_waiting() {
+                return None;
+            }
+            watch.wait();
+        }
+    }
+}
+
+/// A finder thread: publish the sources in `groups` after every discovery
+/// cycle until the list is no longer watched.
+fn discover(ndi: &NDI, groups: Option<&str>, list: &Weak<SourceList>, search: Duration) {
+    let label = groups.unwrap_or("public");
+    let finder = loop {
+        match finder(ndi, groups) {
+            Ok(finder) => break finder,
+            Err(e) => {
+                tracing::warn!("NDI finder for groups '{}' failed: {}", label, e);
+                if list.strong_count() == 0 {
+                    return;
+                }
+                std::thread::sleep(search);
+            }
+        }
+    };
 
     loop {
-        if cancel.is_cancelled() {
-            anyhow::bail!("Cancelled while searching for NDI source '{}'", source_name);
-        }
-
-        let sources = finder.find_sources(Duration::from_secs(2))?;
-        for source in &sources {
-            if source.name.contains(source_name) {
-                tracing::info!(
-                    "NDI input: '{}' matched source '{}'",
-                    source_name,
-                    source.name
-                );
-                return Ok(source.clone());
+        // Returns early when sources come or go
+        let sources = finder
+            .wait_for_sources(search)
+            .and_then(|_| finder.current_sources());
+        let Some(list) = list.upgrade() else {
+            break;
+        };
+        match sources {
+            Ok(sources) => {
+                *list.sources.lock().unwrap() = Some(sources);
+                list.updated.notify_all();
+            }
+            Err(e) => {
+                tracing::warn!("NDI finder for groups '{}' failed: {}", label, e);
+                drop(list);
+                std::thread::sleep(search);
             }
         }
+    }
+    tracing::debug!("NDI finder for groups '{}' stopped", label);
+}
 
-        tracing::debug!("NDI source '{}' not found, retrying...", source_name);
-        std::thread::sleep(Duration::from_secs(1));
+/// A finder for local and network sources, limited to `groups` if given.
+pub fn finder(ndi: &NDI, groups: Option<&str>) -> Result<Finder> {
+    let mut finder_opts = FinderOptions::builder().show_local_sources(true);
+    if let Some(groups) = groups {
+        finder_opts = finder_opts.groups(groups);
     }
+    Ok(Finder::new(ndi, &finder_opts.build())?)
 }
 
 /// List all NDI sources visible on the network.
 pub fn list_sources(ndi: &NDI) -> Result<Vec<String>> {
-    let finder_opts = FinderOptions::builder().show_local_sources(true).build();
-    let finder = Finder::new(ndi, &finder_opts)?;
+    let finder = finder(ndi, None)?;
 
     println!("Searching for NDI sources (5 seconds)...");
     let sources = finder.find_sources(Duration::from_secs(5))?;
@@ -1,94 +1,439 @@
 use anyhow::Result;
-use grafton_ndi::{BorrowedVideoFrame, PixelFormat, Sender, SenderOptions, NDI};
+use grafton_ndi::{BorrowedVideoFrame, MetadataFrame, PixelFormat, Sender, SenderOptions, NDI};
 use image::RgbaImage;
+use serde::Serialize;
+use std::ffi::CString;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{RecvTimeoutError, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::audio::AudioBlock;
+use crate::config::{OutputPixelFormat, SilentAudioConfig};
+
+/// Silence fills in once real audio has been absent this long.
+const SILENCE_AFTER: Duration = Duration::from_millis(200);
+
+/// How often a dry-run output writes its snapshot.
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often the sender's tally and receiver count are read.
+const TALLY_POLL: Duration = Duration::from_millis(250);
+
+/// What downstream receivers report about an output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct OutputTally {
+    pub on_program: bool,
+    pub on_preview: bool,
+    /// Receivers connected to the output
+    pub connections: u32,
+}
+
+/// A converted frame for the send thread, with the metadata that goes out on it.
+type OutgoingFrame = (Arc<Vec<u8>>, Option<String>);
+
+/// New output name, and where to report whether the sender was recreated.
+pub type RenameRequest = (String, tokio::sync::oneshot::Sender<Result<()>>);
 
 pub struct NdiOutput {
-    tx: std::sync::mpsc::SyncSender<Vec<u8>>,
-    bgra_buf: Vec<u8>,
+    tx: std::sync::mpsc::SyncSender<OutgoingFrame>,
+    /// Standalone metadata frames and their timecode
+    metadata_tx: std::sync::mpsc::Sender<(String, i64)>,
+    audio_tx: std::sync::mpsc::Sender<AudioBlock>,
+    rename_tx: std::sync::mpsc::Sender<RenameRequest>,
+    pixel_format: OutputPixelFormat,
+    width: u32,
+    height: u32,
+    /// Last converted frame; shared with the send thread, so identical frames
+    /// can be repeated without converting again
+    frame_buf: Arc<Vec<u8>>,
+    /// Metadata for the next frame sent (see `attach_metadata`)
+    frame_metadata: Option<String>,
+    /// Frames repeated from the previous buffer instead of converted
+    repeated: Arc<Mutex<u64>>,
+    tally: Arc<Mutex<OutputTally>>,
     _send_thread: std::thread::JoinHandle<()>,
 }
 
+/// Produces silent audio blocks, one video frame long each.
+struct Silence {
+    config: SilentAudioConfig,
+    frame_rate: u32,
+    /// Carried from sample_rate / frame_rate so block lengths average out exactly
+    remainder: u32,
+}
+
+impl Silence {
+    fn new(config: SilentAudioConfig, frame_rate: u32) -> Self {
+        Self {
+            config,
+            frame_rate,
+            remainder: 0,
+        }
+    }
+
+    fn next_block(&mut self) -> AudioBlock {
+        let total = self.config.sample_rate + self.remainder;
+        let samples = total / self.frame_rate;
+        self.remainder = total % self.frame_rate;
+        AudioBlock {
+            sample_rate: self.config.sample_rate as i32,
+            channels: self.config.channels as i32,
+            samples: samples as i32,
+            data: vec![0.0; (samples * self.config.channels) as usize],
+        }
+    }
+}
+
 impl NdiOutput {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         ndi: &NDI,
         output_name: &str,
         width: u32,
         height: u32,
         frame_rate: u32,
+        picture_aspect_ratio: Option<f32>,
+        pixel_format: OutputPixelFormat,
+        silent_audio: Option<SilentAudioConfig>,
     ) -> Result<Self> {
-        let opts = SenderOptions::builder(output_name)
-            .clock_video(false)
-            .clock_audio(true)
-            .build();
-        let sender = Sender::new(ndi, &opts)?;
+        let sender = Sender::new(ndi, &sender_options(output_name))?;
 
         tracing::info!(
-            "NDI output '{}' created ({}x{}@{}fps)",
+            "NDI output '{}' created ({}x{}@{}fps, {:?})",
             output_name,
             width,
             height,
-            frame_rate
+            frame_rate,
+            pixel_format
         );
 
-        let buf_size = (width * height * 4) as usize;
+        let buf_size = frame_size(pixel_format, width, height);
+        let fourcc = match pixel_format {
+            OutputPixelFormat::Bgra => PixelFormat::BGRA,
+            OutputPixelFormat::Bgrx => PixelFormat::BGRX,
+            OutputPixelFormat::Uyvy => PixelFormat::UYVY,
+            OutputPixelFormat::Uyva => PixelFormat::UYVA,
+        };
 
         // Bounded channel: 1 frame buffer. If NDI send is busy, render drops the frame.
-        let (tx, rx) = std::sync::mpsc::sync_channel::<Vec<u8>>(1);
+        let (tx, rx) = std::sync::mpsc::sync_channel::<OutgoingFrame>(1);
+        // Unbounded: standalone metadata (e.g. captions) is never dropped with a video frame
+        let (metadata_tx, metadata_rx) = std::sync::mpsc::channel::<(String, i64)>();
+        // Unbounded: audio gaps are audible, so blocks are never dropped
+        let (audio_tx, audio_rx) = std::sync::mpsc::channel::<AudioBlock>();
+        let (rename_tx, rename_rx) = std::sync::mpsc::channel::<RenameRequest>();
 
         let w = width as i32;
         let h = height as i32;
         let fr = frame_rate as i32;
-        let name = output_name.to_string();
+        let mut name = output_name.to_string();
+        let ndi = ndi.clone();
+        let tally: Arc<Mutex<OutputTally>> = Arc::default();
+        let tally_ref = tally.clone();
 
         let send_thread = std::thread::Builder::new()
             .name(format!("ndi-{}", name))
             .spawn(move || {
+                crate::realtime::apply_current_thread(crate::realtime::ThreadRole::Ndi);
                 let mut sender = sender;
-                while let Ok(bgra_data) = rx.recv() {
-                    if let Ok(frame) = BorrowedVideoFrame::try_from_uncompressed(
-                        &bgra_data,
-                        w,
-                        h,
-                        PixelFormat::BGRA,
-                        fr,
-                        1,
-                    ) {
+                let mut silence = silent_audio.map(|cfg| Silence::new(cfg, frame_rate));
+                let mut last_audio: Option<Instant> = None;
+                let mut last_tally_poll: Option<Instant> = None;
+                loop {
+                    // Wake up regularly so audio isn't held back by the video cadence
+                    let frame_data = match rx.recv_timeout(Duration::from_millis(5)) {
+                        Ok(data) => Some(data),
+                        Err(RecvTimeoutError::Timeout) => None,
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    };
+
+                    // A new sender takes over; dropping the old one withdraws its name
+                    while let Ok((new_name, reply)) = rename_rx.try_recv() {
+                        let result = match Sender::new(&ndi, &sender_options(&new_name)) {
+                            Ok(new_sender) => {
+                                tracing::info!("NDI output '{}' renamed to '{}'", name, new_name);
+                                sender = new_sender;
+                                name = new_name;
+                                *tally_ref.lock().unwrap() = OutputTally::default();
+                                Ok(())
+                            }
+                            Err(e) => Err(e.into()),
+                        };
+                        let _ = reply.send(result);
+                    }
+
+                    while let Ok(block) = audio_rx.try_recv() {
+                        last_audio = Some(Instant::now());
+                        match block.to_frame() {
+                            Ok(frame) => sender.send_audio(&frame),
+                            Err(e) => {
+                                tracing::warn!("NDI output '{}' audio frame invalid: {}", name, e)
+                            }
+                        }
+                    }
+
+                    if last_tally_poll.is_none_or(|at| at.elapsed() >= TALLY_POLL) {
+                        last_tally_poll = Some(Instant::now());
+                        let connections = sender.get_no_connections(Duration::ZERO);
+                        let mut tally = tally_ref.lock().unwrap();
+                        // None: unchanged since the last read
+                        if let Ok(Some(on)) = sender.tally(Duration::ZERO) {
+                            tally.on_program = on.on_program;
+                            tally.on_preview = on.on_preview;
+                        }
+                        if let Ok(connections) = connections {
+                            tally.connections = connections.max(0) as u32;
+                        }
+                    }
+
+                    // Standalone metadata isn't tied to a video frame, so it goes out as it comes
+                    while let Ok((data, timecode)) = metadata_rx.try_recv() {
+                        let metadata = MetadataFrame::with_data(data, timecode);
+                        if let Err(e) = sender.send_metadata(&metadata) {
+                            tracing::warn!("NDI output '{}' metadata send failed: {}", name, e);
+                        }
+                    }
+
+                    let Some((frame_data, metadata)) = frame_data else {
+                        continue;
+                    };
+
+                    // A payload with a NUL byte isn't valid NDI metadata
+                    let metadata = metadata.and_then(|m| CString::new(m).ok());
+                    if let Ok(mut frame) =
+                        BorrowedVideoFrame::try_from_uncompressed(&frame_data, w, h, fourcc, fr, 1)
+                    {
+                        if let Some(ratio) = picture_aspect_ratio {
+                            frame.picture_aspect_ratio = ratio;
+                        }
+                        frame.metadata = metadata.as_deref();
                         let token = sender.send_video_async(&frame);
                         drop(token);
                     }
+
+                    // One video frame's worth of silence while there's no real audio
+                    if let Some(ref mut silence) = silence {
+                        if last_audio.is_none_or(|at| at.elapsed() >= SILENCE_AFTER) {
+                            match silence.next_block().to_frame() {
+                                Ok(frame) => sender.send_audio(&frame),
+                                Err(e) => {
+                                    tracing::warn!(
+                                        "NDI output '{}' silent audio invalid: {}",
+                                        name,
+                                        e
+                                    )
+                                }
+                            }
+                        }
+                    }
                 }
             })
             .expect("Failed to spawn NDI send thread");
 
         Ok(Self {
             tx,
-            bgra_buf: vec![0u8; buf_size],
+            metadata_tx,
+            audio_tx,
+            rename_tx,
+            pixel_format,
+            width,
+            height,
+            frame_buf: Arc::new(vec![0u8; buf_size]),
+            frame_metadata: None,
+            repeated: Arc::default(),
+            tally,
             _send_thread: send_thread,
         })
     }
 
-    /// Send an RGBA image as NDI BGRA. Non-blocking: if the previous frame
-    /// hasn't finished sending, this frame is dropped.
+    /// Stand-in for `new` under `--dry-run`: no NDI sender is created, and every
+    /// few seconds the latest frame is written to `<dir>/<output_name>.png`.
+    pub fn dry_run(output_name: &str, width: u32, height: u32, dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| anyhow::anyhow!("snapshot dir {}: {}", dir.display(), e))?;
+        let mut path = snapshot_path(dir, output_name);
+
+        tracing::info!(
+            "Dry run: NDI output '{}' not created, snapshots go to {}",
+            output_name,
+            path.display()
+        );
+
+        let (tx, rx) = std::sync::mpsc::sync_channel::<OutgoingFrame>(1);
+        let (metadata_tx, metadata_rx) = std::sync::mpsc::channel::<(String, i64)>();
+        let (audio_tx, audio_rx) = std::sync::mpsc::channel::<AudioBlock>();
+        let (rename_tx, rename_rx) = std::sync::mpsc::channel::<RenameRequest>();
+        let dir = dir.to_path_buf();
+
+        let send_thread = std::thread::Builder::new()
+            .name(format!("snapshot-{}", output_name))
+            .spawn(move || {
+                let mut last_snapshot: Option<Instant> = None;
+                while let Ok((bgra, _metadata)) = rx.recv() {
+                    while let Ok((new_name, reply)) = rename_rx.try_recv() {
+                        path = snapshot_path(&dir, &new_name);
+                        tracing::info!("Dry run: snapshots now go to {}", path.display());
+                        let _ = reply.send(Ok(()));
+                    }
+                    // Keep audio inputs and caption passthrough running, but discard their output
+                    while metadata_rx.try_recv().is_ok() {}
+                    while audio_rx.try_recv().is_ok() {}
+                    if last_snapshot.is_some_and(|at| at.elapsed() < SNAPSHOT_INTERVAL) {
+                        continue;
+                    }
+                    last_snapshot = Some(Instant::now());
+                    let mut rgba = Arc::unwrap_or_clone(bgra);
+                    for px in rgba.chunks_exact_mut(4) {
+                        px.swap(0, 2);
+                    }
+                    let Some(img) = RgbaImage::from_raw(width, height, rgba) else {
+                        continue;
+                    };
+                    if let Err(e) = img.save(&path) {
+                        tracing::warn!("Snapshot {}: {}", path.display(), e);
+                    }
+                }
+            })
+            .expect("Failed to spawn snapshot thread");
+
+        Ok(Self {
+            tx,
+            metadata_tx,
+            audio_tx,
+            rename_tx,
+            // Snapshots decode the frame as BGRA
+            pixel_format: OutputPixelFormat::Bgra,
+            width,
+            height,
+            frame_buf: Arc::new(vec![0u8; (width * height * 4) as usize]),
+            frame_metadata: None,
+            repeated: Arc::default(),
+            tally: Arc::default(),
+            _send_thread: send_thread,
+        })
+    }
+
+    /// Count of frames sent by `repeat_last`.
+    pub fn repeated(&self) -> Arc<Mutex<u64>> {
+        self.repeated.clone()
+    }
+
+    /// Tally and receiver count of the output, kept up to date by the send
+    /// thread (never set under `--dry-run`).
+    pub fn tally(&self) -> Arc<Mutex<OutputTally>> {
+        self.tally.clone()
+    }
+
+    /// Send an RGBA image in the output's pixel format. Non-blocking: if the
+    /// previous frame hasn't finished sending, this frame is dropped.
     pub fn send_frame(&mut self, image: &RgbaImage) -> Result<()> {
         let src = image.as_raw();
-        let needed = src.len();
 
-        if self.bgra_buf.len() != needed {
-            self.bgra_buf.resize(needed, 0);
+        // Convert in place unless the send thread still holds the previous buffer
+        let size = frame_size(self.pixel_format, self.width, self.height);
+        if Arc::get_mut(&mut self.frame_buf).is_none() {
+            self.frame_buf = Arc::new(vec![0u8; size]);
+        }
+        let dst = Arc::get_mut(&mut self.frame_buf).expect("buffer is unshared");
+        if dst.len() != size {
+            dst.resize(size, 0);
         }
 
-        // RGBA → BGRA conversion
-        let dst = &mut self.bgra_buf;
-        for (d, s) in dst.chunks_exact_mut(4).zip(src.chunks_exact(4)) {
-            d[0] = s[2]; // B
-            d[1] = s[1]; // G
-            d[2] = s[0]; // R
-            d[3] = s[3]; // A
+        match self.pixel_format {
+            OutputPixelFormat::Bgra | OutputPixelFormat::Bgrx => {
+                let opaque = self.pixel_format == OutputPixelFormat::Bgrx;
+                // RGBA → BGRA conversion
+                for (d, s) in dst.chunks_exact_mut(4).zip(src.chunks_exact(4)) {
+                    d[0] = s[2]; // B
+                    d[1] = s[1]; // G
+                    d[2] = s[0]; // R
+                    d[3] = if opaque { 255 } else { s[3] }; // A
+                }
+            }
+            OutputPixelFormat::Uyvy | OutputPixelFormat::Uyva => crate::color::rgba_to_uyvy(
+                src,
+                self.width,
+                self.height,
+                self.pixel_format == OutputPixelFormat::Uyva,
+                dst,
+            ),
         }
 
-        // Non-blocking send to NDI thread (drops frame if busy)
-        let _ = self.tx.try_send(self.bgra_buf.clone());
+        self.queue_frame();
 
         Ok(())
     }
+
+    /// Send the last converted frame again, for output the caller knows hasn't
+    /// changed since, skipping the conversion.
+    pub fn repeat_last(&mut self) {
+        *self.repeated.lock().unwrap() += 1;
+        self.queue_frame();
+    }
+
+    /// Non-blocking send of the converted buffer to the NDI thread. If it's
+    /// still busy the frame is dropped, and its metadata waits for the next one.
+    fn queue_frame(&mut self) {
+        let metadata = self.frame_metadata.take();
+        if let Err(TrySendError::Full((_, metadata))) =
+            self.tx.try_send((self.frame_buf.clone(), metadata))
+        {
+            self.frame_metadata = metadata;
+        }
+    }
+
+    /// Put an NDI metadata payload (XML) on the next video frame sent, after
+    /// any attached before it.
+    pub fn attach_metadata(&mut self, data: String) {
+        match self.frame_metadata {
+            Some(ref mut metadata) => metadata.push_str(&data),
+            None => self.frame_metadata = Some(data),
+        }
+    }
+
+    /// Queue a standalone NDI metadata frame (XML) with its timecode.
+    pub fn send_metadata(&self, data: String, timecode: i64) {
+        let _ = self.metadata_tx.send((data, timecode));
+    }
+
+    /// Handle for feeding audio blocks to this output from another thread.
+    pub fn audio_sender(&self) -> std::sync::mpsc::Sender<AudioBlock> {
+        self.audio_tx.clone()
+    }
+
+    /// Handle for renaming this output from another thread. The sender is
+    /// recreated under the new name while frames, audio and metadata keep flowing.
+    pub fn renamer(&self) -> std::sync::mpsc::Sender<RenameRequest> {
+        self.rename_tx.clone()
+    }
+}
+
+/// Bytes in one frame of the given format.
+fn frame_size(format: OutputPixelFormat, width: u32, height: u32) -> usize {
+    let pixels = (width * height) as usize;
+    match format {
+        OutputPixelFormat::Bgra | OutputPixelFormat::Bgrx => pixels * 4,
+        OutputPixelFormat::Uyvy => pixels * 2,
+        // Alpha plane after the packed 4:2:2 data
+        OutputPixelFormat::Uyva => pixels * 3,
+    }
+}
+
+fn sender_options(output_name: &str) -> SenderOptions {
+    SenderOptions::builder(output_name)
+        .clock_video(false)
+        // Audio arrives live from an NDI source, so it's already paced; clocking
+        // it here would block the send thread and stall video
+        .clock_audio(false)
+        .build()
+}
+
+/// `<dir>/<output_name>.png`, with the name reduced to a safe file name.
+fn snapshot_path(dir: &Path, output_name: &str) -> PathBuf {
+    let file_name: String = output_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    dir.join(format!("{}.png", file_name))
 }
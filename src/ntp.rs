@@ -0,0 +1,172 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::net::{ToSocketAddrs, UdpSocket};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio_util::sync::CancellationToken;
+
+use crate::errors::{record, ErrorCode, ErrorSlot};
+
+/// Seconds between the NTP era (1900) and the Unix epoch.
+const NTP_UNIX_OFFSET: f64 = 2_208_988_800.0;
+const QUERY_TIMEOUT: Duration = Duration::from_secs(2);
+/// Retry sooner than `interval` until the first successful query.
+const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Result of the latest successful NTP query.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct NtpSync {
+    /// Server time minus host time, in milliseconds
+    pub offset_ms: f64,
+    /// Network round trip of the query, in milliseconds
+    pub round_trip_ms: f64,
+    /// Stratum of the server
+    pub stratum: u8,
+}
+
+impl NtpSync {
+    /// The host clock corrected by the measured offset.
+    pub fn now(&self) -> SystemTime {
+        let offset = Duration::from_secs_f64(self.offset_ms.abs() / 1000.0);
+        if self.offset_ms >= 0.0 {
+            SystemTime::now() + offset
+        } else {
+            SystemTime::now() - offset
+        }
+    }
+}
+
+/// Latest NTP measurement, shared between the query thread, the clock layer and status.
+pub type SharedSync = Arc<Mutex<Option<NtpSync>>>;
+
+/// Query `server` every `interval` on a dedicated thread until cancelled,
+/// keeping the latest offset in `sync`. Failures keep the previous offset.
+pub fn spawn(
+    server: &str,
+    interval: Duration,
+    sync: SharedSync,
+    last_error: ErrorSlot,
+    cancel: CancellationToken,
+) {
+    let server = if server.contains(':') {
+        server.to_string()
+    } else {
+        format!("{}:123", server)
+    };
+
+    std::thread::Builder::new()
+        .name("ntp".to_string())
+        .spawn(move || {
+            while !cancel.is_cancelled() {
+                match query(&server) {
+                    Ok(measured) => {
+                        let first = sync.lock().unwrap().replace(measured).is_none();
+                        if first {
+                            tracing::info!(
+                                "NTP {}: offset {:+.1} ms (round trip {:.1} ms, stratum {})",
+                                server,
+                                measured.offset_ms,
+                                measured.round_trip_ms,
+                                measured.stratum
+                            );
+                        }
+                    }
+                    Err(e) => {
+                        tracing::warn!("NTP {}: {}", server, e);
+                        record(
+                            &last_error,
+                            ErrorCode::NtpSyncFailed,
+                            format!("{}: {}", server, e),
+                        );
+                    }
+                }
+                let wait = if sync.lock().unwrap().is_some() {
+                    interval
+                } else {
+                    RETRY_INTERVAL.min(interval)
+                };
+                sleep_unless_cancelled(wait, &cancel);
+            }
+        })
+        .expect("Failed to spawn NTP thread");
+}
+
+fn sleep_unless_cancelled(duration: Duration, cancel: &CancellationToken) {
+    let step = Duration::from_millis(100);
+    let mut slept = Duration::ZERO;
+    while slept < duration && !cancel.is_cancelled() {
+        std::thread::sleep(step);
+        slept += step;
+    }
+}
+
+/// One SNTP (RFC 4330) exchange.
+fn query(server: &str) -> Result<NtpSync> {
+    let addr = server
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("no address"))?;
+    let socket = UdpSocket::bind(if addr.is_ipv4() {
+        "0.0.0.0:0"
+    } else {
+        "[::]:0"
+    })?;
+    socket.set_read_timeout(Some(QUERY_TIMEOUT))?;
+    socket.connect(addr)?;
+
+    // LI 0, version 4, mode 3 (client); our send time goes in the transmit timestamp
+    let mut request = [0u8; 48];
+    request[0] = 0x23;
+    let t1 = unix_now();
+    request[40..48].copy_from_slice(&to_ntp(t1));
+    socket.send(&request)?;
+
+    let mut reply = [0u8; 48];
+    let len = socket.recv(&mut reply)?;
+    let t4 = unix_now();
+    if len < 48 {
+        anyhow::bail!("short reply ({} bytes)", len);
+    }
+    if reply[0] & 0x07 != 4 {
+        anyhow::bail!("reply is not from a server");
+    }
+    if reply[24..32] != request[40..48] {
+        anyhow::bail!("reply doesn't match the request");
+    }
+    let stratum = reply[1];
+    if stratum == 0 || reply[0] >> 6 == 3 {
+        anyhow::bail!("server is unsynchronized");
+    }
+
+    let t2 = from_ntp(&reply[32..40]);
+    let t3 = from_ntp(&reply[40..48]);
+    Ok(NtpSync {
+        offset_ms: ((t2 - t1) + (t3 - t4)) / 2.0 * 1000.0,
+        round_trip_ms: ((t4 - t1) - (t3 - t2)).max(0.0) * 1000.0,
+        stratum,
+    })
+}
+
+fn unix_now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0)
+}
+
+/// Unix seconds to a 64-bit NTP timestamp (32.32 fixed point since 1900).
+fn to_ntp(unix: f64) -> [u8; 8] {
+    let ntp = unix + NTP_UNIX_OFFSET;
+    let secs = ntp.trunc() as u32;
+    let frac = (ntp.fract() * 4_294_967_296.0) as u32;
+    let mut out = [0u8; 8];
+    out[..4].copy_from_slice(&secs.to_be_bytes());
+    out[4..].copy_from_slice(&frac.to_be_bytes());
+    out
+}
+
+fn from_ntp(bytes: &[u8]) -> f64 {
+    let secs = u32::from_be_bytes(bytes[..4].try_into().unwrap()) as f64;
+    let frac = u32::from_be_bytes(bytes[4..8].try_into().unwrap()) as f64;
+    secs + frac / 4_294_967_296.0 - NTP_UNIX_OFFSET
+}
@@ -0,0 +1,102 @@
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::config::{OverloadConfig, OverloadPolicy};
+
+/// Snapshot of a channel's overload state, shared with status reporting.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OverloadStatus {
+    pub active: bool,
+    /// Number of times the degraded mode has been engaged since startup.
+    pub engaged_count: u64,
+    /// Render time of the most recent composited frame.
+    pub last_render_ms: f32,
+}
+
+/// Tracks render time against the frame interval and decides when to degrade.
+///
+/// Hysteresis: the policy engages after `trigger_frames` consecutive frames over
+/// budget and only disengages after `recover_frames` consecutive frames under
+/// `recover_ratio * frame_interval`, so a single slow frame doesn't flap the mode.
+pub struct OverloadMonitor {
+    policy: OverloadPolicy,
+    trigger_frames: u32,
+    recover_frames: u32,
+    recover_ratio: f32,
+    over_count: u32,
+    under_count: u32,
+    active: bool,
+    status: Arc<Mutex<OverloadStatus>>,
+}
+
+impl OverloadMonitor {
+    pub fn new(config: &OverloadConfig, status: Arc<Mutex<OverloadStatus>>) -> Self {
+        Self {
+            policy: config.policy,
+            trigger_frames: config.trigger_frames,
+            recover_frames: config.recover_frames,
+            recover_ratio: config.recover_ratio,
+            over_count: 0,
+            under_count: 0,
+            active: false,
+            status,
+        }
+    }
+
+    pub fn policy(&self) -> OverloadPolicy {
+        self.policy
+    }
+
+    /// Returns the policy currently in effect (`None` when not degraded).
+    pub fn effective_policy(&self) -> OverloadPolicy {
+        if self.active {
+            self.policy
+        } else {
+            OverloadPolicy::None
+        }
+    }
+
+    /// Record the render time of a composited frame. Skipped (repeated) frames
+    /// must not be recorded, otherwise half-rate mode would recover immediately.
+    pub fn record(&mut self, render_time: Duration, frame_interval: Duration, channel: &str) {
+        let render_ms = render_time.as_secs_f32() * 1000.0;
+
+        if self.policy != OverloadPolicy::None {
+            if render_time > frame_interval {
+                self.over_count = self.over_count.saturating_add(1);
+                self.under_count = 0;
+            } else if render_time.as_secs_f32() < frame_interval.as_secs_f32() * self.recover_ratio
+            {
+                self.under_count = self.under_count.saturating_add(1);
+                self.over_count = 0;
+            }
+
+            if !self.active && self.over_count >= self.trigger_frames {
+                self.active = true;
+                self.over_count = 0;
+                tracing::warn!(
+                    "Channel '{}': overloaded ({:.1}ms render > {:.1}ms budget), engaging '{}'",
+                    channel,
+                    render_ms,
+                    frame_interval.as_secs_f32() * 1000.0,
+                    self.policy.as_str()
+                );
+                let mut status = self.status.lock().unwrap();
+                status.active = true;
+                status.engaged_count += 1;
+            } else if self.active && self.under_count >= self.recover_frames {
+                self.active = false;
+                self.under_count = 0;
+                tracing::info!(
+                    "Channel '{}': recovered from overload, disengaging '{}'",
+                    channel,
+                    self.policy.as_str()
+                );
+                self.status.lock().unwrap().active = false;
+            }
+        }
+
+        self.status.lock().unwrap().last_render_ms = render_ms;
+    }
+}
@@ -0,0 +1,330 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::channel::GpuCtxParam;
+use crate::config::{CompositorMode, Config};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    /// Works, but not the way the config may intend (e.g. CPU fallback)
+    Warn,
+    Fail,
+}
+
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+/// Results of the startup checks, in the order they ran.
+#[derive(Default)]
+pub struct Report {
+    pub checks: Vec<Check>,
+}
+
+impl Report {
+    fn push(&mut self, name: &'static str, status: CheckStatus, detail: impl Into<String>) {
+        self.checks.push(Check {
+            name,
+            status,
+            detail: detail.into(),
+        });
+    }
+
+    /// Print the report as a table (`--check`).
+    pub fn print(&self) {
+        println!("Preflight checks:\n");
+        for check in &self.checks {
+            let status = match check.status {
+                CheckStatus::Pass => "\x1b[32mPASS\x1b[0m",
+                CheckStatus::Warn => "\x1b[33mWARN\x1b[0m",
+                CheckStatus::Fail => "\x1b[31mFAIL\x1b[0m",
+            };
+            println!("  {}  {:<14} {}", status, check.name, check.detail);
+        }
+        println!();
+    }
+
+    /// Log the report (normal startup).
+    pub fn log(&self) {
+        for check in &self.checks {
+            match check.status {
+                CheckStatus::Pass => tracing::info!("Preflight {}: {}", check.name, check.detail),
+                CheckStatus::Warn => tracing::warn!("Preflight {}: {}", check.name, check.detail),
+                CheckStatus::Fail => tracing::error!("Preflight {}: {}", check.name, check.detail),
+            }
+        }
+    }
+
+    /// Err naming the failed checks, if any.
+    pub fn into_result(self) -> Result<()> {
+        let failed: Vec<&str> = self
+            .checks
+            .iter()
+            .filter(|c| c.status == CheckStatus::Fail)
+            .map(|c| c.name)
+            .collect();
+        if !failed.is_empty() {
+            anyhow::bail!("Preflight failed: {}", failed.join(", "));
+        }
+        Ok(())
+    }
+}
+
+/// `--check`: run every check without starting anything.
+pub fn check(config_path: &Path, compositor: Option<CompositorMode>) -> Report {
+    let mut report = Report::default();
+
+    let config = match Config::load(config_path) {
+        Ok(config) => {
+            report.push(
+                "Config",
+                CheckStatus::Pass,
+                format!(
+                    "{} — {} channel{}",
+                    config_path.display(),
+                    config.channel.len(),
+                    if config.channel.len() == 1 { "" } else { "s" }
+                ),
+            );
+            Some(config)
+        }
+        Err(e) => {
+            report.push("Config", CheckStatus::Fail, e.to_string());
+            None
+        }
+    };
+
+    match grafton_ndi::NDI::new() {
+        Ok(_) => report.push("NDI runtime", CheckStatus::Pass, "loaded"),
+        Err(e) => report.push(
+            "NDI runtime",
+            CheckStatus::Fail,
+            format!(
+                "{} — install the NDI runtime (libndi); on macOS run with DYLD_LIBRARY_PATH=/usr/local/lib",
+                e
+            ),
+        ),
+    }
+
+    let Some(config) = config else {
+        return report;
+    };
+
+    let mut mode = compositor.unwrap_or(config.settings.compositor);
+    if mode == CompositorMode::Cpu && config.channels_require_gpu() {
+        mode = CompositorMode::Gpu;
+    }
+    let gpu_ctx = check_gpu(&mut report, mode);
+    check_browser(&mut report, &config);
+    check_shaders(&mut report, &config, &gpu_ctx);
+    check_port(&mut report, config.settings.status_port);
+    report
+}
+
+/// Checks run on every startup, after NDI and the GPU are initialized, for
+/// problems that would otherwise only surface once channels are starting.
+pub fn startup(config: &Config) -> Report {
+    let mut report = Report::default();
+    check_browser(&mut report, config);
+    check_port(&mut report, config.settings.status_port);
+    report
+}
+
+#[cfg(feature = "gpu")]
+fn check_gpu(report: &mut Report, mode: CompositorMode) -> GpuCtxParam {
+    if mode == CompositorMode::Cpu {
+        report.push(
+            "GPU adapter",
+            CheckStatus::Pass,
+            "not used (compositor = cpu)",
+        );
+        return None;
+    }
+    let ctx = crate::gpu_context::GpuContext::try_new();
+    match (&ctx, mode) {
+        (Some(_), _) => report.push(
+            "GPU adapter",
+            CheckStatus::Pass,
+            "compute compositor available",
+        ),
+        (None, CompositorMode::Gpu) => report.push(
+            "GPU adapter",
+            CheckStatus::Fail,
+            "no usable adapter, but compositor = gpu — use `auto` or `cpu`",
+        ),
+        (None, _) => report.push(
+            "GPU adapter",
+            CheckStatus::Warn,
+            "no usable adapter — falling back to the CPU compositor",
+        ),
+    }
+    ctx
+}
+
+#[cfg(not(feature = "gpu"))]
+fn check_gpu(report: &mut Report, mode: CompositorMode) -> GpuCtxParam {
+    match mode {
+        CompositorMode::Cpu => report.push("GPU adapter", CheckStatus::Pass, "not used (compositor = cpu)"),
+        CompositorMode::Gpu => report.push(
+            "GPU adapter",
+            CheckStatus::Fail,
+            "compositor = gpu, but this build lacks the `gpu` feature — rebuild with --features gpu",
+        ),
+        CompositorMode::Auto => report.push(
+            "GPU adapter",
+            CheckStatus::Warn,
+            "built without the `gpu` feature — using the CPU compositor",
+        ),
+    }
+    None
+}
+
+fn check_browser(report: &mut Report, config: &Config) {
+    if !config.has_browser_overlays() {
+        report.push(
+            "Chromium",
+            CheckStatus::Pass,
+            "not needed (no browser overlays)",
+        );
+        return;
+    }
+    let options = chromiumoxide::detection::DetectionOptions::default();
+    match chromiumoxide::detection::default_executable(options) {
+        Ok(path) => report.push("Chromium", CheckStatus::Pass, path.display().to_string()),
+        Err(e) => report.push(
+            "Chromium",
+            CheckStatus::Fail,
+            format!(
+                "{} — install Google Chrome or set CHROME to its executable",
+                e
+            ),
+        ),
+    }
+}
+
+#[cfg(feature = "gpu")]
+fn check_shaders(report: &mut Report, config: &Config, gpu_ctx: &GpuCtxParam) {
+    let filters: Vec<&crate::config::FilterConfig> = config
+        .channel
+        .iter()
+        .flat_map(|ch| {
+            ch.ndi_input
+                .iter()
+                .flat_map(|c| c.filters.iter())
+                .chain(
+                    ch.all_browser_overlays()
+                        .into_iter()
+                        .flat_map(|o| o.filters.iter()),
+                )
+                .chain(ch.filters.iter())
+                .chain(ch.compare.iter().flat_map(|c| c.filters.iter()))
+        })
+        .collect();
+    if filters.is_empty() {
+        report.push("Shaders", CheckStatus::Pass, "no filters configured");
+        return;
+    }
+    let Some(ref ctx) = gpu_ctx else {
+        report.push(
+            "Shaders",
+            CheckStatus::Warn,
+            "filters configured, but no GPU compositor — they will be skipped",
+        );
+        return;
+    };
+
+    // Each (shader, entry point) once, with the texture layout the filter binds
+    let mut seen: Vec<(&str, &str, usize)> = Vec::new();
+    let mut errors: Vec<String> = Vec::new();
+    for filter in filters {
+        let passes: Vec<(&str, &str)> = if filter.passes.is_empty() {
+            vec![(filter.shader.as_str(), "main")]
+        } else {
+            filter
+                .passes
+                .iter()
+                .map(|p| {
+                    (
+                        p.shader.as_deref().unwrap_or(&filter.shader),
+                        p.entry_point.as_str(),
+                    )
+                })
+                .collect()
+        };
+        for (shader, entry_point) in passes {
+            let key = (shader, entry_point, filter.textures.len());
+            if seen.contains(&key) {
+                continue;
+            }
+            seen.push(key);
+
+            let result = std::fs::read_to_string(shader)
+                .map_err(|e| e.to_string())
+                .and_then(|source| {
+                    let layout = (!filter.textures.is_empty())
+                        .then(|| ctx.filter_texture_layout(filter.textures.len()));
+                    ctx.compile_filter_pipeline(shader, &source, entry_point, layout.as_ref())
+                });
+            if let Err(e) = result {
+                errors.push(format!("{} ({}): {}", shader, entry_point, e));
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        report.push(
+            "Shaders",
+            CheckStatus::Pass,
+            format!("{} compiled", seen.len()),
+        );
+    } else {
+        report.push("Shaders", CheckStatus::Fail, errors.join("; "));
+    }
+}
+
+#[cfg(not(feature = "gpu"))]
+fn check_shaders(report: &mut Report, config: &Config, _gpu_ctx: &GpuCtxParam) {
+    let has_filters = config.channel.iter().any(|ch| {
+        ch.ndi_input.as_ref().is_some_and(|c| !c.filters.is_empty())
+            || ch
+                .all_browser_overlays()
+                .iter()
+                .any(|o| !o.filters.is_empty())
+            || !ch.filters.is_empty()
+            || ch.compare.as_ref().is_some_and(|c| !c.filters.is_empty())
+    });
+    if has_filters {
+        report.push(
+            "Shaders",
+            CheckStatus::Warn,
+            "filters configured, but built without the `gpu` feature — they will be skipped",
+        );
+    } else {
+        report.push("Shaders", CheckStatus::Pass, "no filters configured");
+    }
+}
+
+fn check_port(report: &mut Report, port: u16) {
+    if port == 0 {
+        report.push("Status port", CheckStatus::Pass, "disabled");
+        return;
+    }
+    match std::net::TcpListener::bind(("0.0.0.0", port)) {
+        Ok(_) => report.push(
+            "Status port",
+            CheckStatus::Pass,
+            format!("{} available", port),
+        ),
+        Err(e) => report.push(
+            "Status port",
+            CheckStatus::Fail,
+            format!(
+                "{} unavailable: {} — stop the other process or change settings.status_port",
+                port, e
+            ),
+        ),
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::config::{self, AutoChannelConfig};
+use crate::ndi_input::SourceFinder;
+use crate::supervisor::{Launcher, RunningChannel};
+
+/// How often the sources the finders see are matched against auto channel rules.
+const SCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Starts and stops `[[auto_channel]]` channels as matching NDI sources come and go.
+pub struct Provisioner {
+    pub rules: Vec<AutoChannelConfig>,
+    /// Aliases from `[sources]`, for templates that use them (e.g. `audio_source`)
+    pub sources: HashMap<String, String>,
+    pub launcher: Launcher,
+}
+
+/// A running auto channel.
+struct Provisioned {
+    running: RunningChannel,
+    linger: Duration,
+    last_seen: Instant,
+}
+
+impl Provisioner {
+    /// Provision channels until cancelled. Auto channels are stopped along with
+    /// the rest of the mixer; their `stopped` hooks run from main.
+    pub async fn run(self) {
+        let cancel = self.launcher.cancel.clone();
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<Vec<Vec<String>>>(1);
+        spawn_discovery(
+            self.launcher.inputs.finder(),
+            &self.rules,
+            tx,
+            cancel.clone(),
+        );
+
+        let mut running: HashMap<String, Provisioned> = HashMap::new();
+        // Sources that couldn't be provisioned, so the failure is logged once
+        let mut skipped: HashSet<String> = HashSet::new();
+
+        loop {
+            let found = tokio::select! {
+                _ = cancel.cancelled() => break,
+                found = rx.recv() => match found {
+                    Some(found) => found,
+                    None => break,
+                },
+            };
+            let now = Instant::now();
+            let mut seen: HashSet<&str> = HashSet::new();
+
+            for (rule, names) in self.rules.iter().zip(&found) {
+                for source in names {
+                    // A source matched by an earlier rule belongs to that rule
+                    if !seen.insert(source.as_str()) {
+                        continue;
+                    }
+                    if let Some(auto) = running.get_mut(source) {
+                        auto.last_seen = now;
+                        continue;
+                    }
+                    if skipped.contains(source) || self.is_own_output(source) {
+                        continue;
+                    }
+                    match self.start(rule, source).await {
+                        Ok(auto) => {
+                            running.insert(source.clone(), auto);
+                        }
+                        Err(e) => {
+                            tracing::warn!("Auto channel for source '{}': {}", source, e);
+                            skipped.insert(source.clone());
+                        }
+                    }
+                }
+            }
+
+            skipped.retain(|source| seen.contains(source.as_str()));
+            let gone: Vec<String> = running
+                .iter()
+                .filter(|(_, auto)| now.duration_since(auto.last_seen) >= auto.linger)
+                .map(|(source, _)| source.clone())
+                .collect();
+            for source in gone {
+                let auto = running.remove(&source).unwrap();
+                tracing::info!(
+                    "Auto channel '{}' stopped: source '{}' is gone",
+                    auto.running.channel.state.name,
+                    source
+                );
+                self.launcher.stop(auto.running).await;
+            }
+        }
+    }
+
+    async fn start(&self, rule: &AutoChannelConfig, source: &str) -> anyhow::Result<Provisioned> {
+        let config = rule.instantiate(source, &self.sources)?;
+        let channels = &self.launcher.channels;
+        if channels.contains(&config.name)
+            || channels.disabled().contains(&config.name)
+            || channels.failed().iter().any(|f| f.name == config.name)
+        {
+            anyhow::bail!("a channel named '{}' already exists", config.name);
+        }
+
+        let running = self.launcher.start(&config).await?;
+        tracing::info!(
+            "Auto channel '{}' started for source '{}'",
+            config.name,
+            source
+        );
+
+        Ok(Provisioned {
+            running,
+            linger: Duration::from_secs(rule.linger),
+            last_seen: Instant::now(),
+        })
+    }
+
+    /// Sources that are one of our own outputs, so a broad pattern doesn't
+    /// provision channels from the mixer's output.
+    fn is_own_output(&self, source: &str) -> bool {
+        let stream = config::stream_name(source);
+        self.launcher
+            .channels
+            .snapshot()
+            .iter()
+            .any(|ch| *ch.output_name.lock().unwrap() == stream)
+    }
+}
+
+/// Match the shared finders' sources on a dedicated thread (waiting for them
+/// blocks), sending the names matching each rule, in rule order, after every scan.
+fn spawn_discovery(
+    finder: &SourceFinder,
+    rules: &[AutoChannelConfig],
+    tx: tokio::sync::mpsc::Sender<Vec<Vec<String>>>,
+    cancel: CancellationToken,
+) {
+    let finder = finder.clone();
+    let patterns: Vec<(String, Option<String>)> = rules
+        .iter()
+        .map(|r| (r.source.clone(), r.groups.clone()))
+        .collect();
+
+    std::thread::Builder::new()
+        .name("ndi-discovery".to_string())
+        .spawn(move || {
+            discovery_loop(&finder, &patterns, &tx, &cancel);
+        })
+        .expect("Failed to spawn NDI discovery thread");
+}
+
+/// `patterns`: (source pattern, groups) per rule.
+fn discovery_loop(
+    finder: &SourceFinder,
+    patterns: &[(String, Option<String>)],
+    tx: &tokio::sync::mpsc::Sender<Vec<Vec<String>>>,
+    cancel: &CancellationToken,
+) {
+    // One watch per distinct group list
+    let mut watches = HashMap::new();
+    for (_, groups) in patterns {
+        if !watches.contains_key(groups) {
+            watches.insert(groups.clone(), finder.watch(groups.as_deref()));
+        }
+    }
+
+    while !cancel.is_cancelled() {
+        let mut visible = HashMap::new();
+        for (groups, watch) in &watches {
+            // Until a finder's first cycle ends, absent sources mean nothing
+            let Some(sources) = watch.sources() else {
+                watch.wait();
+                break;
+            };
+            let names: Vec<String> = sources.into_iter().map(|s| s.name).collect();
+            visible.insert(groups, names);
+        }
+        if visible.len() < watches.len() {
+            continue;
+        }
+        let found = patterns
+            .iter()
+            .map(|(pattern, groups)| {
+                visible[groups]
+                    .iter()
+                    .filter(|name| config::glob_match(pattern, name))
+                    .cloned()
+                    .collect()
+            })
+            .collect();
+        if tx.blocking_send(found).is_err() {
+            break;
+        }
+        std::thread::sleep(SCAN_INTERVAL);
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::config::{ConversionMethod, RateAdaptation};
+use crate::ndi_input::NdiFrame;
+
+/// Gaps longer than this (a stalled or lost source) don't count towards the
+/// input frame interval.
+const MAX_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What the NDI layer shows for one output frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pick {
+    Latest,
+    Previous,
+    /// The previous frame blended towards the latest; weight of the latest out of 256
+    Blend(u16),
+}
+
+impl Pick {
+    /// Distinguishes what is shown for the same input frame, for layer generations.
+    pub fn code(self) -> u64 {
+        match self {
+            Pick::Latest => 0,
+            Pick::Blend(weight) => weight as u64,
+            Pick::Previous => 257,
+        }
+    }
+}
+
+/// Schedules input frames onto output frames when the source runs slower than
+/// the channel.
+///
+/// Plain sampling shows the latest frame, so a 25 fps source on a 30 fps channel
+/// repeats a frame whenever arrival jitter says so. With adaptation the output
+/// runs one input frame behind and places each output frame between the two
+/// most recent input frames by time: `cadence` shows the nearer one, spreading
+/// repeats evenly; `blend` crossfades between them. Sources at or above the
+/// output rate are sampled as before.
+pub struct RateAdapter {
+    mode: RateAdaptation,
+    output_interval: Duration,
+    /// Smoothed time between input frames
+    input_interval: Option<Duration>,
+    latest_at: Option<Instant>,
+}
+
+impl RateAdapter {
+    pub fn new(mode: RateAdaptation, output_frame_rate: u32) -> Self {
+        Self {
+            mode,
+            output_interval: Duration::from_secs_f64(1.0 / output_frame_rate as f64),
+            input_interval: None,
+            latest_at: None,
+        }
+    }
+
+    /// Whether previous frames must be kept around.
+    pub fn enabled(&self) -> bool {
+        self.mode != RateAdaptation::Latest
+    }
+
+    /// Note a new input frame received at `at`.
+    pub fn push(&mut self, at: Instant) {
+        if let Some(latest_at) = self.latest_at {
+            let gap = at.saturating_duration_since(latest_at);
+            if !gap.is_zero() && gap <= MAX_INTERVAL {
+                self.input_interval = Some(match self.input_interval {
+                    Some(interval) => interval.mul_f64(0.9) + gap.mul_f64(0.1),
+                    None => gap,
+                });
+            }
+        }
+        self.latest_at = Some(at);
+    }
+
+    /// Forget the input's timing (source lost or cleared).
+    pub fn reset(&mut self) {
+        self.input_interval = None;
+        self.latest_at = None;
+    }
+
+    /// What to show at `now`, given that a previous frame is available.
+    pub fn pick(&self, now: Instant) -> Pick {
+        let (Some(interval), Some(latest_at)) = (self.input_interval, self.latest_at) else {
+            return Pick::Latest;
+        };
+        if self.mode == RateAdaptation::Latest || interval <= self.output_interval {
+            return Pick::Latest;
+        }
+        // How far the output is between the previous frame (0) and the latest (1)
+        let phase = now.saturating_duration_since(latest_at).as_secs_f64() / interval.as_secs_f64();
+        match self.mode {
+            RateAdaptation::Cadence if phase < 0.5 => Pick::Previous,
+            RateAdaptation::Blend if phase < 1.0 => match (phase * 256.0).round() as u16 {
+                0 => Pick::Previous,
+                256.. => Pick::Latest,
+                weight => Pick::Blend(weight),
+            },
+            _ => Pick::Latest,
+        }
+    }
+}
+
+/// Arrivals this far off the timeline (a restarted source, a reset timestamp)
+/// start it anew instead of being smoothed towards.
+const RESYNC_AFTER: f64 = 0.25;
+/// Fraction of each frame's arrival error the timeline is moved by: enough to
+/// follow clock drift between sender and mixer, little enough to ignore jitter.
+const DRIFT_CORRECTION: f64 = 1.0 / 16.0;
+
+/// Re-times an input onto the channel's frame rate for standards conversion.
+///
+/// Input frames are placed on a steady timeline, spaced by their NDI timestamps
+/// where the sender sets them or by the measured frame interval otherwise. The
+/// output runs `delay_frames` input frames behind that timeline, so every
+/// output frame falls between two input frames regardless of arrival jitter
+/// and whether the source is slower or faster than the channel. `cadence`
+/// shows the nearer of the two, which gives even patterns such as 3:2 for
+/// 24 → 60 fps or dropping every sixth frame for 60 → 50 fps; `blend`
+/// crossfades between them.
+pub struct FrameRateConverter {
+    method: ConversionMethod,
+    delay_frames: u32,
+    /// Origin of the timeline
+    epoch: Instant,
+    /// Smoothed time between input frames, in seconds
+    interval: Option<f64>,
+    last: Option<LastFrame>,
+    /// Frames not handed to the render thread yet, with their time on the timeline
+    pending: VecDeque<(f64, NdiFrame)>,
+    /// Times of the frames the render thread holds as previous and latest
+    shown: (Option<f64>, Option<f64>),
+}
+
+/// The input frame pushed last.
+struct LastFrame {
+    time: f64,
+    arrival: f64,
+    timestamp: Option<i64>,
+}
+
+impl FrameRateConverter {
+    pub fn new(method: ConversionMethod, delay_frames: u32) -> Self {
+        Self {
+            method,
+            delay_frames,
+            epoch: Instant::now(),
+            interval: None,
+            last: None,
+            pending: VecDeque::new(),
+            shown: (None, None),
+        }
+    }
+
+    fn seconds(&self, at: Instant) -> f64 {
+        at.saturating_duration_since(self.epoch).as_secs_f64()
+    }
+
+    /// Place a received frame on the timeline.
+    pub fn push(&mut self, frame: NdiFrame) {
+        let arrival = self.seconds(frame.received_at);
+        // NDI timestamps count 100 ns units on the sender's clock
+        let sent_gap = match (
+            frame.timestamp,
+            self.last.as_ref().and_then(|l| l.timestamp),
+        ) {
+            (Some(timestamp), Some(last)) if timestamp > last => {
+                Some((timestamp - last) as f64 / 10_000_000.0)
+            }
+            _ => None,
+        };
+        let time = match self.last {
+            Some(ref last) => {
+                let gap = sent_gap.unwrap_or(arrival - last.arrival);
+                if gap > 0.0 && gap <= MAX_INTERVAL.as_secs_f64() {
+                    self.interval = Some(match self.interval {
+                        Some(interval) => interval * 0.9 + gap * 0.1,
+                        None => gap,
+                    });
+                }
+                let predicted = last.time + sent_gap.or(self.interval).unwrap_or(0.0);
+                let error = arrival - predicted;
+                if error.abs() > RESYNC_AFTER {
+                    arrival
+                } else {
+                    predicted + error * DRIFT_CORRECTION
+                }
+            }
+            None => arrival,
+        };
+        self.last = Some(LastFrame {
+            time,
+            arrival,
+            timestamp: frame.timestamp,
+        });
+        self.pending.push_back((time, frame));
+    }
+
+    /// The point on the timeline the output shows at `now`.
+    fn target(&self, now: Instant) -> f64 {
+        self.seconds(now) - self.interval.unwrap_or(0.0) * self.delay_frames as f64
+    }
+
+    /// The next frame to become the latest one shown at `now`, the latest
+    /// becoming the previous one. Called until None; frames passed over in
+    /// between are the ones a faster source drops.
+    pub fn next(&mut self, now: Instant) -> Option<NdiFrame> {
+        if self.shown.1.is_some_and(|latest| latest > self.target(now)) {
+            return None;
+        }
+        let (time, frame) = self.pending.pop_front()?;
+        self.shown = (self.shown.1, Some(time));
+        Some(frame)
+    }
+
+    /// What to show at `now`, given the frames `next` has handed out.
+    pub fn pick(&self, now: Instant) -> Pick {
+        let (Some(previous), Some(latest)) = self.shown else {
+            return Pick::Latest;
+        };
+        let target = self.target(now);
+        if target >= latest || latest <= previous {
+            return Pick::Latest;
+        }
+        // How far the output is between the previous frame (0) and the latest (1)
+        let phase = ((target - previous) / (latest - previous)).max(0.0);
+        match self.method {
+            ConversionMethod::Cadence if phase < 0.5 => Pick::Previous,
+            ConversionMethod::Cadence => Pick::Latest,
+            ConversionMethod::Blend => match (phase * 256.0).round() as u16 {
+                0 => Pick::Previous,
+                256.. => Pick::Latest,
+                weight => Pick::Blend(weight),
+            },
+        }
+    }
+
+    /// Forget the input (source lost or cleared).
+    pub fn reset(&mut self) {
+        *self = Self::new(self.method, self.delay_frames);
+    }
+}
+
+/// Blend two equally sized frames of 8-bit samples: `weight` out of 256 of `latest`.
+/// Works for RGBA and for UYVY, whose conversion to RGB is affine.
+pub fn blend_into(out: &mut Vec<u8>, previous: &[u8], latest: &[u8], weight: u16) {
+    let weight = weight as u32;
+    out.clear();
+    out.extend(
+        previous
+            .iter()
+            .zip(latest)
+            .map(|(&a, &b)| ((a as u32 * (256 - weight) + b as u32 * weight + 128) >> 8) as u8),
+    );
+}
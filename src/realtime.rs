@@ -0,0 +1,117 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+use crate::config::{RealtimeConfig, ThreadPriority};
+
+static CONFIG: OnceLock<RealtimeConfig> = OnceLock::new();
+static RENDER_CORE_CURSOR: AtomicUsize = AtomicUsize::new(0);
+static NDI_CORE_CURSOR: AtomicUsize = AtomicUsize::new(0);
+
+/// Which part of the video path a thread belongs to.
+#[derive(Debug, Clone, Copy)]
+pub enum ThreadRole {
+    /// Per-channel render loop
+    Render,
+    /// NDI receive and send threads
+    Ndi,
+}
+
+/// Install the process-wide realtime settings. Must be called before channels start.
+pub fn init(config: RealtimeConfig) {
+    let _ = CONFIG.set(config);
+}
+
+/// Apply the configured priority and core pinning to the calling thread.
+/// Failures (e.g. missing privileges for realtime scheduling) are logged, never fatal.
+pub fn apply_current_thread(role: ThreadRole) {
+    let Some(config) = CONFIG.get() else {
+        return;
+    };
+
+    let (priority, cores, cursor) = match role {
+        ThreadRole::Render => (
+            config.render_priority,
+            &config.render_cores,
+            &RENDER_CORE_CURSOR,
+        ),
+        ThreadRole::Ndi => (config.ndi_priority, &config.ndi_cores, &NDI_CORE_CURSOR),
+    };
+
+    let thread = std::thread::current();
+    let name = thread.name().unwrap_or("unnamed");
+
+    if priority != ThreadPriority::Default {
+        if let Err(e) = set_priority(priority) {
+            tracing::warn!(
+                "Thread '{}': failed to set {:?} priority: {}",
+                name,
+                priority,
+                e
+            );
+        }
+    }
+
+    // Round-robin threads of the same role across the configured cores
+    if !cores.is_empty() {
+        let core = cores[cursor.fetch_add(1, Ordering::Relaxed) % cores.len()];
+        if core_affinity::set_for_current(core_affinity::CoreId { id: core }) {
+            tracing::debug!("Thread '{}' pinned to core {}", name, core);
+        } else {
+            tracing::warn!(
+                "Thread '{}': core pinning to {} not supported on this platform",
+                name,
+                core
+            );
+        }
+    }
+}
+
+#[cfg(target_vendor = "apple")]
+fn set_priority(priority: ThreadPriority) -> Result<(), String> {
+    // QoS class is the supported way to prioritize threads on macOS
+    let rc = unsafe {
+        libc::pthread_set_qos_class_self_np(libc::qos_class_t::QOS_CLASS_USER_INTERACTIVE, 0)
+    };
+    if rc != 0 {
+        return Err(format!("pthread_set_qos_class_self_np returned {}", rc));
+    }
+    if priority == ThreadPriority::Realtime {
+        set_realtime_policy(libc::SCHED_RR)?;
+    }
+    Ok(())
+}
+
+#[cfg(all(unix, not(target_vendor = "apple")))]
+fn set_priority(priority: ThreadPriority) -> Result<(), String> {
+    match priority {
+        ThreadPriority::Default => Ok(()),
+        ThreadPriority::High => {
+            // On Linux the nice value is per-thread when addressed by tid
+            let tid = unsafe { libc::gettid() };
+            let rc = unsafe { libc::setpriority(libc::PRIO_PROCESS as _, tid as libc::id_t, -10) };
+            if rc != 0 {
+                return Err(std::io::Error::last_os_error().to_string());
+            }
+            Ok(())
+        }
+        ThreadPriority::Realtime => set_realtime_policy(libc::SCHED_FIFO),
+    }
+}
+
+#[cfg(not(unix))]
+fn set_priority(_priority: ThreadPriority) -> Result<(), String> {
+    Err("thread priorities are not supported on this platform".to_string())
+}
+
+#[cfg(unix)]
+fn set_realtime_policy(policy: libc::c_int) -> Result<(), String> {
+    unsafe {
+        let mut param: libc::sched_param = std::mem::zeroed();
+        param.sched_priority = libc::sched_get_priority_max(policy);
+        let rc = libc::pthread_setschedparam(libc::pthread_self(), policy, &param);
+        if rc != 0 {
+            return Err(std::io::Error::from_raw_os_error(rc).to_string());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tokio_util::sync::CancellationToken;
+
+use crate::channel::ChannelRegistry;
+use crate::config::{ChannelConfig, Config};
+
+/// How often the config file's modification time is checked.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Reloads the config file on SIGHUP or when it changes on disk, and applies
+/// the live settings of each configured channel (see
+/// `ChannelConfig::copy_live_settings`) without restarting it.
+///
+/// Other edits need a restart: they are logged, and a channel with any of
+/// them is left as it is. Each reload is compared with the file as last read,
+/// so runtime changes through the control API aren't reported as edits.
+pub struct Watcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    /// The file as last read
+    channels: Vec<ChannelConfig>,
+    settings: String,
+    sources: HashMap<String, String>,
+}
+
+impl Watcher {
+    /// `config` is the file as loaded, before any command-line overrides.
+    pub fn new(path: &Path, config: &Config) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            modified: modified_time(path),
+            channels: config.channel.clone(),
+            settings: format!("{:?}", config.settings),
+            sources: config.sources.clone(),
+        }
+    }
+
+    pub fn spawn(mut self, registry: ChannelRegistry, cancel: CancellationToken) {
+        tokio::spawn(async move {
+            let mut hangup = Hangup::new();
+            let mut timer = tokio::time::interval(POLL_INTERVAL);
+            loop {
+                tokio::select! {
+                    _ = cancel.cancelled() => break,
+                    _ = hangup.recv() => {
+                        tracing::info!("SIGHUP: reloading {}", self.path.display());
+                    }
+                    _ = timer.tick() => {
+                        if modified_time(&self.path) == self.modified {
+                            continue;
+                        }
+                        tracing::info!("{} changed, reloading", self.path.display());
+                    }
+                }
+                self.reload(&registry).await;
+            }
+        });
+    }
+
+    async fn reload(&mut self, registry: &ChannelRegistry) {
+        self.modified = modified_time(&self.path);
+        let config = match Config::load(&self.path) {
+            Ok(config) => config,
+            Err(e) => {
+                tracing::warn!(
+                    "Config reload failed, keeping the running configuration: {:#}",
+                    e
+                );
+                return;
+            }
+        };
+
+        if format!("{:?}", config.settings) != self.settings || config.sources != self.sources {
+            tracing::warn!("Config reload: [settings] and [sources] changes need a restart");
+        }
+        for previous in &self.channels {
+            if !config.channel.iter().any(|c| c.name == previous.name) {
+                tracing::warn!(
+                    "Config reload: removing channel '{}' needs a restart",
+                    previous.name
+                );
+            }
+        }
+
+        let mut applied = 0;
+        for channel in &config.channel {
+            let Some(previous) = self.channels.iter().find(|c| c.name == channel.name) else {
+                tracing::warn!(
+                    "Config reload: adding channel '{}' needs a restart",
+                    channel.name
+                );
+                continue;
+            };
+            // Only live settings differ if taking them makes the old config the new one
+            let mut live = previous.clone();
+            let changed = live.copy_live_settings(channel);
+            if format!("{:?}", live) != format!("{:?}", channel) {
+                tracing::warn!(
+                    "Config reload: channel '{}' has changes that need a restart; none applied",
+                    channel.name
+                );
+                continue;
+            }
+            if !changed {
+                continue;
+            }
+            match registry.reload(channel.clone()).await {
+                Ok(()) => applied += 1,
+                Err(e) => tracing::warn!(
+                    "Config reload: channel '{}' not updated: {:#}",
+                    channel.name,
+                    e
+                ),
+            }
+        }
+        tracing::info!(
+            "Config reloaded; live settings updated on {} channel{}",
+            applied,
+            if applied == 1 { "" } else { "s" }
+        );
+
+        self.channels = config.channel;
+        self.settings = format!("{:?}", config.settings);
+        self.sources = config.sources;
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Resolves on every SIGHUP; never where there is no SIGHUP.
+struct Hangup {
+    #[cfg(unix)]
+    signal: Option<tokio::signal::unix::Signal>,
+}
+
+impl Hangup {
+    fn new() -> Self {
+        #[cfg(unix)]
+        {
+            let kind = tokio::signal::unix::SignalKind::hangup();
+            let signal = tokio::signal::unix::signal(kind)
+                .inspect_err(|e| tracing::warn!("Config reload on SIGHUP unavailable: {}", e))
+                .ok();
+            Self { signal }
+        }
+        #[cfg(not(unix))]
+        Self {}
+    }
+
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(ref mut signal) = self.signal {
+            if signal.recv().await.is_some() {
+                return;
+            }
+        }
+        std::future::pending::<()>().await
+    }
+}
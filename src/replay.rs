@@ -0,0 +1,331 @@
+use anyhow::Result;
+use image::RgbaImage;
+use serde::Serialize;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::ReplayConfig;
+
+/// Replay buffer state for `/status`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReplayStatus {
+    /// Frames held, up to `capacity`
+    pub frames: usize,
+    /// Frames the buffer holds at most (`seconds`, capped by `max_memory_mb`)
+    pub capacity: usize,
+    pub memory_bytes: u64,
+    /// Frames not converted because the worker fell behind the render thread;
+    /// the previous frame stands in for each so the buffer keeps real time
+    pub dropped: u64,
+}
+
+/// A finished replay dump.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayDump {
+    pub path: String,
+    pub frames: usize,
+    pub seconds: f64,
+}
+
+type DumpReply = tokio::sync::oneshot::Sender<Result<ReplayDump>>;
+
+/// Frame buffers passed between the render thread and the worker; when both
+/// are in use the render thread drops frames rather than wait.
+const FRAME_BUFFERS: usize = 2;
+
+enum Job {
+    /// RGBA pixels of an output frame, after `skipped` frames that were dropped
+    Frame {
+        pixels: Vec<u8>,
+        skipped: u64,
+    },
+    Dump(DumpReply),
+}
+
+/// Rolling buffer of a channel's last output frames, kept as YUV 4:2:0 by a
+/// worker thread so the render thread only copies the canvas into a free buffer.
+pub struct Replay {
+    jobs: Sender<Job>,
+    /// Buffers the worker has finished with
+    free: Receiver<Vec<u8>>,
+    /// Frames dropped since the last one handed over
+    skipped: Cell<u64>,
+    status: Arc<Mutex<ReplayStatus>>,
+}
+
+/// Asks a channel's replay worker to write its buffer to disk.
+#[derive(Clone)]
+pub struct ReplayHandle {
+    jobs: Sender<Job>,
+    pub status: Arc<Mutex<ReplayStatus>>,
+}
+
+impl Replay {
+    pub fn start(
+        cfg: &ReplayConfig,
+        channel: &str,
+        width: u32,
+        height: u32,
+        frame_rate: u32,
+    ) -> Result<Self> {
+        std::fs::create_dir_all(&cfg.dir)
+            .map_err(|e| anyhow::anyhow!("Failed to create replay dir {}: {}", cfg.dir, e))?;
+
+        let frame_bytes = yuv420_size(width, height) as u64;
+        let by_memory = (cfg.max_memory_mb * 1024 * 1024 / frame_bytes.max(1)) as usize;
+        let capacity = ((cfg.seconds * frame_rate as u64) as usize)
+            .min(by_memory)
+            .max(1);
+        if capacity < (cfg.seconds * frame_rate as u64) as usize {
+            tracing::warn!(
+                "Channel '{}': replay buffer holds {:.1}s, not {}s, within max_memory_mb = {}",
+                channel,
+                capacity as f64 / frame_rate as f64,
+                cfg.seconds,
+                cfg.max_memory_mb
+            );
+        }
+
+        let status = Arc::new(Mutex::new(ReplayStatus {
+            capacity,
+            ..Default::default()
+        }));
+        let (jobs, rx) = mpsc::channel();
+        let (free_tx, free) = mpsc::channel();
+        for _ in 0..FRAME_BUFFERS {
+            let _ = free_tx.send(Vec::new());
+        }
+        let worker = Worker {
+            ring: VecDeque::with_capacity(capacity),
+            free: free_tx,
+            capacity,
+            width,
+            height,
+            frame_rate,
+            dir: PathBuf::from(&cfg.dir),
+            channel: channel.to_string(),
+            status: status.clone(),
+        };
+        std::thread::Builder::new()
+            .name(format!("replay-{}", channel))
+            .spawn(move || worker.run(rx))
+            .expect("Failed to spawn replay thread");
+
+        Ok(Self {
+            jobs,
+            free,
+            skipped: Cell::new(0),
+            status,
+        })
+    }
+
+    pub fn handle(&self) -> ReplayHandle {
+        ReplayHandle {
+            jobs: self.jobs.clone(),
+            status: self.status.clone(),
+        }
+    }
+
+    /// Add an output frame; dropped if the worker is still busy with earlier ones.
+    pub fn push(&self, frame: &RgbaImage) {
+        let Ok(mut pixels) = self.free.try_recv() else {
+            self.skipped.set(self.skipped.get() + 1);
+            self.status.lock().unwrap().dropped += 1;
+            return;
+        };
+        pixels.clear();
+        pixels.extend_from_slice(frame.as_raw());
+        let skipped = self.skipped.replace(0);
+        let _ = self.jobs.send(Job::Frame { pixels, skipped });
+    }
+}
+
+impl ReplayHandle {
+    /// Write the buffered frames to a Y4M file, returning once it is written.
+    pub async fn dump(&self) -> Result<ReplayDump> {
+        let (reply, outcome) = tokio::sync::oneshot::channel();
+        let stopped = || anyhow::anyhow!("Replay buffer stopped");
+        self.jobs.send(Job::Dump(reply)).map_err(|_| stopped())?;
+        outcome.await.map_err(|_| stopped())?
+    }
+}
+
+struct Worker {
+    ring: VecDeque<Arc<Vec<u8>>>,
+    free: Sender<Vec<u8>>,
+    capacity: usize,
+    width: u32,
+    height: u32,
+    frame_rate: u32,
+    dir: PathBuf,
+    channel: String,
+    status: Arc<Mutex<ReplayStatus>>,
+}
+
+impl Worker {
+    /// Runs until the channel drops its sender.
+    fn run(mut self, jobs: Receiver<Job>) {
+        for job in jobs {
+            match job {
+                Job::Frame { pixels, skipped } => {
+                    // Repeat the last frame for each dropped one, so the dump's
+                    // fixed frame rate still matches the time that passed
+                    if let Some(last) = self.ring.back().cloned() {
+                        for _ in 0..skipped.min(self.capacity as u64) {
+                            self.record(last.clone());
+                        }
+                    }
+                    // Reuse the evicted frame's memory unless a repeat still shares it
+                    let mut frame = if self.ring.len() == self.capacity {
+                        self.ring.pop_front().and_then(|f| Arc::try_unwrap(f).ok())
+                    } else {
+                        None
+                    }
+                    .unwrap_or_default();
+                    frame.resize(yuv420_size(self.width, self.height), 0);
+                    rgba_to_yuv420(&pixels, self.width, self.height, &mut frame);
+                    let _ = self.free.send(pixels);
+                    self.record(Arc::new(frame));
+                    let mut status = self.status.lock().unwrap();
+                    status.frames = self.ring.len();
+                    status.memory_bytes =
+                        self.ring.len() as u64 * yuv420_size(self.width, self.height) as u64;
+                }
+                Job::Dump(reply) => {
+                    // Write from a snapshot on its own thread so buffering continues
+                    let frames: Vec<Arc<Vec<u8>>> = self.ring.iter().cloned().collect();
+                    let path = self.dir.join(format!(
+                        "{}-{}.y4m",
+                        file_stem(&self.channel),
+                        SystemTime::now()
+                            .duration_since(UNIX_EPOCH)
+                            .map(|d| d.as_millis())
+                            .unwrap_or(0)
+                    ));
+                    let (width, height, frame_rate) = (self.width, self.height, self.frame_rate);
+                    let channel = self.channel.clone();
+                    std::thread::spawn(move || {
+                        let result =
+                            write_y4m(&path, &frames, width, height, frame_rate).map(|_| {
+                                ReplayDump {
+                                    path: path.display().to_string(),
+                                    frames: frames.len(),
+                                    seconds: frames.len() as f64 / frame_rate as f64,
+                                }
+                            });
+                        match result {
+                            Ok(ref dump) => tracing::info!(
+                                "Channel '{}': replay of {:.1}s written to {}",
+                                channel,
+                                dump.seconds,
+                                dump.path
+                            ),
+                            Err(ref e) => {
+                                tracing::warn!("Channel '{}': replay dump failed: {}", channel, e)
+                            }
+                        }
+                        let _ = reply.send(result);
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl Worker {
+    fn record(&mut self, frame: Arc<Vec<u8>>) {
+        if self.ring.len() == self.capacity {
+            self.ring.pop_front();
+        }
+        self.ring.push_back(frame);
+    }
+}
+
+/// Channel name made safe for a file name.
+fn file_stem(channel: &str) -> String {
+    channel
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn yuv420_size(width: u32, height: u32) -> usize {
+    let (cw, ch) = (width.div_ceil(2) as usize, height.div_ceil(2) as usize);
+    width as usize * height as usize + 2 * cw * ch
+}
+
+/// Full-range BT.709 RGBA to limited-range BT.709 planar Y'CbCr 4:2:0, the
+/// usual video file layout. Chroma is the average of each 2×2 block.
+fn rgba_to_yuv420(src: &[u8], width: u32, height: u32, out: &mut [u8]) {
+    let (w, h) = (width as usize, height as usize);
+    let (cw, ch) = (w.div_ceil(2), h.div_ceil(2));
+    let (luma, chroma) = out.split_at_mut(w * h);
+    let (cb_plane, cr_plane) = chroma.split_at_mut(cw * ch);
+    let luma_of = |r: f32, g: f32, b: f32| 0.2126 * r + 0.7152 * g + 0.0722 * b;
+
+    for (src_row, luma_row) in src.chunks_exact(w * 4).zip(luma.chunks_exact_mut(w)) {
+        for (p, l) in src_row.chunks_exact(4).zip(luma_row) {
+            let y = luma_of(p[0] as f32, p[1] as f32, p[2] as f32);
+            *l = (16.0 + y * 219.0 / 255.0).round() as u8;
+        }
+    }
+    // Two source rows per chroma row; an odd last row stands alone
+    let chroma_rows = cb_plane
+        .chunks_exact_mut(cw)
+        .zip(cr_plane.chunks_exact_mut(cw));
+    for (rows, (cb_row, cr_row)) in src.chunks(w * 8).zip(chroma_rows) {
+        let (top, bottom) = rows.split_at(w * 4);
+        for (cx, (cb, cr)) in cb_row.iter_mut().zip(cr_row.iter_mut()).enumerate() {
+            let (mut r, mut g, mut b, mut n) = (0.0f32, 0.0f32, 0.0f32, 0.0f32);
+            for row in [top, bottom].into_iter().filter(|row| !row.is_empty()) {
+                for p in row[cx * 8..(cx * 8 + 8).min(row.len())].chunks_exact(4) {
+                    r += p[0] as f32;
+                    g += p[1] as f32;
+                    b += p[2] as f32;
+                    n += 1.0;
+                }
+            }
+            let (r, g, b) = (r / n, g / n, b / n);
+            let l = luma_of(r, g, b);
+            *cb = (128.0 + (b - l) / 1.8556 * 224.0 / 255.0).round() as u8;
+            *cr = (128.0 + (r - l) / 1.5748 * 224.0 / 255.0).round() as u8;
+        }
+    }
+}
+
+fn write_y4m(
+    path: &Path,
+    frames: &[Arc<Vec<u8>>],
+    width: u32,
+    height: u32,
+    frame_rate: u32,
+) -> Result<()> {
+    if frames.is_empty() {
+        anyhow::bail!("the replay buffer is empty");
+    }
+    let file = std::fs::File::create(path)
+        .map_err(|e| anyhow::anyhow!("Failed to create {}: {}", path.display(), e))?;
+    let mut out = std::io::BufWriter::new(file);
+    writeln!(
+        out,
+        "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C420jpeg XCOLORRANGE=LIMITED",
+        width, height, frame_rate
+    )?;
+    for frame in frames {
+        out.write_all(b"FRAME\n")?;
+        out.write_all(frame)?;
+    }
+    out.flush()?;
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System};
+use tokio_util::sync::CancellationToken;
+
+/// Process-wide resource usage, refreshed periodically by `spawn_sampler`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProcessUsage {
+    /// CPU usage of the whole process (100 = one full core).
+    pub cpu_percent: f32,
+    /// Resident set size in bytes.
+    pub rss_bytes: u64,
+}
+
+/// Per-channel resource usage, updated by the render thread every `stats_interval_ms`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChannelUsage {
+    /// Share of wall time the render thread spent compositing and sending
+    /// (excludes frame pacing sleeps).
+    pub render_cpu_percent: f32,
+    /// GPU memory allocated by this channel's compositor, if it uses the GPU.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gpu_memory_bytes: Option<u64>,
+}
+
+/// Per-channel frame rates, measured by the render thread over each
+/// `stats_interval_ms`, in frames per second.
+#[derive(Debug, Clone, Default)]
+pub struct FrameRates {
+    /// Frames sent
+    pub output: f32,
+    /// New NDI input frames picked up (None without an NDI input)
+    pub ndi: Option<f32>,
+    /// New frames picked up from each browser overlay, in config order
+    pub overlays: Vec<f32>,
+}
+
+/// Sample process CPU and memory every `interval` until cancelled.
+pub fn spawn_sampler(
+    usage: Arc<Mutex<ProcessUsage>>,
+    interval: Duration,
+    cancel: CancellationToken,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let pid = match sysinfo::get_current_pid() {
+            Ok(pid) => pid,
+            Err(e) => {
+                tracing::warn!("Resource sampling unavailable: {}", e);
+                return;
+            }
+        };
+        let mut sys = System::new();
+        let refresh = ProcessRefreshKind::nothing().with_cpu().with_memory();
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = ticker.tick() => {
+                    sys.refresh_processes_specifics(ProcessesToUpdate::Some(&[pid]), true, refresh);
+                    if let Some(process) = sys.process(pid) {
+                        let mut usage = usage.lock().unwrap();
+                        usage.cpu_percent = process.cpu_usage();
+                        usage.rss_bytes = process.memory();
+                    }
+                }
+            }
+        }
+    })
+}
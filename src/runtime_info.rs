@@ -0,0 +1,104 @@
+use serde::Serialize;
+use std::path::Path;
+
+use crate::capabilities::Capabilities;
+use crate::config::ChannelConfig;
+use crate::source::LayerId;
+
+/// What `--print-runtime-json` prints once the mixer is up, for orchestration
+/// tooling that would otherwise have to scrape the logs.
+#[derive(Debug, Serialize)]
+pub struct RuntimeInfo {
+    pub pid: u32,
+    /// Absolute path of the config file
+    pub config: String,
+    /// Version, compositor and GPU adapter, as `/capabilities` reports them
+    #[serde(flatten)]
+    pub capabilities: Capabilities,
+    pub listen: Listen,
+    /// Configured channels, enabled or not
+    pub channels: Vec<ChannelInfo>,
+    /// Auto channel rules, whose channels start as their sources appear
+    pub auto_channels: usize,
+}
+
+/// Where the mixer takes commands.
+#[derive(Debug, Serialize)]
+pub struct Listen {
+    /// HTTP status and control API (None when `status_port = 0`)
+    pub status_port: Option<u16>,
+    pub control_socket: Option<String>,
+    pub control_stdin: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChannelInfo {
+    pub name: String,
+    pub output_name: String,
+    pub enabled: bool,
+    pub width: u32,
+    pub height: u32,
+    pub frame_rate: u32,
+    /// NDI input source with `[sources]` aliases resolved
+    pub ndi_source: Option<String>,
+    pub ndi_alias: Option<String>,
+    /// Every layer, in tie-break order
+    pub layers: Vec<LayerInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LayerInfo {
+    pub id: LayerId,
+    pub z_index: i32,
+}
+
+impl ChannelInfo {
+    pub fn of(config: &ChannelConfig) -> Self {
+        Self {
+            name: config.name.clone(),
+            output_name: config.output_name.clone(),
+            enabled: config.enabled,
+            width: config.width,
+            height: config.height,
+            frame_rate: config.frame_rate,
+            ndi_source: config.ndi_input.as_ref().map(|c| c.source.clone()),
+            ndi_alias: config.ndi_input.as_ref().and_then(|c| c.alias.clone()),
+            layers: config
+                .layers()
+                .iter()
+                .map(|l| LayerInfo {
+                    id: l.id,
+                    z_index: l.z_index,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl RuntimeInfo {
+    pub fn new(
+        config_path: &Path,
+        channels: Vec<ChannelInfo>,
+        auto_channels: usize,
+        capabilities: Capabilities,
+        listen: Listen,
+    ) -> Self {
+        let config = std::fs::canonicalize(config_path).unwrap_or_else(|_| config_path.into());
+        Self {
+            pid: std::process::id(),
+            config: config.display().to_string(),
+            capabilities,
+            listen,
+            channels,
+            auto_channels,
+        }
+    }
+
+    /// Print as a single line of JSON on stdout.
+    pub fn print(&self) {
+        match serde_json::to_string(self) {
+            Ok(json) => println!("{}", json),
+            Err(e) => tracing::warn!("Failed to serialize runtime info: {}", e),
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+
+use crate::control::Command;
+
+/// Commands queued for a frame of a channel's output timeline, run by the
+/// render thread just before that frame is rendered.
+#[derive(Debug, Default)]
+pub struct Schedule {
+    /// Number of the frame about to be rendered (frames sent since the channel started)
+    frame: u64,
+    next_id: u64,
+    /// Ordered by frame, then by the order they were added
+    pending: Vec<ScheduledCommand>,
+}
+
+/// A channel's schedule, shared between its render thread and the control API.
+pub type SharedSchedule = Arc<Mutex<Schedule>>;
+
+#[derive(Debug, Clone)]
+pub struct ScheduledCommand {
+    pub id: u64,
+    pub frame: u64,
+    pub command: Command,
+}
+
+/// Schedule state for `/status` and `GET /channels/{name}/schedule`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleStatus {
+    pub frame: u64,
+    pub timecode: String,
+    pub pending: Vec<ScheduledEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduledEntry {
+    pub id: u64,
+    pub frame: u64,
+    pub timecode: String,
+    pub command: Command,
+}
+
+impl Schedule {
+    /// Queue `command` for `frame`, returning its id. Frames already rendered are refused.
+    pub fn add(&mut self, frame: u64, command: Command) -> Result<u64> {
+        if frame < self.frame {
+            anyhow::bail!(
+                "Frame {} has already gone out (the channel is at frame {})",
+                frame,
+                self.frame
+            );
+        }
+        self.next_id += 1;
+        let at = self.pending.partition_point(|c| c.frame <= frame);
+        self.pending.insert(
+            at,
+            ScheduledCommand {
+                id: self.next_id,
+                frame,
+                command,
+            },
+        );
+        Ok(self.next_id)
+    }
+
+    /// Drop a pending command; false if it isn't queued (any more).
+    pub fn cancel(&mut self, id: u64) -> bool {
+        let before = self.pending.len();
+        self.pending.retain(|c| c.id != id);
+        self.pending.len() != before
+    }
+
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Move to `frame` and take the commands due by then, in order.
+    pub fn advance(&mut self, frame: u64) -> Vec<ScheduledCommand> {
+        self.frame = frame;
+        let due = self.pending.partition_point(|c| c.frame <= frame);
+        self.pending.drain(..due).collect()
+    }
+
+    pub fn status(&self, frame_rate: u32) -> ScheduleStatus {
+        ScheduleStatus {
+            frame: self.frame,
+            timecode: format_timecode(self.frame, frame_rate),
+            pending: self
+                .pending
+                .iter()
+                .map(|c| ScheduledEntry {
+                    id: c.id,
+                    frame: c.frame,
+                    timecode: format_timecode(c.frame, frame_rate),
+                    command: c.command.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Parse `HH:MM:SS:FF` into a frame number at `frame_rate` (non-drop-frame).
+pub fn parse_timecode(timecode: &str, frame_rate: u32) -> Result<u64> {
+    let invalid = || anyhow::anyhow!("Invalid timecode '{}', expected HH:MM:SS:FF", timecode);
+    let parts: Vec<u64> = timecode
+        .split([':', ';'])
+        .map(|p| p.parse().map_err(|_| invalid()))
+        .collect::<Result<_>>()?;
+    let [hours, minutes, seconds, frames] = parts[..] else {
+        return Err(invalid());
+    };
+    if minutes >= 60 || seconds >= 60 {
+        return Err(invalid());
+    }
+    if frames >= frame_rate as u64 {
+        anyhow::bail!(
+            "Timecode '{}' has frame {}, but the channel runs at {} fps",
+            timecode,
+            frames,
+            frame_rate
+        );
+    }
+    Ok(((hours * 60 + minutes) * 60 + seconds) * frame_rate as u64 + frames)
+}
+
+/// Frame number as `HH:MM:SS:FF` at `frame_rate`.
+pub fn format_timecode(frame: u64, frame_rate: u32) -> String {
+    let fps = frame_rate.max(1) as u64;
+    let secs = frame / fps;
+    format!(
+        "{:02}:{:02}:{:02}:{:02}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+        frame % fps
+    )
+}
@@ -0,0 +1,125 @@
+use image::{GrayImage, Luma, RgbaImage};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Upper bound on the pixels a measurement reads; larger frames are sampled on a grid.
+const MAX_SAMPLES: u64 = 1 << 20;
+/// Waveform height: one row per 8-bit luma level, 255 at the top.
+const WAVEFORM_HEIGHT: u32 = 256;
+
+/// Histograms of one output frame, 256 bins per channel.
+#[derive(Debug, Clone, Serialize)]
+pub struct Histograms {
+    pub luma: Vec<u32>,
+    pub red: Vec<u32>,
+    pub green: Vec<u32>,
+    pub blue: Vec<u32>,
+}
+
+/// Luma summary, in 8-bit code values.
+#[derive(Debug, Clone, Serialize)]
+pub struct LumaStats {
+    pub min: u8,
+    pub max: u8,
+    pub mean: f32,
+    /// Share of samples at 0, in percent
+    pub black_clip_percent: f32,
+    /// Share of samples at 255, in percent
+    pub white_clip_percent: f32,
+}
+
+/// Scopes of a channel's most recently measured output frame.
+#[derive(Debug, Clone, Serialize)]
+pub struct Scopes {
+    /// Unix time of the measurement, in milliseconds
+    pub timestamp: u64,
+    /// Pixels read: every `step`th pixel of every `step`th row
+    pub samples: u64,
+    pub step: u32,
+    pub luma: LumaStats,
+    pub histogram: Histograms,
+    /// Luma waveform: one column per output column group, brightness = how
+    /// many samples have that level (None unless `waveform = true`)
+    #[serde(skip)]
+    pub waveform: Option<Arc<GrayImage>>,
+}
+
+/// Latest scopes of a channel, shared with the HTTP endpoint.
+pub type SharedScopes = Arc<Mutex<Option<Scopes>>>;
+
+/// BT.709 luma of full-range RGB, as the pipeline carries it.
+fn luma(r: u8, g: u8, b: u8) -> u8 {
+    ((54 * r as u32 + 183 * g as u32 + 19 * b as u32 + 128) >> 8) as u8
+}
+
+/// Measure a frame. `waveform_width` > 0 also builds a waveform that many columns wide.
+pub fn measure(frame: &RgbaImage, waveform_width: u32) -> Scopes {
+    let (width, height) = frame.dimensions();
+    let pixels = width as u64 * height as u64;
+    let step = ((pixels as f64 / MAX_SAMPLES as f64).sqrt().ceil() as u32).max(1);
+
+    let mut histogram = Histograms {
+        luma: vec![0; 256],
+        red: vec![0; 256],
+        green: vec![0; 256],
+        blue: vec![0; 256],
+    };
+    let waveform_width = waveform_width.min(width);
+    let mut waveform_counts = vec![0u32; waveform_width as usize * WAVEFORM_HEIGHT as usize];
+    let mut samples = 0u64;
+    let mut sum = 0u64;
+
+    for y in (0..height).step_by(step as usize) {
+        for x in (0..width).step_by(step as usize) {
+            let [r, g, b, _] = frame.get_pixel(x, y).0;
+            let l = luma(r, g, b);
+            histogram.luma[l as usize] += 1;
+            histogram.red[r as usize] += 1;
+            histogram.green[g as usize] += 1;
+            histogram.blue[b as usize] += 1;
+            sum += l as u64;
+            samples += 1;
+            if waveform_width > 0 {
+                let column = (x as u64 * waveform_width as u64 / width as u64) as usize;
+                let row = (WAVEFORM_HEIGHT - 1 - l as u32) as usize;
+                waveform_counts[row * waveform_width as usize + column] += 1;
+            }
+        }
+    }
+
+    let percent = |count: u32| count as f32 / samples.max(1) as f32 * 100.0;
+    let luma_stats = LumaStats {
+        min: histogram.luma.iter().position(|&c| c > 0).unwrap_or(0) as u8,
+        max: histogram.luma.iter().rposition(|&c| c > 0).unwrap_or(0) as u8,
+        mean: sum as f32 / samples.max(1) as f32,
+        black_clip_percent: percent(histogram.luma[0]),
+        white_clip_percent: percent(histogram.luma[255]),
+    };
+
+    let waveform = (waveform_width > 0).then(|| {
+        // Log scale, so sparse levels stay visible next to large flat areas
+        let peak = waveform_counts.iter().copied().max().unwrap_or(0).max(1) as f32;
+        let scale = 255.0 / (1.0 + peak).ln();
+        Arc::new(GrayImage::from_fn(
+            waveform_width,
+            WAVEFORM_HEIGHT,
+            |x, y| {
+                let count = waveform_counts[(y * waveform_width + x) as usize] as f32;
+                Luma([((1.0 + count).ln() * scale) as u8])
+            },
+        ))
+    });
+
+    Scopes {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+        samples,
+        step,
+        luma: luma_stats,
+        histogram,
+        waveform,
+    }
+}
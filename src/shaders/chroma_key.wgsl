@@ -0,0 +1,56 @@
+// Built-in chroma key for NDI inputs (`[channel.ndi_input.chroma_key]`).
+// Mirrors the CPU fallback in chroma_key.rs.
+//
+// Params (alphabetical order):
+//   params[0] = key_b, key_g, key_r  (key color, 0.0 to 1.0)
+//   params[0].w = similarity  (CbCr distance keyed fully transparent)
+//   params[1].x = smoothness  (distance over which edges fade back in)
+//   params[1].y = spill       (spill desaturation range, 0 = off)
+
+struct FilterUniforms {
+    time: f32,
+    width: f32,
+    height: f32,
+    param_count: f32,
+    params: array<vec4f, 4>,
+}
+
+@group(0) @binding(0) var input_tex: texture_2d<f32>;
+@group(0) @binding(1) var output_tex: texture_storage_2d<rgba8unorm, write>;
+@group(0) @binding(2) var<uniform> uniforms: FilterUniforms;
+
+// BT.709 chroma of an RGB color
+fn cbcr(rgb: vec3f) -> vec2f {
+    return vec2f(
+        dot(rgb, vec3f(-0.1146, -0.3854, 0.5)),
+        dot(rgb, vec3f(0.5, -0.4542, -0.0458)),
+    );
+}
+
+@compute @workgroup_size(16, 16)
+fn main(@builtin(global_invocation_id) gid: vec3u) {
+    let w = u32(uniforms.width);
+    let h = u32(uniforms.height);
+    if gid.x >= w || gid.y >= h {
+        return;
+    }
+
+    let pos = vec2i(vec2u(gid.xy));
+    let color = textureLoad(input_tex, pos, 0);
+
+    let key = uniforms.params[0].zyx;
+    let similarity = uniforms.params[0].w;
+    let smoothness = uniforms.params[1].x;
+    let spill = uniforms.params[1].y;
+
+    let base = distance(cbcr(color.rgb), cbcr(key)) - similarity;
+    let mask = pow(clamp(base / max(smoothness, 0.0001), 0.0, 1.0), 1.5);
+
+    var rgb = color.rgb;
+    if spill > 0.0 {
+        let luma = dot(rgb, vec3f(0.2126, 0.7152, 0.0722));
+        rgb = mix(vec3f(luma), rgb, pow(clamp(base / spill, 0.0, 1.0), 1.5));
+    }
+
+    textureStore(output_tex, pos, vec4f(rgb, color.a * mask));
+}
@@ -0,0 +1,83 @@
+// Blur — multi-pass downsample/upsample blur.
+//
+// Run as a multi-pass filter. Each halving step roughly doubles the radius,
+// and the last pass must return to full size:
+//   passes = [
+//     { entry_point = "downsample", scale = 0.5 },
+//     { entry_point = "downsample", scale = 0.25 },
+//     { entry_point = "upsample", scale = 0.5 },
+//     { entry_point = "upsample", scale = 1.0 },
+//   ]
+//
+// Params: none.
+
+struct FilterUniforms {
+    time: f32,
+    width: f32,
+    height: f32,
+    param_count: f32,
+    params: array<vec4f, 4>,
+}
+
+@group(0) @binding(0) var input_tex: texture_2d<f32>;
+@group(0) @binding(1) var output_tex: texture_storage_2d<rgba8unorm, write>;
+@group(0) @binding(2) var<uniform> uniforms: FilterUniforms;
+
+fn load_clamped(p: vec2i) -> vec4f {
+    let max_pos = vec2i(textureDimensions(input_tex)) - 1;
+    return textureLoad(input_tex, clamp(p, vec2i(0), max_pos), 0);
+}
+
+// Manual bilinear fetch (the input is bound as a non-filterable texture)
+fn sample_bilinear(pos: vec2f) -> vec4f {
+    let p = pos - 0.5;
+    let base = vec2i(floor(p));
+    let f = fract(p);
+    let top = mix(load_clamped(base), load_clamped(base + vec2i(1, 0)), f.x);
+    let bottom = mix(load_clamped(base + vec2i(0, 1)), load_clamped(base + vec2i(1, 1)), f.x);
+    return mix(top, bottom, f.y);
+}
+
+// Input position (in input texels) of this output pixel's center
+fn source_pos(gid: vec2u) -> vec2f {
+    let scale = vec2f(textureDimensions(input_tex)) / vec2f(uniforms.width, uniforms.height);
+    return (vec2f(gid) + 0.5) * scale;
+}
+
+@compute @workgroup_size(16, 16)
+fn downsample(@builtin(global_invocation_id) gid: vec3u) {
+    if gid.x >= u32(uniforms.width) || gid.y >= u32(uniforms.height) {
+        return;
+    }
+
+    // Center plus four diagonal taps one input texel out
+    let c = source_pos(gid.xy);
+    var sum = sample_bilinear(c) * 4.0;
+    sum += sample_bilinear(c + vec2f(-1.0, -1.0));
+    sum += sample_bilinear(c + vec2f(1.0, -1.0));
+    sum += sample_bilinear(c + vec2f(-1.0, 1.0));
+    sum += sample_bilinear(c + vec2f(1.0, 1.0));
+
+    textureStore(output_tex, vec2i(gid.xy), sum / 8.0);
+}
+
+@compute @workgroup_size(16, 16)
+fn upsample(@builtin(global_invocation_id) gid: vec3u) {
+    if gid.x >= u32(uniforms.width) || gid.y >= u32(uniforms.height) {
+        return;
+    }
+
+    // Tent filter over the neighbouring input texels
+    let c = source_pos(gid.xy);
+    var sum = sample_bilinear(c) * 4.0;
+    sum += sample_bilinear(c + vec2f(-1.0, 0.0)) * 2.0;
+    sum += sample_bilinear(c + vec2f(1.0, 0.0)) * 2.0;
+    sum += sample_bilinear(c + vec2f(0.0, -1.0)) * 2.0;
+    sum += sample_bilinear(c + vec2f(0.0, 1.0)) * 2.0;
+    sum += sample_bilinear(c + vec2f(-1.0, -1.0));
+    sum += sample_bilinear(c + vec2f(1.0, -1.0));
+    sum += sample_bilinear(c + vec2f(-1.0, 1.0));
+    sum += sample_bilinear(c + vec2f(1.0, 1.0));
+
+    textureStore(output_tex, vec2i(gid.xy), sum / 16.0);
+}
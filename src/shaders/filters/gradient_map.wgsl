@@ -0,0 +1,44 @@
+// Gradient map — recolors the image by looking up its luma in a gradient image.
+//
+// Textures:
+//   textures[0] = gradient (any size; sampled left to right along its middle row)
+//
+// Params (alphabetical order):
+//   params[0] = mix (0.0 to 1.0, default 1.0 — blend between original and mapped color)
+
+struct FilterUniforms {
+    time: f32,
+    width: f32,
+    height: f32,
+    param_count: f32,
+    params: array<vec4f, 4>,
+}
+
+@group(0) @binding(0) var input_tex: texture_2d<f32>;
+@group(0) @binding(1) var output_tex: texture_storage_2d<rgba8unorm, write>;
+@group(0) @binding(2) var<uniform> uniforms: FilterUniforms;
+
+@group(1) @binding(0) var tex_sampler: sampler;
+@group(1) @binding(1) var gradient: texture_2d<f32>;
+
+@compute @workgroup_size(16, 16)
+fn main(@builtin(global_invocation_id) gid: vec3u) {
+    let w = u32(uniforms.width);
+    let h = u32(uniforms.height);
+    if gid.x >= w || gid.y >= h {
+        return;
+    }
+
+    let pos = vec2i(vec2u(gid.xy));
+    let color = textureLoad(input_tex, pos, 0);
+
+    let amount = select(1.0, uniforms.params[0].x, uniforms.param_count > 0.0);
+
+    let luma = dot(color.rgb, vec3f(0.2126, 0.7152, 0.0722));
+    // Stay inside the edge texels so the repeating sampler doesn't wrap
+    let dims = vec2f(textureDimensions(gradient));
+    let u = clamp(luma, 0.5 / dims.x, 1.0 - 0.5 / dims.x);
+    let mapped = textureSampleLevel(gradient, tex_sampler, vec2f(u, 0.5), 0.0).rgb;
+
+    textureStore(output_tex, pos, vec4f(mix(color.rgb, mapped, amount), color.a));
+}
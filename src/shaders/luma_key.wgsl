@@ -0,0 +1,43 @@
+// Built-in luma key for NDI inputs and browser overlays (`luma_key`).
+// Mirrors the CPU fallback in luma_key.rs.
+//
+// Params (alphabetical order):
+//   params[0].x = invert     (1 = key out bright pixels instead of dark ones)
+//   params[0].y = softness   (luma range over which pixels fade back in)
+//   params[0].z = threshold  (luma keyed fully transparent, 0.0 to 1.0)
+
+struct FilterUniforms {
+    time: f32,
+    width: f32,
+    height: f32,
+    param_count: f32,
+    params: array<vec4f, 4>,
+}
+
+@group(0) @binding(0) var input_tex: texture_2d<f32>;
+@group(0) @binding(1) var output_tex: texture_storage_2d<rgba8unorm, write>;
+@group(0) @binding(2) var<uniform> uniforms: FilterUniforms;
+
+@compute @workgroup_size(16, 16)
+fn main(@builtin(global_invocation_id) gid: vec3u) {
+    let w = u32(uniforms.width);
+    let h = u32(uniforms.height);
+    if gid.x >= w || gid.y >= h {
+        return;
+    }
+
+    let pos = vec2i(vec2u(gid.xy));
+    let color = textureLoad(input_tex, pos, 0);
+
+    let invert = uniforms.params[0].x;
+    let softness = uniforms.params[0].y;
+    let threshold = uniforms.params[0].z;
+
+    var luma = dot(color.rgb, vec3f(0.2126, 0.7152, 0.0722));
+    if invert > 0.5 {
+        luma = 1.0 - luma;
+    }
+    let mask = clamp((luma - threshold) / max(softness, 0.0001), 0.0, 1.0);
+
+    textureStore(output_tex, pos, vec4f(color.rgb, color.a * mask));
+}
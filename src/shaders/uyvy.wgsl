@@ -0,0 +1,82 @@
+// UYVY → RGBA conversion for NDI inputs received in native 4:2:2.
+//
+// The source is uploaded as an rgba8unorm texture at half width, so each
+// texel holds one pixel pair (U, Y0, V, Y1). Output is written at the
+// compositor size, cropping to the region of interest and scaling with
+// nearest-neighbour sampling like the CPU path. Outside the destination
+// rectangle (letterbox bars of the `contain` fit) the output is transparent.
+
+struct Params {
+    out_width: u32,
+    out_height: u32,
+    src_width: u32,
+    src_height: u32,
+    roi_x: u32,
+    roi_y: u32,
+    roi_w: u32,
+    roi_h: u32,
+    dst_x: u32,
+    dst_y: u32,
+    dst_w: u32,
+    dst_h: u32,
+    // Luma coefficients of the source encoding
+    kr: f32,
+    kb: f32,
+    // 1 = expand 16–235 RGB levels to full range after decoding
+    expand_range: u32,
+    // 1 = map BT.2020 gamut to BT.709 in linear light
+    bt2020_gamut: u32,
+}
+
+@group(0) @binding(0) var src: texture_2d<f32>;
+@group(0) @binding(1) var dst: texture_storage_2d<rgba8unorm, write>;
+@group(0) @binding(2) var<uniform> params: Params;
+
+const GAMMA: f32 = 2.4;
+
+@compute @workgroup_size(16, 16)
+fn main(@builtin(global_invocation_id) gid: vec3u) {
+    if gid.x >= params.out_width || gid.y >= params.out_height {
+        return;
+    }
+
+    if gid.x < params.dst_x || gid.y < params.dst_y
+        || gid.x >= params.dst_x + params.dst_w || gid.y >= params.dst_y + params.dst_h {
+        textureStore(dst, vec2i(vec2u(gid.xy)), vec4f(0.0));
+        return;
+    }
+
+    let sx = params.roi_x + (gid.x - params.dst_x) * params.roi_w / params.dst_w;
+    let sy = params.roi_y + (gid.y - params.dst_y) * params.roi_h / params.dst_h;
+    let pair = textureLoad(src, vec2i(i32(sx / 2u), i32(sy)), 0);
+
+    // Video-range YCbCr as sent by NDI
+    let luma = select(pair.y, pair.w, (sx & 1u) == 1u);
+    let y = (luma * 255.0 - 16.0) / 219.0;
+    let cb = (pair.x * 255.0 - 128.0) / 224.0;
+    let cr = (pair.z * 255.0 - 128.0) / 224.0;
+
+    let kr = params.kr;
+    let kb = params.kb;
+    let kg = 1.0 - kr - kb;
+    let r = y + 2.0 * (1.0 - kr) * cr;
+    let b = y + 2.0 * (1.0 - kb) * cb;
+    let g = (y - kr * r - kb * b) / kg;
+    var rgb = clamp(vec3f(r, g, b), vec3f(0.0), vec3f(1.0));
+
+    if params.expand_range == 1u {
+        rgb = clamp((rgb * 255.0 - 16.0) / 219.0, vec3f(0.0), vec3f(1.0));
+    }
+
+    if params.bt2020_gamut == 1u {
+        let to_709 = mat3x3f(
+            vec3f(1.6605, -0.1246, -0.0182),
+            vec3f(-0.5876, 1.1329, -0.1006),
+            vec3f(-0.0728, -0.0083, 1.1187),
+        );
+        let linear = to_709 * pow(rgb, vec3f(GAMMA));
+        rgb = pow(clamp(linear, vec3f(0.0), vec3f(1.0)), vec3f(1.0 / GAMMA));
+    }
+
+    textureStore(dst, vec2i(vec2u(gid.xy)), vec4f(rgb, 1.0));
+}
@@ -0,0 +1,43 @@
+use anyhow::Result;
+use image::{ImageBuffer, Rgba, RgbaImage};
+
+use crate::config::SlateConfig;
+use crate::text::{self, TextStyle};
+
+/// Pre-render a slate at the channel's output size so engaging it costs nothing
+/// on the render thread.
+pub fn render(cfg: &SlateConfig, width: u32, height: u32) -> Result<RgbaImage> {
+    let background = text::parse_color(&cfg.background).unwrap_or([0, 0, 0, 255]);
+    let mut canvas: RgbaImage = ImageBuffer::from_pixel(width, height, Rgba([0, 0, 0, 255]));
+    text::fill_rect(&mut canvas, 0, 0, width, height, background);
+
+    if let Some(ref path) = cfg.image {
+        let img = image::open(path)
+            .map_err(|e| anyhow::anyhow!("Failed to load slate image {}: {}", path, e))?
+            .to_rgba8();
+        let img =
+            image::imageops::resize(&img, width, height, image::imageops::FilterType::Triangle);
+        image::imageops::overlay(&mut canvas, &img, 0, 0);
+    }
+
+    if let Some(ref message) = cfg.message {
+        let font = text::load_font(cfg.font.as_deref().unwrap_or(text::DEFAULT_FONT))?;
+        let style = TextStyle {
+            size: cfg.font_size,
+            color: text::parse_color(&cfg.color).unwrap_or([255, 255, 255, 255]),
+            background: [0, 0, 0, 0],
+            padding: 0,
+        };
+        let lines = text::wrap(&font, style.size, message, width as f32 * 0.9);
+        let (block_w, block_h) = text::block_size(&font, &style, &lines);
+        let x = (width as i32 - block_w as i32) / 2;
+        let y = (height as i32 - block_h as i32) / 2;
+        text::draw_lines(&mut canvas, &font, &style, &lines, x, y);
+    }
+
+    // Output is always opaque, even if the configured background has alpha
+    for px in canvas.pixels_mut() {
+        px.0[3] = 255;
+    }
+    Ok(canvas)
+}
@@ -0,0 +1,217 @@
+use image::RgbaImage;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::captions::CaptionLayer;
+use crate::clock::ClockLayer;
+use crate::compositor::SharedLayerKey;
+use crate::text_overlay::TextOverlay;
+use crate::timer::TimerLayer;
+
+/// Stable identity of a layer within its channel. Its string form — "ndi",
+/// "overlay:<index>", "image:<index>", "text:<index>", "captions", "clock" or
+/// "timer" — is what scenes, config warnings, `/status` and the control API
+/// use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayerId {
+    Ndi,
+    Browser(usize),
+    Image(usize),
+    Text(usize),
+    Captions,
+    Clock,
+    Timer,
+}
+
+impl LayerId {
+    /// Draw order among layers with equal z_index: NDI input, then browser,
+    /// image and text overlays in config order, then the timer, the clock and
+    /// captions.
+    pub fn tie_break(self) -> usize {
+        match self {
+            LayerId::Ndi => 0,
+            LayerId::Browser(i) => 1 + i,
+            LayerId::Image(i) => usize::MAX / 2 + i,
+            LayerId::Text(i) => usize::MAX / 4 * 3 + i,
+            LayerId::Timer => usize::MAX - 2,
+            LayerId::Clock => usize::MAX - 1,
+            LayerId::Captions => usize::MAX,
+        }
+    }
+}
+
+impl std::fmt::Display for LayerId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LayerId::Ndi => write!(f, "ndi"),
+            LayerId::Browser(i) => write!(f, "overlay:{}", i),
+            LayerId::Image(i) => write!(f, "image:{}", i),
+            LayerId::Text(i) => write!(f, "text:{}", i),
+            LayerId::Captions => write!(f, "captions"),
+            LayerId::Clock => write!(f, "clock"),
+            LayerId::Timer => write!(f, "timer"),
+        }
+    }
+}
+
+impl std::str::FromStr for LayerId {
+    type Err = anyhow::Error;
+
+    fn from_str(id: &str) -> anyhow::Result<Self> {
+        let parsed = match id {
+            "ndi" => Some(LayerId::Ndi),
+            "captions" => Some(LayerId::Captions),
+            "clock" => Some(LayerId::Clock),
+            "timer" => Some(LayerId::Timer),
+            _ => id.split_once(':').and_then(|(kind, i)| match kind {
+                "overlay" => i.parse().ok().map(LayerId::Browser),
+                "image" => i.parse().ok().map(LayerId::Image),
+                "text" => i.parse().ok().map(LayerId::Text),
+                _ => None,
+            }),
+        };
+        parsed.ok_or_else(|| anyhow::anyhow!("invalid layer id '{}'", id))
+    }
+}
+
+impl Serialize for LayerId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for LayerId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Anything the render thread draws as a layer. The NDI input is composited
+/// separately, since it also carries rate adaptation, fades and UYVY frames.
+pub trait Source: Send {
+    /// Take in whatever arrived since the last output frame. Returns whether
+    /// there is a new frame.
+    fn update(&mut self, _frame_index: u64) -> bool {
+        false
+    }
+
+    /// The frame `update` just took in, for keying it on the CPU. None for
+    /// sources that draw their own frames.
+    fn latest_mut(&mut self) -> Option<&mut RgbaImage> {
+        None
+    }
+
+    /// Whether the source has something to show yet (for warmup).
+    fn ready(&self) -> bool {
+        true
+    }
+
+    /// The current frame and its generation, bumped whenever its pixels
+    /// change. None while there is nothing to draw.
+    fn frame(&mut self, now: Instant) -> Option<(&RgbaImage, u64)>;
+
+    /// Identifies the current frame to other channels showing the same source,
+    /// so the GPU compositor uploads it once. None for per-channel sources.
+    fn shared_key(&self) -> Option<SharedLayerKey> {
+        None
+    }
+}
+
+/// Frames captured from a browser overlay's page.
+pub struct BrowserSource {
+    latest: Arc<Mutex<Option<(RgbaImage, i64)>>>,
+    loaded: Arc<Mutex<bool>>,
+    /// New frames are only picked up every Nth output frame
+    divisor: u64,
+    /// The overlay's capture, shared with other channels showing it
+    source_key: u64,
+    last: Option<(RgbaImage, i64)>,
+    generation: u64,
+}
+
+impl BrowserSource {
+    pub fn new(
+        latest: Arc<Mutex<Option<(RgbaImage, i64)>>>,
+        loaded: Arc<Mutex<bool>>,
+        divisor: u64,
+        source_key: u64,
+    ) -> Self {
+        Self {
+            latest,
+            loaded,
+            divisor: divisor.max(1),
+            source_key,
+            last: None,
+            generation: 0,
+        }
+    }
+}
+
+impl Source for BrowserSource {
+    fn update(&mut self, frame_index: u64) -> bool {
+        if !frame_index.is_multiple_of(self.divisor) {
+            return false;
+        }
+        let Some(frame) = self.latest.lock().unwrap().take() else {
+            return false;
+        };
+        self.last = Some(frame);
+        self.generation += 1;
+        true
+    }
+
+    fn latest_mut(&mut self) -> Option<&mut RgbaImage> {
+        self.last.as_mut().map(|(img, _)| img)
+    }
+
+    fn ready(&self) -> bool {
+        *self.loaded.lock().unwrap() && self.last.is_some()
+    }
+
+    fn frame(&mut self, _now: Instant) -> Option<(&RgbaImage, u64)> {
+        self.last.as_ref().map(|(img, _)| (img, self.generation))
+    }
+
+    fn shared_key(&self) -> Option<SharedLayerKey> {
+        self.last.as_ref().map(|&(_, frame)| SharedLayerKey {
+            source: self.source_key,
+            frame,
+        })
+    }
+}
+
+/// A still image, loaded once; the GPU compositor uploads it once.
+pub struct ImageSource(pub RgbaImage);
+
+impl Source for ImageSource {
+    fn frame(&mut self, _now: Instant) -> Option<(&RgbaImage, u64)> {
+        Some((&self.0, 0))
+    }
+}
+
+impl Source for TextOverlay {
+    fn frame(&mut self, _now: Instant) -> Option<(&RgbaImage, u64)> {
+        TextOverlay::frame(self)
+    }
+}
+
+impl Source for CaptionLayer {
+    fn frame(&mut self, _now: Instant) -> Option<(&RgbaImage, u64)> {
+        CaptionLayer::frame(self)
+    }
+}
+
+impl Source for ClockLayer {
+    fn frame(&mut self, _now: Instant) -> Option<(&RgbaImage, u64)> {
+        ClockLayer::frame(self)
+    }
+}
+
+impl Source for TimerLayer {
+    fn frame(&mut self, now: Instant) -> Option<(&RgbaImage, u64)> {
+        TimerLayer::frame(self, now)
+    }
+}
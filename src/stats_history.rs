@@ -0,0 +1,147 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const MINUTE: Duration = Duration::from_secs(60);
+
+/// Aggregates of one minute of a channel's output.
+#[derive(Debug, Clone, Serialize)]
+pub struct MinuteStats {
+    /// Start of the minute, in Unix seconds
+    pub start: u64,
+    pub frames: u64,
+    /// Average output frame rate
+    pub fps: f32,
+    /// Lowest output frame rate of a stats window in the minute
+    pub fps_min: f32,
+    /// Frames that took longer than the frame interval to render and send
+    pub late_frames: u64,
+    pub render_ms_avg: f32,
+    pub render_ms_max: f32,
+    /// Age of the NDI input frame on air when it was sent (None without input frames)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_age_ms_avg: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_age_ms_max: Option<f32>,
+}
+
+/// A channel's completed minutes, oldest first, up to `stats_history_hours`.
+#[derive(Debug, Default)]
+pub struct StatsHistory {
+    capacity: usize,
+    minutes: VecDeque<MinuteStats>,
+}
+
+pub type SharedStatsHistory = Arc<Mutex<StatsHistory>>;
+
+impl StatsHistory {
+    pub fn new(hours: u32) -> Self {
+        Self {
+            capacity: hours as usize * 60,
+            minutes: VecDeque::new(),
+        }
+    }
+
+    /// The last `count` minutes (all of them when None), oldest first.
+    pub fn minutes(&self, count: Option<usize>) -> Vec<MinuteStats> {
+        let skip = count.map_or(0, |n| self.minutes.len().saturating_sub(n));
+        self.minutes.iter().skip(skip).cloned().collect()
+    }
+
+    fn push(&mut self, minute: MinuteStats) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.minutes.len() == self.capacity {
+            self.minutes.pop_front();
+        }
+        self.minutes.push_back(minute);
+    }
+}
+
+/// Accumulates the current minute on the render thread and adds it to the
+/// history once the minute is over.
+pub struct MinuteRecorder {
+    history: SharedStatsHistory,
+    frame_interval: Duration,
+    started: Instant,
+    start: u64,
+    frames: u64,
+    late_frames: u64,
+    render_total: Duration,
+    render_max: Duration,
+    input_frames: u64,
+    input_age_total: Duration,
+    input_age_max: Duration,
+    fps_min: Option<f32>,
+}
+
+impl MinuteRecorder {
+    pub fn new(history: SharedStatsHistory, frame_interval: Duration) -> Self {
+        Self {
+            history,
+            frame_interval,
+            started: Instant::now(),
+            start: unix_now(),
+            frames: 0,
+            late_frames: 0,
+            render_total: Duration::ZERO,
+            render_max: Duration::ZERO,
+            input_frames: 0,
+            input_age_total: Duration::ZERO,
+            input_age_max: Duration::ZERO,
+            fps_min: None,
+        }
+    }
+
+    /// Count a sent frame: how long it took, and the age of the NDI frame in it.
+    pub fn frame(&mut self, render: Duration, input_age: Option<Duration>) {
+        self.frames += 1;
+        if render > self.frame_interval {
+            self.late_frames += 1;
+        }
+        self.render_total += render;
+        self.render_max = self.render_max.max(render);
+        if let Some(age) = input_age {
+            self.input_frames += 1;
+            self.input_age_total += age;
+            self.input_age_max = self.input_age_max.max(age);
+        }
+        if self.started.elapsed() >= MINUTE {
+            self.finish();
+        }
+    }
+
+    /// Output frame rate of a stats window.
+    pub fn fps(&mut self, fps: f32) {
+        self.fps_min = Some(self.fps_min.map_or(fps, |min| min.min(fps)));
+    }
+
+    fn finish(&mut self) {
+        let ms = |d: Duration| (d.as_secs_f32() * 10_000.0).round() / 10.0;
+        let elapsed = self.started.elapsed().as_secs_f32();
+        let fps = (self.frames as f32 / elapsed * 10.0).round() / 10.0;
+        let minute = MinuteStats {
+            start: self.start,
+            frames: self.frames,
+            fps,
+            fps_min: self.fps_min.unwrap_or(fps),
+            late_frames: self.late_frames,
+            render_ms_avg: ms(self.render_total / self.frames.max(1) as u32),
+            render_ms_max: ms(self.render_max),
+            input_age_ms_avg: (self.input_frames > 0)
+                .then(|| ms(self.input_age_total / self.input_frames as u32)),
+            input_age_ms_max: (self.input_frames > 0).then(|| ms(self.input_age_max)),
+        };
+        self.history.lock().unwrap().push(minute);
+        *self = Self::new(self.history.clone(), self.frame_interval);
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -1,16 +1,77 @@
-use axum::{extract::State, routing::get, Json, Router};
-use serde::Serialize;
-use std::sync::Arc;
-use std::time::Instant;
+use axum::{
+    body::Body,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        ConnectInfo, Extension, Path, Query, Request, State,
+    },
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Html, IntoResponse, Response,
+    },
+    routing::{delete, get, post},
+    Json, Router,
+};
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use crate::channel::ChannelState;
+use crate::audit::{AuditEntry, AuditLog};
+use crate::browser::{self, CaptureStats, Interaction};
+use crate::capabilities::Capabilities;
+use crate::channel::{AudioInputState, BlankMode, ChannelRegistry, ChannelState, FailedChannel};
+use crate::config::{
+    ApiToken, CaptureMode, ControlApiConfig, OutputPixelFormat, OverloadPolicy, TimeSource,
+};
+use crate::control::{self, Command};
+use crate::cpu_budget::CpuBudgetStatus;
+use crate::errors::ErrorReport;
+use crate::gpu_recovery::GpuHealth;
+use crate::ndi_input::{InputFormat, InputTally, SourceState};
+use crate::ndi_output::OutputTally;
+use crate::ntp::NtpSync;
+use crate::overload::OverloadStatus;
+use crate::replay::ReplayStatus;
+use crate::resources::{ChannelUsage, ProcessUsage};
+use crate::schedule::ScheduleStatus;
+use crate::scopes::{Scopes, SharedScopes};
+use crate::source::LayerId;
+use crate::stats_history::MinuteStats;
+use crate::timer::TimerStatus;
+use crate::virtual_camera::VirtualCameraStatus;
+
+/// Version of the `/status` layout. Bumped when a field is removed, renamed or
+/// changes meaning; new fields don't bump it.
+const STATUS_SCHEMA_VERSION: u32 = 1;
 
 #[derive(Serialize)]
 struct StatusResponse {
     version: String,
+    schema_version: u32,
     compositor: String,
     uptime_seconds: u64,
+    resources: ProcessResourcesJson,
     channels: Vec<ChannelStatusJson>,
+    /// Configured channels that are not running
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    disabled_channels: Vec<String>,
+    /// Configured channels that failed to start
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    failed_channels: Vec<FailedChannel>,
+}
+
+#[derive(Serialize)]
+struct ProcessResourcesJson {
+    #[serde(flatten)]
+    usage: ProcessUsage,
+    /// Sum of GPU memory held by all channel compositors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gpu_memory_bytes: Option<u64>,
 }
 
 #[derive(Serialize)]
@@ -19,95 +80,1367 @@ struct ChannelStatusJson {
     output_name: String,
     resolution: String,
     frame_rate: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    aspect_ratio: Option<f32>,
+    pixel_format: OutputPixelFormat,
+    /// `gpu` or `cpu`, after the channel's `compositor` setting
+    compositor: &'static str,
     ndi_input: Option<NdiInputStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    audio_input: Option<AudioInputStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    audio_mix: Option<AudioMixStatus>,
     browser_overlays: Vec<BrowserOverlayStatus>,
+    /// Every layer, top of the stack first
+    layers: Vec<LayerStatus>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    layer_groups: Vec<LayerGroupStatus>,
+    /// Names of the channel's scenes
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    scenes: Vec<String>,
+    /// The active scene, once one has been switched to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scene: Option<String>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     filters: Vec<String>,
+    /// Split position while the compare view is on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    compare: Option<CompareStatus>,
+    /// Whether a receiver has the output on program or preview, and how many are connected
+    tally: OutputTally,
+    /// Frames sent per second, over the last stats interval
+    output_fps: f32,
     frames_output: u64,
+    /// Of `frames_output`, frames repeated unchanged (`output_dedup` only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    frames_repeated: Option<u64>,
+    /// Seconds the counters have been running (since startup or the last reset)
+    counters_age_seconds: u64,
+    slate: bool,
+    /// What the output is held on while blanked through the control API
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output_blank: Option<BlankMode>,
+    warming_up: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    overload: Option<OverloadStatusJson>,
+    resources: ChannelUsage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    captions: Option<CaptionsStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    clock: Option<ClockStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timer: Option<TimerStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    replay: Option<ReplayStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    virtual_camera: Option<VirtualCameraStatus>,
+    /// Output timeline position and pending scheduled commands
+    schedule: ScheduleStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cc_passthrough: Option<CcPassthroughStatus>,
+    /// Why the channel last composited without the GPU
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gpu_fallback: Option<ErrorReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gpu: Option<GpuHealth>,
+}
+
+#[derive(Serialize)]
+struct LayerStatus {
+    /// What `set_opacity` takes as `layer`
+    id: LayerId,
+    label: String,
+    z_index: i32,
+}
+
+#[derive(Serialize)]
+struct LayerGroupStatus {
+    name: String,
+    opacity: f32,
+    visible: bool,
+    /// IDs of the member layers, top of the stack first
+    layers: Vec<LayerId>,
+}
+
+#[derive(Serialize)]
+struct CcPassthroughStatus {
+    received: u64,
+    forwarded: u64,
+    /// Most recent caption payload, only when `expose_captions` is on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last: Option<String>,
+}
+
+#[derive(Serialize)]
+struct CompareStatus {
+    split: f32,
+    /// Channel filters right of the divider (empty = unfiltered)
+    filters: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct CaptionsStatus {
+    source: String,
+    showing: bool,
+}
+
+#[derive(Serialize)]
+struct ClockStatus {
+    time_source: TimeSource,
+    /// Whether an NTP reply has been received (always true for the system clock)
+    synced: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ntp: Option<NtpSync>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_error: Option<ErrorReport>,
+}
+
+#[derive(Serialize)]
+struct OverloadStatusJson {
+    policy: OverloadPolicy,
+    #[serde(flatten)]
+    status: OverloadStatus,
 }
 
 #[derive(Serialize)]
 struct NdiInputStatus {
     source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alias: Option<String>,
     connected: bool,
+    /// searching, connected, lost or timed_out
+    state: &'static str,
     frames_received: u64,
+    /// Format of the most recent frame, as received
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<InputFormat>,
+    /// The source's frame rate differs noticeably from the channel's
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    rate_mismatch: bool,
+    /// New frames per second the channel picks up from the input
+    fps: f32,
+    /// Channels sharing this input's receiver, when more than one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shared_by: Option<usize>,
+    /// stretch, contain or cover
+    fit: &'static str,
+    /// Tally forwarded to the source (and the key source)
+    tally: InputTally,
+    /// Key source of an external key/fill pair
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key: Option<KeyInputStatus>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     filters: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_error: Option<ErrorReport>,
+}
+
+#[derive(Serialize)]
+struct KeyInputStatus {
+    source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alias: Option<String>,
+    /// searching, connected, lost or timed_out
+    state: &'static str,
+    frames_received: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_error: Option<ErrorReport>,
+}
+
+#[derive(Serialize)]
+struct AudioInputStatus {
+    source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alias: Option<String>,
+    connected: bool,
+    /// searching, connected or lost
+    state: &'static str,
+    frames_received: u64,
+}
+
+impl AudioInputStatus {
+    fn of(input: &AudioInputState) -> Self {
+        let state = *input.state.lock().unwrap();
+        Self {
+            source: input.source.clone(),
+            alias: input.alias.clone(),
+            connected: state == SourceState::Connected,
+            state: state.as_str(),
+            frames_received: *input.frames_received.lock().unwrap(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct AudioMixStatus {
+    sample_rate: u32,
+    channels: u32,
+    master_db: f32,
+    sources: Vec<AudioMixSourceStatus>,
+}
+
+#[derive(Serialize)]
+struct AudioMixSourceStatus {
+    #[serde(flatten)]
+    input: AudioInputStatus,
+    gain_db: f32,
+    mute: bool,
 }
 
 #[derive(Serialize)]
 struct BrowserOverlayStatus {
     url: String,
     loaded: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     filters: Vec<String>,
+    capture_mode: CaptureMode,
+    /// New frames per second the channel picks up from the overlay
+    fps: f32,
+    capture: CaptureStats,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cpu_budget: Option<CpuBudgetStatus>,
+    /// Channels sharing this overlay's capture, when more than one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shared_by: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_error: Option<ErrorReport>,
 }
 
+/// Per-token rate limits count requests in fixed windows of this length.
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Largest control request body accepted.
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
 struct AppState {
-    channels: Vec<Arc<ChannelState>>,
+    channels: ChannelRegistry,
     compositor: String,
+    process_usage: Arc<Mutex<ProcessUsage>>,
     start_time: Instant,
+    tokens: Vec<ApiToken>,
+    /// Requests per token name in the current window
+    rate_windows: Mutex<HashMap<String, (Instant, u32)>>,
+    audit: Option<Arc<AuditLog>>,
+    capabilities: Capabilities,
+}
+
+impl AppState {
+    /// Count a request against a token's limit; false once it's exceeded.
+    fn within_rate_limit(&self, token: &ApiToken) -> bool {
+        if token.rate_limit == 0 {
+            return true;
+        }
+        let mut windows = self.rate_windows.lock().unwrap();
+        let (start, count) = windows
+            .entry(token.name.clone())
+            .or_insert((Instant::now(), 0));
+        if start.elapsed() >= RATE_WINDOW {
+            *start = Instant::now();
+            *count = 0;
+        }
+        if *count >= token.rate_limit {
+            return false;
+        }
+        *count += 1;
+        true
+    }
 }
 
 /// Start the HTTP status endpoint on the given port.
-/// `channel_states` must be Arc-wrapped so they can be shared with the HTTP handler.
-pub async fn serve_http(channel_states: Vec<Arc<ChannelState>>, compositor: &str, port: u16) -> anyhow::Result<()> {
+pub async fn serve_http(
+    channels: ChannelRegistry,
+    compositor: &str,
+    process_usage: Arc<Mutex<ProcessUsage>>,
+    port: u16,
+    control_api: &ControlApiConfig,
+    audit: Option<Arc<AuditLog>>,
+    capabilities: Capabilities,
+) -> anyhow::Result<()> {
     let state = Arc::new(AppState {
-        channels: channel_states,
+        channels,
         compositor: compositor.to_string(),
+        process_usage,
         start_time: Instant::now(),
+        tokens: control_api.tokens.clone(),
+        rate_windows: Mutex::new(HashMap::new()),
+        audit,
+        capabilities,
     });
 
+    let control = Router::new()
+        .route("/command", post(command_handler))
+        .route("/slate", post(slate_all).delete(clear_slate_all))
+        .route(
+            "/channels/{name}/slate",
+            post(slate_channel).delete(clear_slate_channel),
+        )
+        .route("/filters/reset_clock", post(reset_filter_clock_all))
+        .route(
+            "/channels/{name}/filters/reset_clock",
+            post(reset_filter_clock_channel),
+        )
+        .route("/counters/reset", post(reset_counters_all))
+        .route(
+            "/channels/{name}/counters/reset",
+            post(reset_counters_channel),
+        )
+        .route(
+            "/channels/{name}/overlays/{index}/interact",
+            post(interact_overlay),
+        )
+        .route("/channels/{name}/enable", post(enable_channel))
+        .route("/channels/{name}/disable", post(disable_channel))
+        .route("/channels/{name}/resolution", post(set_resolution))
+        .route("/channels/{name}/output_name", post(set_output_name))
+        .route("/channels/{name}/input/source", post(set_input_source))
+        .route("/channels/{name}/compare", post(set_compare))
+        .route("/channels/{name}/replay/dump", post(dump_replay))
+        .route("/channels/{name}/output/blank", post(blank_output))
+        .route("/channels/{name}/output/resume", post(resume_output))
+        .route("/channels/{name}/groups/{group}", post(set_layer_group))
+        .route("/channels/{name}/scene/{scene}", post(set_scene))
+        .route(
+            "/channels/{name}/schedule",
+            get(schedule_handler)
+                .post(add_schedule)
+                .delete(clear_schedule),
+        )
+        .route("/channels/{name}/schedule/{id}", delete(cancel_scheduled))
+        .route("/channels/{name}/timer/start", post(start_timer))
+        .route("/channels/{name}/timer/pause", post(pause_timer))
+        .route("/channels/{name}/timer/reset", post(reset_timer))
+        .route("/ws", get(ws_handler))
+        .route_layer(middleware::from_fn_with_state(state.clone(), control_guard));
+
     let app = Router::new()
+        .route("/", get(status_page_handler))
         .route("/status", get(status_handler))
+        .route("/events", get(events_handler))
+        .route("/capabilities", get(capabilities_handler))
+        .route("/stats", get(stats_handler))
+        .route("/channels/{name}/scopes", get(scopes_handler))
+        .route(
+            "/channels/{name}/scopes/waveform.png",
+            get(waveform_handler),
+        )
+        .merge(control)
         .with_state(state);
 
     let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port)).await?;
     tracing::info!("Status endpoint: http://localhost:{}/status", port);
 
-    axum::serve(listener, app).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
     Ok(())
 }
 
-async fn status_handler(State(state): State<Arc<AppState>>) -> Json<StatusResponse> {
-    let channels: Vec<ChannelStatusJson> = state
+/// Authenticate, rate-limit and audit every control request. Without configured
+/// tokens the API stays open and requests are audited as "anonymous".
+async fn control_guard(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(remote): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let request_line = format!("{} {}", request.method(), request.uri().path());
+    // Browsers can't set headers on WebSocket connections, so `/ws` also takes `?access_token=`
+    let query_token = (request.uri().path() == "/ws")
+        .then(|| request.uri().query())
+        .flatten()
+        .and_then(|q| q.split('&').find_map(|p| p.strip_prefix("access_token=")));
+    let bearer = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .or(query_token);
+    let caller = if state.tokens.is_empty() {
+        Ok("anonymous")
+    } else {
+        match bearer.and_then(|b| state.tokens.iter().find(|t| t.token == b)) {
+            Some(token) if state.within_rate_limit(token) => Ok(token.name.as_str()),
+            Some(token) => Err((
+                StatusCode::TOO_MANY_REQUESTS,
+                token.name.as_str(),
+                "rate limit exceeded",
+            )),
+            None => Err((
+                StatusCode::UNAUTHORIZED,
+                "anonymous",
+                "missing or unknown API token",
+            )),
+        }
+    };
+
+    // Buffer the body so the audit log can record it
+    let (parts, body) = request.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, MAX_BODY_BYTES).await else {
+        return control_error(StatusCode::PAYLOAD_TOO_LARGE, "request body too large")
+            .into_response();
+    };
+    let body_json = serde_json::from_slice(&bytes).ok();
+
+    let (client, response, error) = match caller {
+        Ok(client) => {
+            let mut request = Request::from_parts(parts, Body::from(bytes));
+            request
+                .extensions_mut()
+                .insert(ApiClient(client.to_string()));
+            (client, next.run(request).await, None)
+        }
+        Err((status, client, error)) => {
+            tracing::warn!(
+                "Control API: {} from {} rejected: {}",
+                request_line,
+                remote,
+                error
+            );
+            (
+                client,
+                control_error(status, error).into_response(),
+                Some(error.to_string()),
+            )
+        }
+    };
+
+    if let Some(ref audit) = state.audit {
+        let status = response.status();
+        audit.record(&AuditEntry {
+            via: "http",
+            client,
+            remote: Some(remote.to_string()),
+            request: request_line,
+            body: body_json,
+            ok: status.is_success() || status == StatusCode::SWITCHING_PROTOCOLS,
+            status: Some(status.as_u16()),
+            error,
+        });
+    }
+    response
+}
+
+/// Which part of the status `/status` returns.
+#[derive(Debug, Default, Deserialize)]
+struct StatusQuery {
+    /// Comma-separated channel names (default: every running channel)
+    channel: Option<String>,
+    /// Comma-separated channel fields to include; `name` is always included
+    fields: Option<String>,
+}
+
+/// Items of a comma-separated query value.
+fn comma_list(value: &str) -> Vec<&str> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+async fn status_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<StatusQuery>,
+) -> ControlResponse {
+    let mut channels = state.channels.snapshot();
+    if let Some(ref names) = query.channel {
+        let names = comma_list(names);
+        if let Some(missing) = names
+            .iter()
+            .find(|name| !channels.iter().any(|ch| ch.name == **name))
+        {
+            return Err(control_error(
+                StatusCode::NOT_FOUND,
+                format!("Channel '{}' is not running", missing),
+            ));
+        }
+        channels.retain(|ch| names.contains(&ch.name.as_str()));
+    }
+
+    let mut response = serde_json::to_value(status_response(&state, &channels))
+        .map_err(|e| control_error(StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    if let Some(ref fields) = query.fields {
+        let fields = comma_list(fields);
+        let listed = response["channels"].as_array_mut().into_iter().flatten();
+        for channel in listed.filter_map(|ch| ch.as_object_mut()) {
+            channel.retain(|key, _| key == "name" || fields.contains(&key.as_str()));
+        }
+    }
+    Ok(Json(response))
+}
+
+/// The status of every channel as an HTML page, for checking from a browser.
+async fn status_page_handler(State(state): State<Arc<AppState>>) -> Response {
+    let channels = state.channels.snapshot();
+    match serde_json::to_value(status_response(&state, &channels)) {
+        Ok(status) => Html(crate::status_page::render(&status)).into_response(),
+        Err(e) => control_error(StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+    }
+}
+
+fn status_response(state: &AppState, channels: &[Arc<ChannelState>]) -> StatusResponse {
+    // Process-wide, so summed over every channel whichever are listed
+    let gpu_memory_bytes = state
         .channels
+        .snapshot()
         .iter()
-        .map(|ch| {
-            let ndi_input = ch.ndi_source.as_ref().map(|src| NdiInputStatus {
-                source: src.clone(),
-                connected: *ch.ndi_connected.lock().unwrap(),
-                frames_received: *ch.ndi_frames_received.lock().unwrap(),
-                filters: ch.ndi_filters.clone(),
-            });
-
-            let browser_overlays: Vec<BrowserOverlayStatus> = ch
-                .browser_overlays
-                .iter()
-                .map(|b| BrowserOverlayStatus {
-                    url: b.url.clone(),
-                    loaded: *b.loaded.lock().unwrap(),
-                    filters: b.filters.clone(),
-                })
-                .collect();
-
-            ChannelStatusJson {
-                name: ch.name.clone(),
-                output_name: ch.output_name.clone(),
-                resolution: format!("{}x{}", ch.width, ch.height),
-                frame_rate: ch.frame_rate,
-                ndi_input,
-                browser_overlays,
-                filters: ch.channel_filters.clone(),
-                frames_output: *ch.frames_output.lock().unwrap(),
-            }
-        })
-        .collect();
+        .filter_map(|ch| ch.usage.lock().unwrap().gpu_memory_bytes)
+        .reduce(|a, b| a + b);
 
-    Json(StatusResponse {
+    StatusResponse {
         version: env!("CARGO_PKG_VERSION").to_string(),
+        schema_version: STATUS_SCHEMA_VERSION,
         compositor: state.compositor.clone(),
         uptime_seconds: state.start_time.elapsed().as_secs(),
-        channels,
-    })
+        resources: ProcessResourcesJson {
+            usage: state.process_usage.lock().unwrap().clone(),
+            gpu_memory_bytes,
+        },
+        channels: channels.iter().map(|ch| channel_status(ch)).collect(),
+        disabled_channels: state.channels.disabled(),
+        failed_channels: state.channels.failed(),
+    }
+}
+
+fn channel_status(ch: &ChannelState) -> ChannelStatusJson {
+    let ndi_state = *ch.ndi_state.lock().unwrap();
+    let ndi_format = ch.ndi_format.lock().unwrap().clone();
+    let frame_rates = ch.frame_rates.lock().unwrap().clone();
+    let ndi_source = ch
+        .ndi_source
+        .as_ref()
+        .map(|src| src.lock().unwrap().clone());
+    let ndi_input = ndi_source.map(|src| NdiInputStatus {
+        source: src.name,
+        alias: src.alias,
+        connected: ndi_state == SourceState::Connected,
+        state: ndi_state.as_str(),
+        frames_received: *ch.ndi_frames_received.lock().unwrap(),
+        rate_mismatch: ndi_format
+            .as_ref()
+            .is_some_and(|f| f.rate_mismatch(ch.frame_rate)),
+        fps: frame_rates.ndi.unwrap_or(0.0),
+        format: ndi_format,
+        shared_by: ch
+            .ndi_share
+            .as_ref()
+            .map(|s| s.channels())
+            .filter(|&n| n > 1),
+        fit: ch.ndi_fit.as_str(),
+        tally: ch
+            .ndi_tally
+            .as_ref()
+            .map(|t| *t.lock().unwrap())
+            .unwrap_or_default(),
+        key: ch.ndi_key.as_ref().map(|(source, input)| KeyInputStatus {
+            source: source.name.clone(),
+            alias: source.alias.clone(),
+            state: input.state.lock().unwrap().as_str(),
+            frames_received: *input.frames_received.lock().unwrap(),
+            last_error: input.last_error.lock().unwrap().clone(),
+        }),
+        filters: ch.ndi_filters.clone(),
+        last_error: ch.ndi_last_error.lock().unwrap().clone(),
+    });
+
+    let browser_overlays: Vec<BrowserOverlayStatus> = ch
+        .browser_overlays
+        .iter()
+        .enumerate()
+        .map(|(i, b)| BrowserOverlayStatus {
+            url: b.url.clone(),
+            loaded: *b.loaded.lock().unwrap(),
+            error: b.error.lock().unwrap().clone(),
+            filters: b.filters.clone(),
+            capture_mode: b.capture_mode,
+            fps: frame_rates.overlays.get(i).copied().unwrap_or(0.0),
+            capture: b.capture.lock().unwrap().clone(),
+            cpu_budget: b.cpu_budget.as_ref().map(|s| s.lock().unwrap().clone()),
+            shared_by: Some(b.share.channels()).filter(|&n| n > 1),
+            last_error: b.last_error.lock().unwrap().clone(),
+        })
+        .collect();
+
+    ChannelStatusJson {
+        name: ch.name.clone(),
+        output_name: ch.output_name.lock().unwrap().clone(),
+        resolution: format!("{}x{}", ch.width, ch.height),
+        frame_rate: ch.frame_rate,
+        aspect_ratio: ch.picture_aspect_ratio,
+        pixel_format: ch.output_pixel_format,
+        compositor: if ch.gpu_health.is_some() {
+            "gpu"
+        } else {
+            "cpu"
+        },
+        ndi_input,
+        audio_input: ch.audio_input.as_ref().map(AudioInputStatus::of),
+        audio_mix: ch.audio_mix.as_ref().map(|mix| {
+            let levels = mix.levels.lock().unwrap();
+            AudioMixStatus {
+                sample_rate: mix.sample_rate,
+                channels: mix.channels,
+                master_db: levels.master_db,
+                sources: mix
+                    .sources
+                    .iter()
+                    .zip(&levels.sources)
+                    .map(|(a, level)| AudioMixSourceStatus {
+                        input: AudioInputStatus::of(a),
+                        gain_db: level.gain_db,
+                        mute: level.mute,
+                    })
+                    .collect(),
+            }
+        }),
+        browser_overlays,
+        layers: ch
+            .layers
+            .iter()
+            .map(|l| LayerStatus {
+                id: l.source,
+                label: l.label.clone(),
+                z_index: l.z_index,
+            })
+            .collect(),
+        layer_groups: ch
+            .layer_mix
+            .lock()
+            .unwrap()
+            .groups
+            .iter()
+            .map(|g| LayerGroupStatus {
+                name: g.name.clone(),
+                opacity: g.opacity.target(),
+                visible: g.visible,
+                layers: ch
+                    .layers
+                    .iter()
+                    .filter(|l| g.members.contains(&l.source))
+                    .map(|l| l.source)
+                    .collect(),
+            })
+            .collect(),
+        scenes: ch.scenes.iter().map(|s| s.name.clone()).collect(),
+        scene: ch
+            .layer_mix
+            .lock()
+            .unwrap()
+            .scene
+            .as_ref()
+            .map(|s| s.name.clone()),
+        filters: ch.channel_filters.clone(),
+        compare: {
+            let compare = *ch.compare.lock().unwrap();
+            compare.enabled.then(|| CompareStatus {
+                split: compare.split,
+                filters: ch.compare_filters.clone(),
+            })
+        },
+        tally: *ch.output_tally.lock().unwrap(),
+        output_fps: frame_rates.output,
+        frames_output: *ch.frames_output.lock().unwrap(),
+        frames_repeated: ch.frames_repeated.as_ref().map(|n| *n.lock().unwrap()),
+        counters_age_seconds: ch.counters_since.lock().unwrap().elapsed().as_secs(),
+        slate: *ch.slate_active.lock().unwrap(),
+        output_blank: *ch.output_blank.lock().unwrap(),
+        warming_up: *ch.warming_up.lock().unwrap(),
+        overload: (ch.overload_policy != OverloadPolicy::None).then(|| OverloadStatusJson {
+            policy: ch.overload_policy,
+            status: ch.overload.lock().unwrap().clone(),
+        }),
+        resources: ch.usage.lock().unwrap().clone(),
+        captions: ch.captions.as_ref().map(|c| CaptionsStatus {
+            source: c.source.clone(),
+            showing: *c.showing.lock().unwrap(),
+        }),
+        clock: ch.clock.as_ref().map(|c| {
+            let ntp = c.sync.as_ref().and_then(|s| *s.lock().unwrap());
+            ClockStatus {
+                time_source: c.time_source,
+                synced: c.sync.is_none() || ntp.is_some(),
+                ntp,
+                last_error: c.last_error.lock().unwrap().clone(),
+            }
+        }),
+        timer: ch.timer.as_ref().map(|t| t.lock().unwrap().status()),
+        replay: ch.replay.as_ref().map(|r| r.status.lock().unwrap().clone()),
+        virtual_camera: ch
+            .virtual_camera
+            .as_ref()
+            .map(|c| c.lock().unwrap().clone()),
+        schedule: ch.schedule.lock().unwrap().status(ch.frame_rate),
+        cc_passthrough: ch.cc_passthrough.as_ref().map(|tap| {
+            let tap = tap.lock().unwrap();
+            CcPassthroughStatus {
+                received: tap.received,
+                forwarded: tap.forwarded,
+                last: tap.last.clone().filter(|_| ch.expose_captions),
+            }
+        }),
+        gpu_fallback: ch.gpu_fallback.lock().unwrap().clone(),
+        gpu: ch.gpu_health.as_ref().map(|h| h.lock().unwrap().clone()),
+    }
+}
+
+async fn capabilities_handler(State(state): State<Arc<AppState>>) -> Json<Capabilities> {
+    Json(state.capabilities.clone())
+}
+
+#[derive(Debug, Deserialize)]
+struct StatsQuery {
+    /// Only this channel
+    channel: Option<String>,
+    /// Only the last this many minutes
+    minutes: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct StatsResponse {
+    channels: Vec<ChannelStatsJson>,
+}
+
+#[derive(Serialize)]
+struct ChannelStatsJson {
+    name: String,
+    /// Completed minutes, oldest first
+    minutes: Vec<MinuteStats>,
+}
+
+/// Per-minute history of each running channel (`stats_history_hours`).
+async fn stats_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<StatsResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let channels = state.channels.snapshot();
+    let channels = control::target_channels(&channels, query.channel.as_deref())
+        .map_err(|e| control_error(StatusCode::NOT_FOUND, e))?;
+    let channels = channels
+        .into_iter()
+        .map(|ch| ChannelStatsJson {
+            name: ch.name.clone(),
+            minutes: ch.stats_history.lock().unwrap().minutes(query.minutes),
+        })
+        .collect();
+    Ok(Json(StatsResponse { channels }))
+}
+
+#[derive(Debug, Deserialize)]
+struct EventsQuery {
+    /// Milliseconds between checks for changes
+    #[serde(default = "default_events_interval_ms")]
+    interval_ms: u64,
+}
+
+fn default_events_interval_ms() -> u64 {
+    1000
+}
+
+/// Server-sent events: channel, input and overlay state changes as they
+/// happen, plus a frame counter snapshot on every check.
+async fn events_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<EventsQuery>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let interval = Duration::from_millis(query.interval_ms.clamp(100, 60_000));
+    Sse::new(crate::events::stream(state.channels.clone(), interval))
+        .keep_alive(KeepAlive::default())
+}
+
+/// Name of the API token a control request was made with ("anonymous" without tokens).
+#[derive(Clone)]
+struct ApiClient(String);
+
+/// WebSocket: the `/events` changes as `{"event": ..., "data": ...}` messages,
+/// and control commands in, each answered with `{"reply": {"ok": ...}}`.
+async fn ws_handler(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(remote): ConnectInfo<SocketAddr>,
+    Extension(ApiClient(client)): Extension<ApiClient>,
+    Query(query): Query<EventsQuery>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    let interval = Duration::from_millis(query.interval_ms.clamp(100, 60_000));
+    upgrade.on_upgrade(move |socket| serve_ws(socket, state, interval, client, remote))
+}
+
+async fn serve_ws(
+    mut socket: WebSocket,
+    state: Arc<AppState>,
+    interval: Duration,
+    client: String,
+    remote: SocketAddr,
+) {
+    tracing::debug!("WebSocket client {} ({}) connected", remote, client);
+    let mut timer = tokio::time::interval(interval);
+    timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut tracker = crate::events::Tracker::default();
+    loop {
+        let outgoing = tokio::select! {
+            _ = timer.tick() => tracker
+                .poll(&state.channels.snapshot())
+                .into_iter()
+                .map(|e| serde_json::json!({ "event": e.name, "data": e.data }))
+                .collect(),
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Text(text))) => {
+                    // Each command counts against the token's rate limit, as an HTTP request would
+                    let limited = state
+                        .tokens
+                        .iter()
+                        .find(|t| t.name == client)
+                        .is_some_and(|t| !state.within_rate_limit(t));
+                    let reply = if limited {
+                        tracing::warn!(
+                            "Control API: WebSocket command from {} rejected: rate limit exceeded",
+                            remote
+                        );
+                        crate::control_stream::reject_line(
+                            &text,
+                            "rate limit exceeded",
+                            "websocket",
+                            &client,
+                            Some(remote.to_string()),
+                            state.audit.as_deref(),
+                        )
+                    } else {
+                        crate::control_stream::run_line(
+                            &text,
+                            &state.channels,
+                            "websocket",
+                            &client,
+                            Some(remote.to_string()),
+                            state.audit.as_deref(),
+                        )
+                        .await
+                    };
+                    // Echo the client's `ref`, so replies can be matched to commands
+                    let reference = serde_json::from_str::<serde_json::Value>(&text)
+                        .ok()
+                        .and_then(|v| v.get("ref").cloned());
+                    vec![serde_json::json!({ "reply": reply, "ref": reference })]
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Pings are answered by axum
+                Some(Ok(_)) => Vec::new(),
+            },
+        };
+        for message in outgoing {
+            if socket
+                .send(Message::Text(message.to_string().into()))
+                .await
+                .is_err()
+            {
+                tracing::debug!("WebSocket client {} disconnected", remote);
+                return;
+            }
+        }
+    }
+    tracing::debug!("WebSocket client {} disconnected", remote);
+}
+
+/// A channel's scopes; 404 when the channel isn't running or has no
+/// `[channel.scopes]`, 503 until the first measurement.
+fn channel_scopes(
+    state: &AppState,
+    name: &str,
+) -> Result<Scopes, (StatusCode, Json<serde_json::Value>)> {
+    let channels = state.channels.snapshot();
+    let channel = control::target_channels(&channels, Some(name))
+        .map_err(|e| control_error(StatusCode::NOT_FOUND, e))?[0];
+    let scopes: &SharedScopes = channel.scopes.as_ref().ok_or_else(|| {
+        control_error(
+            StatusCode::NOT_FOUND,
+            format!("Channel '{}' has no scopes configured", name),
+        )
+    })?;
+    let latest = scopes.lock().unwrap().clone();
+    latest.ok_or_else(|| control_error(StatusCode::SERVICE_UNAVAILABLE, "No measurement yet"))
+}
+
+async fn scopes_handler(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<Json<Scopes>, (StatusCode, Json<serde_json::Value>)> {
+    channel_scopes(&state, &name).map(Json)
+}
+
+/// The latest luma waveform as a grayscale PNG; 404 unless `waveform = true`.
+async fn waveform_handler(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let waveform = channel_scopes(&state, &name)?.waveform.ok_or_else(|| {
+        control_error(
+            StatusCode::NOT_FOUND,
+            format!("Channel '{}' has no waveform configured", name),
+        )
+    })?;
+    let mut png = std::io::Cursor::new(Vec::new());
+    waveform
+        .write_to(&mut png, image::ImageFormat::Png)
+        .map_err(|e| control_error(StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    Ok(([(header::CONTENT_TYPE, "image/png")], png.into_inner()).into_response())
+}
+
+type ControlResponse = Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)>;
+
+fn run_command(state: &AppState, command: Command) -> ControlResponse {
+    control::execute(&state.channels.snapshot(), &command)
+        .map(|_| Json(serde_json::json!({ "ok": true })))
+        .map_err(|e| control_error(StatusCode::NOT_FOUND, e))
+}
+
+async fn slate_all(State(state): State<Arc<AppState>>) -> ControlResponse {
+    run_command(&state, Command::Slate { channel: None })
+}
+
+async fn clear_slate_all(State(state): State<Arc<AppState>>) -> ControlResponse {
+    run_command(&state, Command::ClearSlate { channel: None })
+}
+
+async fn slate_channel(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> ControlResponse {
+    run_command(
+        &state,
+        Command::Slate {
+            channel: Some(name),
+        },
+    )
+}
+
+async fn clear_slate_channel(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> ControlResponse {
+    run_command(
+        &state,
+        Command::ClearSlate {
+            channel: Some(name),
+        },
+    )
+}
+
+#[derive(Deserialize)]
+struct Blank {
+    #[serde(default)]
+    mode: BlankMode,
+}
+
+/// Hold the output on black; an optional `{"mode": "slate"}` body shows the slate instead.
+async fn blank_output(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    body: Option<Json<Blank>>,
+) -> ControlResponse {
+    run_command(
+        &state,
+        Command::BlankOutput {
+            channel: name,
+            mode: body.map(|Json(b)| b.mode).unwrap_or_default(),
+        },
+    )
+}
+
+async fn resume_output(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> ControlResponse {
+    run_command(&state, Command::ResumeOutput { channel: name })
+}
+
+#[derive(Deserialize)]
+struct LayerGroupChange {
+    #[serde(default)]
+    opacity: Option<f32>,
+    #[serde(default)]
+    visible: Option<bool>,
+    #[serde(default)]
+    duration_ms: Option<u64>,
+}
+
+async fn set_layer_group(
+    State(state): State<Arc<AppState>>,
+    Path((name, group)): Path<(String, String)>,
+    Json(body): Json<LayerGroupChange>,
+) -> ControlResponse {
+    run_command(
+        &state,
+        Command::SetLayerGroup {
+            channel: name,
+            group,
+            opacity: body.opacity,
+            visible: body.visible,
+            duration_ms: body.duration_ms,
+        },
+    )
+}
+
+async fn set_scene(
+    State(state): State<Arc<AppState>>,
+    Path((name, scene)): Path<(String, String)>,
+) -> ControlResponse {
+    run_command(
+        &state,
+        Command::SetScene {
+            channel: name,
+            scene,
+        },
+    )
+}
+
+/// The channel's timeline position and pending scheduled commands.
+async fn schedule_handler(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> ControlResponse {
+    let channels = state.channels.snapshot();
+    let channel = control::target_channels(&channels, Some(&name))
+        .map_err(|e| control_error(StatusCode::NOT_FOUND, e))?[0];
+    let schedule = channel.schedule.lock().unwrap().status(channel.frame_rate);
+    Ok(Json(serde_json::json!(schedule)))
+}
+
+#[derive(Deserialize)]
+struct ScheduleRequest {
+    #[serde(default)]
+    frame: Option<u64>,
+    #[serde(default)]
+    timecode: Option<String>,
+    command: Command,
+}
+
+/// Queue a command for an output frame; the reply carries its `id`.
+async fn add_schedule(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(body): Json<ScheduleRequest>,
+) -> ControlResponse {
+    require_channel(&state, &name)?;
+    let id = control::schedule(
+        &state.channels.snapshot(),
+        &name,
+        body.frame,
+        body.timecode.as_deref(),
+        &body.command,
+    )
+    .map_err(|e| control_error(StatusCode::BAD_REQUEST, e))?;
+    Ok(Json(serde_json::json!({ "ok": true, "id": id })))
+}
+
+async fn clear_schedule(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> ControlResponse {
+    run_command(
+        &state,
+        Command::Unschedule {
+            channel: name,
+            id: None,
+        },
+    )
+}
+
+async fn cancel_scheduled(
+    State(state): State<Arc<AppState>>,
+    Path((name, id)): Path<(String, u64)>,
+) -> ControlResponse {
+    run_command(
+        &state,
+        Command::Unschedule {
+            channel: name,
+            id: Some(id),
+        },
+    )
+}
+
+async fn reset_filter_clock_all(State(state): State<Arc<AppState>>) -> ControlResponse {
+    run_command(&state, Command::ResetFilterClock { channel: None })
+}
+
+async fn reset_filter_clock_channel(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> ControlResponse {
+    run_command(
+        &state,
+        Command::ResetFilterClock {
+            channel: Some(name),
+        },
+    )
+}
+
+async fn reset_counters_all(State(state): State<Arc<AppState>>) -> ControlResponse {
+    run_command(&state, Command::ResetCounters { channel: None })
+}
+
+async fn reset_counters_channel(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> ControlResponse {
+    run_command(
+        &state,
+        Command::ResetCounters {
+            channel: Some(name),
+        },
+    )
+}
+
+fn control_error(
+    status: StatusCode,
+    error: impl ToString,
+) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        status,
+        Json(serde_json::json!({ "ok": false, "error": error.to_string() })),
+    )
+}
+
+/// Send a click, keystroke or scroll to a browser overlay (index in config order).
+async fn interact_overlay(
+    State(state): State<Arc<AppState>>,
+    Path((name, index)): Path<(String, usize)>,
+    Json(interaction): Json<Interaction>,
+) -> ControlResponse {
+    let channels = state.channels.snapshot();
+    let channel = control::target_channels(&channels, Some(&name))
+        .map_err(|e| control_error(StatusCode::NOT_FOUND, e))?[0];
+    let overlay = channel.browser_overlays.get(index).ok_or_else(|| {
+        control_error(
+            StatusCode::NOT_FOUND,
+            format!("Channel '{}' has no browser overlay {}", name, index),
+        )
+    })?;
+    let page = overlay.page.lock().unwrap().clone().ok_or_else(|| {
+        control_error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            format!("Browser overlay {} is not loaded", overlay.url),
+        )
+    })?;
+
+    tracing::info!(
+        "Channel '{}': overlay {} interaction {:?}",
+        name,
+        index,
+        interaction
+    );
+    browser::interact(&page, overlay.viewport, &interaction)
+        .await
+        .map_err(|e| control_error(StatusCode::BAD_REQUEST, e))?;
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
+
+async fn enable_channel(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> ControlResponse {
+    set_enabled(&state, &name, true).await
+}
+
+async fn disable_channel(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> ControlResponse {
+    set_enabled(&state, &name, false).await
+}
+
+/// Start or stop a configured channel; 400 for auto channels or if its pipeline fails to start.
+async fn set_enabled(state: &AppState, name: &str, enabled: bool) -> ControlResponse {
+    require_channel(state, name)?;
+    state
+        .channels
+        .set_enabled(name, enabled)
+        .await
+        .map(|_| Json(serde_json::json!({ "ok": true })))
+        .map_err(|e| control_error(StatusCode::BAD_REQUEST, e))
+}
+
+#[derive(Deserialize)]
+struct Resolution {
+    width: u32,
+    height: u32,
+}
+
+/// Restart a configured channel at another resolution; 400 for auto channels,
+/// invalid sizes or if it fails to start at the new size.
+async fn set_resolution(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(resolution): Json<Resolution>,
+) -> ControlResponse {
+    require_channel(&state, &name)?;
+    state
+        .channels
+        .set_resolution(&name, resolution.width, resolution.height)
+        .await
+        .map(|_| Json(serde_json::json!({ "ok": true })))
+        .map_err(|e| control_error(StatusCode::BAD_REQUEST, e))
+}
+
+#[derive(Deserialize)]
+struct OutputName {
+    output_name: String,
+}
+
+/// Rename a configured channel's NDI output; 400 for auto channels or a name
+/// that's empty or taken.
+async fn set_output_name(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(body): Json<OutputName>,
+) -> ControlResponse {
+    require_channel(&state, &name)?;
+    state
+        .channels
+        .set_output_name(&name, &body.output_name)
+        .await
+        .map(|_| Json(serde_json::json!({ "ok": true })))
+        .map_err(|e| control_error(StatusCode::BAD_REQUEST, e))
+}
+
+#[derive(Deserialize)]
+struct InputSourceBody {
+    source: String,
+}
+
+/// Switch a configured channel's NDI input to another source; 400 for auto
+/// channels, channels without an NDI input or an empty source.
+async fn set_input_source(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(body): Json<InputSourceBody>,
+) -> ControlResponse {
+    require_channel(&state, &name)?;
+    state
+        .channels
+        .set_input_source(&name, &body.source)
+        .await
+        .map(|_| Json(serde_json::json!({ "ok": true })))
+        .map_err(|e| control_error(StatusCode::BAD_REQUEST, e))
+}
+
+#[derive(Deserialize)]
+struct Compare {
+    enabled: bool,
+    #[serde(default)]
+    split: Option<f32>,
+}
+
+/// Switch a channel's A/B compare view; 400 without a GPU compositor or for a
+/// split outside 0.0–1.0.
+async fn set_compare(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(body): Json<Compare>,
+) -> ControlResponse {
+    require_channel(&state, &name)?;
+    let command = Command::SetCompare {
+        channel: name,
+        enabled: body.enabled,
+        split: body.split,
+    };
+    control::execute(&state.channels.snapshot(), &command)
+        .map(|_| Json(serde_json::json!({ "ok": true })))
+        .map_err(|e| control_error(StatusCode::BAD_REQUEST, e))
+}
+
+/// Write a channel's replay buffer to disk and report the file; 404 without a
+/// replay buffer, 500 if it is empty or writing fails.
+async fn dump_replay(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> ControlResponse {
+    let channels = state.channels.snapshot();
+    let channel = control::target_channels(&channels, Some(&name))
+        .map_err(|e| control_error(StatusCode::NOT_FOUND, e))?[0];
+    let replay = channel.replay.as_ref().ok_or_else(|| {
+        control_error(
+            StatusCode::NOT_FOUND,
+            format!("Channel '{}' has no replay buffer", name),
+        )
+    })?;
+    let dump = replay
+        .dump()
+        .await
+        .map_err(|e| control_error(StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    Ok(Json(serde_json::json!({ "ok": true, "replay": dump })))
+}
+
+async fn start_timer(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> ControlResponse {
+    run_command(
+        &state,
+        Command::StartTimer {
+            channel: Some(name),
+        },
+    )
+}
+
+async fn pause_timer(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> ControlResponse {
+    run_command(
+        &state,
+        Command::PauseTimer {
+            channel: Some(name),
+        },
+    )
+}
+
+#[derive(Deserialize)]
+struct TimerReset {
+    #[serde(default)]
+    duration: Option<u64>,
+}
+
+/// Stop and rewind a timer; an optional `{"duration": 600}` body sets a new countdown length.
+async fn reset_timer(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    body: Option<Json<TimerReset>>,
+) -> ControlResponse {
+    run_command(
+        &state,
+        Command::ResetTimer {
+            channel: Some(name),
+            duration: body.and_then(|Json(b)| b.duration),
+        },
+    )
+}
+
+/// 404 unless the channel is running, disabled or failed.
+fn require_channel(
+    state: &AppState,
+    name: &str,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let known = state.channels.contains(name)
+        || state.channels.disabled().iter().any(|n| n == name)
+        || state.channels.failed().iter().any(|f| f.name == name);
+    if !known {
+        return Err(control_error(
+            StatusCode::NOT_FOUND,
+            format!("Unknown channel '{}'", name),
+        ));
+    }
+    Ok(())
+}
+
+/// Run any control command given as JSON — the same format as the control stream.
+async fn command_handler(
+    State(state): State<Arc<AppState>>,
+    Json(command): Json<Command>,
+) -> ControlResponse {
+    control::dispatch(&state.channels, &command)
+        .await
+        .map(|_| Json(serde_json::json!({ "ok": true })))
+        .map_err(|e| control_error(StatusCode::BAD_REQUEST, e))
 }
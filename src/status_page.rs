@@ -0,0 +1,176 @@
+use serde_json::Value;
+use std::fmt::Write;
+
+/// Seconds between reloads of the page.
+const REFRESH_SECONDS: u32 = 2;
+
+/// The human-readable status page served at `/`: one row per channel from the
+/// same data as `/status`, reloading itself every `REFRESH_SECONDS`. Plain
+/// HTML without scripts, so it works in any phone browser.
+pub fn render(status: &Value) -> String {
+    let mut rows = String::new();
+    for ch in status["channels"].as_array().into_iter().flatten() {
+        let (health, notes) = channel_health(ch);
+        let input = match ch["ndi_input"].as_object() {
+            Some(input) => format!(
+                "{}<br><small>{} · {:.1} fps</small>",
+                escape(input["alias"].as_str().unwrap_or(text(&input["source"]))),
+                escape(text(&input["state"])),
+                input["fps"].as_f64().unwrap_or(0.0)
+            ),
+            None => "—".to_string(),
+        };
+        let tally = &ch["tally"];
+        let tally = match (tally["on_program"].as_bool(), tally["on_preview"].as_bool()) {
+            (Some(true), _) => "<span class=\"pgm\">PGM</span>",
+            (_, Some(true)) => "<span class=\"pvw\">PVW</span>",
+            _ => "",
+        };
+        let _ = write!(
+            rows,
+            "<tr class=\"{health}\"><td><b>{}</b><br><small>{}</small></td>\
+             <td>{}</td><td>{:.1} / {}<br><small>{} · {:.0}% load</small></td>\
+             <td>{tally} {}</td><td>{}</td></tr>",
+            escape(text(&ch["name"])),
+            escape(text(&ch["output_name"])),
+            input,
+            ch["output_fps"].as_f64().unwrap_or(0.0),
+            ch["frame_rate"],
+            escape(text(&ch["resolution"])),
+            ch["resources"]["render_cpu_percent"]
+                .as_f64()
+                .unwrap_or(0.0),
+            ch["tally"]["connections"],
+            escape(&notes.join(", ")),
+        );
+    }
+    for ch in status["failed_channels"].as_array().into_iter().flatten() {
+        let _ = write!(
+            rows,
+            "<tr class=\"down\"><td><b>{}</b></td><td colspan=\"3\">failed to start \
+             ({} attempts)</td><td>{}</td></tr>",
+            escape(text(&ch["name"])),
+            ch["attempts"],
+            escape(text(&ch["error"])),
+        );
+    }
+    for name in status["disabled_channels"].as_array().into_iter().flatten() {
+        let _ = write!(
+            rows,
+            "<tr class=\"off\"><td><b>{}</b></td><td colspan=\"4\">disabled</td></tr>",
+            escape(text(name)),
+        );
+    }
+
+    let resources = &status["resources"];
+    format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\">\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\
+         <meta http-equiv=\"refresh\" content=\"{REFRESH_SECONDS}\">\
+         <title>NDI Mixer</title><style>{STYLE}</style></head><body>\
+         <h1>NDI Mixer <small>v{} · {} · up {} · CPU {:.0}% · {} MB</small></h1>\
+         <table><tr><th>Channel</th><th>Input</th><th>FPS</th><th>Tally / receivers</th>\
+         <th>Notes</th></tr>{rows}</table>\
+         <p><small>Refreshes every {REFRESH_SECONDS}s · <a href=\"/status\">JSON</a></small></p>\
+         </body></html>\n",
+        escape(text(&status["version"])),
+        escape(text(&status["compositor"])).to_uppercase(),
+        uptime(status["uptime_seconds"].as_u64().unwrap_or(0)),
+        resources["cpu_percent"].as_f64().unwrap_or(0.0),
+        resources["rss_bytes"].as_u64().unwrap_or(0) / 1_000_000,
+    )
+}
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;margin:1em;background:#111;color:#ddd}\
+h1{font-size:1.2em}h1 small{color:#888;font-weight:normal}\
+table{border-collapse:collapse;width:100%}th,td{padding:.4em;text-align:left;vertical-align:top}\
+th{color:#888;font-weight:normal;border-bottom:1px solid #333}td small{color:#999}\
+tr.ok td:first-child{border-left:4px solid #3a3}tr.warn td:first-child{border-left:4px solid #d90}\
+tr.down td:first-child{border-left:4px solid #d33}tr.off{color:#666}\
+tr.off td:first-child{border-left:4px solid #444}tr+tr td{border-top:1px solid #222}\
+.pgm{background:#c22;color:#fff;padding:0 .3em}.pvw{background:#2a2;color:#fff;padding:0 .3em}\
+a{color:#8af}";
+
+/// `ok`, `warn` or `down`, with what's wrong (or noteworthy).
+fn channel_health(ch: &Value) -> (&'static str, Vec<String>) {
+    let mut down = Vec::new();
+    let mut warn = Vec::new();
+    let mut notes = Vec::new();
+
+    if let Some(state @ ("lost" | "timed_out" | "searching")) = ch["ndi_input"]["state"].as_str() {
+        down.push(format!("input {}", state));
+    }
+    if ch["ndi_input"]["rate_mismatch"].as_bool() == Some(true) {
+        warn.push("input frame rate mismatch".to_string());
+    }
+    if let Some(mode) = ch["output_blank"].as_str() {
+        down.push(format!("output blanked ({})", mode));
+    }
+    if ch["slate"].as_bool() == Some(true) {
+        warn.push("slate on air".to_string());
+    }
+    if ch["warming_up"].as_bool() == Some(true) {
+        warn.push("warming up".to_string());
+    }
+    if ch["overload"]["active"].as_bool() == Some(true) {
+        warn.push(format!(
+            "overloaded ({})",
+            ch["overload"]["policy"].as_str().unwrap_or("")
+        ));
+    }
+    let frame_rate = ch["frame_rate"].as_f64().unwrap_or(0.0);
+    if ch["output_fps"]
+        .as_f64()
+        .is_some_and(|fps| fps < frame_rate * 0.9)
+    {
+        warn.push("dropping frames".to_string());
+    }
+    for (i, overlay) in ch["browser_overlays"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        if let Some(error) = overlay["error"].as_str() {
+            down.push(format!("overlay {} failed: {}", i, error));
+        } else if overlay["loaded"].as_bool() == Some(false) {
+            warn.push(format!("overlay {} loading", i));
+        } else if overlay["capture"]["health"].as_str() == Some("poor") {
+            warn.push(format!("overlay {} capture poor", i));
+        }
+    }
+    if ch["gpu"]["backing_off"].as_bool() == Some(true) {
+        warn.push("GPU backing off".to_string());
+    }
+    if let Some(scene) = ch["scene"].as_str() {
+        notes.push(format!("scene {}", scene));
+    }
+
+    let health = if !down.is_empty() {
+        "down"
+    } else if !warn.is_empty() {
+        "warn"
+    } else {
+        "ok"
+    };
+    (health, down.into_iter().chain(warn).chain(notes).collect())
+}
+
+fn text(value: &Value) -> &str {
+    value.as_str().unwrap_or("")
+}
+
+fn uptime(seconds: u64) -> String {
+    match seconds {
+        s if s < 3600 => format!("{}m", s / 60),
+        s if s < 86400 => format!("{}h {}m", s / 3600, s % 3600 / 60),
+        s => format!("{}d {}h", s / 86400, s % 86400 / 3600),
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
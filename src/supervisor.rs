@@ -0,0 +1,402 @@
+use anyhow::Result;
+use futures::StreamExt;
+use grafton_ndi::NDI;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio_util::sync::CancellationToken;
+
+use crate::browser::OverlayManager;
+use crate::channel::{
+    Channel, ChannelChange, ChannelRegistry, ChannelRequest, GpuCtxParam, InputSource,
+};
+use crate::config::{ChannelConfig, ChannelErrorPolicy, HookEvent, Settings};
+use crate::ndi_input::InputManager;
+
+/// Everything needed to start a channel after startup (enabling a channel,
+/// auto channels).
+#[derive(Clone)]
+pub struct Launcher {
+    pub settings: Settings,
+    pub ndi: NDI,
+    pub inputs: InputManager,
+    pub overlays: Option<OverlayManager>,
+    pub gpu_ctx: GpuCtxParam,
+    pub channels: ChannelRegistry,
+    /// The mixer's token; each channel gets a child of it
+    pub cancel: CancellationToken,
+}
+
+/// A channel started by a `Launcher`, stoppable on its own.
+pub struct RunningChannel {
+    pub channel: Channel,
+    cancel: CancellationToken,
+}
+
+impl Launcher {
+    /// Start a channel and add it to the registry.
+    pub async fn start(&self, config: &ChannelConfig) -> Result<RunningChannel> {
+        let running = self.launch(config).await?;
+        self.channels.insert(running.channel.state.clone());
+        Ok(running)
+    }
+
+    /// Start a channel without adding it to the registry.
+    async fn launch(&self, config: &ChannelConfig) -> Result<RunningChannel> {
+        let cancel = self.cancel.child_token();
+        let channel = Channel::start(
+            config,
+            &self.settings,
+            &self.ndi,
+            &self.inputs,
+            self.overlays.as_ref(),
+            self.gpu_ctx.clone(),
+            cancel.clone(),
+        )
+        .await
+        .inspect_err(|_| cancel.cancel())?;
+        Ok(RunningChannel { channel, cancel })
+    }
+
+    /// Stop a channel's pipeline, take it out of the registry and fire its
+    /// `stopped` hooks. Returns once its NDI output is released, so the name
+    /// can be reused right away.
+    pub async fn stop(&self, running: RunningChannel) {
+        running.cancel.cancel();
+        let state = running.channel.state.clone();
+        self.channels.remove(&state.name);
+        running.channel.finished().await;
+        state.hooks.fire(HookEvent::Stopped);
+    }
+}
+
+/// Owns the configured `[[channel]]`s: starts the enabled ones at startup, then
+/// enables, disables, resizes and renames channels on request while keeping
+/// their configuration.
+pub struct Supervisor {
+    configs: Vec<ChannelConfig>,
+    /// `[sources]` aliases, for switching inputs by alias
+    sources: HashMap<String, String>,
+    running: HashMap<String, RunningChannel>,
+    launcher: Launcher,
+}
+
+impl Supervisor {
+    pub fn new(
+        configs: Vec<ChannelConfig>,
+        sources: HashMap<String, String>,
+        launcher: Launcher,
+    ) -> Self {
+        Self {
+            configs,
+            sources,
+            running: HashMap::new(),
+            launcher,
+        }
+    }
+
+    /// Start every enabled channel, up to `startup_concurrency` at a time;
+    /// disabled ones are only listed. With `on_channel_error = "abort"`, fails
+    /// with every channel's error if any channel fails, after stopping the ones
+    /// that did start; otherwise failed channels are recorded and skipped.
+    pub async fn start_enabled(&mut self) -> Result<()> {
+        let mut enabled = Vec::new();
+        for config in &self.configs {
+            if config.enabled {
+                enabled.push(config);
+            } else {
+                tracing::info!("Channel '{}' is disabled", config.name);
+                self.launcher.channels.set_disabled(&config.name, true);
+            }
+        }
+
+        let launcher = &self.launcher;
+        // In config order, so channels are listed the way they're configured
+        let results: Vec<_> = futures::stream::iter(enabled)
+            .map(|config| async move { (config, launcher.launch(config).await) })
+            .buffered(launcher.settings.startup_concurrency)
+            .collect()
+            .await;
+
+        let mut failures = Vec::new();
+        for (config, result) in results {
+            match result {
+                Ok(running) => {
+                    launcher.channels.insert(running.channel.state.clone());
+                    self.running.insert(config.name.clone(), running);
+                }
+                Err(e) if launcher.settings.on_channel_error != ChannelErrorPolicy::Abort => {
+                    tracing::error!(
+                        "Channel '{}' failed to start, continuing without it: {:#}",
+                        config.name,
+                        e
+                    );
+                    launcher.channels.record_failure(&config.name, &e);
+                }
+                Err(e) => failures.push(format!("channel '{}': {:#}", config.name, e)),
+            }
+        }
+        if !failures.is_empty() {
+            for (_, running) in self.running.drain() {
+                self.launcher.stop(running).await;
+            }
+            anyhow::bail!(
+                "{} channel(s) failed to start:\n  {}",
+                failures.len(),
+                failures.join("\n  ")
+            );
+        }
+        Ok(())
+    }
+
+    /// Serve channel change requests until cancelled, retrying failed channels
+    /// with `on_channel_error = "retry"`.
+    pub async fn run(mut self, mut requests: UnboundedReceiver<ChannelRequest>) {
+        let cancel = self.launcher.cancel.clone();
+        let retry = self.launcher.settings.on_channel_error == ChannelErrorPolicy::Retry;
+        let retry_delay = Duration::from_secs(self.launcher.settings.channel_retry_delay);
+        let mut retry_timer =
+            tokio::time::interval_at(tokio::time::Instant::now() + retry_delay, retry_delay);
+        loop {
+            let (name, change, reply) = tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = retry_timer.tick(), if retry => {
+                    self.retry_failed().await;
+                    continue;
+                }
+                request = requests.recv() => match request {
+                    Some(request) => request,
+                    None => break,
+                },
+            };
+            let outcome = match change {
+                ChannelChange::Enable => self.set_enabled(&name, true).await,
+                ChannelChange::Disable => self.set_enabled(&name, false).await,
+                ChannelChange::Resolution { width, height } => {
+                    self.set_resolution(&name, width, height).await
+                }
+                ChannelChange::OutputName(output_name) => {
+                    self.set_output_name(&name, &output_name).await
+                }
+                ChannelChange::InputSource(source) => self.set_input_source(&name, &source),
+                ChannelChange::Reload(config) => self.reload(&name, &config).await,
+            };
+            let _ = reply.send(outcome);
+        }
+    }
+
+    /// Try once more to start each channel that failed to start.
+    async fn retry_failed(&mut self) {
+        for failed in self.launcher.channels.failed() {
+            let Some(config) = self.configs.iter().find(|c| c.name == failed.name) else {
+                continue;
+            };
+            match self.launcher.start(config).await {
+                Ok(running) => {
+                    self.running.insert(config.name.clone(), running);
+                    self.launcher.channels.clear_failure(&config.name);
+                    tracing::info!(
+                        "Channel '{}' started after {} failed attempts",
+                        config.name,
+                        failed.attempts
+                    );
+                }
+                Err(e) => {
+                    tracing::warn!("Channel '{}' still fails to start: {:#}", config.name, e);
+                    self.launcher.channels.record_failure(&config.name, &e);
+                }
+            }
+        }
+    }
+
+    async fn set_enabled(&mut self, name: &str, enabled: bool) -> Result<()> {
+        let config = self
+            .configs
+            .iter()
+            .find(|c| c.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown configured channel '{}'", name))?;
+        match (enabled, self.running.remove(name)) {
+            (true, None) => {
+                let running = self.launcher.start(config).await.inspect_err(|e| {
+                    // A failed channel stays failed (and retried) until it starts
+                    if self
+                        .launcher
+                        .channels
+                        .failed()
+                        .iter()
+                        .any(|f| f.name == name)
+                    {
+                        self.launcher.channels.record_failure(name, e);
+                    }
+                })?;
+                self.running.insert(config.name.clone(), running);
+                self.launcher.channels.set_disabled(name, false);
+                self.launcher.channels.clear_failure(name);
+                tracing::info!("Channel '{}' enabled", name);
+            }
+            (false, Some(running)) => {
+                self.launcher.stop(running).await;
+                self.launcher.channels.set_disabled(name, true);
+                tracing::info!("Channel '{}' disabled", name);
+            }
+            // Already in the requested state
+            (true, Some(running)) => {
+                self.running.insert(config.name.clone(), running);
+            }
+            // Disabling a failed channel stops any retries
+            (false, None) => {
+                if self.launcher.channels.clear_failure(name) {
+                    self.launcher.channels.set_disabled(name, true);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Change a channel's output resolution. A running channel is restarted so
+    /// its NDI sender, canvases and GPU textures are recreated at the new size;
+    /// if it fails to start, it comes back at the previous resolution, or is
+    /// reported failed if that fails too.
+    async fn set_resolution(&mut self, name: &str, width: u32, height: u32) -> Result<()> {
+        let config = self
+            .configs
+            .iter_mut()
+            .find(|c| c.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown configured channel '{}'", name))?;
+        let previous = (config.width, config.height);
+        if previous == (width, height) {
+            return Ok(());
+        }
+        (config.width, config.height) = (width, height);
+        if let Err(e) = config.validate() {
+            (config.width, config.height) = previous;
+            return Err(e);
+        }
+
+        if let Some(running) = self.running.remove(name) {
+            self.launcher.stop(running).await;
+            match self.launcher.start(config).await {
+                Ok(running) => {
+                    self.running.insert(config.name.clone(), running);
+                }
+                Err(e) => {
+                    (config.width, config.height) = previous;
+                    match self.launcher.start(config).await {
+                        Ok(running) => {
+                            self.running.insert(config.name.clone(), running);
+                        }
+                        Err(restart_error) => {
+                            tracing::error!(
+                                "Channel '{}' failed to restart: {:#}",
+                                name,
+                                restart_error
+                            );
+                            // Failed like a channel that didn't start, so it's retried
+                            self.launcher.channels.record_failure(name, &restart_error);
+                        }
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        tracing::info!(
+            "Channel '{}' resolution changed to {}x{}",
+            name,
+            width,
+            height
+        );
+        Ok(())
+    }
+
+    /// Rename a channel's NDI output. A running channel only has its sender
+    /// recreated; a disabled one uses the new name once enabled.
+    async fn set_output_name(&mut self, name: &str, output_name: &str) -> Result<()> {
+        if output_name.is_empty() {
+            anyhow::bail!("output_name must not be empty");
+        }
+        let taken_by = self
+            .configs
+            .iter()
+            .map(|c| (c.name.clone(), c.output_name.clone()))
+            .chain(self.launcher.channels.snapshot().iter().map(|ch| {
+                let output = ch.output_name.lock().unwrap().clone();
+                (ch.name.clone(), output)
+            }))
+            .find(|(channel, output)| channel != name && output == output_name);
+        if let Some((channel, _)) = taken_by {
+            anyhow::bail!(
+                "Output name '{}' is already used by channel '{}'",
+                output_name,
+                channel
+            );
+        }
+
+        let config = self
+            .configs
+            .iter_mut()
+            .find(|c| c.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown configured channel '{}'", name))?;
+        if config.output_name == output_name {
+            return Ok(());
+        }
+        if let Some(running) = self.running.get(name) {
+            running.channel.state.rename_output(output_name).await?;
+        }
+        config.output_name = output_name.to_string();
+        tracing::info!("Channel '{}' output renamed to '{}'", name, output_name);
+        Ok(())
+    }
+
+    /// Switch a channel's NDI input to another source. A running channel only
+    /// has its input resubscribed; a disabled one starts on the new source once
+    /// enabled.
+    fn set_input_source(&mut self, name: &str, source: &str) -> Result<()> {
+        if source.trim().is_empty() {
+            anyhow::bail!("source must not be empty");
+        }
+        let config = self
+            .configs
+            .iter_mut()
+            .find(|c| c.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown configured channel '{}'", name))?;
+        let input = config
+            .ndi_input
+            .as_mut()
+            .ok_or_else(|| anyhow::anyhow!("Channel '{}' has no NDI input", name))?;
+        let source = match self.sources.get(source) {
+            Some(ndi_name) => InputSource {
+                name: ndi_name.clone(),
+                alias: Some(source.to_string()),
+            },
+            None => InputSource {
+                name: source.to_string(),
+                alias: None,
+            },
+        };
+        if let Some(running) = self.running.get(name) {
+            running.channel.state.switch_source(source.clone())?;
+        }
+        tracing::info!("Channel '{}' input switched to '{}'", name, source.label());
+        input.source = source.name;
+        input.alias = source.alias;
+        Ok(())
+    }
+
+    /// Take the live settings of a reloaded config (see
+    /// `ChannelConfig::copy_live_settings`) and apply them to the channel if it
+    /// runs. The rest of the stored config, runtime changes included, stays.
+    async fn reload(&mut self, name: &str, reloaded: &ChannelConfig) -> Result<()> {
+        let config = self
+            .configs
+            .iter_mut()
+            .find(|c| c.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown configured channel '{}'", name))?;
+        let previous = config.clone();
+        config.copy_live_settings(reloaded);
+        if let Some(running) = self.running.get(name) {
+            running.channel.state.apply_live(config, &previous).await;
+        }
+        tracing::info!("Channel '{}' live settings reloaded", name);
+        Ok(())
+    }
+}
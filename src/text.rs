@@ -0,0 +1,259 @@
+use ab_glyph::{Font, FontArc, FontVec, PxScale, ScaleFont};
+use anyhow::Result;
+use image::{ImageBuffer, Rgba, RgbaImage};
+use std::path::Path;
+
+use crate::config::TextPosition;
+
+/// Default font used by native text layers when none is configured.
+pub const DEFAULT_FONT: &str = "/System/Library/Fonts/Supplemental/Arial.ttf";
+
+/// Load a TTF/OTF font (or the first face of a TTC collection).
+pub fn load_font(path: &str) -> Result<FontArc> {
+    let data = std::fs::read(Path::new(path))
+        .map_err(|e| anyhow::anyhow!("Failed to read font {}: {}", path, e))?;
+    let font = FontVec::try_from_vec_and_index(data, 0)
+        .map_err(|e| anyhow::anyhow!("Failed to parse font {}: {}", path, e))?;
+    Ok(FontArc::new(font))
+}
+
+/// Parse `#RRGGBB` or `#RRGGBBAA` into straight RGBA.
+pub fn parse_color(s: &str) -> Option<[u8; 4]> {
+    let hex = s.strip_prefix('#')?;
+    if !hex.is_ascii() {
+        return None;
+    }
+    let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    match hex.len() {
+        6 => Some([byte(0)?, byte(2)?, byte(4)?, 255]),
+        8 => Some([byte(0)?, byte(2)?, byte(4)?, byte(6)?]),
+        _ => None,
+    }
+}
+
+/// Visual style for a block of text.
+#[derive(Debug, Clone)]
+pub struct TextStyle {
+    pub size: f32,
+    pub color: [u8; 4],
+    /// Box drawn behind the text (alpha 0 = no box)
+    pub background: [u8; 4],
+    /// Padding between the text and the edge of the background box
+    pub padding: u32,
+}
+
+/// Width in pixels of a single line of text.
+pub fn line_width(font: &FontArc, size: f32, text: &str) -> f32 {
+    let scaled = font.as_scaled(PxScale::from(size));
+    let mut width = 0.0;
+    let mut prev = None;
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        if let Some(p) = prev {
+            width += scaled.kern(p, id);
+        }
+        width += scaled.h_advance(id);
+        prev = Some(id);
+    }
+    width
+}
+
+/// Greedy word wrap so no line exceeds `max_width` pixels.
+pub fn wrap(font: &FontArc, size: f32, text: &str, max_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut current = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if current.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", current, word)
+            };
+            if !current.is_empty() && line_width(font, size, &candidate) > max_width {
+                lines.push(std::mem::take(&mut current));
+                current = word.to_string();
+            } else {
+                current = candidate;
+            }
+        }
+        lines.push(current);
+    }
+    lines
+}
+
+/// Size of the box `draw_lines` will fill for these lines, including padding.
+pub fn block_size(font: &FontArc, style: &TextStyle, lines: &[String]) -> (u32, u32) {
+    let scaled = font.as_scaled(PxScale::from(style.size));
+    let line_height = scaled.height() + scaled.line_gap();
+    let width = lines
+        .iter()
+        .map(|l| line_width(font, style.size, l))
+        .fold(0.0f32, f32::max);
+    (
+        width.ceil() as u32 + style.padding * 2,
+        (line_height * lines.len() as f32).ceil() as u32 + style.padding * 2,
+    )
+}
+
+/// Draw lines of text with their top-left box corner at (x, y), each line centered
+/// within the block. Text is alpha-blended over whatever is already in `canvas`.
+pub fn draw_lines(
+    canvas: &mut RgbaImage,
+    font: &FontArc,
+    style: &TextStyle,
+    lines: &[String],
+    x: i32,
+    y: i32,
+) {
+    let (block_w, block_h) = block_size(font, style, lines);
+
+    if style.background[3] > 0 {
+        fill_rect(canvas, x, y, block_w, block_h, style.background);
+    }
+
+    let scaled = font.as_scaled(PxScale::from(style.size));
+    let line_height = scaled.height() + scaled.line_gap();
+    let inner_w = block_w.saturating_sub(style.padding * 2) as f32;
+
+    for (i, line) in lines.iter().enumerate() {
+        let offset = (inner_w - line_width(font, style.size, line)) / 2.0;
+        let mut caret_x = x as f32 + style.padding as f32 + offset;
+        let baseline = y as f32 + style.padding as f32 + scaled.ascent() + line_height * i as f32;
+        let mut prev = None;
+
+        for c in line.chars() {
+            let mut glyph = scaled.scaled_glyph(c);
+            if let Some(p) = prev {
+                caret_x += scaled.kern(p, glyph.id);
+            }
+            glyph.position = ab_glyph::point(caret_x, baseline);
+            caret_x += scaled.h_advance(glyph.id);
+            prev = Some(glyph.id);
+
+            if let Some(outlined) = font.outline_glyph(glyph) {
+                let bounds = outlined.px_bounds();
+                outlined.draw(|gx, gy, coverage| {
+                    let px = bounds.min.x as i32 + gx as i32;
+                    let py = bounds.min.y as i32 + gy as i32;
+                    blend_pixel(canvas, px, py, style.color, coverage);
+                });
+            }
+        }
+    }
+}
+
+/// A line of text at a fixed spot on a transparent canvas-sized image, redrawn
+/// only when the text changes; only the previous text's box is cleared.
+pub struct AnchoredText {
+    font: FontArc,
+    style: TextStyle,
+    position: TextPosition,
+    margin: u32,
+    image: RgbaImage,
+    text: Option<String>,
+    /// Box of the text currently drawn
+    drawn: Option<(i32, i32, u32, u32)>,
+    generation: u64,
+}
+
+impl AnchoredText {
+    pub fn new(
+        font: FontArc,
+        style: TextStyle,
+        position: TextPosition,
+        margin: u32,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        Self {
+            font,
+            style,
+            position,
+            margin,
+            image: ImageBuffer::from_pixel(width, height, Rgba([0, 0, 0, 0])),
+            text: None,
+            drawn: None,
+            generation: 0,
+        }
+    }
+
+    /// Show `text` (None hides it). Returns the image and its generation, or
+    /// None while hidden.
+    pub fn show(&mut self, text: Option<&str>) -> Option<(&RgbaImage, u64)> {
+        if text != self.text.as_deref() {
+            self.render(text);
+            self.text = text.map(String::from);
+            self.generation += 1;
+        }
+        self.text.as_ref().map(|_| (&self.image, self.generation))
+    }
+
+    fn render(&mut self, text: Option<&str>) {
+        if let Some((x, y, w, h)) = self.drawn.take() {
+            clear_rect(&mut self.image, x, y, w, h);
+        }
+        let Some(text) = text else {
+            return;
+        };
+
+        let lines = vec![text.to_string()];
+        let (block_w, block_h) = block_size(&self.font, &self.style, &lines);
+        let (width, height, margin) = (
+            self.image.width() as i32,
+            self.image.height() as i32,
+            self.margin as i32,
+        );
+        let x = match self.position {
+            TextPosition::TopLeft | TextPosition::BottomLeft => margin,
+            TextPosition::Top | TextPosition::Bottom => (width - block_w as i32) / 2,
+            TextPosition::TopRight | TextPosition::BottomRight => width - margin - block_w as i32,
+        };
+        let y = match self.position {
+            TextPosition::TopLeft | TextPosition::Top | TextPosition::TopRight => margin,
+            _ => height - margin - block_h as i32,
+        };
+        draw_lines(&mut self.image, &self.font, &self.style, &lines, x, y);
+        self.drawn = Some((x, y, block_w, block_h));
+    }
+}
+
+/// Make a rectangle fully transparent, clipped to the image.
+fn clear_rect(img: &mut RgbaImage, x: i32, y: i32, w: u32, h: u32) {
+    let x0 = x.clamp(0, img.width() as i32) as u32;
+    let y0 = y.clamp(0, img.height() as i32) as u32;
+    let x1 = (x + w as i32).clamp(0, img.width() as i32) as u32;
+    let y1 = (y + h as i32).clamp(0, img.height() as i32) as u32;
+    for py in y0..y1 {
+        for px in x0..x1 {
+            img.put_pixel(px, py, Rgba([0, 0, 0, 0]));
+        }
+    }
+}
+
+/// Fill a rectangle by blending `color` over the canvas, clipped to its bounds.
+pub fn fill_rect(canvas: &mut RgbaImage, x: i32, y: i32, w: u32, h: u32, color: [u8; 4]) {
+    for py in y..y + h as i32 {
+        for px in x..x + w as i32 {
+            blend_pixel(canvas, px, py, color, 1.0);
+        }
+    }
+}
+
+/// Straight-alpha "source over" of a single pixel, with coverage as extra opacity.
+fn blend_pixel(canvas: &mut RgbaImage, x: i32, y: i32, color: [u8; 4], coverage: f32) {
+    if x < 0 || y < 0 || x >= canvas.width() as i32 || y >= canvas.height() as i32 {
+        return;
+    }
+    let sa = color[3] as f32 / 255.0 * coverage.clamp(0.0, 1.0);
+    if sa <= 0.0 {
+        return;
+    }
+    let dst = canvas.get_pixel_mut(x as u32, y as u32);
+    let da = dst.0[3] as f32 / 255.0;
+    let out_a = sa + da * (1.0 - sa);
+    for (d, s) in dst.0.iter_mut().zip(color).take(3) {
+        let blended = (s as f32 * sa + *d as f32 * da * (1.0 - sa)) / out_a.max(f32::EPSILON);
+        *d = blended.round() as u8;
+    }
+    dst.0[3] = (out_a * 255.0).round() as u8;
+}
@@ -0,0 +1,62 @@
+use anyhow::Result;
+use image::RgbaImage;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::clock::format_time;
+use crate::config::TextOverlayConfig;
+use crate::text::{self, AnchoredText, TextStyle};
+
+/// A line of text drawn natively, for labels and simple clocks that don't
+/// need a browser page. The text is expanded every frame and only redrawn
+/// when it changes.
+pub struct TextOverlay {
+    template: String,
+    channel: String,
+    /// The channel's current NDI output name, which can be renamed at runtime
+    output_name: Arc<Mutex<String>>,
+    utc: bool,
+    frame_rate: u32,
+    text: AnchoredText,
+}
+
+impl TextOverlay {
+    pub fn new(
+        cfg: &TextOverlayConfig,
+        channel: &str,
+        output_name: Arc<Mutex<String>>,
+        (width, height): (u32, u32),
+        frame_rate: u32,
+    ) -> Result<Self> {
+        let font = text::load_font(cfg.font.as_deref().unwrap_or(text::DEFAULT_FONT))?;
+        Ok(Self {
+            template: cfg.text.clone(),
+            channel: channel.to_string(),
+            output_name,
+            utc: cfg.utc,
+            frame_rate,
+            text: AnchoredText::new(
+                font,
+                TextStyle {
+                    size: cfg.font_size,
+                    color: text::parse_color(&cfg.color).unwrap_or([255, 255, 255, 255]),
+                    background: text::parse_color(&cfg.background).unwrap_or([0, 0, 0, 0]),
+                    padding: (cfg.font_size / 4.0) as u32,
+                },
+                cfg.position,
+                cfg.margin,
+                width,
+                height,
+            ),
+        })
+    }
+
+    /// Returns the text rendered for the current frame and its generation.
+    pub fn frame(&mut self) -> Option<(&RgbaImage, u64)> {
+        // Times first, so a `%` in a channel or output name is shown as is
+        let text = format_time(&self.template, SystemTime::now(), self.utc, self.frame_rate)
+            .replace("{channel}", &self.channel)
+            .replace("{output}", &self.output_name.lock().unwrap());
+        self.text.show(Some(&text))
+    }
+}
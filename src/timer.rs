@@ -0,0 +1,207 @@
+use anyhow::Result;
+use image::RgbaImage;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::config::{TimerConfig, TimerEnd, TimerMode};
+use crate::text::{self, AnchoredText, TextStyle};
+
+/// Run state of a countdown or stopwatch, driven by the control API.
+#[derive(Debug)]
+pub struct Timer {
+    pub mode: TimerMode,
+    /// Countdown length
+    pub duration: Duration,
+    /// Time counted before the current run
+    banked: Duration,
+    /// Start of the current run; None while paused
+    running_since: Option<Instant>,
+}
+
+/// A channel's timer, shared between its render thread and the control API.
+pub type SharedTimer = Arc<Mutex<Timer>>;
+
+/// Timer state for `/status`, in seconds.
+#[derive(Debug, Clone, Serialize)]
+pub struct TimerStatus {
+    pub mode: TimerMode,
+    pub running: bool,
+    pub elapsed: f64,
+    /// Countdown only; negative once the countdown has run out
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining: Option<f64>,
+}
+
+impl Timer {
+    pub fn new(mode: TimerMode, duration: Duration, autostart: bool) -> Self {
+        Self {
+            mode,
+            duration,
+            banked: Duration::ZERO,
+            running_since: autostart.then(Instant::now),
+        }
+    }
+
+    /// Start or resume counting; no effect while running.
+    pub fn start(&mut self) {
+        self.running_since.get_or_insert_with(Instant::now);
+    }
+
+    /// Stop counting, keeping the time counted so far.
+    pub fn pause(&mut self) {
+        if let Some(since) = self.running_since.take() {
+            self.banked += since.elapsed();
+        }
+    }
+
+    /// Stop and return to the start, optionally with a new countdown length.
+    pub fn reset(&mut self, duration: Option<Duration>) {
+        self.banked = Duration::ZERO;
+        self.running_since = None;
+        if let Some(duration) = duration {
+            self.duration = duration;
+        }
+    }
+
+    /// Time counted up to `at`.
+    pub fn elapsed_at(&self, at: Instant) -> Duration {
+        self.banked
+            + self
+                .running_since
+                .map_or(Duration::ZERO, |since| at.saturating_duration_since(since))
+    }
+
+    pub fn status(&self) -> TimerStatus {
+        let elapsed = self.elapsed_at(Instant::now()).as_secs_f64();
+        let round = |secs: f64| (secs * 1000.0).round() / 1000.0;
+        TimerStatus {
+            mode: self.mode,
+            running: self.running_since.is_some(),
+            elapsed: round(elapsed),
+            remaining: (self.mode == TimerMode::Countdown)
+                .then(|| round(self.duration.as_secs_f64() - elapsed)),
+        }
+    }
+}
+
+/// Countdown/stopwatch layer: shows the timer's value at each frame's start,
+/// counted in whole output frames.
+pub struct TimerLayer {
+    timer: SharedTimer,
+    format: String,
+    frame_rate: u32,
+    on_end: TimerEnd,
+    text: AnchoredText,
+}
+
+impl TimerLayer {
+    pub fn new(cfg: &TimerConfig, width: u32, height: u32, frame_rate: u32) -> Result<Self> {
+        let font = text::load_font(cfg.font.as_deref().unwrap_or(text::DEFAULT_FONT))?;
+        Ok(Self {
+            timer: Arc::new(Mutex::new(Timer::new(
+                cfg.mode,
+                Duration::from_secs(cfg.duration),
+                cfg.autostart,
+            ))),
+            format: cfg.format.clone(),
+            frame_rate,
+            on_end: cfg.on_end,
+            text: AnchoredText::new(
+                font,
+                TextStyle {
+                    size: cfg.font_size,
+                    color: text::parse_color(&cfg.color).unwrap_or([255, 255, 255, 255]),
+                    background: text::parse_color(&cfg.background).unwrap_or([0, 0, 0, 0]),
+                    padding: (cfg.font_size / 4.0) as u32,
+                },
+                cfg.position,
+                cfg.margin,
+                width,
+                height,
+            ),
+        })
+    }
+
+    pub fn timer(&self) -> SharedTimer {
+        self.timer.clone()
+    }
+
+    /// Returns the timer rendered for the frame starting at `at` and its
+    /// generation, or None when a finished countdown is hidden.
+    pub fn frame(&mut self, at: Instant) -> Option<(&RgbaImage, u64)> {
+        let (mode, duration, elapsed) = {
+            let timer = self.timer.lock().unwrap();
+            (timer.mode, timer.duration, timer.elapsed_at(at))
+        };
+        let fps = self.frame_rate as u128;
+        let shows_frames = self.format.contains("%f");
+
+        let text = match mode {
+            TimerMode::Stopwatch => Some(format_timer(
+                &self.format,
+                frames_floor(elapsed, fps),
+                fps,
+                false,
+            )),
+            TimerMode::Countdown if elapsed < duration => {
+                // Round up, so zero only shows once the countdown has run out
+                let mut frames = frames_ceil(duration - elapsed, fps);
+                if !shows_frames {
+                    frames = frames.div_ceil(fps) * fps;
+                }
+                Some(format_timer(&self.format, frames, fps, false))
+            }
+            TimerMode::Countdown => match self.on_end {
+                TimerEnd::Hold => Some(format_timer(&self.format, 0, fps, false)),
+                TimerEnd::Hide => None,
+                TimerEnd::Overrun => {
+                    let over = frames_floor(elapsed - duration, fps);
+                    let whole = if shows_frames { over } else { over / fps * fps };
+                    Some(format_timer(&self.format, over, fps, whole > 0))
+                }
+            },
+        };
+        self.text.show(text.as_deref())
+    }
+}
+
+fn frames_floor(d: Duration, fps: u128) -> u128 {
+    d.as_nanos() * fps / 1_000_000_000
+}
+
+fn frames_ceil(d: Duration, fps: u128) -> u128 {
+    (d.as_nanos() * fps).div_ceil(1_000_000_000)
+}
+
+/// Expand `%H %M %S %f %%` for a count of frames. The largest unit in the
+/// format carries the rest, so `%M:%S` shows 90 minutes as `90:00`.
+fn format_timer(format: &str, frames: u128, fps: u128, negative: bool) -> String {
+    let has = |spec: &str| format.contains(spec);
+    let total_secs = frames / fps;
+    let hours = if has("%H") { total_secs / 3600 } else { 0 };
+    let rest = total_secs - hours * 3600;
+    let minutes = if has("%M") { rest / 60 } else { 0 };
+    let seconds = rest - minutes * 60;
+
+    let mut out = String::with_capacity(format.len() + 4);
+    if negative {
+        out.push('-');
+    }
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('H') => out.push_str(&format!("{:02}", hours)),
+            Some('M') => out.push_str(&format!("{:02}", minutes)),
+            Some('S') => out.push_str(&format!("{:02}", seconds)),
+            Some('f') => out.push_str(&format!("{:02}", frames % fps)),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
@@ -0,0 +1,139 @@
+use std::time::{Duration, Instant};
+
+use crate::compositor::Rect;
+use crate::config::{Easing, TransitionConfig, TransitionKind};
+
+/// A running transition: eased progress from 0 at `started` to 1 after `duration`.
+#[derive(Debug, Clone, Copy)]
+pub struct Transition {
+    started: Instant,
+    duration: Duration,
+    easing: Easing,
+}
+
+impl Transition {
+    pub fn new(duration: Duration, easing: Easing, now: Instant) -> Self {
+        Self {
+            started: now,
+            duration,
+            easing,
+        }
+    }
+
+    /// A transition starting at `now`; None when `config` cuts.
+    pub fn start(config: Option<&TransitionConfig>, now: Instant) -> Option<Self> {
+        let config = config.filter(|c| c.kind != TransitionKind::Cut && c.duration_ms > 0)?;
+        Some(Self::new(
+            Duration::from_millis(config.duration_ms),
+            config.easing,
+            now,
+        ))
+    }
+
+    /// How far the transition has got by `now`, eased, from 0 to 1.
+    pub fn progress(&self, now: Instant) -> f32 {
+        let t =
+            now.saturating_duration_since(self.started).as_secs_f32() / self.duration.as_secs_f32();
+        ease(self.easing, t.clamp(0.0, 1.0))
+    }
+
+    pub fn finished(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.started) >= self.duration
+    }
+}
+
+fn ease(easing: Easing, t: f32) -> f32 {
+    match easing {
+        Easing::Linear => t,
+        Easing::EaseIn => t * t,
+        Easing::EaseOut => t * (2.0 - t),
+        Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+    }
+}
+
+/// Values a tween can ease between.
+pub trait Lerp: Copy {
+    /// The value `t` of the way from `self` to `to`.
+    fn lerp(self, to: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+}
+
+/// A canvas area, its size kept at least one pixel.
+impl Lerp for Rect {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        let lerp = |a: u32, b: u32| (a as f32).lerp(b as f32, t).round() as u32;
+        (
+            lerp(self.0, to.0),
+            lerp(self.1, to.1),
+            lerp(self.2, to.2).max(1),
+            lerp(self.3, to.3).max(1),
+        )
+    }
+}
+
+/// A value set through the control API, easing to its target over a
+/// transition instead of snapping to it.
+#[derive(Debug, Clone, Copy)]
+pub struct Tween<T> {
+    /// Where the transition began; None until the render thread first draws
+    /// a tween that starts from the layer's configured value
+    from: Option<T>,
+    to: T,
+    transition: Option<Transition>,
+}
+
+impl<T: Lerp> Tween<T> {
+    /// Settled at `value`.
+    pub fn new(value: T) -> Self {
+        Self {
+            from: Some(value),
+            to: value,
+            transition: None,
+        }
+    }
+
+    /// Easing to `to` from the value the render thread has for the layer
+    /// (see `value`), or straight to it without a transition.
+    pub fn towards(to: T, transition: Option<Transition>) -> Self {
+        Self {
+            from: None,
+            to,
+            transition,
+        }
+    }
+
+    /// Ease from wherever the tween is at `now` to `to`.
+    pub fn retarget(&mut self, to: T, transition: Option<Transition>, now: Instant) {
+        self.from = self.current(now);
+        self.to = to;
+        self.transition = transition;
+    }
+
+    /// Where the tween is at `now`; None while it eases from a value only the
+    /// render thread knows.
+    pub fn current(&self, now: Instant) -> Option<T> {
+        match self.transition {
+            Some(t) if !t.finished(now) => Some(self.from?.lerp(self.to, t.progress(now))),
+            _ => Some(self.to),
+        }
+    }
+
+    /// Where the tween is at `now`, starting from `start` if it didn't know
+    /// where it began.
+    pub fn value(&mut self, start: T, now: Instant) -> T {
+        let from = *self.from.get_or_insert(start);
+        match self.transition {
+            Some(t) if !t.finished(now) => from.lerp(self.to, t.progress(now)),
+            _ => self.to,
+        }
+    }
+
+    pub fn target(&self) -> T {
+        self.to
+    }
+}
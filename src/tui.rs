@@ -0,0 +1,522 @@
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Sparkline};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::VecDeque;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::channel::{ChannelRegistry, ChannelState};
+use crate::control::{self, Command};
+use crate::ndi_input::SourceState;
+use crate::resources::ProcessUsage;
+use crate::source::LayerId;
+
+/// Log lines kept for the log pane.
+const LOG_TAIL_CAPACITY: usize = 200;
+
+/// Log lines shown at the bottom of the dashboard.
+const LOG_PANE_LINES: u16 = 8;
+
+/// FPS samples (one per second) kept per channel for the sparkline.
+const FPS_HISTORY: usize = 120;
+
+/// Log output captured for the dashboard while it owns the terminal.
+#[derive(Clone, Default)]
+pub struct LogTail {
+    lines: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl LogTail {
+    fn push(&self, text: &str) {
+        let mut lines = self.lines.lock().unwrap();
+        for line in text.lines().filter(|l| !l.is_empty()) {
+            if lines.len() >= LOG_TAIL_CAPACITY {
+                lines.pop_front();
+            }
+            lines.push_back(line.to_string());
+        }
+    }
+
+    fn last(&self, n: usize) -> Vec<String> {
+        let lines = self.lines.lock().unwrap();
+        lines
+            .iter()
+            .skip(lines.len().saturating_sub(n))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Buffers one formatted log event and hands it to the tail when dropped.
+pub struct LogWriter {
+    tail: LogTail,
+    buf: Vec<u8>,
+}
+
+impl Write for LogWriter {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        self.tail.push(&String::from_utf8_lossy(&self.buf));
+    }
+}
+
+impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for LogTail {
+    type Writer = LogWriter;
+
+    fn make_writer(&'a self) -> LogWriter {
+        LogWriter {
+            tail: self.clone(),
+            buf: Vec::new(),
+        }
+    }
+}
+
+/// Everything the dashboard shows or acts on.
+pub struct Dashboard {
+    pub channels: ChannelRegistry,
+    pub compositor: String,
+    pub usage: Arc<Mutex<ProcessUsage>>,
+    pub status_port: u16,
+    /// Slate hotkeys from the config; checked before the built-in keys
+    pub hotkeys: Vec<(char, Command)>,
+    pub log: LogTail,
+}
+
+struct ChannelFps {
+    history: VecDeque<u64>,
+    last_frames: u64,
+}
+
+impl ChannelFps {
+    fn new(ch: &ChannelState) -> Self {
+        Self {
+            history: VecDeque::with_capacity(FPS_HISTORY),
+            last_frames: *ch.frames_output.lock().unwrap(),
+        }
+    }
+}
+
+struct App {
+    dashboard: Dashboard,
+    /// Channels as of the last refresh, with their fps history
+    channels: Vec<Arc<ChannelState>>,
+    fps: Vec<ChannelFps>,
+    last_sample: Instant,
+    selected_channel: usize,
+    selected_layer: usize,
+}
+
+/// Run the interactive dashboard until cancelled or the operator quits, which
+/// cancels `cancel`. Blocks; call from a blocking task.
+pub fn run(dashboard: Dashboard, cancel: CancellationToken) -> Result<()> {
+    let mut app = App {
+        dashboard,
+        channels: Vec::new(),
+        fps: Vec::new(),
+        last_sample: Instant::now(),
+        selected_channel: 0,
+        selected_layer: 0,
+    };
+
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut app, &cancel);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    app: &mut App,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    while !cancel.is_cancelled() {
+        app.refresh_channels();
+        app.sample_fps();
+        terminal.draw(|frame| draw(frame, app))?;
+
+        if !event::poll(Duration::from_millis(250))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        // Raw mode swallows SIGINT, so Ctrl+C arrives as a key
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            tracing::info!("Shutting down...");
+            cancel.cancel();
+            break;
+        }
+        if let KeyCode::Char(c) = key.code {
+            if let Some((_, command)) = app.dashboard.hotkeys.iter().find(|(k, _)| *k == c) {
+                if let Err(e) = control::execute(&app.channels, command) {
+                    tracing::warn!("Hotkey '{}': {}", c, e);
+                }
+                continue;
+            }
+        }
+        match key.code {
+            KeyCode::Char('q') => {
+                tracing::info!("Shutting down...");
+                cancel.cancel();
+                break;
+            }
+            KeyCode::Left | KeyCode::BackTab => app.select_channel(-1),
+            KeyCode::Right | KeyCode::Tab => app.select_channel(1),
+            KeyCode::Up => app.select_layer(-1),
+            KeyCode::Down => app.select_layer(1),
+            KeyCode::Char('m') => app.toggle_layer(false),
+            KeyCode::Char('s') => app.toggle_layer(true),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+impl App {
+    /// Pick up channels started or stopped since the last frame, keeping the fps
+    /// history of the ones that are still running.
+    fn refresh_channels(&mut self) {
+        let channels = self.dashboard.channels.snapshot();
+        let unchanged = channels.len() == self.channels.len()
+            && channels
+                .iter()
+                .zip(&self.channels)
+                .all(|(a, b)| Arc::ptr_eq(a, b));
+        if unchanged {
+            return;
+        }
+        let mut old: Vec<_> = self.channels.drain(..).zip(self.fps.drain(..)).collect();
+        self.fps = channels
+            .iter()
+            .map(
+                |ch| match old.iter().position(|(o, _)| Arc::ptr_eq(o, ch)) {
+                    Some(i) => old.swap_remove(i).1,
+                    None => ChannelFps::new(ch),
+                },
+            )
+            .collect();
+        self.channels = channels;
+        self.selected_channel = self
+            .selected_channel
+            .min(self.channels.len().saturating_sub(1));
+    }
+
+    fn sample_fps(&mut self) {
+        let elapsed = self.last_sample.elapsed();
+        if elapsed < Duration::from_secs(1) {
+            return;
+        }
+        self.last_sample = Instant::now();
+        for (ch, fps) in self.channels.iter().zip(self.fps.iter_mut()) {
+            let frames = *ch.frames_output.lock().unwrap();
+            let rate = frames.saturating_sub(fps.last_frames) as f64 / elapsed.as_secs_f64();
+            fps.last_frames = frames;
+            if fps.history.len() >= FPS_HISTORY {
+                fps.history.pop_front();
+            }
+            fps.history.push_back(rate.round() as u64);
+        }
+    }
+
+    fn select_channel(&mut self, step: isize) {
+        let count = self.channels.len();
+        if count > 0 {
+            self.selected_channel =
+                (self.selected_channel as isize + step).rem_euclid(count as isize) as usize;
+            self.selected_layer = 0;
+        }
+    }
+
+    fn select_layer(&mut self, step: isize) {
+        let Some(ch) = self.channels.get(self.selected_channel) else {
+            return;
+        };
+        if !ch.layers.is_empty() {
+            self.selected_layer = (self.selected_layer as isize + step)
+                .clamp(0, ch.layers.len() as isize - 1) as usize;
+        }
+    }
+
+    /// Mute (or solo) the selected layer of the selected channel.
+    fn toggle_layer(&mut self, solo: bool) {
+        let Some(ch) = self.channels.get(self.selected_channel) else {
+            return;
+        };
+        let Some(layer) = ch.layers.get(self.selected_layer) else {
+            return;
+        };
+        let mut mix = ch.layer_mix.lock().unwrap();
+        if solo {
+            mix.toggle_solo(layer.source);
+            tracing::info!(
+                "Channel '{}': {} {}",
+                ch.name,
+                layer.label,
+                if mix.solo.is_some() {
+                    "soloed"
+                } else {
+                    "unsoloed"
+                }
+            );
+        } else {
+            mix.toggle_mute(layer.source);
+            tracing::info!(
+                "Channel '{}': {} {}",
+                ch.name,
+                layer.label,
+                if mix.muted.contains(&layer.source) {
+                    "muted"
+                } else {
+                    "unmuted"
+                }
+            );
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, app: &App) {
+    let [header, channels, log, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(0),
+        Constraint::Length(LOG_PANE_LINES + 2),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let dashboard = &app.dashboard;
+    let usage = dashboard.usage.lock().unwrap().clone();
+    let mut title = vec![
+        Span::styled(
+            format!("NDI Mixer v{}", env!("CARGO_PKG_VERSION")),
+            Style::new().bold(),
+        ),
+        Span::raw(format!(
+            " — {} channel{} ({}) — CPU {:.0}%, RSS {} MB",
+            app.channels.len(),
+            if app.channels.len() == 1 { "" } else { "s" },
+            dashboard.compositor.to_uppercase(),
+            usage.cpu_percent,
+            usage.rss_bytes / (1024 * 1024)
+        )),
+    ];
+    if dashboard.status_port > 0 {
+        title.push(Span::raw(format!(
+            " — http://localhost:{}/status",
+            dashboard.status_port
+        )));
+    }
+    frame.render_widget(Line::from(title), header);
+
+    let panes = Layout::vertical(app.channels.iter().map(|_| Constraint::Fill(1))).split(channels);
+    for (i, (ch, area)) in app.channels.iter().zip(panes.iter()).enumerate() {
+        let selected_layer = (i == app.selected_channel).then_some(app.selected_layer);
+        draw_channel(frame, *area, ch, &app.fps[i], selected_layer);
+    }
+
+    let lines: Vec<Line> = dashboard
+        .log
+        .last(LOG_PANE_LINES as usize)
+        .into_iter()
+        .map(Line::from)
+        .collect();
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title(" Log ")),
+        log,
+    );
+
+    let mut help = String::from(" ←/→ channel  ↑/↓ layer  m mute  s solo  q quit");
+    for (key, command) in &dashboard.hotkeys {
+        if let Command::ToggleSlate { channel } = command {
+            help.push_str(&format!(
+                "  {} slate {}",
+                key,
+                channel.as_deref().unwrap_or("all")
+            ));
+        }
+    }
+    frame.render_widget(Line::from(help).dark_gray(), footer);
+}
+
+fn draw_channel(
+    frame: &mut Frame,
+    area: Rect,
+    ch: &ChannelState,
+    fps: &ChannelFps,
+    selected_layer: Option<usize>,
+) {
+    let border = if selected_layer.is_some() {
+        Style::new().fg(Color::Yellow)
+    } else {
+        Style::new()
+    };
+    let block = Block::bordered().border_style(border).title(format!(
+        " {} → {} ({}x{}@{}) ",
+        ch.name,
+        ch.output_name.lock().unwrap(),
+        ch.width,
+        ch.height,
+        ch.frame_rate
+    ));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [info, graph] =
+        Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(inner);
+
+    let mut lines = vec![status_line(ch)];
+    let rates = ch.frame_rates.lock().unwrap().clone();
+    let mix = ch.layer_mix.lock().unwrap();
+    for (i, layer) in ch.layers.iter().enumerate() {
+        let marker = if selected_layer == Some(i) {
+            "▸ "
+        } else {
+            "  "
+        };
+        let mut spans = vec![
+            Span::raw(marker),
+            Span::raw(format!("z{:<4} ", layer.z_index)),
+        ];
+        let label = Span::raw(layer.label.clone());
+        spans.push(if mix.is_visible(layer.source) {
+            label
+        } else {
+            label.add_modifier(Modifier::CROSSED_OUT).dark_gray()
+        });
+        if let Some(ref group) = layer.group {
+            spans.push(Span::styled(format!("  [{}]", group), Style::new().dim()));
+        }
+        // Incoming rate of layers fed from outside, to spot which one stalls
+        let rate = match layer.source {
+            LayerId::Ndi => rates.ndi,
+            LayerId::Browser(i) => rates.overlays.get(i).copied(),
+            _ => None,
+        };
+        if let Some(rate) = rate {
+            spans.push(Span::styled(
+                format!("  {:.1} fps", rate),
+                Style::new().dim(),
+            ));
+        }
+        if mix.solo == Some(layer.source) {
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(" SOLO ", Style::new().black().on_yellow()));
+        } else if mix.muted.contains(&layer.source) {
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(" MUTE ", Style::new().white().on_dark_gray()));
+        }
+        lines.push(Line::from(spans));
+    }
+    drop(mix);
+    frame.render_widget(Paragraph::new(lines), info);
+
+    let data: Vec<u64> = fps.history.iter().copied().collect();
+    let current = data.last().copied().unwrap_or(0);
+    // Newest samples on the right
+    let visible = data
+        .len()
+        .saturating_sub(graph.width.saturating_sub(2) as usize);
+    frame.render_widget(
+        Sparkline::default()
+            .block(Block::bordered().title(format!(" {} fps ", current)))
+            .data(&data[visible..])
+            .max(ch.frame_rate as u64)
+            .style(Style::new().fg(Color::Green)),
+        graph,
+    );
+}
+
+fn status_line(ch: &ChannelState) -> Line<'static> {
+    let mut spans = Vec::new();
+
+    if let Some(ref src) = ch.ndi_source {
+        let src = src.lock().unwrap().label().to_string();
+        let (mark, style, note) = match *ch.ndi_state.lock().unwrap() {
+            SourceState::Connected => ("+", Style::new().green(), ""),
+            SourceState::Searching => ("~", Style::new().yellow(), ""),
+            SourceState::Lost => ("x", Style::new().red(), " (lost)"),
+            SourceState::TimedOut => ("x", Style::new().red(), " (not found)"),
+        };
+        spans.push(Span::raw("NDI: "));
+        spans.push(Span::styled(mark, style));
+        spans.push(Span::raw(format!(" {}{}", src, note)));
+        if let Some(ref format) = *ch.ndi_format.lock().unwrap() {
+            let style = if format.rate_mismatch(ch.frame_rate) {
+                Style::new().yellow()
+            } else {
+                Style::new().dim()
+            };
+            spans.push(Span::styled(format!(" [{}]", format.summary()), style));
+        }
+    } else {
+        spans.push(Span::raw("NDI: -"));
+    }
+
+    if !ch.browser_overlays.is_empty() {
+        let total = ch.browser_overlays.len();
+        let loaded = ch
+            .browser_overlays
+            .iter()
+            .filter(|b| *b.loaded.lock().unwrap())
+            .count();
+        let failed = ch
+            .browser_overlays
+            .iter()
+            .filter(|b| b.error.lock().unwrap().is_some())
+            .count();
+        spans.push(Span::raw("  |  Browser: "));
+        spans.push(if failed > 0 {
+            Span::styled(format!("x {}/{} failed", failed, total), Style::new().red())
+        } else if loaded == total {
+            Span::styled(format!("+ {} loaded", total), Style::new().green())
+        } else {
+            Span::styled(
+                format!("~ {}/{} loaded", loaded, total),
+                Style::new().yellow(),
+            )
+        });
+    }
+
+    spans.push(Span::raw(format!(
+        "  |  {}f",
+        *ch.frames_output.lock().unwrap()
+    )));
+
+    if ch.overload.lock().unwrap().active {
+        spans.push(Span::styled(
+            format!("  [overload: {}]", ch.overload_policy.as_str()),
+            Style::new().yellow(),
+        ));
+    }
+    if *ch.slate_active.lock().unwrap() {
+        spans.push(Span::raw("  "));
+        spans.push(Span::styled(" SLATE ", Style::new().white().on_red()));
+    } else if ch.output_blank.lock().unwrap().is_some() {
+        spans.push(Span::raw("  "));
+        spans.push(Span::styled(" BLANK ", Style::new().white().on_black()));
+    }
+    if *ch.warming_up.lock().unwrap() {
+        spans.push(Span::styled("  [warming up]", Style::new().cyan()));
+    }
+
+    Line::from(spans)
+}
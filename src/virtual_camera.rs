@@ -0,0 +1,222 @@
+use anyhow::Result;
+use image::RgbaImage;
+use serde::Serialize;
+use std::fs::File;
+use std::io::Write;
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+
+use crate::config::VirtualCameraConfig;
+
+/// Virtual camera state for `/status`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VirtualCameraStatus {
+    pub device: String,
+    pub frames_written: u64,
+    /// Frames not written because the device or conversion fell behind
+    pub dropped: u64,
+    /// Most recent write failure
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+/// Feeds a channel's output to a v4l2loopback device, so local apps can pick
+/// it as a webcam. Conversion and writes run on a worker thread.
+pub struct VirtualCamera {
+    frames: SyncSender<RgbaImage>,
+    pub status: Arc<Mutex<VirtualCameraStatus>>,
+}
+
+impl VirtualCamera {
+    pub fn start(
+        cfg: &VirtualCameraConfig,
+        channel: &str,
+        width: u32,
+        height: u32,
+    ) -> Result<Self> {
+        // YUYV carries chroma per pixel pair; an odd last column is dropped
+        let width = width & !1;
+        let device = open_device(&cfg.device, width, height)?;
+        tracing::info!(
+            "Channel '{}': virtual camera on {} ({}x{} YUYV)",
+            channel,
+            cfg.device,
+            width,
+            height
+        );
+
+        let status = Arc::new(Mutex::new(VirtualCameraStatus {
+            device: cfg.device.clone(),
+            ..Default::default()
+        }));
+        // Room for a couple of frames; the render thread drops frames rather than wait
+        let (frames, rx) = mpsc::sync_channel(2);
+        let worker = Worker {
+            device,
+            width,
+            height,
+            channel: channel.to_string(),
+            status: status.clone(),
+        };
+        std::thread::Builder::new()
+            .name(format!("vcam-{}", channel))
+            .spawn(move || worker.run(rx))
+            .expect("Failed to spawn virtual camera thread");
+
+        Ok(Self { frames, status })
+    }
+
+    /// Add an output frame; dropped if the worker is still busy with earlier ones.
+    pub fn push(&self, frame: &RgbaImage) {
+        if let Err(TrySendError::Full(_)) = self.frames.try_send(frame.clone()) {
+            self.status.lock().unwrap().dropped += 1;
+        }
+    }
+}
+
+struct Worker {
+    device: File,
+    width: u32,
+    height: u32,
+    channel: String,
+    status: Arc<Mutex<VirtualCameraStatus>>,
+}
+
+impl Worker {
+    /// Runs until the channel drops its sender.
+    fn run(mut self, frames: Receiver<RgbaImage>) {
+        let mut buffer = vec![0u8; (self.width * self.height * 2) as usize];
+        for frame in frames {
+            rgba_to_yuyv(&frame, self.width, self.height, &mut buffer);
+            let result = self.device.write_all(&buffer);
+            let mut status = self.status.lock().unwrap();
+            match result {
+                Ok(()) => status.frames_written += 1,
+                Err(e) => {
+                    // Log the first failure of a run, not one per frame
+                    if status.last_error.is_none() {
+                        tracing::warn!(
+                            "Channel '{}': virtual camera write to {} failed: {}",
+                            self.channel,
+                            status.device,
+                            e
+                        );
+                    }
+                    status.last_error = Some(e.to_string());
+                    status.dropped += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Full-range BT.709 RGBA to limited-range BT.709 packed YUYV (4:2:2), the
+/// format webcam consumers accept most widely. Chroma is the average of each pair.
+fn rgba_to_yuyv(frame: &RgbaImage, width: u32, height: u32, out: &mut [u8]) {
+    let luma = |[r, g, b, _]: [u8; 4]| 0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32;
+    let (fw, fh) = frame.dimensions();
+    for y in 0..height.min(fh) {
+        for x in (0..width.min(fw & !1)).step_by(2) {
+            let p0 = frame.get_pixel(x, y).0;
+            let p1 = frame.get_pixel(x + 1, y).0;
+            let (l0, l1) = (luma(p0), luma(p1));
+            let r = (p0[0] as f32 + p1[0] as f32) / 2.0;
+            let b = (p0[2] as f32 + p1[2] as f32) / 2.0;
+            let l = (l0 + l1) / 2.0;
+            let i = ((y * width + x) * 2) as usize;
+            out[i] = (16.0 + l0 * 219.0 / 255.0).round() as u8;
+            out[i + 1] = (128.0 + (b - l) / 1.8556 * 224.0 / 255.0).round() as u8;
+            out[i + 2] = (16.0 + l1 * 219.0 / 255.0).round() as u8;
+            out[i + 3] = (128.0 + (r - l) / 1.5748 * 224.0 / 255.0).round() as u8;
+        }
+    }
+}
+
+/// Open a v4l2loopback device and set its output format to YUYV at the channel size.
+#[cfg(target_os = "linux")]
+fn open_device(path: &str, width: u32, height: u32) -> Result<File> {
+    use std::os::fd::AsRawFd;
+
+    /// `struct v4l2_pix_format`
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct PixFormat {
+        width: u32,
+        height: u32,
+        pixelformat: u32,
+        field: u32,
+        bytesperline: u32,
+        sizeimage: u32,
+        colorspace: u32,
+        priv_: u32,
+        flags: u32,
+        ycbcr_enc: u32,
+        quantization: u32,
+        xfer_func: u32,
+    }
+
+    /// The `fmt` union of `struct v4l2_format`; the pointer gives it the kernel's alignment
+    #[repr(C)]
+    union FormatData {
+        pix: PixFormat,
+        raw: [u8; 200],
+        _align: [*const libc::c_void; 0],
+    }
+
+    /// `struct v4l2_format`
+    #[repr(C)]
+    struct Format {
+        kind: u32,
+        fmt: FormatData,
+    }
+
+    const BUF_TYPE_VIDEO_OUTPUT: u32 = 2;
+    const FIELD_NONE: u32 = 1;
+    const COLORSPACE_REC709: u32 = 3;
+    const YCBCR_ENC_709: u32 = 2;
+    const QUANTIZATION_LIM_RANGE: u32 = 2;
+    const PIX_FMT_YUYV: u32 = u32::from_le_bytes(*b"YUYV");
+    // _IOWR('V', 5, struct v4l2_format)
+    const VIDIOC_S_FMT: u32 =
+        (3 << 30) | ((std::mem::size_of::<Format>() as u32) << 16) | ((b'V' as u32) << 8) | 5;
+
+    let device = std::fs::OpenOptions::new()
+        .write(true)
+        .open(path)
+        .map_err(|e| anyhow::anyhow!("Failed to open virtual camera {}: {}", path, e))?;
+
+    let mut format = Format {
+        kind: BUF_TYPE_VIDEO_OUTPUT,
+        fmt: FormatData { raw: [0; 200] },
+    };
+    format.fmt.pix = PixFormat {
+        width,
+        height,
+        pixelformat: PIX_FMT_YUYV,
+        field: FIELD_NONE,
+        bytesperline: width * 2,
+        sizeimage: width * height * 2,
+        colorspace: COLORSPACE_REC709,
+        priv_: 0,
+        flags: 0,
+        ycbcr_enc: YCBCR_ENC_709,
+        quantization: QUANTIZATION_LIM_RANGE,
+        xfer_func: 0,
+    };
+    let result = unsafe { libc::ioctl(device.as_raw_fd(), VIDIOC_S_FMT as _, &mut format) };
+    if result != 0 {
+        anyhow::bail!(
+            "{} is not a v4l2loopback device or rejected {}x{} YUYV: {}",
+            path,
+            width,
+            height,
+            std::io::Error::last_os_error()
+        );
+    }
+    Ok(device)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_device(_path: &str, _width: u32, _height: u32) -> Result<File> {
+    anyhow::bail!("Virtual camera output needs v4l2loopback, which is only available on Linux")
+}